@@ -4,13 +4,22 @@ use rand::distributions::{Distribution, Standard};
 use rand::{thread_rng, Rng};
 use varint_simd::{
     decode,
+    decode_eight_u16_unsafe,
     decode_eight_u8_unsafe,
     decode_four_unsafe,
     decode_len,
     decode_len_unsafe,
+    decode_sixteen_u8_unsafe,
+    decode_slice,
     decode_two_unsafe, //decode_two_wide_unsafe,
     decode_unsafe,
     encode,
+    encode_slice,
+    encode_to_slice,
+    encoded_len,
+    encoded_len_slice,
+    validate,
+    Backend,
     VarIntTarget,
 };
 
@@ -29,7 +38,7 @@ where
         let mut idx = 0;
         for _ in 0..C {
             if encoded.len() < idx + 16 {
-                encoded.extend(std::iter::repeat(0).take(idx + 11 - encoded.len()))
+                encoded.extend(std::iter::repeat_n(0, idx + 11 - encoded.len()))
             }
             let len = rng.gen::<T>().encode_var(&mut encoded[idx..]);
             idx += len;
@@ -72,10 +81,10 @@ fn decode_batched_varint_simd_unsafe<T: VarIntTarget, const C: usize>(
     let out = &mut input.1;
 
     let mut slice = &data[..];
-    for i in 0..C {
+    for out in out.iter_mut().take(C) {
         // SAFETY: the input slice should have at least 16 bytes of allocated padding at the end
         let (num, len) = unsafe { decode_unsafe::<T>(slice.as_ptr()) };
-        out[i] = num;
+        *out = num;
         slice = &slice[len..];
     }
 }
@@ -144,28 +153,155 @@ fn decode_batched_varint_simd_8x_u8_unsafe<const C: usize>(input: &mut (Vec<u8>,
     }
 }
 
+#[inline(always)]
+fn decode_batched_varint_simd_8x_u16_unsafe<const C: usize>(input: &mut (Vec<u8>, Vec<u16>)) {
+    let data = &input.0;
+    let out = &mut input.1;
+
+    // The encoded buffer is only padded for 16-byte reads, so finish one value at a time
+    let mut slice = &data[..];
+    let mut i = 0;
+    while i + 8 <= C && slice.len() >= 32 {
+        let (nums, total_len) = unsafe { decode_eight_u16_unsafe(slice.as_ptr()) };
+        out[i..(i + 8)].copy_from_slice(&nums);
+        slice = &slice[(total_len as usize)..];
+        i += 8;
+    }
+    for num in out[i..].iter_mut() {
+        let (value, len) = unsafe { decode_unsafe::<u16>(slice.as_ptr()) };
+        *num = value;
+        slice = &slice[len..];
+    }
+}
+
+#[inline(always)]
+fn decode_batched_varint_simd_16x_u8_unsafe<const C: usize>(input: &mut (Vec<u8>, Vec<u8>)) {
+    let data = &input.0;
+    let out = &mut input.1;
+
+    // The encoded buffer is only padded for 16-byte reads, so finish one value at a time
+    let mut slice = &data[..];
+    let mut i = 0;
+    while i + 16 <= C && slice.len() >= 32 {
+        let (nums, total_len) = unsafe { decode_sixteen_u8_unsafe(slice.as_ptr()) };
+        out[i..(i + 16)].copy_from_slice(&nums);
+        slice = &slice[(total_len as usize)..];
+        i += 16;
+    }
+    for num in out[i..].iter_mut() {
+        let (value, len) = unsafe { decode_unsafe::<u8>(slice.as_ptr()) };
+        *num = value;
+        slice = &slice[len..];
+    }
+}
+
+#[inline(always)]
+fn decode_batched_backend_unsafe<T: VarIntTarget, const C: usize>(
+    backend: Backend,
+    input: &mut (Vec<u8>, Vec<T>),
+) {
+    let data = &input.0;
+    let out = &mut input.1;
+
+    let mut slice = &data[..];
+    for out in out.iter_mut().take(C) {
+        // SAFETY: the input slice should have at least 16 bytes of allocated padding at the end
+        let (num, len) = unsafe { backend.decode_unsafe::<T>(slice.as_ptr()) };
+        *out = num;
+        slice = &slice[len..];
+    }
+}
+
+#[inline(always)]
+fn decode_batched_backend_2x_unsafe<T: VarIntTarget, const C: usize>(
+    backend: Backend,
+    input: &mut (Vec<u8>, Vec<T>),
+) {
+    let data = &input.0;
+    let out = &mut input.1;
+
+    let mut slice = &data[..];
+    for i in 0..(C / 2) {
+        let (num1, num2, len1, len2) = unsafe { backend.decode_two_unsafe::<T, T>(slice.as_ptr()) };
+        out[i * 2] = num1;
+        out[i * 2 + 1] = num2;
+        slice = &slice[((len1 + len2) as usize)..];
+    }
+}
+
+#[inline(always)]
+fn decode_batched_backend_4x_unsafe<T: VarIntTarget, const C: usize>(
+    backend: Backend,
+    input: &mut (Vec<u8>, Vec<T>),
+) {
+    let data = &input.0;
+    let out = &mut input.1;
+
+    let mut slice = &data[..];
+    for i in 0..(C / 4) {
+        let (num1, num2, num3, num4, len1, len2, len3, len4, _invalid) =
+            unsafe { backend.decode_four_unsafe::<T, T, T, T>(slice.as_ptr()) };
+        out[i * 4] = num1;
+        out[i * 4 + 1] = num2;
+        out[i * 4 + 2] = num3;
+        out[i * 4 + 3] = num4;
+        slice = &slice[((len1 + len2 + len3 + len4) as usize)..];
+    }
+}
+
+#[inline(always)]
+fn decode_batched_backend_8x_u8_unsafe<const C: usize>(
+    backend: Backend,
+    input: &mut (Vec<u8>, Vec<u8>),
+) {
+    let data = &input.0;
+    let out = &mut input.1;
+
+    let mut slice = &data[..];
+    for i in 0..(C / 8) {
+        let (nums, total_len) = unsafe { backend.decode_eight_u8_unsafe(slice.as_ptr()) };
+        out[i * 8..i * 8 + 8].copy_from_slice(&nums);
+        slice = &slice[(total_len as usize)..];
+    }
+}
+
 #[inline(always)]
 fn decode_batched_varint_simd_safe<T: VarIntTarget, const C: usize>(input: &mut (Vec<u8>, Vec<T>)) {
     let data = &input.0;
     let out = &mut input.1;
 
     let mut slice = &data[..];
-    for i in 0..C {
+    for out in out.iter_mut().take(C) {
         let (num, len) = decode::<T>(slice).unwrap();
-        out[i] = num;
+        *out = num;
         slice = &slice[len..];
     }
 }
 
+#[inline(always)]
+fn decode_batched_varint_simd_slice<T: VarIntTarget, const C: usize>(
+    input: &mut (Vec<u8>, Vec<T>),
+) {
+    let data = &input.0;
+    let out = &mut input.1;
+
+    decode_slice::<T>(data, &mut out[..C]).unwrap();
+}
+
+#[inline(always)]
+fn validate_batched_varint_simd<T: VarIntTarget, const C: usize>(input: &mut (Vec<u8>, Vec<T>)) {
+    validate::<T>(&input.0).unwrap();
+}
+
 #[inline(always)]
 fn decode_batched_integer_encoding<T: VarInt, const C: usize>(input: &mut (Vec<u8>, Vec<T>)) {
     let data = &input.0;
     let out = &mut input.1;
 
     let mut slice = &data[..];
-    for i in 0..C {
+    for out in out.iter_mut().take(C) {
         let (num, len) = T::decode_var(slice).unwrap();
-        out[i] = num;
+        *out = num;
         slice = &slice[len..];
     }
 }
@@ -176,9 +312,9 @@ fn decode_batched_rustc_u8<const C: usize>(input: &mut (Vec<u8>, Vec<u8>)) {
     let out = &mut input.1;
 
     let mut slice = &data[..];
-    for i in 0..C {
+    for out in out.iter_mut().take(C) {
         let (num, len) = leb128::read_u16_leb128(slice);
-        out[i] = num as u8;
+        *out = num as u8;
         slice = &slice[len..];
     }
 }
@@ -189,9 +325,9 @@ fn decode_batched_rustc_u16<const C: usize>(input: &mut (Vec<u8>, Vec<u16>)) {
     let out = &mut input.1;
 
     let mut slice = &data[..];
-    for i in 0..C {
+    for out in out.iter_mut().take(C) {
         let (num, len) = leb128::read_u16_leb128(slice);
-        out[i] = num;
+        *out = num;
         slice = &slice[len..];
     }
 }
@@ -202,9 +338,9 @@ fn decode_batched_rustc_u32<const C: usize>(input: &mut (Vec<u8>, Vec<u32>)) {
     let out = &mut input.1;
 
     let mut slice = &data[..];
-    for i in 0..C {
+    for out in out.iter_mut().take(C) {
         let (num, len) = leb128::read_u32_leb128(slice);
-        out[i] = num;
+        *out = num;
         slice = &slice[len..];
     }
 }
@@ -215,9 +351,9 @@ fn decode_batched_rustc_u64<const C: usize>(input: &mut (Vec<u8>, Vec<u64>)) {
     let out = &mut input.1;
 
     let mut slice = &data[..];
-    for i in 0..C {
+    for out in out.iter_mut().take(C) {
         let (num, len) = leb128::read_u64_leb128(slice);
-        out[i] = num;
+        *out = num;
         slice = &slice[len..];
     }
 }
@@ -228,9 +364,9 @@ fn decode_batched_prost<T: VarIntTarget, const C: usize>(input: &mut (Vec<u8>, V
     let out = &mut input.1;
 
     let mut slice = &data[..];
-    for i in 0..C {
+    for out in out.iter_mut().take(C) {
         let num = prost_varint::decode_varint(&mut slice).unwrap();
-        out[i] = T::cast_u64(num);
+        *out = T::cast_u64(num);
     }
 }
 
@@ -283,6 +419,22 @@ pub fn criterion_benchmark(c: &mut Criterion) {
         )
     });
 
+    group.bench_function("varint-simd/slice", |b| {
+        b.iter_batched_ref(
+            create_batched_encoded_generator::<u8, _, SEQUENCE_LEN>(&mut rng),
+            decode_batched_varint_simd_slice::<u8, SEQUENCE_LEN>,
+            BatchSize::SmallInput,
+        )
+    });
+
+    group.bench_function("varint-simd/validate", |b| {
+        b.iter_batched_ref(
+            create_batched_encoded_generator::<u8, _, SEQUENCE_LEN>(&mut rng),
+            validate_batched_varint_simd::<u8, SEQUENCE_LEN>,
+            BatchSize::SmallInput,
+        )
+    });
+
     group.bench_function("varint-simd/2x/unsafe", |b| {
         b.iter_batched_ref(
             create_batched_encoded_generator::<u8, _, SEQUENCE_LEN>(&mut rng),
@@ -306,6 +458,14 @@ pub fn criterion_benchmark(c: &mut Criterion) {
             BatchSize::SmallInput,
         )
     });
+
+    group.bench_function("varint-simd/16x/unsafe", |b| {
+        b.iter_batched_ref(
+            create_batched_encoded_generator::<u8, _, SEQUENCE_LEN>(&mut rng),
+            decode_batched_varint_simd_16x_u8_unsafe::<SEQUENCE_LEN>,
+            BatchSize::SmallInput,
+        )
+    });
     group.finish();
 
     let mut group = c.benchmark_group("varint-u8/decode_len");
@@ -412,6 +572,22 @@ pub fn criterion_benchmark(c: &mut Criterion) {
         )
     });
 
+    group.bench_function("varint-simd/slice", |b| {
+        b.iter_batched_ref(
+            create_batched_encoded_generator::<u16, _, SEQUENCE_LEN>(&mut rng),
+            decode_batched_varint_simd_slice::<u16, SEQUENCE_LEN>,
+            BatchSize::SmallInput,
+        )
+    });
+
+    group.bench_function("varint-simd/validate", |b| {
+        b.iter_batched_ref(
+            create_batched_encoded_generator::<u16, _, SEQUENCE_LEN>(&mut rng),
+            validate_batched_varint_simd::<u16, SEQUENCE_LEN>,
+            BatchSize::SmallInput,
+        )
+    });
+
     group.bench_function("varint-simd/2x/unsafe", |b| {
         b.iter_batched_ref(
             create_batched_encoded_generator::<u16, _, SEQUENCE_LEN>(&mut rng),
@@ -428,6 +604,14 @@ pub fn criterion_benchmark(c: &mut Criterion) {
         )
     });
 
+    group.bench_function("varint-simd/8x/unsafe", |b| {
+        b.iter_batched_ref(
+            create_batched_encoded_generator::<u16, _, SEQUENCE_LEN>(&mut rng),
+            decode_batched_varint_simd_8x_u16_unsafe::<SEQUENCE_LEN>,
+            BatchSize::SmallInput,
+        )
+    });
+
     group.finish();
 
     let mut group = c.benchmark_group("varint-u16/encode");
@@ -514,6 +698,22 @@ pub fn criterion_benchmark(c: &mut Criterion) {
         )
     });
 
+    group.bench_function("varint-simd/slice", |b| {
+        b.iter_batched_ref(
+            create_batched_encoded_generator::<u32, _, SEQUENCE_LEN>(&mut rng),
+            decode_batched_varint_simd_slice::<u32, SEQUENCE_LEN>,
+            BatchSize::SmallInput,
+        )
+    });
+
+    group.bench_function("varint-simd/validate", |b| {
+        b.iter_batched_ref(
+            create_batched_encoded_generator::<u32, _, SEQUENCE_LEN>(&mut rng),
+            validate_batched_varint_simd::<u32, SEQUENCE_LEN>,
+            BatchSize::SmallInput,
+        )
+    });
+
     group.bench_function("varint-simd/2x/unsafe", |b| {
         b.iter_batched_ref(
             create_batched_encoded_generator::<u32, _, SEQUENCE_LEN>(&mut rng),
@@ -607,6 +807,22 @@ pub fn criterion_benchmark(c: &mut Criterion) {
         )
     });
 
+    group.bench_function("varint-simd/slice", |b| {
+        b.iter_batched_ref(
+            create_batched_encoded_generator::<u64, _, SEQUENCE_LEN>(&mut rng),
+            decode_batched_varint_simd_slice::<u64, SEQUENCE_LEN>,
+            BatchSize::SmallInput,
+        )
+    });
+
+    group.bench_function("varint-simd/validate", |b| {
+        b.iter_batched_ref(
+            create_batched_encoded_generator::<u64, _, SEQUENCE_LEN>(&mut rng),
+            validate_batched_varint_simd::<u64, SEQUENCE_LEN>,
+            BatchSize::SmallInput,
+        )
+    });
+
     // group.bench_function("varint-simd/2x_wide/unsafe", |b| {
     //     b.iter_batched_ref(
     //         create_batched_encoded_generator::<u64, _, SEQUENCE_LEN>(&mut rng),
@@ -660,5 +876,238 @@ pub fn criterion_benchmark(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, criterion_benchmark);
+/// Compares the backends supported by the current CPU on the same data
+pub fn backend_benchmark(c: &mut Criterion) {
+    let mut rng = thread_rng();
+
+    // Must be a multiple of 8
+    const SEQUENCE_LEN: usize = 256;
+
+    let mut group = c.benchmark_group("backend/u8/decode");
+    group.throughput(Throughput::Elements(SEQUENCE_LEN as u64));
+    for backend in Backend::supported() {
+        group.bench_function(format!("{:?}/unsafe", backend), |b| {
+            b.iter_batched_ref(
+                create_batched_encoded_generator::<u8, _, SEQUENCE_LEN>(&mut rng),
+                |input| decode_batched_backend_unsafe::<u8, SEQUENCE_LEN>(backend, input),
+                BatchSize::SmallInput,
+            )
+        });
+
+        group.bench_function(format!("{:?}/2x/unsafe", backend), |b| {
+            b.iter_batched_ref(
+                create_batched_encoded_generator::<u8, _, SEQUENCE_LEN>(&mut rng),
+                |input| decode_batched_backend_2x_unsafe::<u8, SEQUENCE_LEN>(backend, input),
+                BatchSize::SmallInput,
+            )
+        });
+
+        group.bench_function(format!("{:?}/4x/unsafe", backend), |b| {
+            b.iter_batched_ref(
+                create_batched_encoded_generator::<u8, _, SEQUENCE_LEN>(&mut rng),
+                |input| decode_batched_backend_4x_unsafe::<u8, SEQUENCE_LEN>(backend, input),
+                BatchSize::SmallInput,
+            )
+        });
+
+        group.bench_function(format!("{:?}/8x/unsafe", backend), |b| {
+            b.iter_batched_ref(
+                create_batched_encoded_generator::<u8, _, SEQUENCE_LEN>(&mut rng),
+                |input| decode_batched_backend_8x_u8_unsafe::<SEQUENCE_LEN>(backend, input),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("backend/u16/decode");
+    group.throughput(Throughput::Elements(SEQUENCE_LEN as u64));
+    for backend in Backend::supported() {
+        group.bench_function(format!("{:?}/unsafe", backend), |b| {
+            b.iter_batched_ref(
+                create_batched_encoded_generator::<u16, _, SEQUENCE_LEN>(&mut rng),
+                |input| decode_batched_backend_unsafe::<u16, SEQUENCE_LEN>(backend, input),
+                BatchSize::SmallInput,
+            )
+        });
+
+        group.bench_function(format!("{:?}/2x/unsafe", backend), |b| {
+            b.iter_batched_ref(
+                create_batched_encoded_generator::<u16, _, SEQUENCE_LEN>(&mut rng),
+                |input| decode_batched_backend_2x_unsafe::<u16, SEQUENCE_LEN>(backend, input),
+                BatchSize::SmallInput,
+            )
+        });
+
+        group.bench_function(format!("{:?}/4x/unsafe", backend), |b| {
+            b.iter_batched_ref(
+                create_batched_encoded_generator::<u16, _, SEQUENCE_LEN>(&mut rng),
+                |input| decode_batched_backend_4x_unsafe::<u16, SEQUENCE_LEN>(backend, input),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("backend/u32/decode");
+    group.throughput(Throughput::Elements(SEQUENCE_LEN as u64));
+    for backend in Backend::supported() {
+        group.bench_function(format!("{:?}/unsafe", backend), |b| {
+            b.iter_batched_ref(
+                create_batched_encoded_generator::<u32, _, SEQUENCE_LEN>(&mut rng),
+                |input| decode_batched_backend_unsafe::<u32, SEQUENCE_LEN>(backend, input),
+                BatchSize::SmallInput,
+            )
+        });
+
+        group.bench_function(format!("{:?}/2x/unsafe", backend), |b| {
+            b.iter_batched_ref(
+                create_batched_encoded_generator::<u32, _, SEQUENCE_LEN>(&mut rng),
+                |input| decode_batched_backend_2x_unsafe::<u32, SEQUENCE_LEN>(backend, input),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("backend/u64/decode");
+    group.throughput(Throughput::Elements(SEQUENCE_LEN as u64));
+    for backend in Backend::supported() {
+        group.bench_function(format!("{:?}/unsafe", backend), |b| {
+            b.iter_batched_ref(
+                create_batched_encoded_generator::<u64, _, SEQUENCE_LEN>(&mut rng),
+                |input| decode_batched_backend_unsafe::<u64, SEQUENCE_LEN>(backend, input),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("backend/u32/encode");
+    group.throughput(Throughput::Elements(1));
+    for backend in Backend::supported() {
+        group.bench_function(format!("{:?}", backend), |b| {
+            b.iter_batched(
+                || rng.gen::<u32>(),
+                |num| backend.encode(num),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("backend/u64/encode");
+    group.throughput(Throughput::Elements(1));
+    for backend in Backend::supported() {
+        group.bench_function(format!("{:?}", backend), |b| {
+            b.iter_batched(
+                || rng.gen::<u64>(),
+                |num| backend.encode(num),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn encode_slice_group<T: VarIntTarget>(c: &mut Criterion, name: &str)
+where
+    Standard: Distribution<T>,
+{
+    const SEQUENCE_LEN: usize = 256;
+    let mut rng = thread_rng();
+
+    let mut group = c.benchmark_group(format!("varint-{}/encode_slice", name));
+    group.throughput(Throughput::Elements(SEQUENCE_LEN as u64));
+
+    let mut out = vec![0u8; SEQUENCE_LEN * T::MAX_VARINT_BYTES as usize];
+    group.bench_function("varint-simd/single", |b| {
+        b.iter_batched_ref(
+            || {
+                (0..SEQUENCE_LEN)
+                    .map(|_| rng.gen::<T>())
+                    .collect::<Vec<T>>()
+            },
+            |values| {
+                let mut written = 0;
+                for value in values.iter() {
+                    written += encode_to_slice(*value, &mut out[written..]) as usize;
+                }
+                written
+            },
+            BatchSize::SmallInput,
+        )
+    });
+
+    group.bench_function("varint-simd/slice", |b| {
+        b.iter_batched_ref(
+            || {
+                (0..SEQUENCE_LEN)
+                    .map(|_| rng.gen::<T>())
+                    .collect::<Vec<T>>()
+            },
+            |values| encode_slice(values, &mut out),
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+fn encoded_len_group<T: VarIntTarget>(c: &mut Criterion, name: &str)
+where
+    Standard: Distribution<T>,
+{
+    const SEQUENCE_LEN: usize = 256;
+    let mut rng = thread_rng();
+
+    let mut group = c.benchmark_group(format!("varint-{}/encoded_len", name));
+    group.throughput(Throughput::Elements(SEQUENCE_LEN as u64));
+
+    group.bench_function("varint-simd/single", |b| {
+        b.iter_batched_ref(
+            || {
+                (0..SEQUENCE_LEN)
+                    .map(|_| rng.gen::<T>())
+                    .collect::<Vec<T>>()
+            },
+            |values| {
+                values
+                    .iter()
+                    .map(|value| encoded_len(*value))
+                    .sum::<usize>()
+            },
+            BatchSize::SmallInput,
+        )
+    });
+
+    group.bench_function("varint-simd/slice", |b| {
+        b.iter_batched_ref(
+            || {
+                (0..SEQUENCE_LEN)
+                    .map(|_| rng.gen::<T>())
+                    .collect::<Vec<T>>()
+            },
+            |values| encoded_len_slice(values),
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+pub fn encode_slice_benchmark(c: &mut Criterion) {
+    encode_slice_group::<u8>(c, "u8");
+    encode_slice_group::<u16>(c, "u16");
+    encode_slice_group::<u32>(c, "u32");
+    encode_slice_group::<u64>(c, "u64");
+
+    encoded_len_group::<u32>(c, "u32");
+    encoded_len_group::<u64>(c, "u64");
+}
+
+criterion_group!(
+    benches,
+    criterion_benchmark,
+    backend_benchmark,
+    encode_slice_benchmark
+);
 criterion_main!(benches);
@@ -5,48 +5,30 @@ use rustc_version::Channel;
 #[cfg(target_arch = "x86_64")]
 use std::arch::x86_64::__cpuid;
 
-/// Performance of PDEP/PEXT relative to arithmetic/bit operations
-#[derive(PartialOrd, PartialEq)]
-enum PdepPerf {
-    VeryFast = 20,
-    Fast = 10,
-    Slow = 0,
-}
+// The same classification is used for runtime detection
+#[allow(dead_code)] // unused on other hosts
+#[path = "src/cpu/pdep.rs"]
+mod pdep;
+
+use pdep::PdepPerf;
 
 #[cfg(any(target_arch = "x86_64", target_arch = "x86"))]
+#[allow(unused_unsafe)] // __cpuid is safe on newer compilers
 fn pdep_speed() -> PdepPerf {
     let leaf0 = unsafe { __cpuid(0) };
-    let mut buf = Vec::with_capacity(12);
-    buf.extend_from_slice(&leaf0.ebx.to_le_bytes());
-    buf.extend_from_slice(&leaf0.edx.to_le_bytes());
-    buf.extend_from_slice(&leaf0.ecx.to_le_bytes());
+    let mut vendor = [0u8; 12];
+    vendor[..4].copy_from_slice(&leaf0.ebx.to_le_bytes());
+    vendor[4..8].copy_from_slice(&leaf0.edx.to_le_bytes());
+    vendor[8..].copy_from_slice(&leaf0.ecx.to_le_bytes());
 
     println!(
         "Detected CPU manufacturer {}",
-        String::from_utf8_lossy(&buf)
+        String::from_utf8_lossy(&vendor)
     );
 
-    if buf.as_slice() == b"AuthenticAMD" || buf.as_slice() == b"HygonGenuine" {
-        let leaf1 = unsafe { __cpuid(1) };
-
-        let family = (leaf1.eax >> 8) & 0b1111;
-        println!("family {}", family);
-        let extended_family = (leaf1.eax >> 20) & 0b11111111;
-        println!("extended_family {}", extended_family);
-
-        // Zen, Zen+, and Zen 2 CPUs have very poor PDEP/PEXT performance
-        if family == 0xF && (extended_family == 0x8 || extended_family == 0x9) {
-            println!("Detected Zen CPU");
-            return PdepPerf::Slow;
-        }
-
-        if family == 0xF && extended_family == 0xA {
-            println!("Detected Zen 3 CPU");
-            return PdepPerf::Fast;
-        }
-    }
+    let leaf1 = unsafe { __cpuid(1) };
 
-    PdepPerf::VeryFast
+    pdep::pdep_perf(&vendor, leaf1.eax)
 }
 
 #[cfg(not(any(target_arch = "x86_64", target_arch = "x86")))]
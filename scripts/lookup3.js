@@ -0,0 +1,43 @@
+// generate per-lane 2xu16 and 4xu8 lookup tables for the 32-byte AVX2 decoders
+
+function ctz(v) {
+    var c = 32
+    v &= -v
+    if (v) c--
+    if (v & 0x0000FFFF) c -= 16
+    if (v & 0x00FF00FF) c -= 8
+    if (v & 0x0F0F0F0F) c -= 4
+    if (v & 0x33333333) c -= 2
+    if (v & 0x55555555) c -= 1
+    return c
+}
+
+// count: number of varints, max_len: maximum varint length, lane: bytes per output lane
+function table(count, max_len, lane) {
+    let bits = count * max_len;
+    let out = "";
+    for (let mask = 0; mask < 2**bits; mask++) {
+        let bm_not = ~mask;
+        let shuf = [];
+        let pos = 0;
+        for (let i = 0; i < count; i++) {
+            let len = Math.min(ctz(bm_not >>> pos) + 1, max_len);
+            for (let j = 0; j < lane; j++) {
+                // 0x80 rather than 0xff, so that adding an offset keeps the high bit set
+                shuf.push(j < len ? pos + j : 0x80);
+            }
+            pos += len;
+        }
+
+        let packed = 0n;
+        for (let i = shuf.length - 1; i >= 0; i--) {
+            packed = (packed << 8n) | BigInt(shuf[i]);
+        }
+
+        out += `    (0x${packed.toString(16).padStart(16, "0")}, ${pos}), // 0b${mask.toString(2).padStart(bits, "0")}\n`;
+    }
+    return out;
+}
+
+console.log(table(2, 3, 4));
+console.log(table(4, 2, 2));
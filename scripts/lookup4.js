@@ -0,0 +1,47 @@
+// generate the inverted 2xu32, 4xu16 and 8xu8 lookup tables for encoding, which pack varints
+// from fixed-width lanes into adjacent bytes
+
+function pack(lens, lane) {
+    let out = [];
+    for (let i = 0; i < lens.length; i++) {
+        for (let j = 0; j < lens[i]; j++) {
+            out.push(i * lane + j);
+        }
+    }
+    while (out.length < 16) {
+        out.push(255);
+    }
+    return out.join(", ") + ",";
+}
+
+// 2xu32: up to 5 bytes per varint in 8-byte lanes
+for (let first_len = 1; first_len <= 5; first_len++) {
+    for (let second_len = 1; second_len <= 5; second_len++) {
+        console.log(pack([first_len, second_len], 8), `// ${first_len}, ${second_len}`);
+    }
+}
+
+console.log();
+
+// 4xu16: up to 3 bytes per varint in 4-byte lanes
+for (let first_len = 1; first_len <= 3; first_len++) {
+    for (let second_len = 1; second_len <= 3; second_len++) {
+        for (let third_len = 1; third_len <= 3; third_len++) {
+            for (let fourth_len = 1; fourth_len <= 3; fourth_len++) {
+                let lens = [first_len, second_len, third_len, fourth_len];
+                console.log(pack(lens, 4), `// ${lens.join(", ")}`);
+            }
+        }
+    }
+}
+
+console.log();
+
+// 8xu8: one or two bytes per varint in 2-byte lanes, indexed by a bitmask of the two-byte varints
+for (let mask = 0; mask < 2**8; mask++) {
+    let lens = [];
+    for (let i = 0; i < 8; i++) {
+        lens.push((mask >> i) & 1 ? 2 : 1);
+    }
+    console.log(pack(lens, 2), `// 0b${mask.toString(2).padStart(8, "0")}`);
+}
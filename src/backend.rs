@@ -0,0 +1,743 @@
+//! Explicit selection of the implementation used for encoding and decoding.
+//!
+//! The free functions of this crate pick the fastest implementation available on the target and
+//! the current CPU. [`Backend`] instead forces a specific one, so that every implementation can be
+//! tested against the others, or benchmarked on the same data, within a single binary.
+
+use core::mem::MaybeUninit;
+
+#[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+use crate::decode::neon;
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+use crate::decode::x86;
+use crate::decode::{self, scalar, zigzag};
+use crate::encode::{self, multi};
+use crate::num::{self, NonZeroVarIntTarget, SignedVarIntTarget, VarIntTarget};
+use crate::VarIntDecodeError;
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+use crate::cpu;
+
+/// An implementation of the encoding and decoding functions.
+///
+/// Methods have the same semantics as the free functions of the same name. Variants are only
+/// available on the corresponding architectures, and may additionally not be supported by the
+/// CPU the program is running on; see [`Backend::is_supported`] and [`Backend::supported`].
+///
+/// # Examples
+/// ```
+/// use varint_simd::Backend;
+///
+/// for backend in Backend::supported() {
+///     let encoded = backend.encode::<u32>(1337);
+///     assert_eq!(backend.decode::<u32>(&encoded.0), Ok((1337, 2)));
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Backend {
+    /// Portable implementation, without any SIMD or PDEP/PEXT instructions even if their target
+    /// features are enabled at compile time
+    Scalar,
+    /// SSE2 for encoding single values, which are decoded with 64-bit arithmetic. Multi-value
+    /// operations use the [`Backend::Scalar`] implementations.
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    Sse2,
+    /// SSSE3 multi-value decoding and encoding, using SSE2 arithmetic for converting from 7-bit
+    /// groups. Single values are handled as in [`Backend::Sse2`].
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    Ssse3,
+    /// SSSE3 multi-value decoding, using BMI2 PDEP/PEXT for converting to and from 7-bit groups.
+    /// Also requires LZCNT.
+    #[cfg(target_arch = "x86_64")]
+    Ssse3Pext,
+    /// [`Backend::Ssse3`], with AVX2 for [`Backend::decode_two_wide_unsafe`],
+    /// [`Backend::decode_eight_u16_unsafe`] and [`Backend::decode_sixteen_u8_unsafe`]. All other
+    /// operations use the SSSE3 implementations.
+    #[cfg(target_arch = "x86_64")]
+    Avx2,
+    /// NEON for encoding and multi-value decoding
+    #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+    Neon,
+}
+
+impl Backend {
+    /// All backends available on this target, whether or not they are supported by the CPU
+    pub const ALL: &'static [Backend] = &[
+        Backend::Scalar,
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        Backend::Sse2,
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        Backend::Ssse3,
+        #[cfg(target_arch = "x86_64")]
+        Backend::Ssse3Pext,
+        #[cfg(target_arch = "x86_64")]
+        Backend::Avx2,
+        #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+        Backend::Neon,
+    ];
+
+    /// Returns all backends that can be used on the current CPU
+    pub fn supported() -> impl Iterator<Item = Backend> {
+        Self::ALL
+            .iter()
+            .copied()
+            .filter(|backend| backend.is_supported())
+    }
+
+    /// Whether this backend can be used on the current CPU. Features which are not enabled at
+    /// compile time are only detected if the `std` feature is enabled. Under Miri, only
+    /// [`Backend::Scalar`] is supported.
+    #[inline]
+    pub fn is_supported(self) -> bool {
+        match self {
+            Backend::Scalar => true,
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Backend::Sse2 => !cfg!(miri) && cfg!(target_feature = "sse2"),
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Backend::Ssse3 => cpu::has_ssse3(),
+            #[cfg(target_arch = "x86_64")]
+            Backend::Ssse3Pext => cpu::has_ssse3() && cpu::has_bmi2() && cpu::has_lzcnt(),
+            #[cfg(target_arch = "x86_64")]
+            Backend::Avx2 => cpu::has_avx2(),
+            #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+            Backend::Neon => !cfg!(miri),
+        }
+    }
+
+    #[inline(always)]
+    fn assert_supported(self) {
+        assert!(
+            self.is_supported(),
+            "backend {:?} is not supported by this CPU",
+            self
+        );
+    }
+
+    /// See [`decode`](crate::decode()).
+    ///
+    /// **Panics:** if the backend is not supported by the CPU.
+    #[inline]
+    pub fn decode<T: VarIntTarget>(self, bytes: &[u8]) -> Result<(T, usize), VarIntDecodeError> {
+        self.assert_supported();
+        decode::decode_with(bytes, |ptr| unsafe { self.decode_dispatch(ptr) })
+    }
+
+    /// See [`decode_strict`](crate::decode_strict).
+    ///
+    /// **Panics:** if the backend is not supported by the CPU.
+    #[inline]
+    pub fn decode_strict<T: VarIntTarget>(
+        self,
+        bytes: &[u8],
+    ) -> Result<(T, usize), VarIntDecodeError> {
+        let (num, len) = self.decode::<T>(bytes)?;
+
+        if decode::is_canonical(bytes, len) {
+            Ok((num, len))
+        } else {
+            Err(VarIntDecodeError::NonCanonical)
+        }
+    }
+
+    /// See [`decode_zigzag`](crate::decode_zigzag).
+    ///
+    /// **Panics:** if the backend is not supported by the CPU.
+    #[inline]
+    pub fn decode_zigzag<T: SignedVarIntTarget>(
+        self,
+        bytes: &[u8],
+    ) -> Result<(T, usize), VarIntDecodeError> {
+        self.decode::<T::Unsigned>(bytes)
+            .map(|r| (r.0.unzigzag(), r.1))
+    }
+
+    /// See [`decode_sign_extended`](crate::decode_sign_extended).
+    ///
+    /// **Panics:** if the backend is not supported by the CPU.
+    #[inline]
+    pub fn decode_sign_extended<T: SignedVarIntTarget>(
+        self,
+        bytes: &[u8],
+    ) -> Result<(T, usize), VarIntDecodeError> {
+        self.decode::<u64>(bytes).map(|(num, len)| {
+            let num = T::Unsigned::cast_u128(num as i64 as u128);
+            (num::to_signed(num), len)
+        })
+    }
+
+    /// See [`decode_nonzero`](crate::decode_nonzero).
+    ///
+    /// **Panics:** if the backend is not supported by the CPU.
+    #[inline]
+    pub fn decode_nonzero<T: NonZeroVarIntTarget>(
+        self,
+        bytes: &[u8],
+    ) -> Result<(T, usize), VarIntDecodeError> {
+        let (num, len) = self.decode::<T::Inner>(bytes)?;
+        T::new(num)
+            .map(|num| (num, len))
+            .ok_or(VarIntDecodeError::Zero)
+    }
+
+    /// See [`decode_unsafe`](crate::decode_unsafe).
+    ///
+    /// # Safety
+    /// Same as [`decode_unsafe`](crate::decode_unsafe), and the backend must be supported by the
+    /// CPU.
+    #[inline]
+    pub unsafe fn decode_unsafe<T: VarIntTarget>(self, bytes: *const u8) -> (T, usize) {
+        num::assert_fits_vector::<T>();
+        self.decode_dispatch(bytes)
+    }
+
+    /// See [`decode_wide_unsafe`](crate::decode_wide_unsafe).
+    ///
+    /// # Safety
+    /// Same as [`decode_wide_unsafe`](crate::decode_wide_unsafe), and the backend must be supported
+    /// by the CPU.
+    #[inline]
+    pub unsafe fn decode_wide_unsafe<T: VarIntTarget>(self, bytes: *const u8) -> (T, usize) {
+        decode::read_window::<32>(bytes);
+        self.decode_dispatch(bytes)
+    }
+
+    /// [`Backend::decode_unsafe`] without the width check. Reads 32 bytes for types whose varints
+    /// may be longer than 16 bytes.
+    #[inline(always)]
+    unsafe fn decode_dispatch<T: VarIntTarget>(self, bytes: *const u8) -> (T, usize) {
+        decode::read_decode_window::<T>(bytes);
+
+        match self {
+            Backend::Scalar => scalar::decode_unsafe(bytes),
+            // Single values are decoded with 64-bit arithmetic, so the x86 backends without PDEP
+            // share one implementation
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Backend::Sse2 | Backend::Ssse3 => decode::decode_unsafe_impl::<T, false>(bytes),
+            #[cfg(target_arch = "x86_64")]
+            Backend::Avx2 => decode::decode_unsafe_impl::<T, false>(bytes),
+            #[cfg(target_arch = "x86_64")]
+            Backend::Ssse3Pext => decode::decode_unsafe_pdep(bytes),
+            #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+            Backend::Neon => decode::decode_unsafe_impl::<T, false>(bytes),
+        }
+    }
+
+    /// See [`decode_two_unsafe`](crate::decode_two_unsafe).
+    ///
+    /// # Safety
+    /// Same as [`decode_two_unsafe`](crate::decode_two_unsafe), and the backend must be supported
+    /// by the CPU.
+    #[inline]
+    pub unsafe fn decode_two_unsafe<T: VarIntTarget, U: VarIntTarget>(
+        self,
+        bytes: *const u8,
+    ) -> (T, U, u8, u8) {
+        decode::assert_fits_two::<T, U>();
+
+        match self {
+            Backend::Scalar => scalar::decode_two_unsafe(bytes),
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Backend::Sse2 => scalar::decode_two_unsafe(bytes),
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Backend::Ssse3 => x86::decode_two_ssse3(bytes),
+            #[cfg(target_arch = "x86_64")]
+            Backend::Ssse3Pext => x86::decode_two_bmi2(bytes),
+            #[cfg(target_arch = "x86_64")]
+            Backend::Avx2 => x86::decode_two_ssse3(bytes),
+            #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+            Backend::Neon => neon::decode_two_unsafe(bytes),
+        }
+    }
+
+    /// See [`decode_two_wide_unsafe`](crate::decode_two_wide_unsafe). Backends other than
+    /// [`Backend::Avx2`] decode the two values one after the other.
+    ///
+    /// # Safety
+    /// Same as [`decode_two_wide_unsafe`](crate::decode_two_wide_unsafe), and the backend must be
+    /// supported by the CPU.
+    #[inline]
+    pub unsafe fn decode_two_wide_unsafe<T: VarIntTarget, U: VarIntTarget>(
+        self,
+        bytes: *const u8,
+    ) -> (T, U, u8, u8) {
+        decode::assert_fits_two_wide::<T, U>();
+        decode::read_window::<32>(bytes);
+
+        match self {
+            #[cfg(target_arch = "x86_64")]
+            Backend::Avx2 => x86::decode_two_wide_unsafe(bytes),
+            _ => {
+                let (first_num, first_len) = self.decode_dispatch::<T>(bytes);
+                let (second_num, second_len) = self.decode_dispatch::<U>(bytes.add(first_len));
+
+                (first_num, second_num, first_len as u8, second_len as u8)
+            }
+        }
+    }
+
+    /// See [`decode_four_unsafe`](crate::decode_four_unsafe).
+    ///
+    /// # Safety
+    /// Same as [`decode_four_unsafe`](crate::decode_four_unsafe), and the backend must be
+    /// supported by the CPU.
+    #[inline]
+    pub unsafe fn decode_four_unsafe<
+        T: VarIntTarget,
+        U: VarIntTarget,
+        V: VarIntTarget,
+        W: VarIntTarget,
+    >(
+        self,
+        bytes: *const u8,
+    ) -> (T, U, V, W, u8, u8, u8, u8, bool) {
+        decode::assert_fits_four::<T, U, V, W>();
+
+        match self {
+            Backend::Scalar => scalar::decode_four_unsafe(bytes),
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Backend::Sse2 => scalar::decode_four_unsafe(bytes),
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Backend::Ssse3 => x86::decode_four_ssse3(bytes),
+            #[cfg(target_arch = "x86_64")]
+            Backend::Ssse3Pext => x86::decode_four_bmi2(bytes),
+            #[cfg(target_arch = "x86_64")]
+            Backend::Avx2 => x86::decode_four_ssse3(bytes),
+            #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+            Backend::Neon => neon::decode_four_unsafe(bytes),
+        }
+    }
+
+    /// See [`decode_two_zigzag_unsafe`](crate::decode_two_zigzag_unsafe).
+    ///
+    /// # Safety
+    /// Same as [`decode_two_zigzag_unsafe`](crate::decode_two_zigzag_unsafe), and the backend must
+    /// be supported by the CPU.
+    #[inline]
+    pub unsafe fn decode_two_zigzag_unsafe<T: SignedVarIntTarget, U: SignedVarIntTarget>(
+        self,
+        bytes: *const u8,
+    ) -> (T, U, u8, u8) {
+        decode::assert_fits_two::<T::Unsigned, U::Unsigned>();
+
+        let (first, second, first_len, second_len) = match self {
+            Backend::Scalar => zigzag::decode_two_zigzag_scalar(bytes),
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Backend::Sse2 => zigzag::decode_two_zigzag_scalar(bytes),
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Backend::Ssse3 => x86::decode_two_zigzag_ssse3(bytes),
+            #[cfg(target_arch = "x86_64")]
+            Backend::Ssse3Pext => x86::decode_two_zigzag_bmi2(bytes),
+            #[cfg(target_arch = "x86_64")]
+            Backend::Avx2 => x86::decode_two_zigzag_ssse3(bytes),
+            #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+            Backend::Neon => neon::decode_two_zigzag_unsafe(bytes),
+        };
+
+        (
+            num::to_signed(first),
+            num::to_signed(second),
+            first_len,
+            second_len,
+        )
+    }
+
+    /// See [`decode_four_zigzag_unsafe`](crate::decode_four_zigzag_unsafe).
+    ///
+    /// # Safety
+    /// Same as [`decode_four_zigzag_unsafe`](crate::decode_four_zigzag_unsafe), and the backend
+    /// must be supported by the CPU.
+    #[inline]
+    pub unsafe fn decode_four_zigzag_unsafe<
+        T: SignedVarIntTarget,
+        U: SignedVarIntTarget,
+        V: SignedVarIntTarget,
+        W: SignedVarIntTarget,
+    >(
+        self,
+        bytes: *const u8,
+    ) -> (T, U, V, W, u8, u8, u8, u8, bool) {
+        decode::assert_fits_four::<T::Unsigned, U::Unsigned, V::Unsigned, W::Unsigned>();
+
+        let (first, second, third, fourth, first_len, second_len, third_len, fourth_len, invalid) =
+            match self {
+                Backend::Scalar => zigzag::decode_four_zigzag_scalar(bytes),
+                #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+                Backend::Sse2 => zigzag::decode_four_zigzag_scalar(bytes),
+                #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+                Backend::Ssse3 => x86::decode_four_zigzag_ssse3(bytes),
+                #[cfg(target_arch = "x86_64")]
+                Backend::Ssse3Pext => x86::decode_four_zigzag_bmi2(bytes),
+                #[cfg(target_arch = "x86_64")]
+                Backend::Avx2 => x86::decode_four_zigzag_ssse3(bytes),
+                #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+                Backend::Neon => neon::decode_four_zigzag_unsafe(bytes),
+            };
+
+        (
+            num::to_signed(first),
+            num::to_signed(second),
+            num::to_signed(third),
+            num::to_signed(fourth),
+            first_len,
+            second_len,
+            third_len,
+            fourth_len,
+            invalid,
+        )
+    }
+
+    /// See [`decode_eight_u8_unsafe`](crate::decode_eight_u8_unsafe).
+    ///
+    /// # Safety
+    /// Same as [`decode_eight_u8_unsafe`](crate::decode_eight_u8_unsafe), and the backend must be
+    /// supported by the CPU.
+    #[inline]
+    pub unsafe fn decode_eight_u8_unsafe(self, bytes: *const u8) -> ([u8; 8], u8) {
+        match self {
+            Backend::Scalar => scalar::decode_eight_u8_unsafe(bytes),
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Backend::Sse2 => scalar::decode_eight_u8_unsafe(bytes),
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Backend::Ssse3 => x86::decode_eight_u8_unsafe(bytes),
+            #[cfg(target_arch = "x86_64")]
+            Backend::Ssse3Pext | Backend::Avx2 => x86::decode_eight_u8_unsafe(bytes),
+            #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+            Backend::Neon => neon::decode_eight_u8_unsafe(bytes),
+        }
+    }
+
+    /// See [`decode_eight_u8_checked_unsafe`](crate::decode_eight_u8_checked_unsafe).
+    ///
+    /// # Safety
+    /// Same as [`decode_eight_u8_checked_unsafe`](crate::decode_eight_u8_checked_unsafe), and the
+    /// backend must be supported by the CPU.
+    #[inline]
+    pub unsafe fn decode_eight_u8_checked_unsafe(self, bytes: *const u8) -> ([u8; 8], u8, u8) {
+        match self {
+            Backend::Scalar => scalar::decode_eight_u8_checked_unsafe(bytes),
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Backend::Sse2 => scalar::decode_eight_u8_checked_unsafe(bytes),
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Backend::Ssse3 => x86::decode_eight_u8_checked_unsafe(bytes),
+            #[cfg(target_arch = "x86_64")]
+            Backend::Ssse3Pext | Backend::Avx2 => x86::decode_eight_u8_checked_unsafe(bytes),
+            #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+            Backend::Neon => neon::decode_eight_u8_checked_unsafe(bytes),
+        }
+    }
+
+    /// See [`decode_eight_u16_unsafe`](crate::decode_eight_u16_unsafe). Backends other than
+    /// [`Backend::Avx2`] make two calls to [`Backend::decode_four_unsafe`].
+    ///
+    /// # Safety
+    /// Same as [`decode_eight_u16_unsafe`](crate::decode_eight_u16_unsafe), and the backend must be
+    /// supported by the CPU.
+    #[inline]
+    pub unsafe fn decode_eight_u16_unsafe(self, bytes: *const u8) -> ([u16; 8], u8) {
+        decode::read_window::<32>(bytes);
+
+        match self {
+            #[cfg(target_arch = "x86_64")]
+            Backend::Avx2 => x86::decode_eight_u16_unsafe(bytes),
+            _ => {
+                let (a, b, c, d, a_len, b_len, c_len, d_len, _) =
+                    self.decode_four_unsafe::<u16, u16, u16, u16>(bytes);
+                let first_len = a_len + b_len + c_len + d_len;
+                let (e, f, g, h, e_len, f_len, g_len, h_len, _) =
+                    self.decode_four_unsafe::<u16, u16, u16, u16>(bytes.add(first_len as usize));
+
+                (
+                    [a, b, c, d, e, f, g, h],
+                    first_len + e_len + f_len + g_len + h_len,
+                )
+            }
+        }
+    }
+
+    /// See [`decode_sixteen_u8_unsafe`](crate::decode_sixteen_u8_unsafe). Backends other than
+    /// [`Backend::Avx2`] make two calls to [`Backend::decode_eight_u8_unsafe`].
+    ///
+    /// # Safety
+    /// Same as [`decode_sixteen_u8_unsafe`](crate::decode_sixteen_u8_unsafe), and the backend must
+    /// be supported by the CPU.
+    #[inline]
+    pub unsafe fn decode_sixteen_u8_unsafe(self, bytes: *const u8) -> ([u8; 16], u8) {
+        decode::read_window::<32>(bytes);
+
+        match self {
+            #[cfg(target_arch = "x86_64")]
+            Backend::Avx2 => x86::decode_sixteen_u8_unsafe(bytes),
+            _ => {
+                let (first, first_len) = self.decode_eight_u8_unsafe(bytes);
+                let (second, second_len) =
+                    self.decode_eight_u8_unsafe(bytes.add(first_len as usize));
+
+                let mut nums = [0; 16];
+                nums[..8].copy_from_slice(&first);
+                nums[8..].copy_from_slice(&second);
+                (nums, first_len + second_len)
+            }
+        }
+    }
+
+    /// See [`encode`](crate::encode()).
+    ///
+    /// **Panics:** if the backend is not supported by the CPU.
+    #[inline]
+    pub fn encode<T: VarIntTarget>(self, num: T) -> ([u8; 16], u8) {
+        self.assert_supported();
+        unsafe { self.encode_unsafe(num) }
+    }
+
+    /// See [`encode_wide`](crate::encode_wide).
+    ///
+    /// **Panics:** if the backend is not supported by the CPU.
+    #[inline]
+    pub fn encode_wide<T: VarIntTarget>(self, num: T) -> ([u8; 32], u8) {
+        self.assert_supported();
+
+        if T::MAX_VARINT_BYTES > 16 {
+            match self {
+                Backend::Scalar => encode::encode_wide_scalar(num),
+                _ => encode::encode_wide_impl(num),
+            }
+        } else {
+            let (data, size) = unsafe { self.encode_dispatch(num) };
+
+            let mut wide = [0u8; 32];
+            wide[..16].copy_from_slice(&data);
+            (wide, size)
+        }
+    }
+
+    /// See [`encode_zigzag`](crate::encode_zigzag).
+    ///
+    /// **Panics:** if the backend is not supported by the CPU.
+    #[inline]
+    pub fn encode_zigzag<T: SignedVarIntTarget>(self, num: T) -> ([u8; 16], u8) {
+        self.encode(T::Unsigned::zigzag(num))
+    }
+
+    /// See [`encode_sign_extended`](crate::encode_sign_extended).
+    ///
+    /// **Panics:** if the backend is not supported by the CPU.
+    #[inline]
+    pub fn encode_sign_extended<T: SignedVarIntTarget>(self, num: T) -> ([u8; 16], u8) {
+        self.encode(encode::sign_extend(num))
+    }
+
+    /// See [`encode_nonzero`](crate::encode_nonzero).
+    ///
+    /// **Panics:** if the backend is not supported by the CPU.
+    #[inline]
+    pub fn encode_nonzero<T: NonZeroVarIntTarget>(self, num: T) -> ([u8; 16], u8) {
+        self.encode(num.get())
+    }
+
+    /// See [`encode_to_slice`](crate::encode_to_slice).
+    ///
+    /// **Panics:** if the backend is not supported by the CPU, or if the slice is too small to
+    /// contain the varint.
+    #[inline]
+    pub fn encode_to_slice<T: VarIntTarget>(self, num: T, slice: &mut [u8]) -> u8 {
+        let (data, size) = self.encode_wide(num);
+        slice[..size as usize].copy_from_slice(&data[..size as usize]);
+
+        size
+    }
+
+    /// See [`encode_to_ptr`](crate::encode_to_ptr).
+    ///
+    /// # Safety
+    /// The backend must be supported by the CPU, and there must be at least 16 bytes of writable
+    /// memory after the start of the pointer.
+    #[inline]
+    pub unsafe fn encode_to_ptr<T: VarIntTarget>(self, num: T, ptr: *mut u8) -> u8 {
+        let (data, size) = self.encode_unsafe(num);
+        ptr.cast::<[u8; 16]>().write_unaligned(data);
+
+        size
+    }
+
+    /// See [`encode_to_uninit`](crate::encode_to_uninit).
+    ///
+    /// **Panics:** if the backend is not supported by the CPU, or if the slice is too small to
+    /// contain the varint.
+    #[inline]
+    pub fn encode_to_uninit<T: VarIntTarget>(self, num: T, slice: &mut [MaybeUninit<u8>]) -> u8 {
+        let (data, size) = self.encode_wide(num);
+        let dest = &mut slice[..size as usize];
+        unsafe {
+            core::ptr::copy_nonoverlapping(data.as_ptr(), dest.as_mut_ptr().cast(), dest.len());
+        }
+
+        size
+    }
+
+    /// See [`encode_padded`](crate::encode_padded).
+    ///
+    /// **Panics:** if the backend is not supported by the CPU, or if `width` is out of range for
+    /// the number.
+    #[inline]
+    pub fn encode_padded<T: VarIntTarget>(self, num: T, width: u8) -> [u8; 16] {
+        self.assert_supported();
+        encode::assert_padded_width(num, width);
+        unsafe { self.encode_padded_unsafe(num, width) }
+    }
+
+    /// See [`encode_padded_unsafe`](crate::encode_padded_unsafe).
+    ///
+    /// # Safety
+    /// The backend must be supported by the CPU, and `width` must be at least the encoded length of
+    /// the number, and at most the maximum encoded length of the target type.
+    #[inline]
+    pub unsafe fn encode_padded_unsafe<T: VarIntTarget>(self, num: T, width: u8) -> [u8; 16] {
+        num::assert_fits_vector::<T>();
+
+        match self {
+            Backend::Scalar => encode::encode_padded_scalar(num, width),
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Backend::Sse2 | Backend::Ssse3 => encode::encode_padded_impl::<T, false>(num, width),
+            #[cfg(target_arch = "x86_64")]
+            Backend::Avx2 => encode::encode_padded_impl::<T, false>(num, width),
+            #[cfg(target_arch = "x86_64")]
+            Backend::Ssse3Pext => encode::encode_padded_pdep(num, width),
+            #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+            Backend::Neon => encode::encode_padded_impl::<T, false>(num, width),
+        }
+    }
+
+    /// See [`encode_slice`](crate::encode_slice).
+    ///
+    /// **Panics:** if the backend is not supported by the CPU, or if the slice is too small to
+    /// contain the varints.
+    #[inline]
+    pub fn encode_slice<T: VarIntTarget>(self, values: &[T], out: &mut [u8]) -> usize {
+        self.assert_supported();
+
+        let batch = match self {
+            Backend::Scalar => encode::slice::encode_batch_scalar::<T>,
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Backend::Sse2 => encode::slice::encode_batch_scalar::<T>,
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Backend::Ssse3 => encode::slice::encode_batch_ssse3::<T>,
+            #[cfg(target_arch = "x86_64")]
+            Backend::Ssse3Pext => encode::slice::encode_batch_pdep::<T>,
+            #[cfg(target_arch = "x86_64")]
+            Backend::Avx2 => encode::slice::encode_batch_ssse3::<T>,
+            #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+            Backend::Neon => encode::slice::encode_batch_neon::<T>,
+        };
+        unsafe { encode::slice::encode_slice_with(values, out, batch) }
+    }
+
+    /// See [`encode_two_unsafe`](crate::encode_two_unsafe).
+    ///
+    /// # Safety
+    /// The backend must be supported by the CPU.
+    #[inline]
+    pub unsafe fn encode_two_unsafe<T: VarIntTarget, U: VarIntTarget>(
+        self,
+        first: T,
+        second: U,
+    ) -> ([u8; 16], u8, u8, u8) {
+        decode::assert_fits_two::<T, U>();
+
+        match self {
+            Backend::Scalar => multi::encode_two_scalar(first, second),
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Backend::Sse2 => multi::encode_two_scalar(first, second),
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Backend::Ssse3 => multi::encode_two_ssse3(first, second),
+            #[cfg(target_arch = "x86_64")]
+            Backend::Ssse3Pext => multi::encode_two_pdep(first, second),
+            #[cfg(target_arch = "x86_64")]
+            Backend::Avx2 => multi::encode_two_ssse3(first, second),
+            #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+            Backend::Neon => multi::encode_two_neon(first, second),
+        }
+    }
+
+    /// See [`encode_four_unsafe`](crate::encode_four_unsafe).
+    ///
+    /// # Safety
+    /// The backend must be supported by the CPU.
+    #[inline]
+    pub unsafe fn encode_four_unsafe<
+        T: VarIntTarget,
+        U: VarIntTarget,
+        V: VarIntTarget,
+        W: VarIntTarget,
+    >(
+        self,
+        first: T,
+        second: U,
+        third: V,
+        fourth: W,
+    ) -> ([u8; 32], u8, u8, u8, u8, u8) {
+        multi::assert_fits_encode_four::<T, U, V, W>();
+
+        match self {
+            Backend::Scalar => multi::encode_four_scalar(first, second, third, fourth),
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Backend::Sse2 => multi::encode_four_scalar(first, second, third, fourth),
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Backend::Ssse3 => multi::encode_four_ssse3(first, second, third, fourth),
+            #[cfg(target_arch = "x86_64")]
+            Backend::Ssse3Pext => multi::encode_four_pdep(first, second, third, fourth),
+            #[cfg(target_arch = "x86_64")]
+            Backend::Avx2 => multi::encode_four_ssse3(first, second, third, fourth),
+            #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+            Backend::Neon => multi::encode_four_neon(first, second, third, fourth),
+        }
+    }
+
+    /// See [`encode_eight_u8_unsafe`](crate::encode_eight_u8_unsafe).
+    ///
+    /// # Safety
+    /// The backend must be supported by the CPU.
+    #[inline]
+    pub unsafe fn encode_eight_u8_unsafe(self, nums: [u8; 8]) -> ([u8; 16], [u8; 8], u8) {
+        match self {
+            Backend::Scalar => multi::encode_eight_u8_scalar(nums),
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Backend::Sse2 => multi::encode_eight_u8_scalar(nums),
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Backend::Ssse3 => multi::encode_eight_u8_ssse3(nums),
+            #[cfg(target_arch = "x86_64")]
+            Backend::Ssse3Pext | Backend::Avx2 => multi::encode_eight_u8_ssse3(nums),
+            #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+            Backend::Neon => multi::encode_eight_u8_neon(nums),
+        }
+    }
+
+    /// See [`encode_unsafe`](crate::encode_unsafe).
+    ///
+    /// # Safety
+    /// The backend must be supported by the CPU.
+    #[inline]
+    pub unsafe fn encode_unsafe<T: VarIntTarget>(self, num: T) -> ([u8; 16], u8) {
+        num::assert_fits_vector::<T>();
+        self.encode_dispatch(num)
+    }
+
+    /// [`Backend::encode_unsafe`] without the width check, for callers that only reach it for
+    /// types whose varints fit in 16 bytes
+    #[inline(always)]
+    unsafe fn encode_dispatch<T: VarIntTarget>(self, num: T) -> ([u8; 16], u8) {
+        match self {
+            Backend::Scalar => encode::encode_scalar(num),
+            // Single values are encoded with SSE2, so the x86 backends without PDEP share one
+            // implementation
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Backend::Sse2 | Backend::Ssse3 => encode::encode_impl::<T, false>(num),
+            #[cfg(target_arch = "x86_64")]
+            Backend::Avx2 => encode::encode_impl::<T, false>(num),
+            #[cfg(target_arch = "x86_64")]
+            Backend::Ssse3Pext => encode::encode_pdep(num),
+            #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+            Backend::Neon => encode::encode_impl::<T, false>(num),
+        }
+    }
+}
@@ -0,0 +1,142 @@
+//! Runtime CPU feature detection.
+//!
+//! When a feature is enabled at compile time (e.g. with `-C target-cpu=native`), the `has_*`
+//! functions are constant and the SIMD kernels are called directly, exactly as before. Otherwise
+//! the features are detected once and the result is cached in a static.
+//!
+//! Most kernels are generic over the target types, and Rust has no generic statics, so rather
+//! than caching a function pointer per kernel we cache the detected feature set. Checking it is a
+//! relaxed load and a well-predicted branch.
+//!
+//! Whether PDEP/PEXT are worth using depends on the microarchitecture rather than a feature flag,
+//! see [`pdep`]. The `fast_pdep` and `very_fast_pdep` cfgs set by the build script override the
+//! runtime detection.
+//!
+//! Under Miri, every feature is reported as unavailable so that only the portable code paths run.
+
+use core::sync::atomic::{AtomicU8, Ordering};
+
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64::__cpuid;
+
+#[cfg(target_arch = "x86_64")]
+pub(crate) mod pdep;
+
+const INITIALIZED: u8 = 1 << 7;
+const SSSE3: u8 = 1 << 0;
+const AVX2: u8 = 1 << 1;
+const LZCNT: u8 = 1 << 2;
+const BMI2: u8 = 1 << 3;
+#[cfg(target_arch = "x86_64")]
+const FAST_PDEP: u8 = 1 << 4;
+#[cfg(target_arch = "x86_64")]
+const VERY_FAST_PDEP: u8 = 1 << 5;
+
+static FEATURES: AtomicU8 = AtomicU8::new(0);
+
+#[inline(always)]
+fn features() -> u8 {
+    let features = FEATURES.load(Ordering::Relaxed);
+    if features & INITIALIZED != 0 {
+        features
+    } else {
+        detect()
+    }
+}
+
+#[cold]
+fn detect() -> u8 {
+    #[allow(unused_mut)]
+    let mut features = INITIALIZED | detect_runtime();
+
+    #[cfg(target_arch = "x86_64")]
+    if cfg!(target_feature = "bmi2") || features & BMI2 != 0 {
+        features |= match detect_pdep() {
+            pdep::PdepPerf::VeryFast => FAST_PDEP | VERY_FAST_PDEP,
+            pdep::PdepPerf::Fast => FAST_PDEP,
+            pdep::PdepPerf::Slow => 0,
+        };
+    }
+
+    FEATURES.store(features, Ordering::Relaxed);
+    features
+}
+
+#[cfg(target_arch = "x86_64")]
+#[allow(unused_unsafe)] // __cpuid is safe on newer compilers
+fn detect_pdep() -> pdep::PdepPerf {
+    let leaf0 = unsafe { __cpuid(0) };
+    let mut vendor = [0u8; 12];
+    vendor[..4].copy_from_slice(&leaf0.ebx.to_le_bytes());
+    vendor[4..8].copy_from_slice(&leaf0.edx.to_le_bytes());
+    vendor[8..].copy_from_slice(&leaf0.ecx.to_le_bytes());
+
+    let leaf1 = unsafe { __cpuid(1) };
+
+    pdep::pdep_perf(&vendor, leaf1.eax)
+}
+
+#[cfg(feature = "std")]
+fn detect_runtime() -> u8 {
+    let mut features = 0;
+    if std::is_x86_feature_detected!("ssse3") {
+        features |= SSSE3;
+    }
+    if std::is_x86_feature_detected!("avx2") {
+        features |= AVX2;
+    }
+    if std::is_x86_feature_detected!("lzcnt") {
+        features |= LZCNT;
+    }
+    if std::is_x86_feature_detected!("bmi2") {
+        features |= BMI2;
+    }
+    features
+}
+
+// Without the standard library there is no portable way of asking the OS whether it saves the AVX
+// registers, so only the features enabled at compile time are used
+#[cfg(not(feature = "std"))]
+fn detect_runtime() -> u8 {
+    0
+}
+
+/// Whether SSSE3 instructions can be used
+#[inline(always)]
+pub(crate) fn has_ssse3() -> bool {
+    !cfg!(miri) && (cfg!(target_feature = "ssse3") || features() & SSSE3 != 0)
+}
+
+/// Whether AVX2 instructions can be used
+#[inline(always)]
+pub(crate) fn has_avx2() -> bool {
+    !cfg!(miri) && (cfg!(target_feature = "avx2") || features() & AVX2 != 0)
+}
+
+/// Whether the LZCNT instruction can be used
+#[inline(always)]
+pub(crate) fn has_lzcnt() -> bool {
+    !cfg!(miri) && (cfg!(target_feature = "lzcnt") || features() & LZCNT != 0)
+}
+
+/// Whether BMI2 instructions can be used, regardless of their performance
+#[inline(always)]
+#[cfg(target_arch = "x86_64")]
+pub(crate) fn has_bmi2() -> bool {
+    !cfg!(miri) && (cfg!(target_feature = "bmi2") || features() & BMI2 != 0)
+}
+
+/// Whether PDEP/PEXT can be used and are faster than the equivalent bit manipulation
+#[inline(always)]
+#[cfg(target_arch = "x86_64")]
+pub(crate) fn has_fast_pdep() -> bool {
+    !cfg!(miri) && (cfg!(all(target_feature = "bmi2", fast_pdep)) || features() & FAST_PDEP != 0)
+}
+
+/// Whether PDEP/PEXT are fast enough to be preferred over the SSE2 "turbo" paths
+#[inline(always)]
+#[cfg(target_arch = "x86_64")]
+pub(crate) fn has_very_fast_pdep() -> bool {
+    !cfg!(miri)
+        && (cfg!(all(target_feature = "bmi2", very_fast_pdep)) || features() & VERY_FAST_PDEP != 0)
+}
@@ -0,0 +1,35 @@
+//! Classification of PDEP/PEXT performance by CPU vendor and family.
+//!
+//! This file is also included by the build script for the `native-optimizations` feature, so it
+//! must not depend on anything else in the crate.
+
+/// Performance of PDEP/PEXT relative to arithmetic/bit operations
+#[derive(Clone, Copy, PartialOrd, PartialEq)]
+pub(crate) enum PdepPerf {
+    VeryFast = 20,
+    Fast = 10,
+    Slow = 0,
+}
+
+/// Classifies a CPU given the vendor string from CPUID leaf 0 (EBX, EDX, ECX) and the processor
+/// signature from CPUID leaf 1 (EAX). Assumes that BMI2 is supported.
+pub(crate) fn pdep_perf(vendor: &[u8; 12], signature: u32) -> PdepPerf {
+    let base_family = (signature >> 8) & 0xf;
+    let family = if base_family == 0xf {
+        base_family + ((signature >> 20) & 0xff)
+    } else {
+        base_family
+    };
+
+    match vendor {
+        b"GenuineIntel" => PdepPerf::VeryFast,
+        // Excavator (15h) and Zen, Zen+, and Zen 2 (17h) implement these instructions in microcode
+        b"AuthenticAMD" if family < 0x19 => PdepPerf::Slow,
+        // Zen 3 and Zen 4 (19h) and Zen 5 (1Ah) implement them in hardware
+        b"AuthenticAMD" => PdepPerf::Fast,
+        // Hygon Dhyana (18h) is derived from Zen
+        b"HygonGenuine" => PdepPerf::Slow,
+        // We have no idea, so play it safe
+        _ => PdepPerf::Slow,
+    }
+}
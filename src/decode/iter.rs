@@ -0,0 +1,341 @@
+//! Iteration over a buffer of adjacent varints.
+
+use core::iter::FusedIterator;
+
+use super::decode;
+use super::slice::{decode_four_checked, decode_one_checked, decode_two_checked};
+use crate::num::{SignedVarIntTarget, VarIntTarget};
+use crate::VarIntDecodeError;
+
+/// An iterator over the varints in a buffer, created by [`VarIntIter::new`].
+///
+/// While at least 16 bytes remain, values are decoded in batches with the widest applicable
+/// kernel ([`decode_four_unsafe`] for `u8` and `u16`, [`decode_two_unsafe`] for `u32`, and
+/// [`decode_unsafe`] for `u64`). The last bytes, and all `u128` values, are decoded one at a time
+/// with [`decode`].
+///
+/// Each item is either a decoded value or the error that stopped the iteration. After an error,
+/// the iterator only returns `None`, and [`VarIntIter::offset`] points to the start of the invalid
+/// varint.
+///
+/// # Examples
+/// ```
+/// use varint_simd::{VarIntDecodeError, VarIntIter};
+///
+/// let mut iter = VarIntIter::<u32>::new(&[185, 10, 1, 172]);
+/// assert_eq!(iter.next(), Some(Ok(1337)));
+/// assert_eq!(iter.next(), Some(Ok(1)));
+/// assert_eq!(iter.offset(), 3);
+/// assert_eq!(iter.next(), Some(Err(VarIntDecodeError::NotEnoughBytes)));
+/// assert_eq!(iter.next(), None);
+/// assert_eq!(iter.remaining(), &[172]);
+/// ```
+///
+/// [`decode_four_unsafe`]: crate::decode_four_unsafe
+/// [`decode_two_unsafe`]: crate::decode_two_unsafe
+/// [`decode_unsafe`]: crate::decode_unsafe
+#[derive(Debug, Clone)]
+pub struct VarIntIter<'a, T: VarIntTarget> {
+    bytes: &'a [u8],
+    /// Offset of the next value to be returned
+    offset: usize,
+    /// Offset of the first byte after the buffered values
+    read: usize,
+    buffer: [T; 4],
+    lens: [u8; 4],
+    start: u8,
+    end: u8,
+    failed: bool,
+}
+
+impl<'a, T: VarIntTarget> VarIntIter<'a, T> {
+    /// Creates an iterator over the varints in `bytes`
+    #[inline]
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            offset: 0,
+            read: 0,
+            buffer: [T::cast_u32(0); 4],
+            lens: [0; 4],
+            start: 0,
+            end: 0,
+            failed: false,
+        }
+    }
+
+    /// The number of bytes taken up by the values returned so far
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The bytes that have not been returned as values yet
+    #[inline]
+    pub fn remaining(&self) -> &'a [u8] {
+        &self.bytes[self.offset..]
+    }
+
+    /// Decodes the next values into the buffer. Returns `false` if there are no values left, and an
+    /// error if the next value is invalid.
+    #[inline(always)]
+    fn refill(&mut self) -> Result<bool, VarIntDecodeError> {
+        let input = &self.bytes[self.read..];
+
+        if T::MAX_VARINT_BYTES <= 16 && input.len() >= 16 {
+            let window = &input[..16];
+
+            let decoded = if T::MAX_VARINT_BYTES <= 4 {
+                decode_four_checked::<T, false, false>(window).map(|(nums, lens)| {
+                    self.buffer = nums;
+                    self.lens = lens;
+                    4
+                })
+            } else if T::MAX_VARINT_BYTES <= 8 {
+                decode_two_checked::<T, false, false>(window).map(|(nums, lens)| {
+                    self.buffer[..2].copy_from_slice(&nums);
+                    self.lens[..2].copy_from_slice(&lens);
+                    2
+                })
+            } else {
+                decode_one_checked::<T, false, false>(window).map(|(num, len)| {
+                    self.buffer[0] = num;
+                    self.lens[0] = len as u8;
+                    1
+                })
+            };
+
+            if let Some(count) = decoded {
+                self.read += self.lens[..count]
+                    .iter()
+                    .map(|len| *len as usize)
+                    .sum::<usize>();
+                self.start = 0;
+                self.end = count as u8;
+                return Ok(true);
+            }
+        } else if input.is_empty() {
+            return Ok(false);
+        }
+
+        // Either near the end of the input, or something in the window looks wrong, so let the
+        // checked decoder either report the error or make progress
+        let (num, len) = decode::<T>(input)?;
+        self.buffer[0] = num;
+        self.lens[0] = len as u8;
+        self.read += len;
+        self.start = 0;
+        self.end = 1;
+        Ok(true)
+    }
+}
+
+impl<'a, T: VarIntTarget> Iterator for VarIntIter<'a, T> {
+    type Item = Result<T, VarIntDecodeError>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.start == self.end {
+            if self.failed {
+                return None;
+            }
+
+            match self.refill() {
+                Ok(true) => {}
+                Ok(false) => return None,
+                Err(error) => {
+                    self.failed = true;
+                    return Some(Err(error));
+                }
+            }
+        }
+
+        let index = self.start as usize;
+        self.start += 1;
+        self.offset += self.lens[index] as usize;
+        Some(Ok(self.buffer[index]))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let buffered = (self.end - self.start) as usize;
+        if self.failed {
+            return (buffered, Some(buffered));
+        }
+
+        // Every value, as well as an error, takes up at least one byte
+        let unread = self.bytes.len() - self.read;
+        (buffered + (unread != 0) as usize, Some(buffered + unread))
+    }
+}
+
+impl<'a, T: VarIntTarget> FusedIterator for VarIntIter<'a, T> {}
+
+/// An iterator over the ZigZag encoded varints in a buffer, created by [`VarIntZigZagIter::new`].
+/// See also: [`VarIntIter`]
+///
+/// # Examples
+/// ```
+/// use varint_simd::VarIntZigZagIter;
+///
+/// let values: Result<Vec<i32>, _> = VarIntZigZagIter::new(&[39, 40, 0]).collect();
+/// assert_eq!(values, Ok(vec![-20, 20, 0]));
+/// ```
+#[derive(Debug, Clone)]
+pub struct VarIntZigZagIter<'a, T: SignedVarIntTarget> {
+    inner: VarIntIter<'a, T::Unsigned>,
+}
+
+impl<'a, T: SignedVarIntTarget> VarIntZigZagIter<'a, T> {
+    /// Creates an iterator over the ZigZag encoded varints in `bytes`
+    #[inline]
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            inner: VarIntIter::new(bytes),
+        }
+    }
+
+    /// The number of bytes taken up by the values returned so far
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.inner.offset()
+    }
+
+    /// The bytes that have not been returned as values yet
+    #[inline]
+    pub fn remaining(&self) -> &'a [u8] {
+        self.inner.remaining()
+    }
+}
+
+impl<'a, T: SignedVarIntTarget> Iterator for VarIntZigZagIter<'a, T> {
+    type Item = Result<T, VarIntDecodeError>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|r| r.map(|num| num.unzigzag()))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, T: SignedVarIntTarget> FusedIterator for VarIntZigZagIter<'a, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::{VarIntIter, VarIntZigZagIter};
+    use crate::tests::{NUMS_U128, NUMS_U16, NUMS_U32, NUMS_U64, NUMS_U8};
+    use crate::{
+        encode_wide, encode_wide_zigzag, SignedVarIntTarget, VarIntDecodeError, VarIntTarget,
+    };
+
+    fn check_iter<T: VarIntTarget>(nums: &[T]) {
+        // Every pair of values, so that each kernel sees every combination of lengths
+        let mut values = Vec::new();
+        let mut offsets = Vec::new();
+        let mut encoded = Vec::new();
+        for i in nums {
+            for j in nums {
+                for value in [*i, *j] {
+                    let (bytes, len) = encode_wide(value);
+                    encoded.extend_from_slice(&bytes[..len as usize]);
+                    values.push(value);
+                    offsets.push(encoded.len());
+                }
+            }
+        }
+
+        let mut iter = VarIntIter::<T>::new(&encoded);
+        for (i, (value, offset)) in values.iter().zip(&offsets).enumerate() {
+            let (lower, upper) = iter.size_hint();
+            let left = values.len() - i;
+            assert!(lower <= left && upper.unwrap() >= left);
+
+            assert_eq!(iter.next(), Some(Ok(*value)));
+            assert_eq!(iter.offset(), *offset);
+            assert_eq!(iter.remaining(), &encoded[*offset..]);
+        }
+        assert_eq!(iter.size_hint(), (0, Some(0)));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn test_iter() {
+        check_iter(&NUMS_U8[..]);
+        check_iter(&NUMS_U16[..]);
+        check_iter(&NUMS_U32[..]);
+        check_iter(&NUMS_U64[..]);
+        check_iter(&NUMS_U128[..]);
+    }
+
+    fn check_zigzag_iter<T: SignedVarIntTarget>(nums: &[T::Unsigned]) {
+        let values: Vec<T> = nums.iter().map(|num| num.unzigzag()).collect();
+
+        let mut encoded = Vec::new();
+        for value in &values {
+            let (bytes, len) = encode_wide_zigzag(*value);
+            encoded.extend_from_slice(&bytes[..len as usize]);
+        }
+
+        let decoded: Result<Vec<T>, _> = VarIntZigZagIter::new(&encoded).collect();
+        assert_eq!(decoded, Ok(values));
+    }
+
+    #[test]
+    fn test_zigzag_iter() {
+        check_zigzag_iter::<i8>(&NUMS_U8[..]);
+        check_zigzag_iter::<i16>(&NUMS_U16[..]);
+        check_zigzag_iter::<i32>(&NUMS_U32[..]);
+        check_zigzag_iter::<i64>(&NUMS_U64[..]);
+        check_zigzag_iter::<i128>(&NUMS_U128[..]);
+    }
+
+    fn check_iter_window_edges<T: VarIntTarget>() {
+        // A two-byte varint ending just before, at and just after the end of a 16-byte window
+        for end in [15, 16, 17] {
+            let mut input = vec![0x01u8; end];
+            input[end - 2] = 0x80;
+
+            let decoded: Vec<_> = VarIntIter::<T>::new(&input).collect();
+            assert_eq!(decoded.len(), end - 1, "{}", end);
+            assert_eq!(decoded[end - 2], Ok(T::cast_u32(0x80)));
+
+            // The truncated varint is reported once, after every value before it
+            let mut iter = VarIntIter::<T>::new(&input[..end - 1]);
+            for _ in 0..end - 2 {
+                assert_eq!(iter.next(), Some(Ok(T::cast_u32(1))));
+            }
+            assert_eq!(iter.next(), Some(Err(VarIntDecodeError::NotEnoughBytes)));
+            assert_eq!(iter.size_hint(), (0, Some(0)));
+            assert_eq!(iter.next(), None);
+            assert_eq!(iter.offset(), end - 2);
+            assert_eq!(iter.remaining(), &[0x80]);
+        }
+    }
+
+    #[test]
+    fn test_iter_window_edges() {
+        check_iter_window_edges::<u8>();
+        check_iter_window_edges::<u16>();
+        check_iter_window_edges::<u32>();
+        check_iter_window_edges::<u64>();
+    }
+
+    #[test]
+    fn test_iter_overflow_in_last_lane() {
+        // The fourth of a batch of u16 values overflows, after the first three are returned
+        let mut input = vec![0x01, 0x01, 0x01, 0x80, 0x80, 0x04];
+        input.resize(32, 0x01);
+
+        let mut iter = VarIntIter::<u16>::new(&input);
+        for _ in 0..3 {
+            assert_eq!(iter.next(), Some(Ok(1)));
+        }
+        assert_eq!(iter.next(), Some(Err(VarIntDecodeError::Overflow)));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.offset(), 3);
+    }
+}
@@ -1,12 +1,20 @@
+#[cfg(target_arch = "aarch64")]
+use core::arch::aarch64::*;
 #[cfg(target_arch = "x86")]
 use core::arch::x86::*;
 #[cfg(target_arch = "x86_64")]
 use core::arch::x86_64::*;
 
+/// Native 128-bit vector type the shuffle tables are stored as
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+pub type Vec128 = __m128i;
+#[cfg(target_arch = "aarch64")]
+pub type Vec128 = uint8x16_t;
+
 #[repr(align(16), C)]
 struct Align128<T>(T);
 
-pub static LOOKUP_DOUBLE_VEC: [__m128i; 90] = unsafe {
+pub static LOOKUP_DOUBLE_VEC: [Vec128; 90] = unsafe {
     core::mem::transmute(Align128::<[u8; 1440]>([
         0, 255, 255, 255, 255, 255, 255, 255, 1, 255, 255, 255, 255, 255, 255, 255, // 1, 1
         0, 255, 255, 255, 255, 255, 255, 255, 1, 2, 255, 255, 255, 255, 255, 255, // 1, 2
@@ -1128,7 +1136,7 @@ pub static LOOKUP_DOUBLE_STEP1: [(u8, u8, u8); 1024] = [
     (84, 11, 1), // 0b0000001111111111
 ];
 
-pub static LOOKUP_QUAD_VEC: [__m128i; 81] = unsafe {
+pub static LOOKUP_QUAD_VEC: [Vec128; 81] = unsafe {
     core::mem::transmute(Align128::<[u8; 1296]>([
         0, 255, 255, 255, 1, 255, 255, 255, 2, 255, 255, 255, 3, 255, 255, 255, // 1, 1, 1, 1
         0, 255, 255, 255, 1, 255, 255, 255, 2, 255, 255, 255, 3, 4, 255, 255, // 1, 1, 1, 2
@@ -5312,3 +5320,335 @@ pub static LOOKUP_QUAD_STEP1: [u32; 4096] = [
     0x8033311a, // 0b0000111111111110
     0x80333350, // 0b0000111111111111
 ];
+
+/// Shuffle for two u16 varints into 32-bit lanes, indexed by a 6-bit continuation mask, along with
+/// their total length. Unused bytes are 0x80, so an offset can be added to the whole shuffle
+#[cfg(target_arch = "x86_64")]
+pub static LOOKUP_DUAL_U16: [(u64, u8); 64] = [
+    (0x8080800180808000, 2), // 0b000000
+    (0x8080800280800100, 3), // 0b000001
+    (0x8080020180808000, 3), // 0b000010
+    (0x8080800380020100, 4), // 0b000011
+    (0x8080800180808000, 2), // 0b000100
+    (0x8080030280800100, 4), // 0b000101
+    (0x8003020180808000, 4), // 0b000110
+    (0x8080800380020100, 4), // 0b000111
+    (0x8080800180808000, 2), // 0b001000
+    (0x8080800280800100, 3), // 0b001001
+    (0x8080020180808000, 3), // 0b001010
+    (0x8080040380020100, 5), // 0b001011
+    (0x8080800180808000, 2), // 0b001100
+    (0x8004030280800100, 5), // 0b001101
+    (0x8003020180808000, 4), // 0b001110
+    (0x8080040380020100, 5), // 0b001111
+    (0x8080800180808000, 2), // 0b010000
+    (0x8080800280800100, 3), // 0b010001
+    (0x8080020180808000, 3), // 0b010010
+    (0x8080800380020100, 4), // 0b010011
+    (0x8080800180808000, 2), // 0b010100
+    (0x8080030280800100, 4), // 0b010101
+    (0x8003020180808000, 4), // 0b010110
+    (0x8080800380020100, 4), // 0b010111
+    (0x8080800180808000, 2), // 0b011000
+    (0x8080800280800100, 3), // 0b011001
+    (0x8080020180808000, 3), // 0b011010
+    (0x8005040380020100, 6), // 0b011011
+    (0x8080800180808000, 2), // 0b011100
+    (0x8004030280800100, 5), // 0b011101
+    (0x8003020180808000, 4), // 0b011110
+    (0x8005040380020100, 6), // 0b011111
+    (0x8080800180808000, 2), // 0b100000
+    (0x8080800280800100, 3), // 0b100001
+    (0x8080020180808000, 3), // 0b100010
+    (0x8080800380020100, 4), // 0b100011
+    (0x8080800180808000, 2), // 0b100100
+    (0x8080030280800100, 4), // 0b100101
+    (0x8003020180808000, 4), // 0b100110
+    (0x8080800380020100, 4), // 0b100111
+    (0x8080800180808000, 2), // 0b101000
+    (0x8080800280800100, 3), // 0b101001
+    (0x8080020180808000, 3), // 0b101010
+    (0x8080040380020100, 5), // 0b101011
+    (0x8080800180808000, 2), // 0b101100
+    (0x8004030280800100, 5), // 0b101101
+    (0x8003020180808000, 4), // 0b101110
+    (0x8080040380020100, 5), // 0b101111
+    (0x8080800180808000, 2), // 0b110000
+    (0x8080800280800100, 3), // 0b110001
+    (0x8080020180808000, 3), // 0b110010
+    (0x8080800380020100, 4), // 0b110011
+    (0x8080800180808000, 2), // 0b110100
+    (0x8080030280800100, 4), // 0b110101
+    (0x8003020180808000, 4), // 0b110110
+    (0x8080800380020100, 4), // 0b110111
+    (0x8080800180808000, 2), // 0b111000
+    (0x8080800280800100, 3), // 0b111001
+    (0x8080020180808000, 3), // 0b111010
+    (0x8005040380020100, 6), // 0b111011
+    (0x8080800180808000, 2), // 0b111100
+    (0x8004030280800100, 5), // 0b111101
+    (0x8003020180808000, 4), // 0b111110
+    (0x8005040380020100, 6), // 0b111111
+];
+
+/// Shuffle for four u8 varints into 16-bit lanes, indexed by an 8-bit continuation mask, along
+/// with their total length. Unused bytes are 0x80, so an offset can be added to the whole shuffle
+#[cfg(target_arch = "x86_64")]
+pub static LOOKUP_QUAD_U8: [(u64, u8); 256] = [
+    (0x8003800280018000, 4), // 0b00000000
+    (0x8004800380020100, 5), // 0b00000001
+    (0x8004800302018000, 5), // 0b00000010
+    (0x8004800380020100, 5), // 0b00000011
+    (0x8004030280018000, 5), // 0b00000100
+    (0x8005800403020100, 6), // 0b00000101
+    (0x8004800302018000, 5), // 0b00000110
+    (0x8005800403020100, 6), // 0b00000111
+    (0x0403800280018000, 5), // 0b00001000
+    (0x8005040380020100, 6), // 0b00001001
+    (0x8005040302018000, 6), // 0b00001010
+    (0x8005040380020100, 6), // 0b00001011
+    (0x8004030280018000, 5), // 0b00001100
+    (0x8005800403020100, 6), // 0b00001101
+    (0x8005040302018000, 6), // 0b00001110
+    (0x8005800403020100, 6), // 0b00001111
+    (0x8003800280018000, 4), // 0b00010000
+    (0x0504800380020100, 6), // 0b00010001
+    (0x0504800302018000, 6), // 0b00010010
+    (0x0504800380020100, 6), // 0b00010011
+    (0x0504030280018000, 6), // 0b00010100
+    (0x8006050403020100, 7), // 0b00010101
+    (0x0504800302018000, 6), // 0b00010110
+    (0x8006050403020100, 7), // 0b00010111
+    (0x0403800280018000, 5), // 0b00011000
+    (0x8005040380020100, 6), // 0b00011001
+    (0x8005040302018000, 6), // 0b00011010
+    (0x8005040380020100, 6), // 0b00011011
+    (0x0504030280018000, 6), // 0b00011100
+    (0x8006050403020100, 7), // 0b00011101
+    (0x8005040302018000, 6), // 0b00011110
+    (0x8006050403020100, 7), // 0b00011111
+    (0x8003800280018000, 4), // 0b00100000
+    (0x8004800380020100, 5), // 0b00100001
+    (0x8004800302018000, 5), // 0b00100010
+    (0x8004800380020100, 5), // 0b00100011
+    (0x8004030280018000, 5), // 0b00100100
+    (0x0605800403020100, 7), // 0b00100101
+    (0x8004800302018000, 5), // 0b00100110
+    (0x0605800403020100, 7), // 0b00100111
+    (0x0403800280018000, 5), // 0b00101000
+    (0x0605040380020100, 7), // 0b00101001
+    (0x0605040302018000, 7), // 0b00101010
+    (0x0605040380020100, 7), // 0b00101011
+    (0x8004030280018000, 5), // 0b00101100
+    (0x0605800403020100, 7), // 0b00101101
+    (0x0605040302018000, 7), // 0b00101110
+    (0x0605800403020100, 7), // 0b00101111
+    (0x8003800280018000, 4), // 0b00110000
+    (0x0504800380020100, 6), // 0b00110001
+    (0x0504800302018000, 6), // 0b00110010
+    (0x0504800380020100, 6), // 0b00110011
+    (0x0504030280018000, 6), // 0b00110100
+    (0x8006050403020100, 7), // 0b00110101
+    (0x0504800302018000, 6), // 0b00110110
+    (0x8006050403020100, 7), // 0b00110111
+    (0x0403800280018000, 5), // 0b00111000
+    (0x0605040380020100, 7), // 0b00111001
+    (0x0605040302018000, 7), // 0b00111010
+    (0x0605040380020100, 7), // 0b00111011
+    (0x0504030280018000, 6), // 0b00111100
+    (0x8006050403020100, 7), // 0b00111101
+    (0x0605040302018000, 7), // 0b00111110
+    (0x8006050403020100, 7), // 0b00111111
+    (0x8003800280018000, 4), // 0b01000000
+    (0x8004800380020100, 5), // 0b01000001
+    (0x8004800302018000, 5), // 0b01000010
+    (0x8004800380020100, 5), // 0b01000011
+    (0x8004030280018000, 5), // 0b01000100
+    (0x8005800403020100, 6), // 0b01000101
+    (0x8004800302018000, 5), // 0b01000110
+    (0x8005800403020100, 6), // 0b01000111
+    (0x0403800280018000, 5), // 0b01001000
+    (0x8005040380020100, 6), // 0b01001001
+    (0x8005040302018000, 6), // 0b01001010
+    (0x8005040380020100, 6), // 0b01001011
+    (0x8004030280018000, 5), // 0b01001100
+    (0x8005800403020100, 6), // 0b01001101
+    (0x8005040302018000, 6), // 0b01001110
+    (0x8005800403020100, 6), // 0b01001111
+    (0x8003800280018000, 4), // 0b01010000
+    (0x0504800380020100, 6), // 0b01010001
+    (0x0504800302018000, 6), // 0b01010010
+    (0x0504800380020100, 6), // 0b01010011
+    (0x0504030280018000, 6), // 0b01010100
+    (0x0706050403020100, 8), // 0b01010101
+    (0x0504800302018000, 6), // 0b01010110
+    (0x0706050403020100, 8), // 0b01010111
+    (0x0403800280018000, 5), // 0b01011000
+    (0x8005040380020100, 6), // 0b01011001
+    (0x8005040302018000, 6), // 0b01011010
+    (0x8005040380020100, 6), // 0b01011011
+    (0x0504030280018000, 6), // 0b01011100
+    (0x0706050403020100, 8), // 0b01011101
+    (0x8005040302018000, 6), // 0b01011110
+    (0x0706050403020100, 8), // 0b01011111
+    (0x8003800280018000, 4), // 0b01100000
+    (0x8004800380020100, 5), // 0b01100001
+    (0x8004800302018000, 5), // 0b01100010
+    (0x8004800380020100, 5), // 0b01100011
+    (0x8004030280018000, 5), // 0b01100100
+    (0x0605800403020100, 7), // 0b01100101
+    (0x8004800302018000, 5), // 0b01100110
+    (0x0605800403020100, 7), // 0b01100111
+    (0x0403800280018000, 5), // 0b01101000
+    (0x0605040380020100, 7), // 0b01101001
+    (0x0605040302018000, 7), // 0b01101010
+    (0x0605040380020100, 7), // 0b01101011
+    (0x8004030280018000, 5), // 0b01101100
+    (0x0605800403020100, 7), // 0b01101101
+    (0x0605040302018000, 7), // 0b01101110
+    (0x0605800403020100, 7), // 0b01101111
+    (0x8003800280018000, 4), // 0b01110000
+    (0x0504800380020100, 6), // 0b01110001
+    (0x0504800302018000, 6), // 0b01110010
+    (0x0504800380020100, 6), // 0b01110011
+    (0x0504030280018000, 6), // 0b01110100
+    (0x0706050403020100, 8), // 0b01110101
+    (0x0504800302018000, 6), // 0b01110110
+    (0x0706050403020100, 8), // 0b01110111
+    (0x0403800280018000, 5), // 0b01111000
+    (0x0605040380020100, 7), // 0b01111001
+    (0x0605040302018000, 7), // 0b01111010
+    (0x0605040380020100, 7), // 0b01111011
+    (0x0504030280018000, 6), // 0b01111100
+    (0x0706050403020100, 8), // 0b01111101
+    (0x0605040302018000, 7), // 0b01111110
+    (0x0706050403020100, 8), // 0b01111111
+    (0x8003800280018000, 4), // 0b10000000
+    (0x8004800380020100, 5), // 0b10000001
+    (0x8004800302018000, 5), // 0b10000010
+    (0x8004800380020100, 5), // 0b10000011
+    (0x8004030280018000, 5), // 0b10000100
+    (0x8005800403020100, 6), // 0b10000101
+    (0x8004800302018000, 5), // 0b10000110
+    (0x8005800403020100, 6), // 0b10000111
+    (0x0403800280018000, 5), // 0b10001000
+    (0x8005040380020100, 6), // 0b10001001
+    (0x8005040302018000, 6), // 0b10001010
+    (0x8005040380020100, 6), // 0b10001011
+    (0x8004030280018000, 5), // 0b10001100
+    (0x8005800403020100, 6), // 0b10001101
+    (0x8005040302018000, 6), // 0b10001110
+    (0x8005800403020100, 6), // 0b10001111
+    (0x8003800280018000, 4), // 0b10010000
+    (0x0504800380020100, 6), // 0b10010001
+    (0x0504800302018000, 6), // 0b10010010
+    (0x0504800380020100, 6), // 0b10010011
+    (0x0504030280018000, 6), // 0b10010100
+    (0x8006050403020100, 7), // 0b10010101
+    (0x0504800302018000, 6), // 0b10010110
+    (0x8006050403020100, 7), // 0b10010111
+    (0x0403800280018000, 5), // 0b10011000
+    (0x8005040380020100, 6), // 0b10011001
+    (0x8005040302018000, 6), // 0b10011010
+    (0x8005040380020100, 6), // 0b10011011
+    (0x0504030280018000, 6), // 0b10011100
+    (0x8006050403020100, 7), // 0b10011101
+    (0x8005040302018000, 6), // 0b10011110
+    (0x8006050403020100, 7), // 0b10011111
+    (0x8003800280018000, 4), // 0b10100000
+    (0x8004800380020100, 5), // 0b10100001
+    (0x8004800302018000, 5), // 0b10100010
+    (0x8004800380020100, 5), // 0b10100011
+    (0x8004030280018000, 5), // 0b10100100
+    (0x0605800403020100, 7), // 0b10100101
+    (0x8004800302018000, 5), // 0b10100110
+    (0x0605800403020100, 7), // 0b10100111
+    (0x0403800280018000, 5), // 0b10101000
+    (0x0605040380020100, 7), // 0b10101001
+    (0x0605040302018000, 7), // 0b10101010
+    (0x0605040380020100, 7), // 0b10101011
+    (0x8004030280018000, 5), // 0b10101100
+    (0x0605800403020100, 7), // 0b10101101
+    (0x0605040302018000, 7), // 0b10101110
+    (0x0605800403020100, 7), // 0b10101111
+    (0x8003800280018000, 4), // 0b10110000
+    (0x0504800380020100, 6), // 0b10110001
+    (0x0504800302018000, 6), // 0b10110010
+    (0x0504800380020100, 6), // 0b10110011
+    (0x0504030280018000, 6), // 0b10110100
+    (0x8006050403020100, 7), // 0b10110101
+    (0x0504800302018000, 6), // 0b10110110
+    (0x8006050403020100, 7), // 0b10110111
+    (0x0403800280018000, 5), // 0b10111000
+    (0x0605040380020100, 7), // 0b10111001
+    (0x0605040302018000, 7), // 0b10111010
+    (0x0605040380020100, 7), // 0b10111011
+    (0x0504030280018000, 6), // 0b10111100
+    (0x8006050403020100, 7), // 0b10111101
+    (0x0605040302018000, 7), // 0b10111110
+    (0x8006050403020100, 7), // 0b10111111
+    (0x8003800280018000, 4), // 0b11000000
+    (0x8004800380020100, 5), // 0b11000001
+    (0x8004800302018000, 5), // 0b11000010
+    (0x8004800380020100, 5), // 0b11000011
+    (0x8004030280018000, 5), // 0b11000100
+    (0x8005800403020100, 6), // 0b11000101
+    (0x8004800302018000, 5), // 0b11000110
+    (0x8005800403020100, 6), // 0b11000111
+    (0x0403800280018000, 5), // 0b11001000
+    (0x8005040380020100, 6), // 0b11001001
+    (0x8005040302018000, 6), // 0b11001010
+    (0x8005040380020100, 6), // 0b11001011
+    (0x8004030280018000, 5), // 0b11001100
+    (0x8005800403020100, 6), // 0b11001101
+    (0x8005040302018000, 6), // 0b11001110
+    (0x8005800403020100, 6), // 0b11001111
+    (0x8003800280018000, 4), // 0b11010000
+    (0x0504800380020100, 6), // 0b11010001
+    (0x0504800302018000, 6), // 0b11010010
+    (0x0504800380020100, 6), // 0b11010011
+    (0x0504030280018000, 6), // 0b11010100
+    (0x0706050403020100, 8), // 0b11010101
+    (0x0504800302018000, 6), // 0b11010110
+    (0x0706050403020100, 8), // 0b11010111
+    (0x0403800280018000, 5), // 0b11011000
+    (0x8005040380020100, 6), // 0b11011001
+    (0x8005040302018000, 6), // 0b11011010
+    (0x8005040380020100, 6), // 0b11011011
+    (0x0504030280018000, 6), // 0b11011100
+    (0x0706050403020100, 8), // 0b11011101
+    (0x8005040302018000, 6), // 0b11011110
+    (0x0706050403020100, 8), // 0b11011111
+    (0x8003800280018000, 4), // 0b11100000
+    (0x8004800380020100, 5), // 0b11100001
+    (0x8004800302018000, 5), // 0b11100010
+    (0x8004800380020100, 5), // 0b11100011
+    (0x8004030280018000, 5), // 0b11100100
+    (0x0605800403020100, 7), // 0b11100101
+    (0x8004800302018000, 5), // 0b11100110
+    (0x0605800403020100, 7), // 0b11100111
+    (0x0403800280018000, 5), // 0b11101000
+    (0x0605040380020100, 7), // 0b11101001
+    (0x0605040302018000, 7), // 0b11101010
+    (0x0605040380020100, 7), // 0b11101011
+    (0x8004030280018000, 5), // 0b11101100
+    (0x0605800403020100, 7), // 0b11101101
+    (0x0605040302018000, 7), // 0b11101110
+    (0x0605800403020100, 7), // 0b11101111
+    (0x8003800280018000, 4), // 0b11110000
+    (0x0504800380020100, 6), // 0b11110001
+    (0x0504800302018000, 6), // 0b11110010
+    (0x0504800380020100, 6), // 0b11110011
+    (0x0504030280018000, 6), // 0b11110100
+    (0x0706050403020100, 8), // 0b11110101
+    (0x0504800302018000, 6), // 0b11110110
+    (0x0706050403020100, 8), // 0b11110111
+    (0x0403800280018000, 5), // 0b11111000
+    (0x0605040380020100, 7), // 0b11111001
+    (0x0605040302018000, 7), // 0b11111010
+    (0x0605040380020100, 7), // 0b11111011
+    (0x0504030280018000, 6), // 0b11111100
+    (0x0706050403020100, 8), // 0b11111101
+    (0x0605040302018000, 7), // 0b11111110
+    (0x0706050403020100, 8), // 0b11111111
+];
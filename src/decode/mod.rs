@@ -1,13 +1,42 @@
-#[cfg(target_arch = "x86")]
-use core::arch::x86::*;
-#[cfg(target_arch = "x86_64")]
-use core::arch::x86_64::*;
 use core::cmp::min;
 
-use crate::num::{SignedVarIntTarget, VarIntTarget};
+use crate::num::{self, SignedVarIntTarget, VarIntTarget};
 use crate::VarIntDecodeError;
 
+mod iter;
+#[cfg(any(
+    target_arch = "x86",
+    target_arch = "x86_64",
+    all(target_arch = "aarch64", target_feature = "neon")
+))]
 mod lookup;
+mod multi;
+#[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+pub(crate) mod neon;
+mod nonzero;
+pub(crate) mod scalar;
+mod scan;
+mod slice;
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+pub(crate) mod x86;
+pub(crate) mod zigzag;
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+use crate::cpu;
+
+pub use iter::{VarIntIter, VarIntZigZagIter};
+pub use multi::{
+    decode_eight_u8, decode_eight_u8_strict, decode_four, decode_four_strict, decode_two,
+    decode_two_sign_extended, decode_two_strict, decode_two_wide, decode_two_wide_strict,
+};
+pub use nonzero::{decode_four_nonzero, decode_nonzero, decode_two_nonzero};
+pub use scan::{
+    boundaries, boundary_bitmap, count_varints, skip, validate, validate_strict, Boundaries,
+};
+pub use slice::{
+    decode_slice, decode_slice_sign_extended, decode_slice_strict, decode_slice_zigzag,
+};
+pub use zigzag::{decode_four_zigzag_unsafe, decode_two_zigzag_unsafe};
 
 /// Decodes a single varint from the input slice.
 ///
@@ -26,14 +55,28 @@ mod lookup;
 /// ```
 #[inline]
 pub fn decode<T: VarIntTarget>(bytes: &[u8]) -> Result<(T, usize), VarIntDecodeError> {
-    let result = if bytes.len() >= 16 {
-        unsafe { decode_unsafe(bytes.as_ptr()) }
+    decode_with(bytes, |ptr| unsafe { decode_dispatch(ptr) })
+}
+
+/// Safe wrapper around an implementation of [`decode_unsafe`]
+#[inline(always)]
+pub(crate) fn decode_with<T: VarIntTarget>(
+    bytes: &[u8],
+    decode_unsafe: impl Fn(*const u8) -> (T, usize),
+) -> Result<(T, usize), VarIntDecodeError> {
+    let result = if T::MAX_VARINT_BYTES > 16 {
+        if bytes.is_empty() {
+            return Err(VarIntDecodeError::NotEnoughBytes);
+        }
+        with_window::<32, _>(bytes, decode_unsafe)
+    } else if bytes.len() >= 16 {
+        decode_unsafe(bytes.as_ptr())
     } else if !bytes.is_empty() {
         let mut data = [0u8; 16];
         let len = min(16, bytes.len());
         // unsafe { core::ptr::copy_nonoverlapping(bytes.as_ptr(), data.as_mut_ptr(), len); }
         data[..len].copy_from_slice(&bytes[..len]);
-        unsafe { decode_unsafe(data.as_ptr()) }
+        decode_unsafe(data.as_ptr())
     } else {
         return Err(VarIntDecodeError::NotEnoughBytes);
     };
@@ -53,6 +96,74 @@ pub fn decode<T: VarIntTarget>(bytes: &[u8]) -> Result<(T, usize), VarIntDecodeE
     }
 }
 
+/// Decodes a single varint from the input slice the way protobuf decodes `int32` and `int64`
+/// fields, as produced by [`encode_sign_extended`](crate::encode_sign_extended). The varint is
+/// decoded as a `u64`, with the same overflow checking as [`decode`], and then truncated to the
+/// width of the target type, so a negative `int32` takes up 10 bytes and larger values wrap
+/// around.
+///
+/// # Examples
+/// ```
+/// use varint_simd::{decode, decode_sign_extended, VarIntDecodeError};
+///
+/// let bytes = [254, 255, 255, 255, 255, 255, 255, 255, 255, 1];
+/// assert_eq!(decode_sign_extended::<i32>(&bytes), Ok((-2, 10)));
+/// assert_eq!(decode_sign_extended::<i64>(&bytes), Ok((-2, 10)));
+/// assert_eq!(decode::<u32>(&bytes), Err(VarIntDecodeError::Overflow));
+/// ```
+#[inline]
+pub fn decode_sign_extended<T: SignedVarIntTarget>(
+    bytes: &[u8],
+) -> Result<(T, usize), VarIntDecodeError> {
+    decode_with_options::<T::Unsigned, false, true>(bytes)
+        .map(|(num, len)| (num::to_signed(num), len))
+}
+
+/// Decodes a single varint from the input slice with [`decode`]. `STRICT` also rejects
+/// non-canonical varints as [`decode_strict`] does, and `SIGN_EXTEND` decodes the varint as a
+/// `u64` and truncates it, as [`decode_sign_extended`] does.
+#[inline(always)]
+pub(crate) fn decode_with_options<T: VarIntTarget, const STRICT: bool, const SIGN_EXTEND: bool>(
+    bytes: &[u8],
+) -> Result<(T, usize), VarIntDecodeError> {
+    let (num, len) = if SIGN_EXTEND {
+        decode::<u64>(bytes).map(|(num, len)| (T::cast_u128(num as i64 as u128), len))?
+    } else {
+        decode::<T>(bytes)?
+    };
+
+    if STRICT && !is_canonical(bytes, len) {
+        Err(VarIntDecodeError::NonCanonical)
+    } else {
+        Ok((num, len))
+    }
+}
+
+/// Decodes a single varint from the input slice, rejecting encodings that are not the shortest
+/// possible. See also: [`decode`]
+///
+/// A varint is non-canonical if it is longer than one byte and its last byte is zero, since the
+/// same number can be encoded without that trailing zero group. `0x80 0x00` is accepted by
+/// [`decode`] as zero, but produces [`VarIntDecodeError::NonCanonical`] here.
+///
+/// # Examples
+/// ```
+/// use varint_simd::{decode_strict, VarIntDecodeError};
+///
+/// assert_eq!(decode_strict::<u32>(&[185, 10]), Ok((1337, 2)));
+/// assert_eq!(decode_strict::<u32>(&[0x80, 0x00]), Err(VarIntDecodeError::NonCanonical));
+/// ```
+#[inline]
+pub fn decode_strict<T: VarIntTarget>(bytes: &[u8]) -> Result<(T, usize), VarIntDecodeError> {
+    decode_with_options::<T, true, false>(bytes)
+}
+
+/// Whether the complete varint of `len` bytes at the start of `bytes` has no trailing zero group
+#[inline(always)]
+pub(crate) fn is_canonical(bytes: &[u8], len: usize) -> bool {
+    len == 1 || bytes[len - 1] != 0
+}
+
 /// Decodes only the length of a single variant from the input slice.
 ///
 /// # Examples
@@ -67,14 +178,19 @@ pub fn decode<T: VarIntTarget>(bytes: &[u8]) -> Result<(T, usize), VarIntDecodeE
 /// ```
 #[inline]
 pub fn decode_len<T: VarIntTarget>(bytes: &[u8]) -> Result<usize, VarIntDecodeError> {
-    let result = if bytes.len() >= 16 {
-        unsafe { decode_len_unsafe::<T>(bytes.as_ptr()) }
+    let result = if T::MAX_VARINT_BYTES > 16 {
+        if bytes.is_empty() {
+            return Err(VarIntDecodeError::NotEnoughBytes);
+        }
+        with_window::<32, _>(bytes, |ptr| unsafe { decode_len_dispatch::<T>(ptr) })
+    } else if bytes.len() >= 16 {
+        unsafe { decode_len_dispatch::<T>(bytes.as_ptr()) }
     } else if !bytes.is_empty() {
         let mut data = [0u8; 16];
         let len = min(16, bytes.len());
         // unsafe { core::ptr::copy_nonoverlapping(bytes.as_ptr(), data.as_mut_ptr(), len); }
         data[..len].copy_from_slice(&bytes[..len]);
-        unsafe { decode_len_unsafe::<T>(data.as_ptr()) }
+        unsafe { decode_len_dispatch::<T>(data.as_ptr()) }
     } else {
         return Err(VarIntDecodeError::NotEnoughBytes);
     };
@@ -82,6 +198,37 @@ pub fn decode_len<T: VarIntTarget>(bytes: &[u8]) -> Result<usize, VarIntDecodeEr
     Ok(result)
 }
 
+/// Decodes only the length of a single varint from the input slice, checking that it is complete,
+/// fits in the target type, and is canonical, exactly as [`decode_strict`] would. Unlike
+/// [`decode_len`], errors are reported for varints that cannot be decoded.
+///
+/// # Examples
+/// ```
+/// use varint_simd::{decode_len_strict, VarIntDecodeError};
+///
+/// assert_eq!(decode_len_strict::<u32>(&[185, 10]), Ok(2));
+/// assert_eq!(decode_len_strict::<u32>(&[185]), Err(VarIntDecodeError::NotEnoughBytes));
+/// assert_eq!(decode_len_strict::<u32>(&[185, 0]), Err(VarIntDecodeError::NonCanonical));
+/// ```
+#[inline]
+pub fn decode_len_strict<T: VarIntTarget>(bytes: &[u8]) -> Result<usize, VarIntDecodeError> {
+    let len = decode_len::<T>(bytes)?;
+
+    if len > T::MAX_VARINT_BYTES as usize
+        || len == T::MAX_VARINT_BYTES as usize
+            && bytes.len() >= len
+            && bytes[len - 1] > T::MAX_LAST_VARINT_BYTE
+    {
+        Err(VarIntDecodeError::Overflow)
+    } else if len > bytes.len() {
+        Err(VarIntDecodeError::NotEnoughBytes)
+    } else if !is_canonical(bytes, len) {
+        Err(VarIntDecodeError::NonCanonical)
+    } else {
+        Ok(len)
+    }
+}
+
 /// Convenience function for decoding a single varint in ZigZag format from the input slice.
 /// See also: [`decode`]
 ///
@@ -106,11 +253,24 @@ pub fn decode_zigzag<T: SignedVarIntTarget>(bytes: &[u8]) -> Result<(T, usize),
 /// Same as `decode_unsafe`
 #[inline]
 pub unsafe fn decode_len_unsafe<T: VarIntTarget>(bytes: *const u8) -> usize {
+    num::assert_fits_vector::<T>();
+    decode_len_dispatch::<T>(bytes)
+}
+
+/// [`decode_len_unsafe`] without the width check. Reads 32 bytes for types whose varints may be
+/// longer than 16 bytes.
+#[inline(always)]
+pub(crate) unsafe fn decode_len_dispatch<T: VarIntTarget>(bytes: *const u8) -> usize {
+    read_decode_window::<T>(bytes);
+
     if T::MAX_VARINT_BYTES <= 5 {
         let b = bytes.cast::<u64>().read_unaligned();
         let msbs = !b & !0x7f7f7f7f7f7f7f7f;
         let len = msbs.trailing_zeros() + 1; // in bits
         (len / 8) as usize
+    } else if T::MAX_VARINT_BYTES > 16 {
+        let words = wide_words(bytes);
+        wide_len(wide_msbs(words)) as usize / 8
     } else {
         let b0 = bytes.cast::<u64>().read_unaligned();
         let b1 = bytes.cast::<u64>().add(1).read_unaligned();
@@ -126,9 +286,85 @@ pub unsafe fn decode_len_unsafe<T: VarIntTarget>(bytes: *const u8) -> usize {
     }
 }
 
+/// Decodes the lengths of the next two integers. Target types must fit within 16 bytes when varint
+/// encoded, as with [`decode_two_unsafe`].
+///
+/// # Safety
+/// Same as `decode_two_unsafe`
+#[inline]
+pub unsafe fn decode_len_two_unsafe<T: VarIntTarget, U: VarIntTarget>(
+    bytes: *const u8,
+) -> (u8, u8) {
+    assert_fits_two::<T, U>();
+    read_window::<16>(bytes);
+
+    // bytes past the end of the window count as the last byte of a varint
+    let ends = !continuation_mask(bytes);
+    let first_len = ends.trailing_zeros() + 1;
+    let second_len = (ends >> first_len).trailing_zeros() + 1;
+
+    (first_len as u8, second_len as u8)
+}
+
+/// Decodes the lengths of the next four integers. Target types must fit within 16 bytes when
+/// varint encoded, as with [`decode_four_unsafe`].
+///
+/// # Safety
+/// Same as `decode_four_unsafe`
+#[inline]
+pub unsafe fn decode_len_four_unsafe<
+    T: VarIntTarget,
+    U: VarIntTarget,
+    V: VarIntTarget,
+    W: VarIntTarget,
+>(
+    bytes: *const u8,
+) -> (u8, u8, u8, u8) {
+    assert_fits_four::<T, U, V, W>();
+    read_window::<16>(bytes);
+
+    // bytes past the end of the window count as the last byte of a varint
+    let ends = !continuation_mask(bytes);
+    let first_len = ends.trailing_zeros() + 1;
+    let second_len = (ends >> first_len).trailing_zeros() + 1;
+    let third_len = (ends >> (first_len + second_len)).trailing_zeros() + 1;
+    let fourth_len = (ends >> (first_len + second_len + third_len)).trailing_zeros() + 1;
+
+    (
+        first_len as u8,
+        second_len as u8,
+        third_len as u8,
+        fourth_len as u8,
+    )
+}
+
+/// Produces a bitmask of the continuation bytes in the 16-byte window at `bytes`
+#[inline(always)]
+unsafe fn continuation_mask(bytes: *const u8) -> u32 {
+    #[cfg(all(
+        any(target_arch = "x86", target_arch = "x86_64"),
+        target_feature = "sse2",
+        not(miri)
+    ))]
+    {
+        x86::continuation_mask_sse2(bytes)
+    }
+    #[cfg(not(all(
+        any(target_arch = "x86", target_arch = "x86_64"),
+        target_feature = "sse2",
+        not(miri)
+    )))]
+    {
+        scalar::continuation_mask(bytes)
+    }
+}
+
 /// Decodes a single varint from the input pointer. Returns a tuple containing the decoded number
 /// and the number of bytes read.
 ///
+/// `u128` is rejected at compile time, as its varints may be longer than 16 bytes. Use
+/// [`decode_wide_unsafe`] instead.
+///
 /// # Safety
 /// There must be at least 16 bytes of allocated memory after the beginning of the pointer.
 /// Otherwise, there may be undefined behavior. Any data after the end of the varint are ignored.
@@ -137,8 +373,66 @@ pub unsafe fn decode_len_unsafe<T: VarIntTarget>(bytes: *const u8) -> usize {
 ///
 /// You may prefer to use this unsafe interface if you know what you are doing and need a little
 /// extra performance.
+///
+/// ```compile_fail
+/// let data = [0u8; 32];
+/// let decoded = unsafe { varint_simd::decode_unsafe::<u128>(data.as_ptr()) };
+/// ```
 #[inline]
 pub unsafe fn decode_unsafe<T: VarIntTarget>(bytes: *const u8) -> (T, usize) {
+    num::assert_fits_vector::<T>();
+    decode_dispatch(bytes)
+}
+
+/// Decodes a single varint from the input pointer, for any target type including `u128`, whose
+/// varints take up to 19 bytes. Returns a tuple containing the decoded number and the number of
+/// bytes read. See also: [`decode_unsafe`]
+///
+/// # Safety
+/// There must be at least 32 bytes of allocated memory after the beginning of the pointer.
+/// Otherwise, there may be undefined behavior. Any data after the end of the varint are ignored.
+/// A truncated value will be returned if the varint represents a number too large for the target
+/// type.
+///
+/// # Examples
+/// ```
+/// use varint_simd::{decode_wide_unsafe, encode_wide};
+///
+/// let (encoded, len) = encode_wide(u128::MAX);
+/// assert_eq!(len, 19);
+/// assert_eq!(unsafe { decode_wide_unsafe::<u128>(encoded.as_ptr()) }, (u128::MAX, 19));
+/// ```
+#[inline]
+pub unsafe fn decode_wide_unsafe<T: VarIntTarget>(bytes: *const u8) -> (T, usize) {
+    read_window::<32>(bytes);
+    decode_dispatch(bytes)
+}
+
+/// [`decode_unsafe`] without the width check. Reads 32 bytes for types whose varints may be
+/// longer than 16 bytes.
+#[inline(always)]
+pub(crate) unsafe fn decode_dispatch<T: VarIntTarget>(bytes: *const u8) -> (T, usize) {
+    read_decode_window::<T>(bytes);
+
+    #[cfg(target_arch = "x86_64")]
+    if cpu::has_fast_pdep() {
+        return decode_unsafe_pdep(bytes);
+    }
+
+    decode_unsafe_impl::<T, false>(bytes)
+}
+
+#[inline]
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "bmi2")]
+pub(crate) unsafe fn decode_unsafe_pdep<T: VarIntTarget>(bytes: *const u8) -> (T, usize) {
+    decode_unsafe_impl::<T, true>(bytes)
+}
+
+#[inline(always)]
+pub(crate) unsafe fn decode_unsafe_impl<T: VarIntTarget, const PDEP: bool>(
+    bytes: *const u8,
+) -> (T, usize) {
     // It looks like you're trying to understand what this code does. You should probably read
     // this first: https://developers.google.com/protocol-buffers/docs/encoding#varints
 
@@ -162,9 +456,25 @@ pub unsafe fn decode_unsafe<T: VarIntTarget>(bytes: *const u8) -> (T, usize) {
         let varint_part = b & (msbs ^ msbs.wrapping_sub(1));
         // println!("{:#066b} varint_part", varint_part);
 
-        let num = T::scalar_to_num(varint_part);
+        let num = num::scalar_to_num::<T, PDEP>(varint_part);
 
         (num, (len / 8) as usize)
+    } else if T::MAX_VARINT_BYTES > 16 {
+        // the varint may continue into a third word
+        let words = wide_words(bytes);
+        let msbs = wide_msbs(words);
+
+        // only the words up to the first one containing the end of the varint take part
+        let mut parts = [0u64; 4];
+        let mut ended = false;
+        for i in 0..3 {
+            // words[i] & blsmsk(msbs[i])
+            parts[i] = (words[i] & (msbs[i] ^ msbs[i].wrapping_sub(1))) * (!ended as u64);
+            ended |= msbs[i] != 0;
+        }
+
+        let num = T::wide_vector_to_num(core::mem::transmute::<[u64; 4], [u8; 32]>(parts));
+        (num, wide_len(msbs) as usize / 8)
     } else {
         let b0 = bytes.cast::<u64>().read_unaligned();
         let b1 = bytes.cast::<u64>().add(1).read_unaligned();
@@ -192,7 +502,7 @@ pub unsafe fn decode_unsafe<T: VarIntTarget>(bytes: *const u8) -> (T, usize) {
         // let varint_part0 = b0 & !(0xffffffffffffffff << len0.min(63));
         // let varint_part1 = b1 & !(0xffffffffffffffff << (((msbs0 == 0) as u32) * len1.min(63)));
 
-        let num = T::vector_to_num(core::mem::transmute::<[u64; 2], [u8; 16]>([
+        let num = num::vector_to_num::<T, PDEP>(core::mem::transmute::<[u64; 2], [u8; 16]>([
             varint_part0,
             varint_part1,
         ]));
@@ -203,7 +513,7 @@ pub unsafe fn decode_unsafe<T: VarIntTarget>(bytes: *const u8) -> (T, usize) {
 }
 
 /// Decodes two adjacent varints simultaneously. Target types must fit within 16 bytes when varint
-/// encoded. Requires SSSE3 support.
+/// encoded. Uses SSSE3 or NEON if available, and a scalar implementation otherwise.
 ///
 /// For example, it is permissible to decode `u32` and `u32`, and `u64` and `u32`, but it is not
 /// possible to decode two `u64` values with this function simultaneously.
@@ -218,320 +528,108 @@ pub unsafe fn decode_unsafe<T: VarIntTarget>(bytes: *const u8) -> (T, usize) {
 /// there may be undefined behavior. Any data after the two varints are ignored. Truncated values
 /// will be returned if a varint exceeds the target type's limit.
 #[inline]
-#[cfg(any(target_feature = "ssse3", doc))]
-#[cfg_attr(rustc_nightly, doc(cfg(target_feature = "ssse3")))]
 pub unsafe fn decode_two_unsafe<T: VarIntTarget, U: VarIntTarget>(
     bytes: *const u8,
 ) -> (T, U, u8, u8) {
-    if T::MAX_VARINT_BYTES + U::MAX_VARINT_BYTES > 16 {
-        // check will be eliminated at compile time
-        panic!(
-            "exceeded length limit: cannot decode {} and {}, total length {} exceeds 16 bytes",
-            core::any::type_name::<T>(),
-            core::any::type_name::<U>(),
-            T::MAX_VARINT_BYTES + U::MAX_VARINT_BYTES
-        );
-    }
-
-    if T::MAX_VARINT_BYTES <= 5 && U::MAX_VARINT_BYTES <= 5 {
-        // This will work with our lookup table, use that version
-        return decode_two_u32_unsafe(bytes);
-    }
-
-    let b = _mm_loadu_si128(bytes as *const __m128i);
-
-    // First find where the boundaries are
-    let bitmask = _mm_movemask_epi8(b) as u32;
-
-    // Find the number of bytes taken up by each varint
-    let bm_not = !bitmask;
-    let first_len = bm_not.trailing_zeros() + 1; // should compile to bsf or tzcnt
-    let bm_not_2 = bm_not >> first_len;
-    let second_len = bm_not_2.trailing_zeros() + 1;
-
-    let ascend = _mm_setr_epi8(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
-
-    let first_len_vec = _mm_set1_epi8(first_len as i8);
-    let first_mask = _mm_cmplt_epi8(ascend, first_len_vec);
-    let first = _mm_and_si128(b, first_mask);
-
-    let second_shuf = _mm_add_epi8(ascend, first_len_vec);
-    let second_shuffled = _mm_shuffle_epi8(b, second_shuf);
-    let second_mask = _mm_cmplt_epi8(ascend, _mm_set1_epi8(second_len as i8));
-    let second = _mm_and_si128(second_shuffled, second_mask);
+    assert_fits_two::<T, U>();
 
-    let first_num;
-    let second_num;
-
-    // Only use "turbo" mode if the numbers fit in 64-bit lanes
-    let should_turbo = T::MAX_VARINT_BYTES <= 8
-        && U::MAX_VARINT_BYTES <= 8
-        && cfg!(not(all(target_feature = "bmi2", very_fast_pdep)));
-    if should_turbo {
-        // const, so optimized out
-        let comb = _mm_or_si128(first, _mm_bslli_si128(second, 8));
-
-        let x = if T::MAX_VARINT_BYTES <= 2 && U::MAX_VARINT_BYTES <= 2 {
-            dual_u8_stage2(comb)
-        } else if T::MAX_VARINT_BYTES <= 3 && U::MAX_VARINT_BYTES <= 3 {
-            dual_u16_stage2(comb)
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        if cpu::has_ssse3() {
+            x86::decode_two_unsafe(bytes)
         } else {
-            dual_u32_stage2(comb)
-        };
-
-        let x: [u32; 4] = core::mem::transmute(x);
-        // _mm_extract_epi32 requires SSE4.1
-        first_num = T::cast_u32(x[0]);
-        second_num = U::cast_u32(x[2]);
-    } else {
-        first_num = T::vector_to_num(core::mem::transmute::<__m128i, [u8; 16]>(first));
-        second_num = U::vector_to_num(core::mem::transmute::<__m128i, [u8; 16]>(second));
+            scalar::decode_two_unsafe(bytes)
+        }
+    }
+    #[cfg(all(target_arch = "aarch64", target_feature = "neon", not(miri)))]
+    {
+        neon::decode_two_unsafe(bytes)
+    }
+    #[cfg(not(any(
+        target_arch = "x86",
+        target_arch = "x86_64",
+        all(target_arch = "aarch64", target_feature = "neon", not(miri))
+    )))]
+    {
+        scalar::decode_two_unsafe(bytes)
     }
-
-    (first_num, second_num, first_len as u8, second_len as u8)
 }
 
+/// **Experimental. May have relatively poor performance.** Decode two adjacent varints
+/// simultaneously from the input pointer. Uses AVX2 if available, and decodes the two values one
+/// after the other otherwise. Allows for decoding a pair of `u64` values, but each target type
+/// must fit within 16 bytes when varint encoded, which excludes `u128`. For smaller values, the
+/// non-wide variation of this function will probably be faster.
+///
+/// Returns a tuple containing the two decoded values and the two lengths of bytes read for each
+/// value.
+///
+/// # Safety
+/// There must be at least 32 bytes of allocated memory after the beginning of the pointer.
+/// Otherwise, there may be undefined behavior.
 #[inline]
-#[cfg(any(target_feature = "ssse3", doc))]
-unsafe fn decode_two_u32_unsafe<T: VarIntTarget, U: VarIntTarget>(
+pub unsafe fn decode_two_wide_unsafe<T: VarIntTarget, U: VarIntTarget>(
     bytes: *const u8,
 ) -> (T, U, u8, u8) {
-    let b = _mm_loadu_si128(bytes as *const __m128i);
-
-    // Get the movemask and mask out irrelevant parts
-    let bitmask = _mm_movemask_epi8(b) as u32 & 0b1111111111;
-
-    // Use lookup table to get the shuffle mask
-    let (lookup, first_len, second_len) =
-        *lookup::LOOKUP_DOUBLE_STEP1.get_unchecked(bitmask as usize);
-    let shuf = *lookup::LOOKUP_DOUBLE_VEC.get_unchecked(lookup as usize);
-
-    let comb = _mm_shuffle_epi8(b, shuf);
-
-    let first_num;
-    let second_num;
-
-    // Only use "turbo" mode if PDEP/PEXT are not faster
-    let should_turbo = cfg!(not(all(target_feature = "bmi2", very_fast_pdep)));
-    if should_turbo {
-        // const, so optimized out
+    assert_fits_two_wide::<T, U>();
+    read_window::<32>(bytes);
 
-        let x = if T::MAX_VARINT_BYTES <= 2 && U::MAX_VARINT_BYTES <= 2 {
-            dual_u8_stage2(comb)
-        } else if T::MAX_VARINT_BYTES <= 3 && U::MAX_VARINT_BYTES <= 3 {
-            dual_u16_stage2(comb)
+    #[cfg(target_arch = "x86_64")]
+    {
+        if cpu::has_avx2() {
+            x86::decode_two_wide_unsafe(bytes)
         } else {
-            dual_u32_stage2(comb)
-        };
-
-        let x: [u32; 4] = core::mem::transmute(x);
-        // _mm_extract_epi32 requires SSE4.1
-        first_num = T::cast_u32(x[0]);
-        second_num = U::cast_u32(x[2]);
-    } else {
-        first_num = T::vector_to_num(core::mem::transmute::<__m128i, [u8; 16]>(comb));
-        second_num = U::vector_to_num(core::mem::transmute::<__m128i, [u8; 16]>(_mm_bsrli_si128(
-            comb, 8,
-        )));
+            decode_two_wide_sequential(bytes)
+        }
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        decode_two_wide_sequential(bytes)
     }
-
-    (first_num, second_num, first_len, second_len)
 }
 
+/// Reads the first three words of a varint that may be longer than 16 bytes
 #[inline(always)]
-unsafe fn dual_u8_stage2(comb: __m128i) -> __m128i {
-    _mm_or_si128(
-        _mm_and_si128(comb, _mm_set_epi64x(0x000000000000007f, 0x000000000000007f)),
-        _mm_srli_epi64(
-            _mm_and_si128(comb, _mm_set_epi64x(0x0000000000000100, 0x0000000000000100)),
-            1,
-        ),
-    )
+unsafe fn wide_words(bytes: *const u8) -> [u64; 3] {
+    let words = bytes.cast::<u64>();
+    [
+        words.read_unaligned(),
+        words.add(1).read_unaligned(),
+        words.add(2).read_unaligned(),
+    ]
 }
 
+/// Masks of the bytes that end a varint in each word
 #[inline(always)]
-unsafe fn dual_u16_stage2(comb: __m128i) -> __m128i {
-    _mm_or_si128(
-        _mm_or_si128(
-            _mm_and_si128(comb, _mm_set_epi64x(0x000000000000007f, 0x000000000000007f)),
-            _mm_srli_epi64(
-                _mm_and_si128(comb, _mm_set_epi64x(0x0000000000030000, 0x0000000000030000)),
-                2,
-            ),
-        ),
-        _mm_srli_epi64(
-            _mm_and_si128(comb, _mm_set_epi64x(0x0000000000007f00, 0x0000000000007f00)),
-            1,
-        ),
-    )
+fn wide_msbs(words: [u64; 3]) -> [u64; 3] {
+    words.map(|word| !word & !0x7f7f7f7f7f7f7f7f)
 }
 
+/// The length in bits, up to and including the first byte that ends a varint, of up to three words
 #[inline(always)]
-unsafe fn dual_u32_stage2(comb: __m128i) -> __m128i {
-    _mm_or_si128(
-        _mm_or_si128(
-            _mm_and_si128(comb, _mm_set_epi64x(0x000000000000007f, 0x000000000000007f)),
-            _mm_srli_epi64(
-                _mm_and_si128(comb, _mm_set_epi64x(0x0000000f00000000, 0x0000000f00000000)),
-                4,
-            ),
-        ),
-        _mm_or_si128(
-            _mm_or_si128(
-                _mm_srli_epi64(
-                    _mm_and_si128(comb, _mm_set_epi64x(0x000000007f000000, 0x000000007f000000)),
-                    3,
-                ),
-                _mm_srli_epi64(
-                    _mm_and_si128(comb, _mm_set_epi64x(0x00000000007f0000, 0x00000000007f0000)),
-                    2,
-                ),
-            ),
-            _mm_srli_epi64(
-                _mm_and_si128(comb, _mm_set_epi64x(0x0000000000007f00, 0x0000000000007f00)),
-                1,
-            ),
-        ),
-    )
+fn wide_len(msbs: [u64; 3]) -> u32 {
+    if msbs[0] != 0 {
+        msbs[0].trailing_zeros() + 1
+    } else if msbs[1] != 0 {
+        msbs[1].trailing_zeros() + 65
+    } else {
+        msbs[2].trailing_zeros() + 129
+    }
 }
 
-/// **Experimental. May have relatively poor performance.** Decode two adjacent varints
-/// simultaneously from the input pointer. Requires AVX2. Allows for decoding a pair of `u64`
-/// values. For smaller values, the non-wide variation of this function will probably be faster.
-///
-/// Returns a tuple containing the two decoded values and the two lengths of bytes read for each
-/// value.
-///
-/// # Safety
-/// There must be at least 32 bytes of allocated memory after the beginning of the pointer.
-/// Otherwise, there may be undefined behavior. Calling code should ensure that AVX2 is supported
-/// before referencing this function.
-#[inline]
-#[cfg(any(target_feature = "avx2", doc))]
-#[cfg_attr(rustc_nightly, doc(cfg(target_feature = "avx2")))]
-pub unsafe fn decode_two_wide_unsafe<T: VarIntTarget, U: VarIntTarget>(
+#[inline(always)]
+unsafe fn decode_two_wide_sequential<T: VarIntTarget, U: VarIntTarget>(
     bytes: *const u8,
 ) -> (T, U, u8, u8) {
-    let b = _mm256_loadu_si256(bytes as *const __m256i);
-
-    // Get the most significant bits
-    let bitmask = _mm256_movemask_epi8(b) as u32;
-
-    // Find the number of bytes taken up by each varint
-    let bm_not = !bitmask;
-    let first_len = bm_not.trailing_zeros() + 1; // should compile to bsf or tzcnt
-    let bm_not_2 = bm_not >> first_len;
-    let second_len = bm_not_2.trailing_zeros() + 1;
-
-    // Create and parse vector consisting solely of the first varint
-    let ascend = _mm_setr_epi8(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
-    let first_mask = _mm_cmplt_epi8(ascend, _mm_set1_epi8(first_len as i8));
-    let first = _mm_and_si128(_mm256_extracti128_si256(b, 0), first_mask);
-
-    // The second is much more tricky.
-    let shuf_gen = _mm256_setr_epi8(
-        0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11,
-        12, 13, 14, 15,
-    );
-
-    // Rearrange each 128-bit lane such that ORing them together results in the window of data we want)
-    let shuf_add = _mm256_set_m128i(
-        _mm_set1_epi8(-(16i8 - first_len as i8)),
-        _mm_set1_epi8(first_len as i8),
-    );
-    let shuf_added = _mm256_add_epi8(shuf_gen, shuf_add);
-    let shuf = _mm256_or_si256(
-        shuf_added,
-        _mm256_cmpgt_epi8(shuf_added, _mm256_set1_epi8(15)), // TODO: Is this really necessary?
-    );
-    let shuffled = _mm256_shuffle_epi8(b, shuf);
-
-    // OR the halves together, and now we have a view of the second varint
-    let second_shifted = _mm_or_si128(
-        _mm256_extracti128_si256(shuffled, 0),
-        _mm256_extracti128_si256(shuffled, 1),
-    );
-    let second_mask = _mm_cmplt_epi8(ascend, _mm_set1_epi8(second_len as i8));
-    let second = _mm_and_si128(second_shifted, second_mask);
-
-    let first_num;
-    let second_num;
-
-    // PEXT on the two halves is still slower, at least on Coffee Lake and Broadwell
-    let should_turbo = true;
-    if should_turbo {
-        // Decode the two halves in parallel using SSE2
-        let comb_lo = _mm_unpacklo_epi64(first, second);
-        let x_lo = _mm_or_si128(
-            _mm_or_si128(
-                _mm_or_si128(
-                    _mm_and_si128(comb_lo, _mm_set1_epi64x(0x000000000000007f)),
-                    _mm_srli_epi64(
-                        _mm_and_si128(comb_lo, _mm_set1_epi64x(0x7f00000000000000)),
-                        7,
-                    ),
-                ),
-                _mm_or_si128(
-                    _mm_srli_epi64(
-                        _mm_and_si128(comb_lo, _mm_set1_epi64x(0x007f000000000000)),
-                        6,
-                    ),
-                    _mm_srli_epi64(
-                        _mm_and_si128(comb_lo, _mm_set1_epi64x(0x00007f0000000000)),
-                        5,
-                    ),
-                ),
-            ),
-            _mm_or_si128(
-                _mm_or_si128(
-                    _mm_srli_epi64(
-                        _mm_and_si128(comb_lo, _mm_set1_epi64x(0x0000007f00000000)),
-                        4,
-                    ),
-                    _mm_srli_epi64(
-                        _mm_and_si128(comb_lo, _mm_set1_epi64x(0x000000007f000000)),
-                        3,
-                    ),
-                ),
-                _mm_or_si128(
-                    _mm_srli_epi64(
-                        _mm_and_si128(comb_lo, _mm_set1_epi64x(0x00000000007f0000)),
-                        2,
-                    ),
-                    _mm_srli_epi64(
-                        _mm_and_si128(comb_lo, _mm_set1_epi64x(0x0000000000007f00)),
-                        1,
-                    ),
-                ),
-            ),
-        );
-
-        let comb_hi = _mm_unpackhi_epi64(first, second);
-        let x_hi = _mm_or_si128(
-            _mm_slli_epi64(
-                _mm_and_si128(comb_hi, _mm_set1_epi64x(0x0000000000000100)),
-                55,
-            ),
-            _mm_slli_epi64(
-                _mm_and_si128(comb_hi, _mm_set1_epi64x(0x000000000000007f)),
-                56,
-            ),
-        );
-
-        let x = _mm_or_si128(x_lo, x_hi);
-
-        first_num = T::cast_u64(_mm_extract_epi64(x, 0) as u64);
-        second_num = U::cast_u64(_mm_extract_epi64(x, 1) as u64);
-    } else {
-        first_num = T::vector_to_num(core::mem::transmute::<__m128i, [u8; 16]>(first));
-        second_num = U::vector_to_num(core::mem::transmute::<__m128i, [u8; 16]>(second));
-    }
+    // The first length is at most 16, so the second read stays within the 32-byte window
+    let (first_num, first_len) = decode_dispatch::<T>(bytes);
+    let (second_num, second_len) = decode_dispatch::<U>(bytes.add(first_len));
 
     (first_num, second_num, first_len as u8, second_len as u8)
 }
 
 /// Decodes four adjacent varints simultaneously. Target types must fit within 16 bytes when varint
-/// encoded. Requires SSSE3 support.
+/// encoded. Uses SSSE3 or NEON if available, and a scalar implementation otherwise.
 ///
 /// Returns a tuple containing the four encoded values, followed by the number of bytes read for
 /// each encoded value, followed by a boolean indicator for whether the length values may be
@@ -544,8 +642,6 @@ pub unsafe fn decode_two_wide_unsafe<T: VarIntTarget, U: VarIntTarget>(
 /// there may be undefined behavior. Any data after the four varints are ignored. Truncated values
 /// will be returned if a varint exceeds the target type's limit.
 #[inline]
-#[cfg(any(target_feature = "ssse3", doc))]
-#[cfg_attr(rustc_nightly, doc(cfg(target_feature = "ssse3")))]
 pub unsafe fn decode_four_unsafe<
     T: VarIntTarget,
     U: VarIntTarget,
@@ -554,341 +650,666 @@ pub unsafe fn decode_four_unsafe<
 >(
     bytes: *const u8,
 ) -> (T, U, V, W, u8, u8, u8, u8, bool) {
-    if T::MAX_VARINT_BYTES + U::MAX_VARINT_BYTES + V::MAX_VARINT_BYTES + W::MAX_VARINT_BYTES > 16 {
-        // check will be eliminated at compile time
-        panic!(
-            "exceeded length limit: cannot decode {}, {}, {}, and {}, total length {} exceeds 16 bytes",
-            core::any::type_name::<T>(),
-            core::any::type_name::<U>(),
-            core::any::type_name::<V>(),
-            core::any::type_name::<W>(),
-            T::MAX_VARINT_BYTES + U::MAX_VARINT_BYTES + V::MAX_VARINT_BYTES + W::MAX_VARINT_BYTES
-        );
-    }
+    assert_fits_four::<T, U, V, W>();
 
-    if T::MAX_VARINT_BYTES <= 3
-        && U::MAX_VARINT_BYTES <= 3
-        && V::MAX_VARINT_BYTES <= 3
-        && W::MAX_VARINT_BYTES <= 3
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
     {
-        return decode_four_u16_unsafe(bytes);
-    }
-
-    let b = _mm_loadu_si128(bytes as *const __m128i);
-
-    // First find where the boundaries are
-    let bitmask = _mm_movemask_epi8(b) as u32;
-
-    // Find the number of bytes taken up by each varint
-    let bm_not = !bitmask;
-    let first_len = bm_not.trailing_zeros() + 1; // should compile to bsf or tzcnt
-    let bm_not_2 = bm_not >> first_len;
-    let second_len = bm_not_2.trailing_zeros() + 1;
-    let bm_not_3 = bm_not_2 >> second_len;
-    let third_len = bm_not_3.trailing_zeros() + 1;
-    let bm_not_4 = bm_not_3 >> third_len;
-    let fourth_len = bm_not_4.trailing_zeros() + 1;
-
-    let ascend = _mm_setr_epi8(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
-
-    let first_len_vec = _mm_set1_epi8(first_len as i8);
-    let first_mask = _mm_cmplt_epi8(ascend, first_len_vec);
-    let first = _mm_and_si128(b, first_mask);
-
-    let second_shuf = _mm_add_epi8(ascend, first_len_vec);
-    let second_shuffled = _mm_shuffle_epi8(b, second_shuf);
-    let second_len_vec = _mm_set1_epi8(second_len as i8);
-    let second_mask = _mm_cmplt_epi8(ascend, second_len_vec);
-    let second = _mm_and_si128(second_shuffled, second_mask);
-
-    let third_shuf = _mm_add_epi8(ascend, second_len_vec);
-    let third_shuffled = _mm_shuffle_epi8(second_shuffled, third_shuf);
-    let third_len_vec = _mm_set1_epi8(third_len as i8);
-    let third_mask = _mm_cmplt_epi8(ascend, third_len_vec);
-    let third = _mm_and_si128(third_shuffled, third_mask);
-
-    let fourth_shuf = _mm_add_epi8(ascend, third_len_vec);
-    let fourth_shuffled = _mm_shuffle_epi8(third_shuffled, fourth_shuf);
-    let fourth_len_vec = _mm_set1_epi8(fourth_len as i8);
-    let fourth_mask = _mm_cmplt_epi8(ascend, fourth_len_vec);
-    let fourth = _mm_and_si128(fourth_shuffled, fourth_mask);
-
-    let first_num;
-    let second_num;
-    let third_num;
-    let fourth_num;
-
-    // Only use "turbo" mode if the numbers fit in 64-bit lanes
-    let should_turbo = T::MAX_VARINT_BYTES <= 4
-        && U::MAX_VARINT_BYTES <= 4
-        && V::MAX_VARINT_BYTES <= 4
-        && W::MAX_VARINT_BYTES <= 4
-        // PDEP/PEXT are still a little faster here
-        && cfg!(not(all(
-            target_feature = "bmi2",
-            very_fast_pdep
-        )));
-    if should_turbo {
-        // const, so optimized out
-        let comb = _mm_or_si128(
-            _mm_or_si128(first, _mm_bslli_si128(second, 4)),
-            _mm_or_si128(_mm_bslli_si128(third, 8), _mm_bslli_si128(fourth, 12)),
-        );
-
-        let x = if T::MAX_VARINT_BYTES <= 2
-            && U::MAX_VARINT_BYTES <= 2
-            && V::MAX_VARINT_BYTES <= 2
-            && W::MAX_VARINT_BYTES <= 2
-        {
-            _mm_or_si128(
-                _mm_and_si128(comb, _mm_set1_epi32(0x0000007f)),
-                _mm_srli_epi32(_mm_and_si128(comb, _mm_set1_epi32(0x00000100)), 1),
-            )
+        if cpu::has_ssse3() {
+            x86::decode_four_unsafe(bytes)
         } else {
-            _mm_or_si128(
-                _mm_or_si128(
-                    _mm_and_si128(comb, _mm_set1_epi32(0x0000007f)),
-                    _mm_srli_epi32(_mm_and_si128(comb, _mm_set1_epi32(0x00030000)), 2),
-                ),
-                _mm_srli_epi32(_mm_and_si128(comb, _mm_set1_epi32(0x00007f00)), 1),
-            )
-        };
+            scalar::decode_four_unsafe(bytes)
+        }
+    }
+    #[cfg(all(target_arch = "aarch64", target_feature = "neon", not(miri)))]
+    {
+        neon::decode_four_unsafe(bytes)
+    }
+    #[cfg(not(any(
+        target_arch = "x86",
+        target_arch = "x86_64",
+        all(target_arch = "aarch64", target_feature = "neon", not(miri))
+    )))]
+    {
+        scalar::decode_four_unsafe(bytes)
+    }
+}
 
-        let x: [u32; 4] = core::mem::transmute(x);
-        // _mm_extract_epi32 requires SSE4.1
-        first_num = T::cast_u32(x[0]);
-        second_num = U::cast_u32(x[1]);
-        third_num = V::cast_u32(x[2]);
-        fourth_num = W::cast_u32(x[3]);
-    } else {
-        first_num = T::vector_to_num(core::mem::transmute::<__m128i, [u8; 16]>(first));
-        second_num = U::vector_to_num(core::mem::transmute::<__m128i, [u8; 16]>(second));
-        third_num = V::vector_to_num(core::mem::transmute::<__m128i, [u8; 16]>(third));
-        fourth_num = W::vector_to_num(core::mem::transmute::<__m128i, [u8; 16]>(fourth));
+/// Decodes eight adjacent varints into u8's simultaneously. Uses SSSE3 or NEON if available, and
+/// a scalar implementation otherwise. **Does not perform overflow checking and may produce
+/// incorrect output.**
+///
+/// Returns a tuple containing an array of decoded values, and the total number of bytes read.
+///
+/// # Safety
+/// There must be at least 16 bytes of allocated memory after the start of the pointer. Otherwise,
+/// there may be undefined behavior. Truncated values will be returned if the varint represents
+/// a number larger than what a u8 can handle.
+///
+/// This function does not perform overflow checking. If a varint exceeds two bytes in encoded
+/// length, it may be interpreted as multiple varints, and the reported length of data read will
+/// be shorter than expected. Caution is encouraged when using this function.
+#[inline]
+pub unsafe fn decode_eight_u8_unsafe(bytes: *const u8) -> ([u8; 8], u8) {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        if cpu::has_ssse3() {
+            x86::decode_eight_u8_unsafe(bytes)
+        } else {
+            scalar::decode_eight_u8_unsafe(bytes)
+        }
+    }
+    #[cfg(all(target_arch = "aarch64", target_feature = "neon", not(miri)))]
+    {
+        neon::decode_eight_u8_unsafe(bytes)
+    }
+    #[cfg(not(any(
+        target_arch = "x86",
+        target_arch = "x86_64",
+        all(target_arch = "aarch64", target_feature = "neon", not(miri))
+    )))]
+    {
+        scalar::decode_eight_u8_unsafe(bytes)
     }
+}
 
-    (
-        first_num,
-        second_num,
-        third_num,
-        fourth_num,
-        first_len as u8,
-        second_len as u8,
-        third_len as u8,
-        fourth_len as u8,
-        false,
-    )
+/// Decodes eight adjacent varints into u8's simultaneously, like [`decode_eight_u8_unsafe`], while
+/// checking every varint for overflow. Uses SSSE3 or NEON if available, and a scalar
+/// implementation otherwise.
+///
+/// Returns a tuple containing an array of decoded values, the total number of bytes read, and a
+/// bitmask of the varints that overflow, where bit `i` corresponds to the `i`th varint. A varint
+/// overflows if it is longer than two bytes, or if its second byte is greater than one. Since a
+/// varint that is too long would be misread as several varints, the values, the length, and the
+/// bits following the lowest set bit of the bitmask are meaningless.
+///
+/// # Safety
+/// There must be at least 16 bytes of allocated memory after the start of the pointer. Otherwise,
+/// there may be undefined behavior.
+///
+/// # Examples
+/// ```
+/// use varint_simd::decode_eight_u8_checked_unsafe;
+///
+/// let bytes = [1, 2, 3, 172, 2, 6, 7, 8, 9, 0, 0, 0, 0, 0, 0, 0];
+/// let (_, _, overflow) = unsafe { decode_eight_u8_checked_unsafe(bytes.as_ptr()) };
+/// assert_eq!(overflow.trailing_zeros(), 3);
+/// ```
+#[inline]
+pub unsafe fn decode_eight_u8_checked_unsafe(bytes: *const u8) -> ([u8; 8], u8, u8) {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        if cpu::has_ssse3() {
+            x86::decode_eight_u8_checked_unsafe(bytes)
+        } else {
+            scalar::decode_eight_u8_checked_unsafe(bytes)
+        }
+    }
+    #[cfg(all(target_arch = "aarch64", target_feature = "neon", not(miri)))]
+    {
+        neon::decode_eight_u8_checked_unsafe(bytes)
+    }
+    #[cfg(not(any(
+        target_arch = "x86",
+        target_arch = "x86_64",
+        all(target_arch = "aarch64", target_feature = "neon", not(miri))
+    )))]
+    {
+        scalar::decode_eight_u8_checked_unsafe(bytes)
+    }
 }
 
+/// Same as [`decode_eight_u8_checked_unsafe`], with the non-canonical varints also set in the
+/// bitmask
 #[inline]
-#[cfg(any(target_feature = "ssse3", doc))]
-#[cfg_attr(rustc_nightly, doc(cfg(target_feature = "ssse3")))]
-unsafe fn decode_four_u16_unsafe<
-    T: VarIntTarget,
-    U: VarIntTarget,
-    V: VarIntTarget,
-    W: VarIntTarget,
->(
-    bytes: *const u8,
-) -> (T, U, V, W, u8, u8, u8, u8, bool) {
-    let b = _mm_loadu_si128(bytes as *const __m128i);
-
-    // First find where the boundaries are
-    let bitmask = _mm_movemask_epi8(b) as u32;
-
-    // Use the lookup table
-    let lookup = *lookup::LOOKUP_QUAD_STEP1.get_unchecked((bitmask & 0b111111111111) as usize);
-
-    // Fetch the shuffle mask
-    let shuf = *lookup::LOOKUP_QUAD_VEC.get_unchecked((lookup & 0b11111111) as usize);
-
-    // Extract the lengths while we're waiting
-    let first_len = (lookup >> 8) & 0b1111;
-    let second_len = (lookup >> 12) & 0b1111;
-    let third_len = (lookup >> 16) & 0b1111;
-    let fourth_len = (lookup >> 20) & 0b1111;
-
-    let comb = _mm_shuffle_epi8(b, shuf);
-
-    let invalid = lookup >> 31;
-
-    let first_num;
-    let second_num;
-    let third_num;
-    let fourth_num;
-
-    // PDEP/PEXT may be still a little faster here
-    let should_turbo = cfg!(not(all(target_feature = "bmi2", very_fast_pdep)));
-    if should_turbo {
-        // const, so optimized out
-
-        let x = if T::MAX_VARINT_BYTES <= 2
-            && U::MAX_VARINT_BYTES <= 2
-            && V::MAX_VARINT_BYTES <= 2
-            && W::MAX_VARINT_BYTES <= 2
-        {
-            _mm_or_si128(
-                _mm_and_si128(comb, _mm_set1_epi32(0x0000007f)),
-                _mm_srli_epi32(_mm_and_si128(comb, _mm_set1_epi32(0x00000100)), 1),
-            )
+pub(crate) unsafe fn decode_eight_u8_strict_unsafe(bytes: *const u8) -> ([u8; 8], u8, u8) {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        if cpu::has_ssse3() {
+            x86::decode_eight_u8_strict_unsafe(bytes)
         } else {
-            _mm_or_si128(
-                _mm_or_si128(
-                    _mm_and_si128(comb, _mm_set1_epi32(0x0000007f)),
-                    _mm_srli_epi32(_mm_and_si128(comb, _mm_set1_epi32(0x00030000)), 2),
-                ),
-                _mm_srli_epi32(_mm_and_si128(comb, _mm_set1_epi32(0x00007f00)), 1),
-            )
-        };
+            scalar::decode_eight_u8_strict_unsafe(bytes)
+        }
+    }
+    #[cfg(all(target_arch = "aarch64", target_feature = "neon", not(miri)))]
+    {
+        neon::decode_eight_u8_strict_unsafe(bytes)
+    }
+    #[cfg(not(any(
+        target_arch = "x86",
+        target_arch = "x86_64",
+        all(target_arch = "aarch64", target_feature = "neon", not(miri))
+    )))]
+    {
+        scalar::decode_eight_u8_strict_unsafe(bytes)
+    }
+}
 
-        let x: [u32; 4] = core::mem::transmute(x);
-        // _mm_extract_epi32 requires SSE4.1
-        first_num = T::cast_u32(x[0]);
-        second_num = U::cast_u32(x[1]);
-        third_num = V::cast_u32(x[2]);
-        fourth_num = W::cast_u32(x[3]);
-    } else {
-        first_num = T::vector_to_num(core::mem::transmute::<__m128i, [u8; 16]>(comb));
-        second_num = U::vector_to_num(core::mem::transmute::<__m128i, [u8; 16]>(_mm_bsrli_si128(
-            comb, 4,
-        )));
-        third_num = V::vector_to_num(core::mem::transmute::<__m128i, [u8; 16]>(_mm_bsrli_si128(
-            comb, 8,
-        )));
-        fourth_num = W::vector_to_num(core::mem::transmute::<__m128i, [u8; 16]>(_mm_bsrli_si128(
-            comb, 12,
-        )));
+/// Decodes eight adjacent varints into u16's simultaneously from a 32-byte window. Uses AVX2 if
+/// available, and two calls to [`decode_four_unsafe`] otherwise. **Does not perform overflow
+/// checking and may produce incorrect output.**
+///
+/// Returns a tuple containing an array of decoded values, and the total number of bytes read.
+///
+/// # Safety
+/// There must be at least 32 bytes of allocated memory after the start of the pointer. Otherwise,
+/// there may be undefined behavior. Truncated values will be returned if the varint represents
+/// a number larger than what a u16 can handle.
+///
+/// This function does not perform overflow checking. If a varint exceeds three bytes in encoded
+/// length, it may be interpreted as multiple varints, and the reported length of data read will
+/// be shorter than expected.
+///
+/// # Examples
+/// ```
+/// use varint_simd::decode_eight_u16_unsafe;
+///
+/// let mut bytes = [0; 32];
+/// bytes[..10].copy_from_slice(&[185, 10, 1, 2, 172, 2, 255, 255, 3, 7]);
+/// let (nums, len) = unsafe { decode_eight_u16_unsafe(bytes.as_ptr()) };
+/// assert_eq!(nums, [1337, 1, 2, 300, 65535, 7, 0, 0]);
+/// assert_eq!(len, 12);
+/// ```
+#[inline]
+pub unsafe fn decode_eight_u16_unsafe(bytes: *const u8) -> ([u16; 8], u8) {
+    read_window::<32>(bytes);
+
+    #[cfg(target_arch = "x86_64")]
+    {
+        if cpu::has_avx2() {
+            x86::decode_eight_u16_unsafe(bytes)
+        } else {
+            decode_eight_u16_sequential(bytes)
+        }
     }
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        decode_eight_u16_sequential(bytes)
+    }
+}
+
+#[inline(always)]
+unsafe fn decode_eight_u16_sequential(bytes: *const u8) -> ([u16; 8], u8) {
+    // The first four varints take up at most 12 bytes, so the second read stays within the window
+    let (a, b, c, d, a_len, b_len, c_len, d_len, _) =
+        decode_four_unsafe::<u16, u16, u16, u16>(bytes);
+    let first_len = a_len + b_len + c_len + d_len;
+    let (e, f, g, h, e_len, f_len, g_len, h_len, _) =
+        decode_four_unsafe::<u16, u16, u16, u16>(bytes.add(first_len as usize));
 
     (
-        first_num,
-        second_num,
-        third_num,
-        fourth_num,
-        first_len as u8,
-        second_len as u8,
-        third_len as u8,
-        fourth_len as u8,
-        invalid != 0,
+        [a, b, c, d, e, f, g, h],
+        first_len + e_len + f_len + g_len + h_len,
     )
 }
 
-/// Decodes four adjacent varints into u8's simultaneously. Requires SSSE3 support. **Does not
-/// perform overflow checking and may produce incorrect output.**
+/// Decodes sixteen adjacent varints into u8's simultaneously from a 32-byte window. Uses AVX2 if
+/// available, and two calls to [`decode_eight_u8_unsafe`] otherwise. **Does not perform overflow
+/// checking and may produce incorrect output.**
 ///
 /// Returns a tuple containing an array of decoded values, and the total number of bytes read.
 ///
 /// # Safety
-/// There must be at least 16 bytes of allocated memory after the start of the pointer. Otherwise,
+/// There must be at least 32 bytes of allocated memory after the start of the pointer. Otherwise,
 /// there may be undefined behavior. Truncated values will be returned if the varint represents
 /// a number larger than what a u8 can handle.
 ///
 /// This function does not perform overflow checking. If a varint exceeds two bytes in encoded
 /// length, it may be interpreted as multiple varints, and the reported length of data read will
-/// be shorter than expected. Caution is encouraged when using this function.
+/// be shorter than expected.
 #[inline]
-#[cfg(any(target_feature = "ssse3", doc))]
-#[cfg_attr(rustc_nightly, doc(cfg(target_feature = "ssse3")))]
-pub unsafe fn decode_eight_u8_unsafe(bytes: *const u8) -> ([u8; 8], u8) {
-    let b = _mm_loadu_si128(bytes as *const __m128i);
-
-    let ones = _mm_set1_epi8(1);
-    let mut lens = _mm_setzero_si128();
-    let mut shift = _mm_and_si128(_mm_cmplt_epi8(b, _mm_setzero_si128()), ones);
-    let ascend = _mm_setr_epi8(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
-    let asc_one = _mm_setr_epi8(1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16);
-    let mut window_small = _mm_setr_epi8(1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0);
-
-    let broadcast_mask = _mm_setzero_si128();
-
-    // if the first byte is zero, shift down by 1, if the first byte is one, shift down by 2
-    // 0
-    let first_byte = _mm_shuffle_epi8(shift, broadcast_mask);
-    shift = _mm_shuffle_epi8(shift, _mm_add_epi8(asc_one, first_byte));
-    lens = _mm_or_si128(lens, _mm_and_si128(first_byte, window_small));
-    window_small = _mm_bslli_si128(window_small, 1);
-
-    // 1
-    let first_byte = _mm_shuffle_epi8(shift, broadcast_mask);
-    shift = _mm_shuffle_epi8(shift, _mm_add_epi8(asc_one, first_byte));
-    lens = _mm_or_si128(lens, _mm_and_si128(first_byte, window_small));
-    window_small = _mm_bslli_si128(window_small, 1);
-
-    // 2
-    let first_byte = _mm_shuffle_epi8(shift, broadcast_mask);
-    shift = _mm_shuffle_epi8(shift, _mm_add_epi8(asc_one, first_byte));
-    lens = _mm_or_si128(lens, _mm_and_si128(first_byte, window_small));
-    window_small = _mm_bslli_si128(window_small, 1);
-
-    // 3
-    let first_byte = _mm_shuffle_epi8(shift, broadcast_mask);
-    shift = _mm_shuffle_epi8(shift, _mm_add_epi8(asc_one, first_byte));
-    lens = _mm_or_si128(lens, _mm_and_si128(first_byte, window_small));
-    window_small = _mm_bslli_si128(window_small, 1);
-
-    // 4
-    let first_byte = _mm_shuffle_epi8(shift, broadcast_mask);
-    shift = _mm_shuffle_epi8(shift, _mm_add_epi8(asc_one, first_byte));
-    lens = _mm_or_si128(lens, _mm_and_si128(first_byte, window_small));
-    window_small = _mm_bslli_si128(window_small, 1);
-
-    // 5
-    let first_byte = _mm_shuffle_epi8(shift, broadcast_mask);
-    shift = _mm_shuffle_epi8(shift, _mm_add_epi8(asc_one, first_byte));
-    lens = _mm_or_si128(lens, _mm_and_si128(first_byte, window_small));
-    window_small = _mm_bslli_si128(window_small, 1);
-
-    // 6
-    let first_byte = _mm_shuffle_epi8(shift, broadcast_mask);
-    shift = _mm_shuffle_epi8(shift, _mm_add_epi8(asc_one, first_byte));
-    lens = _mm_or_si128(lens, _mm_and_si128(first_byte, window_small));
-    window_small = _mm_bslli_si128(window_small, 1);
-
-    // 7
-    let first_byte = _mm_shuffle_epi8(shift, broadcast_mask);
-    // shift = _mm_shuffle_epi8(shift, _mm_add_epi8(asc_one, first_byte));
-    lens = _mm_or_si128(lens, _mm_and_si128(first_byte, window_small));
-    // window_small = _mm_bslli_si128(window_small, 1);
-
-    // Construct the shuffle
-
-    let lens_invert = _mm_sub_epi8(ones, lens);
-    let mut cumul_lens = _mm_add_epi8(lens_invert, _mm_bslli_si128(lens_invert, 1));
-    cumul_lens = _mm_add_epi8(cumul_lens, _mm_bslli_si128(cumul_lens, 2));
-    cumul_lens = _mm_add_epi8(cumul_lens, _mm_bslli_si128(cumul_lens, 4));
-    cumul_lens = _mm_add_epi8(cumul_lens, _mm_bslli_si128(cumul_lens, 8));
-
-    let cumul_lens_2: [u8; 16] = core::mem::transmute(cumul_lens);
-    let last_len = 8 - cumul_lens_2[7] + 8;
-
-    // Set one-lengthed second bytes to negative
-    let second = _mm_shuffle_epi8(
-        _mm_add_epi8(lens, ones),
-        _mm_setr_epi8(-1, 0, -1, 1, -1, 2, -1, 3, -1, 4, -1, 5, -1, 6, -1, 7),
-    );
-
-    let shuf_pt1 = _mm_or_si128(ascend, _mm_cmpeq_epi8(second, ones));
-
-    // Subtract the cumulative sum of zero-lengths to adjust the indexes
-    let x_shuf = _mm_shuffle_epi8(
-        _mm_bslli_si128(cumul_lens, 1),
-        _mm_setr_epi8(0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7),
-    );
-
-    let shuf = _mm_sub_epi8(shuf_pt1, x_shuf);
-    let comb = _mm_shuffle_epi8(b, shuf);
-
-    let x = _mm_or_si128(
-        _mm_and_si128(comb, _mm_set1_epi16(0x0000007f)),
-        _mm_srli_epi16(_mm_and_si128(comb, _mm_set1_epi16(0x00000100)), 1),
-    );
-
-    let shuf = _mm_shuffle_epi8(
-        x,
-        _mm_setr_epi8(0, 2, 4, 6, 8, 10, 12, 14, -1, -1, -1, -1, -1, -1, -1, -1),
-    );
-    let lower: [u64; 2] = core::mem::transmute(shuf);
-    let nums = lower[0].to_ne_bytes();
-
-    (nums, last_len)
+pub unsafe fn decode_sixteen_u8_unsafe(bytes: *const u8) -> ([u8; 16], u8) {
+    read_window::<32>(bytes);
+
+    #[cfg(target_arch = "x86_64")]
+    {
+        if cpu::has_avx2() {
+            x86::decode_sixteen_u8_unsafe(bytes)
+        } else {
+            decode_sixteen_u8_sequential(bytes)
+        }
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        decode_sixteen_u8_sequential(bytes)
+    }
+}
+
+#[inline(always)]
+unsafe fn decode_sixteen_u8_sequential(bytes: *const u8) -> ([u8; 16], u8) {
+    // The first eight varints take up at most 16 bytes, so the second read stays within the window
+    let (first, first_len) = decode_eight_u8_unsafe(bytes);
+    let (second, second_len) = decode_eight_u8_unsafe(bytes.add(first_len as usize));
+
+    let mut nums = [0; 16];
+    nums[..8].copy_from_slice(&first);
+    nums[8..].copy_from_slice(&second);
+    (nums, first_len + second_len)
+}
+
+/// Under Miri, reads the window that [`decode_dispatch`] requires for the target type
+#[inline(always)]
+pub(crate) unsafe fn read_decode_window<T: VarIntTarget>(bytes: *const u8) {
+    if T::MAX_VARINT_BYTES > 16 {
+        read_window::<32>(bytes);
+    } else {
+        read_window::<16>(bytes);
+    }
+}
+
+/// Calls `decode_unsafe` with a pointer to at least `N` bytes of the input, which are copied into
+/// a zero-padded buffer if the input is shorter
+#[inline(always)]
+pub(crate) fn with_window<const N: usize, R>(
+    bytes: &[u8],
+    decode_unsafe: impl FnOnce(*const u8) -> R,
+) -> R {
+    if bytes.len() >= N {
+        decode_unsafe(bytes.as_ptr())
+    } else {
+        let mut data = [0u8; N];
+        data[..bytes.len()].copy_from_slice(bytes);
+        decode_unsafe(data.as_ptr())
+    }
+}
+
+/// Under Miri, reads the whole window that the caller guarantees to be allocated, so that a
+/// violation of the safety contract is reported even if the varint is short enough that the
+/// decoder itself never touches the end of the window. Does nothing otherwise.
+#[inline(always)]
+pub(crate) unsafe fn read_window<const N: usize>(bytes: *const u8) {
+    #[cfg(miri)]
+    bytes.cast::<[u8; N]>().read_unaligned();
+    #[cfg(not(miri))]
+    let _ = bytes;
+}
+
+#[inline(always)]
+pub(crate) fn assert_fits_two<T: VarIntTarget, U: VarIntTarget>() {
+    if T::MAX_VARINT_BYTES + U::MAX_VARINT_BYTES > 16 {
+        // check will be eliminated at compile time
+        panic!(
+            "exceeded length limit: cannot decode {} and {}, total length {} exceeds 16 bytes",
+            core::any::type_name::<T>(),
+            core::any::type_name::<U>(),
+            T::MAX_VARINT_BYTES + U::MAX_VARINT_BYTES
+        );
+    }
+}
+
+#[inline(always)]
+pub(crate) fn assert_fits_two_wide<T: VarIntTarget, U: VarIntTarget>() {
+    if T::MAX_VARINT_BYTES > 16 || U::MAX_VARINT_BYTES > 16 {
+        // check will be eliminated at compile time
+        panic!(
+            "exceeded length limit: cannot decode {} and {}, each must fit within 16 bytes",
+            core::any::type_name::<T>(),
+            core::any::type_name::<U>()
+        );
+    }
+}
+
+#[inline(always)]
+pub(crate) fn assert_fits_four<
+    T: VarIntTarget,
+    U: VarIntTarget,
+    V: VarIntTarget,
+    W: VarIntTarget,
+>() {
+    if T::MAX_VARINT_BYTES + U::MAX_VARINT_BYTES + V::MAX_VARINT_BYTES + W::MAX_VARINT_BYTES > 16 {
+        // check will be eliminated at compile time
+        panic!(
+            "exceeded length limit: cannot decode {}, {}, {}, and {}, total length {} exceeds 16 bytes",
+            core::any::type_name::<T>(),
+            core::any::type_name::<U>(),
+            core::any::type_name::<V>(),
+            core::any::type_name::<W>(),
+            T::MAX_VARINT_BYTES + U::MAX_VARINT_BYTES + V::MAX_VARINT_BYTES + W::MAX_VARINT_BYTES
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        decode_eight_u16_unsafe, decode_eight_u8_checked_unsafe, decode_eight_u8_unsafe,
+        decode_len_four_unsafe, decode_len_strict, decode_len_two_unsafe, decode_sign_extended,
+        decode_sixteen_u8_unsafe, decode_strict,
+    };
+    use crate::tests::{NUMS_U16, NUMS_U32, NUMS_U64, NUMS_U8};
+    use crate::{
+        decode, decode_len, encode, encode_padded, encode_sign_extended, encode_to_slice,
+        encoded_len, Backend, SignedVarIntTarget, VarIntDecodeError, VarIntTarget,
+    };
+
+    fn check_decode_len_2x<T: VarIntTarget, U: VarIntTarget>(a: &[T], b: &[U]) {
+        for i in a {
+            for j in b {
+                let mut enc = [0u8; 16];
+
+                let first_len = encode_to_slice(*i, &mut enc);
+                let second_len = encode_to_slice(*j, &mut enc[first_len as usize..]);
+
+                let decoded = unsafe { decode_len_two_unsafe::<T, U>(enc.as_ptr()) };
+                assert_eq!(decoded, (first_len, second_len));
+            }
+        }
+    }
+
+    fn check_decode_len_4x<T: VarIntTarget, U: VarIntTarget, V: VarIntTarget, W: VarIntTarget>(
+        a: &[T],
+        b: &[U],
+        c: &[V],
+        d: &[W],
+    ) {
+        for i in a {
+            for j in b {
+                for k in c {
+                    for l in d {
+                        let mut enc = [0u8; 16];
+
+                        let first_len = encode_to_slice(*i, &mut enc);
+                        let second_len = encode_to_slice(*j, &mut enc[first_len as usize..]);
+                        let third_len =
+                            encode_to_slice(*k, &mut enc[(first_len + second_len) as usize..]);
+                        let fourth_len = encode_to_slice(
+                            *l,
+                            &mut enc[(first_len + second_len + third_len) as usize..],
+                        );
+
+                        let decoded = unsafe { decode_len_four_unsafe::<T, U, V, W>(enc.as_ptr()) };
+                        assert_eq!(decoded, (first_len, second_len, third_len, fourth_len));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_len_2x() {
+        check_decode_len_2x::<u8, u64>(&NUMS_U8[..], &NUMS_U64[..]);
+        check_decode_len_2x::<u16, u32>(&NUMS_U16[..], &NUMS_U32[..]);
+        check_decode_len_2x::<u32, u32>(&NUMS_U32[..], &NUMS_U32[..]);
+        check_decode_len_2x::<u64, u16>(&NUMS_U64[..], &NUMS_U16[..]);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // too slow
+    fn test_decode_len_4x() {
+        check_decode_len_4x::<u8, u8, u8, u8>(
+            &NUMS_U8[..],
+            &NUMS_U8[..],
+            &NUMS_U8[..],
+            &NUMS_U8[..],
+        );
+        check_decode_len_4x::<u16, u16, u16, u16>(
+            &NUMS_U16[..],
+            &NUMS_U16[..],
+            &NUMS_U16[..],
+            &NUMS_U16[..],
+        );
+        check_decode_len_4x::<u32, u8, u32, u16>(
+            &NUMS_U32[..],
+            &NUMS_U8[..],
+            &NUMS_U32[..],
+            &NUMS_U16[..],
+        );
+    }
+
+    fn check_decode_8x_u8_checked(window: &[u8], overflow: u8) {
+        // The window is exactly as large as the safety contract requires
+        let window: Box<[u8]> = window.into();
+        assert_eq!(window.len(), 16);
+
+        let decoded = unsafe { decode_eight_u8_checked_unsafe(window.as_ptr()) };
+        assert_eq!(decoded.2, overflow, "{:?}", window);
+        if overflow == 0 {
+            let unchecked = unsafe { decode_eight_u8_unsafe(window.as_ptr()) };
+            assert_eq!((decoded.0, decoded.1), unchecked);
+        }
+
+        for backend in Backend::supported() {
+            let result = unsafe { backend.decode_eight_u8_checked_unsafe(window.as_ptr()) };
+            assert_eq!(result, decoded, "{:?} {:?}", backend, window);
+        }
+    }
+
+    #[test]
+    fn test_decode_8x_u8_checked() {
+        let nums = &NUMS_U8[..];
+        for i in 0..nums.len() {
+            for j in 1..nums.len() {
+                let mut window = [0u8; 16];
+                let mut len = 0;
+                for k in 0..8 {
+                    len += encode_to_slice(nums[(i + j * k) % nums.len()], &mut window[len..])
+                        as usize;
+                }
+                check_decode_8x_u8_checked(&window, 0);
+            }
+        }
+
+        // A two-byte varint that overflows, or a longer one, in each lane
+        for lane in 0..8 {
+            let mut window = [1u8; 16];
+            window[lane] = 0x80;
+            window[lane + 1] = 0x02;
+            check_decode_8x_u8_checked(&window, 1 << lane);
+
+            window[lane + 1] = 0x80;
+            let overflow = unsafe { decode_eight_u8_checked_unsafe(window.as_ptr()) }.2;
+            assert_eq!(overflow.trailing_zeros(), lane as u32, "{:?}", window);
+        }
+    }
+
+    #[test]
+    fn test_decode_8x_u8_checked_last_lane() {
+        // Only the eighth varint overflows, at the end of the window or just before it
+        check_decode_8x_u8_checked(&[&[1; 7][..], &[0x80, 0x02], &[1; 7]].concat(), 0x80);
+        check_decode_8x_u8_checked(&[&[0xFF, 0x01].repeat(7)[..], &[0xFF, 0x02]].concat(), 0x80);
+        check_decode_8x_u8_checked(&[0xFF, 0x02].repeat(8), 0xFF);
+        check_decode_8x_u8_checked(&[0xFF, 0x01].repeat(8), 0);
+    }
+
+    /// Encodes `count` values taken from `nums` with every starting index and stride into a 32-byte
+    /// window, and checks `decode_window` against the expected values and total length
+    fn check_decode_32_byte_window<T: VarIntTarget>(
+        nums: &[T],
+        count: usize,
+        decode_window: impl Fn(Backend, *const u8) -> (Vec<T>, u8),
+    ) {
+        for i in 0..nums.len() {
+            for j in 1..nums.len() {
+                let values: Vec<T> = (0..count).map(|k| nums[(i + j * k) % nums.len()]).collect();
+
+                let mut enc = [0u8; 32];
+                let mut len = 0;
+                for value in values.iter() {
+                    len += encode_to_slice(*value, &mut enc[len as usize..]);
+                }
+
+                let window: Box<[u8]> = enc.into();
+                for backend in Backend::supported() {
+                    let decoded = decode_window(backend, window.as_ptr());
+                    assert_eq!(decoded, (values.clone(), len), "{:?} {:?}", backend, enc);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_8x_u16() {
+        check_decode_32_byte_window::<u16>(&NUMS_U16[..], 8, |backend, ptr| {
+            let decoded = unsafe { decode_eight_u16_unsafe(ptr) };
+            assert_eq!(unsafe { backend.decode_eight_u16_unsafe(ptr) }, decoded);
+            (decoded.0.to_vec(), decoded.1)
+        });
+    }
+
+    #[test]
+    fn test_decode_16x_u8() {
+        check_decode_32_byte_window::<u8>(&NUMS_U8[..], 16, |backend, ptr| {
+            let decoded = unsafe { decode_sixteen_u8_unsafe(ptr) };
+            assert_eq!(unsafe { backend.decode_sixteen_u8_unsafe(ptr) }, decoded);
+            (decoded.0.to_vec(), decoded.1)
+        });
+    }
+
+    #[test]
+    fn test_decode_32_byte_window_edges() {
+        let check_16x_u8 = |window: &[u8], expected| {
+            let window: Box<[u8]> = window.into();
+            let decoded = unsafe { decode_sixteen_u8_unsafe(window.as_ptr()) };
+            assert_eq!(decoded, expected);
+            for backend in Backend::supported() {
+                let result = unsafe { backend.decode_sixteen_u8_unsafe(window.as_ptr()) };
+                assert_eq!(result, decoded, "{:?}", backend);
+            }
+        };
+
+        // The first half ends at the middle of the window, the second at its end
+        check_16x_u8(&[0xFF, 0x01].repeat(16), ([0xFF; 16], 32));
+
+        // The first half ends just before the middle of the window, the second just before its end
+        let mut window = [&[0xFF, 0x01].repeat(7)[..], &[1], &[0xFF, 0x01].repeat(8)].concat();
+        window.push(0);
+        let mut expected = [0xFF; 16];
+        expected[7] = 1;
+        check_16x_u8(&window, (expected, 31));
+
+        let mut window = Box::new([0u8; 32]);
+        window[..24].copy_from_slice(&[0xFF, 0xFF, 0x03].repeat(8));
+        let decoded = unsafe { decode_eight_u16_unsafe(window.as_ptr()) };
+        assert_eq!(decoded, ([u16::MAX; 8], 24));
+        for backend in Backend::supported() {
+            let result = unsafe { backend.decode_eight_u16_unsafe(window.as_ptr()) };
+            assert_eq!(result, decoded, "{:?}", backend);
+        }
+    }
+
+    fn check_decode_strict<T: VarIntTarget>(nums: &[T]) {
+        for num in nums {
+            let len = encoded_len(*num);
+            for width in len as u8..=T::MAX_VARINT_BYTES {
+                let encoded = encode_padded(*num, width);
+                let width = width as usize;
+
+                // Only the shortest encoding is accepted
+                let expected = if width == len {
+                    Ok((*num, width))
+                } else {
+                    Err(VarIntDecodeError::NonCanonical)
+                };
+                assert_eq!(decode::<T>(&encoded), Ok((*num, width)));
+                assert_eq!(decode_strict::<T>(&encoded), expected);
+                assert_eq!(
+                    decode_len_strict::<T>(&encoded),
+                    expected.map(|(_, len)| len)
+                );
+
+                // Errors for incomplete varints are unchanged
+                assert_eq!(
+                    decode_strict::<T>(&encoded[..width - 1]),
+                    decode::<T>(&encoded[..width - 1])
+                );
+
+                for backend in Backend::supported() {
+                    assert_eq!(
+                        backend.decode_strict::<T>(&encoded),
+                        expected,
+                        "{:?}",
+                        backend
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_strict() {
+        check_decode_strict(&NUMS_U8[..]);
+        check_decode_strict(&NUMS_U16[..]);
+        check_decode_strict(&NUMS_U32[..]);
+        check_decode_strict(&NUMS_U64[..]);
+
+        // A single zero byte is canonical, and a trailing zero group never is
+        assert_eq!(decode_strict::<u32>(&[0]), Ok((0, 1)));
+        assert_eq!(
+            decode_strict::<u32>(&[0x80, 0]),
+            Err(VarIntDecodeError::NonCanonical)
+        );
+        assert_eq!(
+            decode_strict::<u64>(&[0xFF, 0x80, 0]),
+            Err(VarIntDecodeError::NonCanonical)
+        );
+    }
+
+    #[test]
+    fn test_decode_len_strict_errors() {
+        use VarIntDecodeError::*;
+
+        // Errors are reported exactly as decode_strict would
+        for input in [
+            &[][..],
+            &[0x80],
+            &[0x80, 0x80, 0x04],
+            &[0x80, 0x80, 0x03],
+            &[0x80, 0x80, 0x00],
+            &[0xFF, 0xFF, 0xFF],
+        ] {
+            assert_eq!(
+                decode_len_strict::<u16>(input),
+                decode_strict::<u16>(input).map(|(_, len)| len),
+                "{:?}",
+                input
+            );
+        }
+        assert_eq!(decode_len_strict::<u16>(&[0x80, 0x80, 0x04]), Err(Overflow));
+        assert_eq!(decode_len::<u16>(&[0x80, 0x80, 0x04]), Ok(3));
+    }
+
+    fn check_decode_sign_extended<T: SignedVarIntTarget>(nums: &[T::Unsigned]) {
+        for num in nums {
+            // Every value round trips, with negative values taking up 10 bytes
+            let num = num.unzigzag();
+            let (encoded, len) = encode_sign_extended(num);
+            assert_eq!(decode_sign_extended::<T>(&encoded), Ok((num, len as usize)));
+
+            for backend in Backend::supported() {
+                assert_eq!(
+                    backend.decode_sign_extended::<T>(&encoded),
+                    Ok((num, len as usize)),
+                    "{:?}",
+                    backend
+                );
+            }
+        }
+
+        // Any u64 is accepted and truncated, and errors are those of decode::<u64>
+        for wide in NUMS_U64.iter() {
+            let (encoded, len) = encode(*wide);
+            let expected = crate::num::to_signed::<T>(T::Unsigned::cast_u64(*wide));
+            assert_eq!(
+                decode_sign_extended::<T>(&encoded),
+                Ok((expected, len as usize))
+            );
+            assert_eq!(
+                decode_sign_extended::<T>(&encoded[..len as usize - 1]),
+                Err(VarIntDecodeError::NotEnoughBytes)
+            );
+        }
+        assert_eq!(
+            decode_sign_extended::<T>(&[
+                0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x02
+            ]),
+            Err(VarIntDecodeError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_decode_sign_extended() {
+        check_decode_sign_extended::<i8>(&NUMS_U8[..]);
+        check_decode_sign_extended::<i16>(&NUMS_U16[..]);
+        check_decode_sign_extended::<i32>(&NUMS_U32[..]);
+        check_decode_sign_extended::<i64>(&NUMS_U64[..]);
+    }
 }
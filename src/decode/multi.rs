@@ -0,0 +1,756 @@
+//! Safe versions of the multi-value decoders, which check bounds and overflow for every value.
+
+use super::{
+    decode_eight_u8_checked_unsafe, decode_eight_u8_strict_unsafe, decode_four_unsafe,
+    decode_two_unsafe, decode_two_wide_unsafe, decode_with_options, is_canonical, with_window,
+};
+use crate::num::{self, SignedVarIntTarget, VarIntTarget};
+use crate::SliceDecodeError;
+
+/// Decodes two adjacent varints from the input slice. Target types must fit within 16 bytes when
+/// varint encoded. See also: [`decode_two_unsafe`]
+///
+/// Produces a tuple containing the two decoded values and the number of bytes read for each. If
+/// a varint overflows its target type or is cut off by the end of the input, an error is returned
+/// with its offset and index. For best performance, provide a slice at least 16 bytes in length.
+///
+/// # Examples
+/// ```
+/// use varint_simd::{decode_two, VarIntDecodeError};
+///
+/// assert_eq!(decode_two::<u32, u8>(&[185, 10, 1]), Ok((1337, 1, 2, 1)));
+///
+/// let error = decode_two::<u32, u8>(&[185, 10, 172, 2]).unwrap_err();
+/// assert_eq!(error.error, VarIntDecodeError::Overflow);
+/// assert_eq!((error.offset, error.index), (2, 1));
+/// ```
+#[inline]
+pub fn decode_two<T: VarIntTarget, U: VarIntTarget>(
+    bytes: &[u8],
+) -> Result<(T, U, usize, usize), SliceDecodeError> {
+    decode_two_impl::<T, U, false, false>(bytes)
+}
+
+/// Decodes two adjacent varints from the input slice, rejecting encodings that are not the
+/// shortest possible with
+/// [`VarIntDecodeError::NonCanonical`](crate::VarIntDecodeError::NonCanonical). See also:
+/// [`decode_two`], [`decode_strict`](crate::decode_strict)
+///
+/// # Examples
+/// ```
+/// use varint_simd::{decode_two_strict, VarIntDecodeError};
+///
+/// assert_eq!(decode_two_strict::<u32, u8>(&[185, 10, 1]), Ok((1337, 1, 2, 1)));
+///
+/// let error = decode_two_strict::<u32, u8>(&[185, 10, 0x81, 0]).unwrap_err();
+/// assert_eq!(error.error, VarIntDecodeError::NonCanonical);
+/// assert_eq!((error.offset, error.index), (2, 1));
+/// ```
+#[inline]
+pub fn decode_two_strict<T: VarIntTarget, U: VarIntTarget>(
+    bytes: &[u8],
+) -> Result<(T, U, usize, usize), SliceDecodeError> {
+    decode_two_impl::<T, U, true, false>(bytes)
+}
+
+/// `STRICT` and `SIGN_EXTEND` select the rules of [`decode_with_options`] for every value
+#[inline(always)]
+fn decode_two_impl<
+    T: VarIntTarget,
+    U: VarIntTarget,
+    const STRICT: bool,
+    const SIGN_EXTEND: bool,
+>(
+    bytes: &[u8],
+) -> Result<(T, U, usize, usize), SliceDecodeError> {
+    let (first, second, first_len, second_len) =
+        with_window::<16, _>(bytes, |ptr| unsafe { decode_two_unsafe::<T, U>(ptr) });
+    let (first_len, second_len) = (first_len as usize, second_len as usize);
+
+    if is_valid::<T, STRICT>(bytes, 0, first_len)
+        && is_valid::<U, STRICT>(bytes, first_len, second_len)
+    {
+        Ok((first, second, first_len, second_len))
+    } else {
+        let mut read = 0;
+        let first = decode_at::<T, STRICT, SIGN_EXTEND>(bytes, &mut read, 0)?;
+        let first_len = read;
+        let second = decode_at::<U, STRICT, SIGN_EXTEND>(bytes, &mut read, 1)?;
+        Ok((first, second, first_len, read - first_len))
+    }
+}
+
+/// Decodes two adjacent varints from the input slice, allowing for a pair of `u64` values. See
+/// also: [`decode_two_wide_unsafe`]
+///
+/// Produces a tuple containing the two decoded values and the number of bytes read for each. If
+/// a varint overflows its target type or is cut off by the end of the input, an error is returned
+/// with its offset and index. For best performance, provide a slice at least 32 bytes in length.
+///
+/// # Examples
+/// ```
+/// use varint_simd::decode_two_wide;
+///
+/// let decoded = decode_two_wide::<u64, u64>(&[185, 10, 172, 2]);
+/// assert_eq!(decoded, Ok((1337, 300, 2, 2)));
+/// ```
+#[inline]
+pub fn decode_two_wide<T: VarIntTarget, U: VarIntTarget>(
+    bytes: &[u8],
+) -> Result<(T, U, usize, usize), SliceDecodeError> {
+    decode_two_wide_impl::<T, U, false, false>(bytes)
+}
+
+/// Decodes two adjacent varints from the input slice, allowing for a pair of `u64` values, and
+/// rejecting encodings that are not the shortest possible. See also: [`decode_two_wide`],
+/// [`decode_strict`](crate::decode_strict)
+///
+/// # Examples
+/// ```
+/// use varint_simd::{decode_two_wide_strict, VarIntDecodeError};
+///
+/// let error = decode_two_wide_strict::<u64, u64>(&[0x80, 0x80, 0, 1]).unwrap_err();
+/// assert_eq!(error.error, VarIntDecodeError::NonCanonical);
+/// assert_eq!((error.offset, error.index), (0, 0));
+/// ```
+#[inline]
+pub fn decode_two_wide_strict<T: VarIntTarget, U: VarIntTarget>(
+    bytes: &[u8],
+) -> Result<(T, U, usize, usize), SliceDecodeError> {
+    decode_two_wide_impl::<T, U, true, false>(bytes)
+}
+
+#[inline(always)]
+fn decode_two_wide_impl<
+    T: VarIntTarget,
+    U: VarIntTarget,
+    const STRICT: bool,
+    const SIGN_EXTEND: bool,
+>(
+    bytes: &[u8],
+) -> Result<(T, U, usize, usize), SliceDecodeError> {
+    let (first, second, first_len, second_len) =
+        with_window::<32, _>(bytes, |ptr| unsafe { decode_two_wide_unsafe::<T, U>(ptr) });
+    let (first_len, second_len) = (first_len as usize, second_len as usize);
+
+    if is_valid::<T, STRICT>(bytes, 0, first_len)
+        && is_valid::<U, STRICT>(bytes, first_len, second_len)
+    {
+        Ok((first, second, first_len, second_len))
+    } else {
+        let mut read = 0;
+        let first = decode_at::<T, STRICT, SIGN_EXTEND>(bytes, &mut read, 0)?;
+        let first_len = read;
+        let second = decode_at::<U, STRICT, SIGN_EXTEND>(bytes, &mut read, 1)?;
+        Ok((first, second, first_len, read - first_len))
+    }
+}
+
+/// Decodes two adjacent varints from the input slice the way protobuf decodes `int32` and `int64`
+/// fields, as [`decode_sign_extended`](crate::decode_sign_extended) does. See also:
+/// [`decode_two`], [`decode_two_wide`]
+///
+/// Each value is decoded with the multi-value decoder for its own width, which covers every
+/// non-negative value that fits. Negative values, which take up 10 bytes, are decoded one at a
+/// time as a `u64` and truncated.
+///
+/// # Examples
+/// ```
+/// use varint_simd::decode_two_sign_extended;
+///
+/// let bytes = [254, 255, 255, 255, 255, 255, 255, 255, 255, 1, 185, 10];
+/// assert_eq!(decode_two_sign_extended::<i32, i32>(&bytes), Ok((-2, 1337, 10, 2)));
+/// ```
+#[inline]
+pub fn decode_two_sign_extended<T: SignedVarIntTarget, U: SignedVarIntTarget>(
+    bytes: &[u8],
+) -> Result<(T, U, usize, usize), SliceDecodeError> {
+    let (first, second, first_len, second_len) =
+        if T::Unsigned::MAX_VARINT_BYTES + U::Unsigned::MAX_VARINT_BYTES <= 16 {
+            decode_two_impl::<T::Unsigned, U::Unsigned, false, true>(bytes)?
+        } else {
+            decode_two_wide_impl::<T::Unsigned, U::Unsigned, false, true>(bytes)?
+        };
+
+    Ok((
+        num::to_signed(first),
+        num::to_signed(second),
+        first_len,
+        second_len,
+    ))
+}
+
+/// Decodes four adjacent varints from the input slice. Target types must fit within 16 bytes when
+/// varint encoded. See also: [`decode_four_unsafe`]
+///
+/// Produces a tuple containing the four decoded values, followed by the number of bytes read for
+/// each. If a varint overflows its target type or is cut off by the end of the input, an error is
+/// returned with its offset and index. For best performance, provide a slice at least 16 bytes in
+/// length.
+///
+/// # Examples
+/// ```
+/// use varint_simd::{decode_four, VarIntDecodeError};
+///
+/// let decoded = decode_four::<u16, u8, u8, u16>(&[185, 10, 1, 2, 172, 2]);
+/// assert_eq!(decoded, Ok((1337, 1, 2, 300, 2, 1, 1, 2)));
+///
+/// let error = decode_four::<u16, u8, u8, u16>(&[185, 10, 1, 2, 172]).unwrap_err();
+/// assert_eq!(error.error, VarIntDecodeError::NotEnoughBytes);
+/// assert_eq!((error.offset, error.index), (4, 3));
+/// ```
+#[inline]
+#[allow(clippy::type_complexity)]
+pub fn decode_four<T: VarIntTarget, U: VarIntTarget, V: VarIntTarget, W: VarIntTarget>(
+    bytes: &[u8],
+) -> Result<(T, U, V, W, usize, usize, usize, usize), SliceDecodeError> {
+    decode_four_impl::<T, U, V, W, false, false>(bytes)
+}
+
+/// Decodes four adjacent varints from the input slice, rejecting encodings that are not the
+/// shortest possible. See also: [`decode_four`], [`decode_strict`](crate::decode_strict)
+///
+/// # Examples
+/// ```
+/// use varint_simd::{decode_four_strict, VarIntDecodeError};
+///
+/// let decoded = decode_four_strict::<u16, u8, u8, u16>(&[185, 10, 1, 2, 172, 2]);
+/// assert_eq!(decoded, Ok((1337, 1, 2, 300, 2, 1, 1, 2)));
+///
+/// let error = decode_four_strict::<u16, u8, u8, u16>(&[185, 10, 1, 2, 0x80, 0]).unwrap_err();
+/// assert_eq!(error.error, VarIntDecodeError::NonCanonical);
+/// assert_eq!((error.offset, error.index), (4, 3));
+/// ```
+#[inline]
+#[allow(clippy::type_complexity)]
+pub fn decode_four_strict<T: VarIntTarget, U: VarIntTarget, V: VarIntTarget, W: VarIntTarget>(
+    bytes: &[u8],
+) -> Result<(T, U, V, W, usize, usize, usize, usize), SliceDecodeError> {
+    decode_four_impl::<T, U, V, W, true, false>(bytes)
+}
+
+#[inline(always)]
+#[allow(clippy::type_complexity)]
+fn decode_four_impl<
+    T: VarIntTarget,
+    U: VarIntTarget,
+    V: VarIntTarget,
+    W: VarIntTarget,
+    const STRICT: bool,
+    const SIGN_EXTEND: bool,
+>(
+    bytes: &[u8],
+) -> Result<(T, U, V, W, usize, usize, usize, usize), SliceDecodeError> {
+    let (first, second, third, fourth, first_len, second_len, third_len, fourth_len, invalid) =
+        with_window::<16, _>(bytes, |ptr| unsafe {
+            decode_four_unsafe::<T, U, V, W>(ptr)
+        });
+    let lens = [
+        first_len as usize,
+        second_len as usize,
+        third_len as usize,
+        fourth_len as usize,
+    ];
+
+    if !invalid
+        && is_valid::<T, STRICT>(bytes, 0, lens[0])
+        && is_valid::<U, STRICT>(bytes, lens[0], lens[1])
+        && is_valid::<V, STRICT>(bytes, lens[0] + lens[1], lens[2])
+        && is_valid::<W, STRICT>(bytes, lens[0] + lens[1] + lens[2], lens[3])
+    {
+        Ok((
+            first, second, third, fourth, lens[0], lens[1], lens[2], lens[3],
+        ))
+    } else {
+        let mut read = 0;
+        let mut lens = [0; 4];
+        let first = decode_at::<T, STRICT, SIGN_EXTEND>(bytes, &mut read, 0)?;
+        lens[0] = read;
+        let second = decode_at::<U, STRICT, SIGN_EXTEND>(bytes, &mut read, 1)?;
+        lens[1] = read - lens[0];
+        let third = decode_at::<V, STRICT, SIGN_EXTEND>(bytes, &mut read, 2)?;
+        lens[2] = read - lens[0] - lens[1];
+        let fourth = decode_at::<W, STRICT, SIGN_EXTEND>(bytes, &mut read, 3)?;
+        lens[3] = read - lens[0] - lens[1] - lens[2];
+        Ok((
+            first, second, third, fourth, lens[0], lens[1], lens[2], lens[3],
+        ))
+    }
+}
+
+/// Decodes eight adjacent varints into u8's from the input slice. See also:
+/// [`decode_eight_u8_checked_unsafe`]
+///
+/// Produces a tuple containing the decoded values, and the total number of bytes read. Unlike the
+/// unsafe version, varints longer than two bytes are detected as overflowing. If a varint
+/// overflows or is cut off by the end of the input, an error is returned with its offset and
+/// index. For best performance, provide a slice at least 16 bytes in length.
+///
+/// # Examples
+/// ```
+/// use varint_simd::{decode_eight_u8, VarIntDecodeError};
+///
+/// let decoded = decode_eight_u8(&[1, 2, 3, 4, 5, 6, 7, 128, 1]);
+/// assert_eq!(decoded, Ok(([1, 2, 3, 4, 5, 6, 7, 128], 9)));
+///
+/// let error = decode_eight_u8(&[1, 2, 3, 172, 2, 6, 7, 8]).unwrap_err();
+/// assert_eq!(error.error, VarIntDecodeError::Overflow);
+/// assert_eq!((error.offset, error.index), (3, 3));
+/// ```
+#[inline]
+pub fn decode_eight_u8(bytes: &[u8]) -> Result<([u8; 8], usize), SliceDecodeError> {
+    decode_eight_u8_impl::<false>(bytes)
+}
+
+/// Decodes eight adjacent varints into u8's from the input slice, rejecting encodings that are
+/// not the shortest possible. Two-byte varints with a zero second byte are detected in the vector
+/// registers along with overflowing ones. See also: [`decode_eight_u8`],
+/// [`decode_strict`](crate::decode_strict)
+///
+/// # Examples
+/// ```
+/// use varint_simd::{decode_eight_u8_strict, VarIntDecodeError};
+///
+/// let error = decode_eight_u8_strict(&[1, 2, 0x83, 0, 5, 6, 7, 8]).unwrap_err();
+/// assert_eq!(error.error, VarIntDecodeError::NonCanonical);
+/// assert_eq!((error.offset, error.index), (2, 2));
+/// ```
+#[inline]
+pub fn decode_eight_u8_strict(bytes: &[u8]) -> Result<([u8; 8], usize), SliceDecodeError> {
+    decode_eight_u8_impl::<true>(bytes)
+}
+
+#[inline(always)]
+fn decode_eight_u8_impl<const STRICT: bool>(
+    bytes: &[u8],
+) -> Result<([u8; 8], usize), SliceDecodeError> {
+    let (nums, len, invalid) = with_window::<16, _>(bytes, |ptr| unsafe {
+        if STRICT {
+            decode_eight_u8_strict_unsafe(ptr)
+        } else {
+            decode_eight_u8_checked_unsafe(ptr)
+        }
+    });
+    let len = len as usize;
+
+    if invalid == 0 && len <= bytes.len() {
+        Ok((nums, len))
+    } else {
+        let mut read = 0;
+        let mut nums = [0; 8];
+        for (index, num) in nums.iter_mut().enumerate() {
+            *num = decode_at::<u8, STRICT, false>(bytes, &mut read, index)?;
+        }
+        Ok((nums, read))
+    }
+}
+
+/// Whether the varint at `bytes[start..start + len]`, as measured by one of the unchecked
+/// decoders, is complete and fits in `T`. `STRICT` also requires it to be canonical.
+#[inline(always)]
+pub(super) fn is_valid<T: VarIntTarget, const STRICT: bool>(
+    bytes: &[u8],
+    start: usize,
+    len: usize,
+) -> bool {
+    let end = start + len;
+    end <= bytes.len()
+        && (len != 0 && len < T::MAX_VARINT_BYTES as usize
+            || len == T::MAX_VARINT_BYTES as usize && bytes[end - 1] <= T::MAX_LAST_VARINT_BYTE)
+        && (!STRICT || is_canonical(&bytes[start..], len))
+}
+
+/// Decodes the varint at `read` with the checked decoder, following the rules selected by `STRICT`
+/// and `SIGN_EXTEND`, and advances past it
+#[inline(always)]
+fn decode_at<T: VarIntTarget, const STRICT: bool, const SIGN_EXTEND: bool>(
+    bytes: &[u8],
+    read: &mut usize,
+    index: usize,
+) -> Result<T, SliceDecodeError> {
+    let (num, len) =
+        decode_with_options::<T, STRICT, SIGN_EXTEND>(&bytes[*read..]).map_err(|error| {
+            SliceDecodeError {
+                error,
+                offset: *read,
+                index,
+            }
+        })?;
+    *read += len;
+    Ok(num)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        decode_eight_u8, decode_eight_u8_strict, decode_four, decode_four_strict, decode_two,
+        decode_two_sign_extended, decode_two_strict, decode_two_wide, decode_two_wide_strict,
+    };
+    use crate::tests::{NUMS_U16, NUMS_U32, NUMS_U64, NUMS_U8};
+    use crate::{
+        encode, encode_padded, encode_sign_extended, encoded_len, SignedVarIntTarget,
+        SliceDecodeError, VarIntDecodeError, VarIntTarget,
+    };
+
+    fn encode_vec<T: VarIntTarget>(num: T, out: &mut Vec<u8>) -> usize {
+        let (bytes, len) = encode(num);
+        out.extend_from_slice(&bytes[..len as usize]);
+        len as usize
+    }
+
+    /// The error for input that ends at `cut`, given the lengths of the varints in it
+    fn truncated_error(lens: &[usize], cut: usize) -> SliceDecodeError {
+        let mut offset = 0;
+        let mut index = 0;
+        while offset + lens[index] <= cut {
+            offset += lens[index];
+            index += 1;
+        }
+        SliceDecodeError {
+            error: VarIntDecodeError::NotEnoughBytes,
+            offset,
+            index,
+        }
+    }
+
+    fn check_decode_2x<T: VarIntTarget, U: VarIntTarget>(a: &[T], b: &[U], wide: bool) {
+        for i in a {
+            for j in b {
+                let mut enc = Vec::new();
+                let first_len = encode_vec(*i, &mut enc);
+                let second_len = encode_vec(*j, &mut enc);
+
+                let decode = if wide {
+                    decode_two_wide::<T, U>
+                } else {
+                    decode_two::<T, U>
+                };
+
+                // The input is not padded to the size of the window
+                assert_eq!(decode(&enc), Ok((*i, *j, first_len, second_len)));
+
+                for cut in 0..enc.len() {
+                    let error = truncated_error(&[first_len, second_len], cut);
+                    assert_eq!(decode(&enc[..cut]), Err(error));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_2x() {
+        check_decode_2x::<u8, u64>(&NUMS_U8[..], &NUMS_U64[..], false);
+        check_decode_2x::<u16, u32>(&NUMS_U16[..], &NUMS_U32[..], false);
+        check_decode_2x::<u32, u32>(&NUMS_U32[..], &NUMS_U32[..], false);
+        check_decode_2x::<u64, u16>(&NUMS_U64[..], &NUMS_U16[..], false);
+
+        check_decode_2x::<u64, u64>(&NUMS_U64[..], &NUMS_U64[..], true);
+        check_decode_2x::<u32, u8>(&NUMS_U32[..], &NUMS_U8[..], true);
+    }
+
+    fn check_decode_4x<T: VarIntTarget, U: VarIntTarget, V: VarIntTarget, W: VarIntTarget>(
+        a: &[T],
+        b: &[U],
+        c: &[V],
+        d: &[W],
+    ) {
+        for i in a {
+            for j in b {
+                for k in c {
+                    for l in d {
+                        let mut enc = Vec::new();
+                        let lens = [
+                            encode_vec(*i, &mut enc),
+                            encode_vec(*j, &mut enc),
+                            encode_vec(*k, &mut enc),
+                            encode_vec(*l, &mut enc),
+                        ];
+
+                        assert_eq!(
+                            decode_four::<T, U, V, W>(&enc),
+                            Ok((*i, *j, *k, *l, lens[0], lens[1], lens[2], lens[3]))
+                        );
+
+                        let cut = enc.len() - 1;
+                        assert_eq!(
+                            decode_four::<T, U, V, W>(&enc[..cut]),
+                            Err(truncated_error(&lens, cut))
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // too slow
+    fn test_decode_4x() {
+        check_decode_4x::<u8, u8, u8, u8>(&NUMS_U8[..], &NUMS_U8[..], &NUMS_U8[..], &NUMS_U8[..]);
+        check_decode_4x::<u16, u16, u16, u16>(
+            &NUMS_U16[..],
+            &NUMS_U16[..],
+            &NUMS_U16[..],
+            &NUMS_U16[..],
+        );
+        check_decode_4x::<u32, u8, u32, u16>(
+            &NUMS_U32[..],
+            &NUMS_U8[..],
+            &NUMS_U32[..],
+            &NUMS_U16[..],
+        );
+    }
+
+    #[test]
+    fn test_decode_8x_u8() {
+        let nums = &NUMS_U8[..];
+        for i in 0..nums.len() {
+            for j in 1..nums.len() {
+                let values: [u8; 8] = core::array::from_fn(|k| nums[(i + j * k) % nums.len()]);
+
+                let mut enc = Vec::new();
+                let lens: Vec<usize> = values.iter().map(|v| encode_vec(*v, &mut enc)).collect();
+
+                assert_eq!(decode_eight_u8(&enc), Ok((values, enc.len())));
+                for cut in 0..enc.len() {
+                    assert_eq!(
+                        decode_eight_u8(&enc[..cut]),
+                        Err(truncated_error(&lens, cut))
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_multiple_window_edges() {
+        use VarIntDecodeError::*;
+        let error = |error, offset, index| SliceDecodeError {
+            error,
+            offset,
+            index,
+        };
+
+        // The second varint ends just before, at and just after the end of a 16-byte window
+        let mut enc = Vec::new();
+        encode_vec(u64::MAX, &mut enc);
+        for second in [1u64 << 28, 1 << 35, 1 << 42] {
+            let mut input = enc.clone();
+            let second_len = encode_vec(second, &mut input);
+            assert_eq!(
+                decode_two_wide::<u64, u64>(&input),
+                Ok((u64::MAX, second, 10, second_len))
+            );
+            let cut = input.len() - 1;
+            assert_eq!(
+                decode_two_wide::<u64, u64>(&input[..cut]),
+                Err(error(NotEnoughBytes, 10, 1))
+            );
+        }
+
+        // Overflow in the last lane, with and without anything after it in the window
+        let mut input = enc.clone();
+        input.extend_from_slice(&[0x80, 0x80, 0x80, 0x80, 0x10]);
+        assert_eq!(decode_two::<u64, u32>(&input), Err(error(Overflow, 10, 1)));
+        input.extend_from_slice(&[0; 16]);
+        assert_eq!(decode_two::<u64, u32>(&input), Err(error(Overflow, 10, 1)));
+
+        let mut input = vec![0xFF, 0xFF, 0x03, 0xFF, 0xFF, 0x03, 0x01, 0x80, 0x80, 0x04];
+        assert_eq!(
+            decode_four::<u16, u16, u16, u16>(&input),
+            Err(error(Overflow, 7, 3))
+        );
+        input.resize(16, 0);
+        assert_eq!(
+            decode_four::<u16, u16, u16, u16>(&input),
+            Err(error(Overflow, 7, 3))
+        );
+
+        let mut input = [0x80, 0x01].repeat(8);
+        assert_eq!(decode_eight_u8(&input), Ok(([0x80; 8], 16)));
+        input[15] = 0x02;
+        assert_eq!(decode_eight_u8(&input), Err(error(Overflow, 14, 7)));
+        assert_eq!(
+            decode_eight_u8(&input[..15]),
+            Err(error(NotEnoughBytes, 14, 7))
+        );
+    }
+
+    /// Appends the encoding of `num` padded by one byte if `pad` is set and the padded encoding fits
+    /// in the target type, and produces its length
+    fn encode_vec_padded<T: VarIntTarget>(num: T, pad: bool, out: &mut Vec<u8>) -> usize {
+        let len =
+            encoded_len(num) + (pad && encoded_len(num) < T::MAX_VARINT_BYTES as usize) as usize;
+        out.extend_from_slice(&encode_padded(num, len as u8)[..len]);
+        len
+    }
+
+    fn non_canonical(offset: usize, index: usize) -> SliceDecodeError {
+        SliceDecodeError {
+            error: VarIntDecodeError::NonCanonical,
+            offset,
+            index,
+        }
+    }
+
+    fn check_decode_2x_strict<T: VarIntTarget, U: VarIntTarget>(a: &[T], b: &[U], wide: bool) {
+        type Decode<T, U> = fn(&[u8]) -> Result<(T, U, usize, usize), SliceDecodeError>;
+        let (decode, decode_strict): (Decode<T, U>, Decode<T, U>) = if wide {
+            (decode_two_wide::<T, U>, decode_two_wide_strict::<T, U>)
+        } else {
+            (decode_two::<T, U>, decode_two_strict::<T, U>)
+        };
+
+        for i in a {
+            for j in b {
+                for pad in 0..3 {
+                    let mut enc = Vec::new();
+                    let first_len = encode_vec_padded(*i, pad == 1, &mut enc);
+                    let second_len = encode_vec_padded(*j, pad == 2, &mut enc);
+
+                    // Non-canonical varints are decoded as usual by the lenient decoder
+                    assert_eq!(decode(&enc), Ok((*i, *j, first_len, second_len)));
+
+                    let expected = if first_len != encoded_len(*i) {
+                        Err(non_canonical(0, 0))
+                    } else if second_len != encoded_len(*j) {
+                        Err(non_canonical(first_len, 1))
+                    } else {
+                        decode(&enc)
+                    };
+                    assert_eq!(decode_strict(&enc), expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_2x_strict() {
+        check_decode_2x_strict::<u8, u64>(&NUMS_U8[..], &NUMS_U64[..], false);
+        check_decode_2x_strict::<u16, u32>(&NUMS_U16[..], &NUMS_U32[..], false);
+        check_decode_2x_strict::<u32, u32>(&NUMS_U32[..], &NUMS_U32[..], false);
+
+        check_decode_2x_strict::<u64, u64>(&NUMS_U64[..], &NUMS_U64[..], true);
+        check_decode_2x_strict::<u32, u8>(&NUMS_U32[..], &NUMS_U8[..], true);
+    }
+
+    fn check_decode_4x_strict<
+        T: VarIntTarget,
+        U: VarIntTarget,
+        V: VarIntTarget,
+        W: VarIntTarget,
+    >(
+        a: &[T],
+        b: &[U],
+        c: &[V],
+        d: &[W],
+    ) {
+        for i in a {
+            for j in b {
+                for k in c {
+                    for l in d {
+                        for pad in 0..5 {
+                            let mut enc = Vec::new();
+                            let lens = [
+                                encode_vec_padded(*i, pad == 1, &mut enc),
+                                encode_vec_padded(*j, pad == 2, &mut enc),
+                                encode_vec_padded(*k, pad == 3, &mut enc),
+                                encode_vec_padded(*l, pad == 4, &mut enc),
+                            ];
+                            let canonical = [
+                                encoded_len(*i),
+                                encoded_len(*j),
+                                encoded_len(*k),
+                                encoded_len(*l),
+                            ];
+
+                            let expected = match (0..4).find(|n| lens[*n] != canonical[*n]) {
+                                Some(index) => {
+                                    Err(non_canonical(lens[..index].iter().sum(), index))
+                                }
+                                None => decode_four::<T, U, V, W>(&enc),
+                            };
+                            assert_eq!(decode_four_strict::<T, U, V, W>(&enc), expected);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // too slow
+    fn test_decode_4x_strict() {
+        check_decode_4x_strict::<u8, u8, u8, u8>(
+            &NUMS_U8[..],
+            &NUMS_U8[..],
+            &NUMS_U8[..],
+            &NUMS_U8[..],
+        );
+        check_decode_4x_strict::<u32, u8, u16, u16>(
+            &NUMS_U32[..],
+            &NUMS_U8[..],
+            &NUMS_U16[..],
+            &NUMS_U16[..],
+        );
+    }
+
+    #[test]
+    fn test_decode_8x_u8_strict() {
+        let nums = &NUMS_U8[..];
+        for i in 0..nums.len() {
+            for j in 1..nums.len() {
+                let values: [u8; 8] = core::array::from_fn(|k| nums[(i + j * k) % nums.len()]);
+
+                for pad in 0..9 {
+                    let mut enc = Vec::new();
+                    let lens: Vec<usize> = (0..8)
+                        .map(|k| encode_vec_padded(values[k], pad == k + 1, &mut enc))
+                        .collect();
+
+                    let expected = match (0..8).find(|k| lens[*k] != encoded_len(values[*k])) {
+                        Some(index) => Err(non_canonical(lens[..index].iter().sum(), index)),
+                        None => Ok((values, enc.len())),
+                    };
+                    assert_eq!(decode_eight_u8(&enc), Ok((values, enc.len())));
+                    assert_eq!(decode_eight_u8_strict(&enc), expected);
+                }
+            }
+        }
+    }
+
+    fn check_decode_2x_sign_extended<T: SignedVarIntTarget, U: SignedVarIntTarget>(
+        a: &[T::Unsigned],
+        b: &[U::Unsigned],
+    ) {
+        for i in a {
+            for j in b {
+                let (i, j) = (i.unzigzag(), j.unzigzag());
+
+                let mut enc = Vec::new();
+                let (first, first_len) = encode_sign_extended(i);
+                let (second, second_len) = encode_sign_extended(j);
+                enc.extend_from_slice(&first[..first_len as usize]);
+                enc.extend_from_slice(&second[..second_len as usize]);
+
+                assert_eq!(
+                    decode_two_sign_extended::<T, U>(&enc),
+                    Ok((i, j, first_len as usize, second_len as usize))
+                );
+
+                for cut in 0..enc.len() {
+                    let error = truncated_error(&[first_len as usize, second_len as usize], cut);
+                    assert_eq!(decode_two_sign_extended::<T, U>(&enc[..cut]), Err(error));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_2x_sign_extended() {
+        check_decode_2x_sign_extended::<i8, i8>(&NUMS_U8[..], &NUMS_U8[..]);
+        check_decode_2x_sign_extended::<i16, i32>(&NUMS_U16[..], &NUMS_U32[..]);
+        check_decode_2x_sign_extended::<i32, i32>(&NUMS_U32[..], &NUMS_U32[..]);
+        check_decode_2x_sign_extended::<i64, i8>(&NUMS_U64[..], &NUMS_U8[..]);
+        check_decode_2x_sign_extended::<i64, i64>(&NUMS_U64[..], &NUMS_U64[..]);
+    }
+}
@@ -0,0 +1,503 @@
+use core::arch::aarch64::*;
+
+use super::lookup;
+use crate::num::{self, VarIntTarget};
+
+const ASCEND: [u8; 16] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
+
+/// Equivalent of `_mm_movemask_epi8`, gathering the MSB of every byte into a 16-bit mask
+#[inline(always)]
+unsafe fn movemask(b: uint8x16_t) -> u32 {
+    let shifts: [i8; 16] = [0, 1, 2, 3, 4, 5, 6, 7, 0, 1, 2, 3, 4, 5, 6, 7];
+    let bits = vshlq_u8(vshrq_n_u8::<7>(b), vld1q_s8(shifts.as_ptr()));
+
+    let lo = vaddv_u8(vget_low_u8(bits)) as u32;
+    let hi = vaddv_u8(vget_high_u8(bits)) as u32;
+    lo | (hi << 8)
+}
+
+/// Equivalent of `_mm_bslli_si128`, shifting the whole vector up by `16 - N` bytes
+#[inline(always)]
+unsafe fn shift_up<const N: i32>(a: uint8x16_t) -> uint8x16_t {
+    vextq_u8::<N>(vdupq_n_u8(0), a)
+}
+
+#[inline]
+pub(crate) unsafe fn decode_two_unsafe<T: VarIntTarget, U: VarIntTarget>(
+    bytes: *const u8,
+) -> (T, U, u8, u8) {
+    decode_two::<T, U, false>(bytes)
+}
+
+#[inline]
+pub(crate) unsafe fn decode_two_zigzag_unsafe<T: VarIntTarget, U: VarIntTarget>(
+    bytes: *const u8,
+) -> (T, U, u8, u8) {
+    decode_two::<T, U, true>(bytes)
+}
+
+/// `ZIGZAG` decodes the values from ZigZag format, leaving the bits of the signed results in the
+/// unsigned types
+#[inline(always)]
+unsafe fn decode_two<T: VarIntTarget, U: VarIntTarget, const ZIGZAG: bool>(
+    bytes: *const u8,
+) -> (T, U, u8, u8) {
+    if T::MAX_VARINT_BYTES <= 5 && U::MAX_VARINT_BYTES <= 5 {
+        // This will work with our lookup table, use that version
+        return decode_two_u32::<T, U, ZIGZAG>(bytes);
+    }
+
+    let b = vld1q_u8(bytes);
+
+    // First find where the boundaries are
+    let bitmask = movemask(b);
+
+    // Find the number of bytes taken up by each varint
+    let bm_not = !bitmask;
+    let first_len = bm_not.trailing_zeros() + 1;
+    let bm_not_2 = bm_not >> first_len;
+    let second_len = bm_not_2.trailing_zeros() + 1;
+
+    let ascend = vld1q_u8(ASCEND.as_ptr());
+
+    let first_len_vec = vdupq_n_u8(first_len as u8);
+    let first_mask = vcltq_u8(ascend, first_len_vec);
+    let first = vandq_u8(b, first_mask);
+
+    // Out-of-range TBL indices produce zero, unlike PSHUFB
+    let second_shuf = vaddq_u8(ascend, first_len_vec);
+    let second_shuffled = vqtbl1q_u8(b, second_shuf);
+    let second_mask = vcltq_u8(ascend, vdupq_n_u8(second_len as u8));
+    let second = vandq_u8(second_shuffled, second_mask);
+
+    let first_num;
+    let second_num;
+
+    // Only use "turbo" mode if the numbers fit in 64-bit lanes
+    let should_turbo = T::MAX_VARINT_BYTES <= 8 && U::MAX_VARINT_BYTES <= 8;
+    if should_turbo {
+        // const, so optimized out
+        let comb = vorrq_u8(first, shift_up::<8>(second));
+
+        let x = if T::MAX_VARINT_BYTES <= 2 && U::MAX_VARINT_BYTES <= 2 {
+            dual_u8_stage2(comb)
+        } else if T::MAX_VARINT_BYTES <= 3 && U::MAX_VARINT_BYTES <= 3 {
+            dual_u16_stage2(comb)
+        } else {
+            dual_u32_stage2(comb)
+        };
+        let x = if ZIGZAG { unzigzag_dual::<T, U>(x) } else { x };
+
+        first_num = T::cast_u32(vgetq_lane_u64::<0>(x) as u32);
+        second_num = U::cast_u32(vgetq_lane_u64::<1>(x) as u32);
+    } else {
+        first_num = vector_to_num::<T, ZIGZAG>(first);
+        second_num = vector_to_num::<U, ZIGZAG>(second);
+    }
+
+    (first_num, second_num, first_len as u8, second_len as u8)
+}
+
+#[inline(always)]
+unsafe fn decode_two_u32<T: VarIntTarget, U: VarIntTarget, const ZIGZAG: bool>(
+    bytes: *const u8,
+) -> (T, U, u8, u8) {
+    let b = vld1q_u8(bytes);
+
+    // Get the movemask and mask out irrelevant parts
+    let bitmask = movemask(b) & 0b1111111111;
+
+    // Use lookup table to get the shuffle mask
+    let (lookup, first_len, second_len) =
+        *lookup::LOOKUP_DOUBLE_STEP1.get_unchecked(bitmask as usize);
+    let shuf = *lookup::LOOKUP_DOUBLE_VEC.get_unchecked(lookup as usize);
+
+    let comb = vqtbl1q_u8(b, shuf);
+
+    let x = if T::MAX_VARINT_BYTES <= 2 && U::MAX_VARINT_BYTES <= 2 {
+        dual_u8_stage2(comb)
+    } else if T::MAX_VARINT_BYTES <= 3 && U::MAX_VARINT_BYTES <= 3 {
+        dual_u16_stage2(comb)
+    } else {
+        dual_u32_stage2(comb)
+    };
+    let x = if ZIGZAG { unzigzag_dual::<T, U>(x) } else { x };
+
+    let first_num = T::cast_u32(vgetq_lane_u64::<0>(x) as u32);
+    let second_num = U::cast_u32(vgetq_lane_u64::<1>(x) as u32);
+
+    (first_num, second_num, first_len, second_len)
+}
+
+/// Equivalent of [`VarIntTarget::vector_to_num`], ZigZag decoding the result if `ZIGZAG` is set
+#[inline(always)]
+unsafe fn vector_to_num<T: VarIntTarget, const ZIGZAG: bool>(res: uint8x16_t) -> T {
+    let num = T::vector_to_num(core::mem::transmute::<uint8x16_t, [u8; 16]>(res));
+    if ZIGZAG {
+        num::unzigzag_bits(num)
+    } else {
+        num
+    }
+}
+
+/// ZigZag decodes the value in each 64-bit lane, after truncating it to the width of its type
+#[inline(always)]
+unsafe fn unzigzag_dual<T: VarIntTarget, U: VarIntTarget>(x: uint64x2_t) -> uint64x2_t {
+    let masks = [num::width_mask::<T>(), num::width_mask::<U>()];
+    let x = vandq_u64(x, vld1q_u64(masks.as_ptr()));
+    let sign = vreinterpretq_u64_s64(vnegq_s64(vreinterpretq_s64_u64(vandq_u64(
+        x,
+        vdupq_n_u64(1),
+    ))));
+    veorq_u64(vshrq_n_u64::<1>(x), sign)
+}
+
+/// ZigZag decodes the value in each 32-bit lane, after truncating it to the width of its type
+#[inline(always)]
+unsafe fn unzigzag_quad<T: VarIntTarget, U: VarIntTarget, V: VarIntTarget, W: VarIntTarget>(
+    x: uint32x4_t,
+) -> uint32x4_t {
+    let masks = [
+        num::width_mask::<T>() as u32,
+        num::width_mask::<U>() as u32,
+        num::width_mask::<V>() as u32,
+        num::width_mask::<W>() as u32,
+    ];
+    let x = vandq_u32(x, vld1q_u32(masks.as_ptr()));
+    let sign = vreinterpretq_u32_s32(vnegq_s32(vreinterpretq_s32_u32(vandq_u32(
+        x,
+        vdupq_n_u32(1),
+    ))));
+    veorq_u32(vshrq_n_u32::<1>(x), sign)
+}
+
+#[inline(always)]
+unsafe fn dual_u8_stage2(comb: uint8x16_t) -> uint64x2_t {
+    let comb = vreinterpretq_u64_u8(comb);
+    vorrq_u64(
+        vandq_u64(comb, vdupq_n_u64(0x000000000000007f)),
+        vshrq_n_u64::<1>(vandq_u64(comb, vdupq_n_u64(0x0000000000000100))),
+    )
+}
+
+#[inline(always)]
+unsafe fn dual_u16_stage2(comb: uint8x16_t) -> uint64x2_t {
+    let comb = vreinterpretq_u64_u8(comb);
+    vorrq_u64(
+        vorrq_u64(
+            vandq_u64(comb, vdupq_n_u64(0x000000000000007f)),
+            vshrq_n_u64::<2>(vandq_u64(comb, vdupq_n_u64(0x0000000000030000))),
+        ),
+        vshrq_n_u64::<1>(vandq_u64(comb, vdupq_n_u64(0x0000000000007f00))),
+    )
+}
+
+#[inline(always)]
+unsafe fn dual_u32_stage2(comb: uint8x16_t) -> uint64x2_t {
+    let comb = vreinterpretq_u64_u8(comb);
+    vorrq_u64(
+        vorrq_u64(
+            vandq_u64(comb, vdupq_n_u64(0x000000000000007f)),
+            vshrq_n_u64::<4>(vandq_u64(comb, vdupq_n_u64(0x0000000f00000000))),
+        ),
+        vorrq_u64(
+            vorrq_u64(
+                vshrq_n_u64::<3>(vandq_u64(comb, vdupq_n_u64(0x000000007f000000))),
+                vshrq_n_u64::<2>(vandq_u64(comb, vdupq_n_u64(0x00000000007f0000))),
+            ),
+            vshrq_n_u64::<1>(vandq_u64(comb, vdupq_n_u64(0x0000000000007f00))),
+        ),
+    )
+}
+
+#[inline(always)]
+unsafe fn quad_stage2<T: VarIntTarget, U: VarIntTarget, V: VarIntTarget, W: VarIntTarget>(
+    comb: uint8x16_t,
+) -> uint32x4_t {
+    let comb = vreinterpretq_u32_u8(comb);
+    if T::MAX_VARINT_BYTES <= 2
+        && U::MAX_VARINT_BYTES <= 2
+        && V::MAX_VARINT_BYTES <= 2
+        && W::MAX_VARINT_BYTES <= 2
+    {
+        vorrq_u32(
+            vandq_u32(comb, vdupq_n_u32(0x0000007f)),
+            vshrq_n_u32::<1>(vandq_u32(comb, vdupq_n_u32(0x00000100))),
+        )
+    } else {
+        vorrq_u32(
+            vorrq_u32(
+                vandq_u32(comb, vdupq_n_u32(0x0000007f)),
+                vshrq_n_u32::<2>(vandq_u32(comb, vdupq_n_u32(0x00030000))),
+            ),
+            vshrq_n_u32::<1>(vandq_u32(comb, vdupq_n_u32(0x00007f00))),
+        )
+    }
+}
+
+#[inline]
+pub(crate) unsafe fn decode_four_unsafe<
+    T: VarIntTarget,
+    U: VarIntTarget,
+    V: VarIntTarget,
+    W: VarIntTarget,
+>(
+    bytes: *const u8,
+) -> (T, U, V, W, u8, u8, u8, u8, bool) {
+    decode_four::<T, U, V, W, false>(bytes)
+}
+
+#[inline]
+pub(crate) unsafe fn decode_four_zigzag_unsafe<
+    T: VarIntTarget,
+    U: VarIntTarget,
+    V: VarIntTarget,
+    W: VarIntTarget,
+>(
+    bytes: *const u8,
+) -> (T, U, V, W, u8, u8, u8, u8, bool) {
+    decode_four::<T, U, V, W, true>(bytes)
+}
+
+/// `ZIGZAG` decodes the values from ZigZag format, leaving the bits of the signed results in the
+/// unsigned types
+#[inline(always)]
+unsafe fn decode_four<
+    T: VarIntTarget,
+    U: VarIntTarget,
+    V: VarIntTarget,
+    W: VarIntTarget,
+    const ZIGZAG: bool,
+>(
+    bytes: *const u8,
+) -> (T, U, V, W, u8, u8, u8, u8, bool) {
+    if T::MAX_VARINT_BYTES <= 3
+        && U::MAX_VARINT_BYTES <= 3
+        && V::MAX_VARINT_BYTES <= 3
+        && W::MAX_VARINT_BYTES <= 3
+    {
+        return decode_four_u16::<T, U, V, W, ZIGZAG>(bytes);
+    }
+
+    let b = vld1q_u8(bytes);
+
+    // First find where the boundaries are
+    let bitmask = movemask(b);
+
+    // Find the number of bytes taken up by each varint
+    let bm_not = !bitmask;
+    let first_len = bm_not.trailing_zeros() + 1;
+    let bm_not_2 = bm_not >> first_len;
+    let second_len = bm_not_2.trailing_zeros() + 1;
+    let bm_not_3 = bm_not_2 >> second_len;
+    let third_len = bm_not_3.trailing_zeros() + 1;
+    let bm_not_4 = bm_not_3 >> third_len;
+    let fourth_len = bm_not_4.trailing_zeros() + 1;
+
+    let ascend = vld1q_u8(ASCEND.as_ptr());
+
+    let first_len_vec = vdupq_n_u8(first_len as u8);
+    let first_mask = vcltq_u8(ascend, first_len_vec);
+    let first = vandq_u8(b, first_mask);
+
+    let second_shuf = vaddq_u8(ascend, first_len_vec);
+    let second_shuffled = vqtbl1q_u8(b, second_shuf);
+    let second_len_vec = vdupq_n_u8(second_len as u8);
+    let second_mask = vcltq_u8(ascend, second_len_vec);
+    let second = vandq_u8(second_shuffled, second_mask);
+
+    let third_shuf = vaddq_u8(ascend, second_len_vec);
+    let third_shuffled = vqtbl1q_u8(second_shuffled, third_shuf);
+    let third_len_vec = vdupq_n_u8(third_len as u8);
+    let third_mask = vcltq_u8(ascend, third_len_vec);
+    let third = vandq_u8(third_shuffled, third_mask);
+
+    let fourth_shuf = vaddq_u8(ascend, third_len_vec);
+    let fourth_shuffled = vqtbl1q_u8(third_shuffled, fourth_shuf);
+    let fourth_len_vec = vdupq_n_u8(fourth_len as u8);
+    let fourth_mask = vcltq_u8(ascend, fourth_len_vec);
+    let fourth = vandq_u8(fourth_shuffled, fourth_mask);
+
+    let first_num;
+    let second_num;
+    let third_num;
+    let fourth_num;
+
+    // Only use "turbo" mode if the numbers fit in 32-bit lanes
+    let should_turbo = T::MAX_VARINT_BYTES <= 4
+        && U::MAX_VARINT_BYTES <= 4
+        && V::MAX_VARINT_BYTES <= 4
+        && W::MAX_VARINT_BYTES <= 4;
+    if should_turbo {
+        // const, so optimized out
+        let comb = vorrq_u8(
+            vorrq_u8(first, shift_up::<12>(second)),
+            vorrq_u8(shift_up::<8>(third), shift_up::<4>(fourth)),
+        );
+
+        let x = quad_stage2::<T, U, V, W>(comb);
+        let x = if ZIGZAG {
+            unzigzag_quad::<T, U, V, W>(x)
+        } else {
+            x
+        };
+
+        first_num = T::cast_u32(vgetq_lane_u32::<0>(x));
+        second_num = U::cast_u32(vgetq_lane_u32::<1>(x));
+        third_num = V::cast_u32(vgetq_lane_u32::<2>(x));
+        fourth_num = W::cast_u32(vgetq_lane_u32::<3>(x));
+    } else {
+        first_num = vector_to_num::<T, ZIGZAG>(first);
+        second_num = vector_to_num::<U, ZIGZAG>(second);
+        third_num = vector_to_num::<V, ZIGZAG>(third);
+        fourth_num = vector_to_num::<W, ZIGZAG>(fourth);
+    }
+
+    (
+        first_num,
+        second_num,
+        third_num,
+        fourth_num,
+        first_len as u8,
+        second_len as u8,
+        third_len as u8,
+        fourth_len as u8,
+        false,
+    )
+}
+
+#[inline(always)]
+unsafe fn decode_four_u16<
+    T: VarIntTarget,
+    U: VarIntTarget,
+    V: VarIntTarget,
+    W: VarIntTarget,
+    const ZIGZAG: bool,
+>(
+    bytes: *const u8,
+) -> (T, U, V, W, u8, u8, u8, u8, bool) {
+    let b = vld1q_u8(bytes);
+
+    // First find where the boundaries are
+    let bitmask = movemask(b);
+
+    // Use the lookup table
+    let lookup = *lookup::LOOKUP_QUAD_STEP1.get_unchecked((bitmask & 0b111111111111) as usize);
+
+    // Fetch the shuffle mask
+    let shuf = *lookup::LOOKUP_QUAD_VEC.get_unchecked((lookup & 0b11111111) as usize);
+
+    // Extract the lengths while we're waiting
+    let first_len = (lookup >> 8) & 0b1111;
+    let second_len = (lookup >> 12) & 0b1111;
+    let third_len = (lookup >> 16) & 0b1111;
+    let fourth_len = (lookup >> 20) & 0b1111;
+
+    let comb = vqtbl1q_u8(b, shuf);
+
+    let invalid = lookup >> 31;
+
+    let x = quad_stage2::<T, U, V, W>(comb);
+    let x = if ZIGZAG {
+        unzigzag_quad::<T, U, V, W>(x)
+    } else {
+        x
+    };
+
+    (
+        T::cast_u32(vgetq_lane_u32::<0>(x)),
+        U::cast_u32(vgetq_lane_u32::<1>(x)),
+        V::cast_u32(vgetq_lane_u32::<2>(x)),
+        W::cast_u32(vgetq_lane_u32::<3>(x)),
+        first_len as u8,
+        second_len as u8,
+        third_len as u8,
+        fourth_len as u8,
+        invalid != 0,
+    )
+}
+
+#[inline]
+pub(crate) unsafe fn decode_eight_u8_unsafe(bytes: *const u8) -> ([u8; 8], u8) {
+    let (nums, len, _) = decode_eight_u8_checked_unsafe(bytes);
+    (nums, len)
+}
+
+#[inline]
+pub(crate) unsafe fn decode_eight_u8_checked_unsafe(bytes: *const u8) -> ([u8; 8], u8, u8) {
+    decode_eight_u8_impl::<false>(bytes)
+}
+
+#[inline]
+pub(crate) unsafe fn decode_eight_u8_strict_unsafe(bytes: *const u8) -> ([u8; 8], u8, u8) {
+    decode_eight_u8_impl::<true>(bytes)
+}
+
+/// `STRICT` also marks the non-canonical varints in the overflow bitmask
+#[inline(always)]
+unsafe fn decode_eight_u8_impl<const STRICT: bool>(bytes: *const u8) -> ([u8; 8], u8, u8) {
+    let b = vld1q_u8(bytes);
+
+    let ones = vdupq_n_u8(1);
+    let mut lens = vdupq_n_u8(0);
+    let mut shift = vshrq_n_u8::<7>(b);
+    let ascend = vld1q_u8(ASCEND.as_ptr());
+    let asc_one = vaddq_u8(ascend, ones);
+    let mut window_small = vsetq_lane_u8::<0>(1, vdupq_n_u8(0));
+
+    // if the first byte is zero, shift down by 1, if the first byte is one, shift down by 2
+    for _ in 0..8 {
+        let first_byte = vdupq_laneq_u8::<0>(shift);
+        shift = vqtbl1q_u8(shift, vaddq_u8(asc_one, first_byte));
+        lens = vorrq_u8(lens, vandq_u8(first_byte, window_small));
+        window_small = shift_up::<15>(window_small);
+    }
+
+    // Construct the shuffle
+
+    let lens_invert = vsubq_u8(ones, lens);
+    let mut cumul_lens = vaddq_u8(lens_invert, shift_up::<15>(lens_invert));
+    cumul_lens = vaddq_u8(cumul_lens, shift_up::<14>(cumul_lens));
+    cumul_lens = vaddq_u8(cumul_lens, shift_up::<12>(cumul_lens));
+    cumul_lens = vaddq_u8(cumul_lens, shift_up::<8>(cumul_lens));
+
+    let last_len = 8 - vgetq_lane_u8::<7>(cumul_lens) + 8;
+
+    // Set one-lengthed second bytes to negative
+    let second_shuf: [u8; 16] = [
+        255, 0, 255, 1, 255, 2, 255, 3, 255, 4, 255, 5, 255, 6, 255, 7,
+    ];
+    let second = vqtbl1q_u8(vaddq_u8(lens, ones), vld1q_u8(second_shuf.as_ptr()));
+
+    let shuf_pt1 = vorrq_u8(ascend, vceqq_u8(second, ones));
+
+    // Subtract the cumulative sum of zero-lengths to adjust the indexes
+    let x_shuf_idx: [u8; 16] = [0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7];
+    let x_shuf = vqtbl1q_u8(shift_up::<15>(cumul_lens), vld1q_u8(x_shuf_idx.as_ptr()));
+
+    let shuf = vsubq_u8(shuf_pt1, x_shuf);
+    let comb = vreinterpretq_u16_u8(vqtbl1q_u8(b, shuf));
+
+    let x = vorrq_u16(
+        vandq_u16(comb, vdupq_n_u16(0x007f)),
+        vshrq_n_u16::<1>(vandq_u16(comb, vdupq_n_u16(0x0100))),
+    );
+
+    // Keep the low byte of each 16-bit lane
+    let nums = core::mem::transmute::<uint8x8_t, [u8; 8]>(vmovn_u16(x));
+
+    // The second byte of each lane is zero for one-byte varints, and must be at most one otherwise
+    let overflow = vcgtq_u16(vshrq_n_u16::<8>(comb), vdupq_n_u16(1));
+    // A two-byte varint is non-canonical if its second byte is zero
+    let overflow = if STRICT {
+        let non_canonical = vceqq_u16(vandq_u16(comb, vdupq_n_u16(0xff80)), vdupq_n_u16(0x0080));
+        vorrq_u16(overflow, non_canonical)
+    } else {
+        overflow
+    };
+    let overflow = vmovn_u16(overflow);
+    let lane_bits: [u8; 8] = [1, 2, 4, 8, 16, 32, 64, 128];
+    let overflow = vaddv_u8(vand_u8(overflow, vld1_u8(lane_bits.as_ptr())));
+
+    (nums, last_len, overflow)
+}
@@ -0,0 +1,326 @@
+//! Decoders for nonzero types, which report a varint of zero as an error.
+
+use super::{decode, decode_four, decode_two, decode_two_wide};
+use crate::num::{NonZeroVarIntTarget, VarIntTarget};
+use crate::{SliceDecodeError, VarIntDecodeError};
+
+/// Decodes a single varint from the input slice to a nonzero type, returning
+/// [`VarIntDecodeError::Zero`] if it encodes zero. See also: [`decode`]
+///
+/// # Examples
+/// ```
+/// use core::num::NonZeroU32;
+/// use varint_simd::{decode_nonzero, VarIntDecodeError};
+///
+/// assert_eq!(decode_nonzero::<NonZeroU32>(&[185, 10]), Ok((NonZeroU32::new(1337).unwrap(), 2)));
+/// assert_eq!(decode_nonzero::<NonZeroU32>(&[0]), Err(VarIntDecodeError::Zero));
+/// ```
+#[inline]
+pub fn decode_nonzero<T: NonZeroVarIntTarget>(
+    bytes: &[u8],
+) -> Result<(T, usize), VarIntDecodeError> {
+    let (num, len) = decode::<T::Inner>(bytes)?;
+    T::new(num)
+        .map(|num| (num, len))
+        .ok_or(VarIntDecodeError::Zero)
+}
+
+/// Decodes two adjacent varints from the input slice to nonzero types, returning
+/// [`VarIntDecodeError::Zero`] with the offset and index of the first one that encodes zero. See
+/// also: [`decode_two`], [`decode_two_wide`]
+///
+/// Pairs that fit within 16 bytes are decoded with [`decode_two`], other pairs of types up to
+/// `u64` with [`decode_two_wide`], and pairs including `NonZeroU128` one at a time.
+///
+/// # Examples
+/// ```
+/// use core::num::{NonZeroU32, NonZeroU64};
+/// use varint_simd::{decode_two_nonzero, VarIntDecodeError};
+///
+/// let (first, second, _, _) = decode_two_nonzero::<NonZeroU32, NonZeroU64>(&[185, 10, 1]).unwrap();
+/// assert_eq!((first.get(), second.get()), (1337, 1));
+///
+/// let error = decode_two_nonzero::<NonZeroU32, NonZeroU64>(&[185, 10, 0]).unwrap_err();
+/// assert_eq!(error.error, VarIntDecodeError::Zero);
+/// assert_eq!((error.offset, error.index), (2, 1));
+/// ```
+#[inline]
+pub fn decode_two_nonzero<T: NonZeroVarIntTarget, U: NonZeroVarIntTarget>(
+    bytes: &[u8],
+) -> Result<(T, U, usize, usize), SliceDecodeError> {
+    let decoded = if T::Inner::MAX_VARINT_BYTES + U::Inner::MAX_VARINT_BYTES <= 16 {
+        Some(decode_two::<T::Inner, U::Inner>(bytes))
+    } else if T::Inner::MAX_VARINT_BYTES <= 16 && U::Inner::MAX_VARINT_BYTES <= 16 {
+        Some(decode_two_wide::<T::Inner, U::Inner>(bytes))
+    } else {
+        None
+    };
+
+    if let Some(Ok((first, second, first_len, second_len))) = decoded {
+        if let (Some(first), Some(second)) = (T::new(first), U::new(second)) {
+            return Ok((first, second, first_len, second_len));
+        }
+    }
+
+    // Decode one at a time to report whichever error comes first
+    let mut read = 0;
+    let first = decode_nonzero_at::<T>(bytes, &mut read, 0)?;
+    let first_len = read;
+    let second = decode_nonzero_at::<U>(bytes, &mut read, 1)?;
+    Ok((first, second, first_len, read - first_len))
+}
+
+/// Decodes four adjacent varints from the input slice to nonzero types, returning
+/// [`VarIntDecodeError::Zero`] with the offset and index of the first one that encodes zero.
+/// Target types must fit within 16 bytes when varint encoded, as with [`decode_four`].
+///
+/// # Examples
+/// ```
+/// use core::num::{NonZeroU16, NonZeroU8};
+/// use varint_simd::{decode_four_nonzero, VarIntDecodeError};
+///
+/// let decoded = decode_four_nonzero::<NonZeroU16, NonZeroU8, NonZeroU8, NonZeroU16>(&[185, 10, 1, 2, 172, 2]);
+/// assert_eq!(decoded.unwrap().3.get(), 300);
+///
+/// let error = decode_four_nonzero::<NonZeroU16, NonZeroU8, NonZeroU8, NonZeroU16>(&[185, 10, 1, 0, 1])
+///     .unwrap_err();
+/// assert_eq!(error.error, VarIntDecodeError::Zero);
+/// assert_eq!((error.offset, error.index), (3, 2));
+/// ```
+#[inline]
+#[allow(clippy::type_complexity)]
+pub fn decode_four_nonzero<
+    T: NonZeroVarIntTarget,
+    U: NonZeroVarIntTarget,
+    V: NonZeroVarIntTarget,
+    W: NonZeroVarIntTarget,
+>(
+    bytes: &[u8],
+) -> Result<(T, U, V, W, usize, usize, usize, usize), SliceDecodeError> {
+    if let Ok((first, second, third, fourth, first_len, second_len, third_len, fourth_len)) =
+        decode_four::<T::Inner, U::Inner, V::Inner, W::Inner>(bytes)
+    {
+        if let (Some(first), Some(second), Some(third), Some(fourth)) =
+            (T::new(first), U::new(second), V::new(third), W::new(fourth))
+        {
+            return Ok((
+                first, second, third, fourth, first_len, second_len, third_len, fourth_len,
+            ));
+        }
+    }
+
+    // Decode one at a time to report whichever error comes first
+    let mut read = 0;
+    let first = decode_nonzero_at::<T>(bytes, &mut read, 0)?;
+    let first_len = read;
+    let second = decode_nonzero_at::<U>(bytes, &mut read, 1)?;
+    let second_len = read - first_len;
+    let third = decode_nonzero_at::<V>(bytes, &mut read, 2)?;
+    let third_len = read - first_len - second_len;
+    let fourth = decode_nonzero_at::<W>(bytes, &mut read, 3)?;
+    let fourth_len = read - first_len - second_len - third_len;
+    Ok((
+        first, second, third, fourth, first_len, second_len, third_len, fourth_len,
+    ))
+}
+
+/// Decodes the varint at `read` with [`decode_nonzero`], and advances past it
+#[inline(always)]
+fn decode_nonzero_at<T: NonZeroVarIntTarget>(
+    bytes: &[u8],
+    read: &mut usize,
+    index: usize,
+) -> Result<T, SliceDecodeError> {
+    let (num, len) = decode_nonzero::<T>(&bytes[*read..]).map_err(|error| SliceDecodeError {
+        error,
+        offset: *read,
+        index,
+    })?;
+    *read += len;
+    Ok(num)
+}
+
+#[cfg(test)]
+mod tests {
+    use core::num::{NonZeroU128, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize};
+
+    use super::{decode_four_nonzero, decode_nonzero, decode_two_nonzero};
+    use crate::num::NonZeroVarIntTarget;
+    use crate::tests::{NUMS_U128, NUMS_U16, NUMS_U32, NUMS_U64, NUMS_U8};
+    use crate::{
+        decode, encode_nonzero, encode_wide, Backend, SliceDecodeError, VarIntDecodeError,
+        VarIntTarget,
+    };
+
+    fn encode_vec<T: VarIntTarget>(num: T, out: &mut Vec<u8>) -> usize {
+        let (bytes, len) = encode_wide(num);
+        out.extend_from_slice(&bytes[..len as usize]);
+        len as usize
+    }
+
+    fn zero_error(offset: usize, index: usize) -> SliceDecodeError {
+        SliceDecodeError {
+            error: VarIntDecodeError::Zero,
+            offset,
+            index,
+        }
+    }
+
+    fn check_decode_nonzero<T: NonZeroVarIntTarget>(nums: &[T::Inner]) {
+        for num in nums {
+            let mut enc = Vec::new();
+            let len = encode_vec(*num, &mut enc);
+
+            let expected = match T::new(*num) {
+                Some(nonzero) => Ok((nonzero, len)),
+                None => Err(VarIntDecodeError::Zero),
+            };
+            assert_eq!(decode_nonzero::<T>(&enc), expected);
+
+            // Other errors are those of decode
+            assert_eq!(
+                decode_nonzero::<T>(&enc[..len - 1]).map(|(num, len)| (num.get(), len)),
+                decode::<T::Inner>(&enc[..len - 1])
+            );
+
+            for backend in Backend::supported() {
+                assert_eq!(backend.decode_nonzero::<T>(&enc), expected, "{:?}", backend);
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_nonzero() {
+        check_decode_nonzero::<NonZeroU8>(&NUMS_U8[..]);
+        check_decode_nonzero::<NonZeroU16>(&NUMS_U16[..]);
+        check_decode_nonzero::<NonZeroU32>(&NUMS_U32[..]);
+        check_decode_nonzero::<NonZeroU64>(&NUMS_U64[..]);
+        check_decode_nonzero::<NonZeroU128>(&NUMS_U128[..]);
+
+        let nums: Vec<usize> = NUMS_U32.iter().map(|n| *n as usize).collect();
+        check_decode_nonzero::<NonZeroUsize>(&nums);
+    }
+
+    fn check_encode_nonzero<T: NonZeroVarIntTarget>(nums: &[T::Inner]) {
+        for nonzero in nums.iter().filter_map(|num| T::new(*num)) {
+            let encoded = encode_nonzero(nonzero);
+            assert_eq!(
+                decode_nonzero::<T>(&encoded.0),
+                Ok((nonzero, encoded.1 as usize))
+            );
+
+            for backend in Backend::supported() {
+                assert_eq!(backend.encode_nonzero(nonzero), encoded, "{:?}", backend);
+            }
+        }
+    }
+
+    #[test]
+    fn test_encode_nonzero() {
+        check_encode_nonzero::<NonZeroU8>(&NUMS_U8[..]);
+        check_encode_nonzero::<NonZeroU16>(&NUMS_U16[..]);
+        check_encode_nonzero::<NonZeroU32>(&NUMS_U32[..]);
+        check_encode_nonzero::<NonZeroU64>(&NUMS_U64[..]);
+    }
+
+    fn check_decode_2x_nonzero<T: NonZeroVarIntTarget, U: NonZeroVarIntTarget>(
+        a: &[T::Inner],
+        b: &[U::Inner],
+    ) {
+        for i in a {
+            for j in b {
+                let mut enc = Vec::new();
+                let first_len = encode_vec(*i, &mut enc);
+                let second_len = encode_vec(*j, &mut enc);
+
+                let expected = match (T::new(*i), U::new(*j)) {
+                    (None, _) => Err(zero_error(0, 0)),
+                    (_, None) => Err(zero_error(first_len, 1)),
+                    (Some(first), Some(second)) => Ok((first, second, first_len, second_len)),
+                };
+                assert_eq!(decode_two_nonzero::<T, U>(&enc), expected);
+
+                // Errors other than zero are reported as usual
+                if expected.is_ok() {
+                    let cut = enc.len() - 1;
+                    let error = decode_two_nonzero::<T, U>(&enc[..cut]).unwrap_err();
+                    assert_eq!(error.error, VarIntDecodeError::NotEnoughBytes);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_2x_nonzero() {
+        check_decode_2x_nonzero::<NonZeroU8, NonZeroU8>(&NUMS_U8[..], &NUMS_U8[..]);
+        check_decode_2x_nonzero::<NonZeroU16, NonZeroU32>(&NUMS_U16[..], &NUMS_U32[..]);
+        check_decode_2x_nonzero::<NonZeroU64, NonZeroU64>(&NUMS_U64[..], &NUMS_U64[..]);
+        check_decode_2x_nonzero::<NonZeroU128, NonZeroU8>(&NUMS_U128[..], &NUMS_U8[..]);
+        check_decode_2x_nonzero::<NonZeroU32, NonZeroU128>(&NUMS_U32[..], &NUMS_U128[..]);
+    }
+
+    fn check_decode_4x_nonzero<
+        T: NonZeroVarIntTarget,
+        U: NonZeroVarIntTarget,
+        V: NonZeroVarIntTarget,
+        W: NonZeroVarIntTarget,
+    >(
+        a: &[T::Inner],
+        b: &[U::Inner],
+        c: &[V::Inner],
+        d: &[W::Inner],
+    ) {
+        for i in a {
+            for j in b {
+                for k in c {
+                    for l in d {
+                        let mut enc = Vec::new();
+                        let lens = [
+                            encode_vec(*i, &mut enc),
+                            encode_vec(*j, &mut enc),
+                            encode_vec(*k, &mut enc),
+                            encode_vec(*l, &mut enc),
+                        ];
+
+                        let decoded = decode_four_nonzero::<T, U, V, W>(&enc);
+                        let zeros = [
+                            T::new(*i).is_none(),
+                            U::new(*j).is_none(),
+                            V::new(*k).is_none(),
+                            W::new(*l).is_none(),
+                        ];
+                        match zeros.iter().position(|zero| *zero) {
+                            Some(index) => assert_eq!(
+                                decoded,
+                                Err(zero_error(lens[..index].iter().sum(), index))
+                            ),
+                            None => {
+                                let decoded = decoded.unwrap();
+                                assert_eq!((decoded.0.get(), decoded.1.get()), (*i, *j));
+                                assert_eq!((decoded.2.get(), decoded.3.get()), (*k, *l));
+                                assert_eq!([decoded.4, decoded.5, decoded.6, decoded.7], lens);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // too slow
+    fn test_decode_4x_nonzero() {
+        check_decode_4x_nonzero::<NonZeroU8, NonZeroU8, NonZeroU8, NonZeroU8>(
+            &NUMS_U8[..],
+            &NUMS_U8[..],
+            &NUMS_U8[..],
+            &NUMS_U8[..],
+        );
+        check_decode_4x_nonzero::<NonZeroU32, NonZeroU8, NonZeroU16, NonZeroU16>(
+            &NUMS_U32[..],
+            &NUMS_U8[..],
+            &NUMS_U16[..],
+            &NUMS_U16[..],
+        );
+    }
+}
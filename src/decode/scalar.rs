@@ -0,0 +1,220 @@
+//! Portable implementations of the multi-value decoders, used when SSSE3 is not available.
+//!
+//! These mirror the boundary detection of the SIMD versions (a movemask over the 16-byte window
+//! followed by successive trailing-zero counts), so results are identical for well-formed input.
+//! Values are assembled with the portable conversions of [`num`], so that
+//! [`Backend::Scalar`](crate::Backend::Scalar) stays free of PDEP/PEXT and SIMD instructions even
+//! when they are enabled at compile time.
+
+use crate::num::{self, VarIntTarget};
+
+/// Copies the 16-byte window at `bytes` into a zero-padded buffer, so that varints which
+/// (incorrectly) extend past the end of the window can be sliced without going out of bounds.
+#[inline(always)]
+unsafe fn load_window(bytes: *const u8) -> [u8; 32] {
+    let mut buf = [0u8; 32];
+    core::ptr::copy_nonoverlapping(bytes, buf.as_mut_ptr(), 16);
+    buf
+}
+
+/// Equivalent of `_mm_movemask_epi8` on the first 16 bytes of the window
+#[inline(always)]
+fn movemask(buf: &[u8; 32]) -> u32 {
+    buf[..16]
+        .iter()
+        .enumerate()
+        .fold(0, |mask, (i, b)| mask | (((b >> 7) as u32) << i))
+}
+
+/// Decodes the varint occupying `len` bytes at `start` within the window
+#[inline(always)]
+fn extract<T: VarIntTarget>(buf: &[u8; 32], start: u32, len: u32) -> T {
+    let start = start.min(16) as usize;
+    let len = len.min(16) as usize;
+
+    num::pack_portable(&buf[start..start + len])
+}
+
+/// Decodes a single varint without SIMD instructions, for
+/// [`Backend::Scalar`](crate::Backend::Scalar). Varints longer than 16 bytes may continue into
+/// the third word, as in [`decode_unsafe_impl`](super::decode_unsafe_impl).
+#[inline]
+pub(crate) unsafe fn decode_unsafe<T: VarIntTarget>(bytes: *const u8) -> (T, usize) {
+    if T::MAX_VARINT_BYTES > 16 {
+        let mut buf = [0u8; 24];
+        core::ptr::copy_nonoverlapping(bytes, buf.as_mut_ptr(), 24);
+        let len = buf
+            .iter()
+            .position(|b| b & 0x80 == 0)
+            .map_or(24, |pos| pos + 1);
+
+        return (num::pack_portable(&buf[..len]), len);
+    }
+
+    let buf = load_window(bytes);
+    let len = (!movemask(&buf)).trailing_zeros() + 1;
+
+    (extract(&buf, 0, len), len.min(16) as usize)
+}
+
+#[inline]
+pub(crate) unsafe fn decode_two_unsafe<T: VarIntTarget, U: VarIntTarget>(
+    bytes: *const u8,
+) -> (T, U, u8, u8) {
+    let buf = load_window(bytes);
+    let bitmask = movemask(&buf);
+
+    let bm_not = !bitmask;
+    let first_len = bm_not.trailing_zeros() + 1;
+    let bm_not_2 = bm_not >> first_len;
+    let second_len = bm_not_2.trailing_zeros() + 1;
+
+    (
+        extract(&buf, 0, first_len),
+        extract(&buf, first_len, second_len),
+        first_len as u8,
+        second_len as u8,
+    )
+}
+
+#[inline]
+pub(crate) unsafe fn decode_four_unsafe<
+    T: VarIntTarget,
+    U: VarIntTarget,
+    V: VarIntTarget,
+    W: VarIntTarget,
+>(
+    bytes: *const u8,
+) -> (T, U, V, W, u8, u8, u8, u8, bool) {
+    let buf = load_window(bytes);
+    let bitmask = movemask(&buf);
+
+    let (first_len, second_len, third_len, fourth_len, invalid) = if T::MAX_VARINT_BYTES <= 3
+        && U::MAX_VARINT_BYTES <= 3
+        && V::MAX_VARINT_BYTES <= 3
+        && W::MAX_VARINT_BYTES <= 3
+    {
+        // Same semantics as the lookup table used by the SIMD version: only the first 12 bytes
+        // are considered and each length is clamped to 3
+        let bm_not = !(bitmask & 0b111111111111);
+        let first_len_raw = bm_not.trailing_zeros();
+        let first_len = (first_len_raw + 1).min(3);
+        let bm_not_2 = bm_not >> first_len;
+        let second_len_raw = bm_not_2.trailing_zeros();
+        let second_len = (second_len_raw + 1).min(3);
+        let bm_not_3 = bm_not_2 >> second_len;
+        let third_len_raw = bm_not_3.trailing_zeros();
+        let third_len = (third_len_raw + 1).min(3);
+        let bm_not_4 = bm_not_3 >> third_len;
+        let fourth_len_raw = bm_not_4.trailing_zeros();
+        let fourth_len = (fourth_len_raw + 1).min(3);
+
+        let invalid =
+            first_len_raw > 3 || second_len_raw > 3 || third_len_raw > 3 || fourth_len_raw > 3;
+
+        (first_len, second_len, third_len, fourth_len, invalid)
+    } else {
+        let bm_not = !bitmask;
+        let first_len = bm_not.trailing_zeros() + 1;
+        let bm_not_2 = bm_not >> first_len;
+        let second_len = bm_not_2.trailing_zeros() + 1;
+        let bm_not_3 = bm_not_2 >> second_len;
+        let third_len = bm_not_3.trailing_zeros() + 1;
+        let bm_not_4 = bm_not_3 >> third_len;
+        let fourth_len = bm_not_4.trailing_zeros() + 1;
+
+        (first_len, second_len, third_len, fourth_len, false)
+    };
+
+    (
+        extract(&buf, 0, first_len),
+        extract(&buf, first_len, second_len),
+        extract(&buf, first_len + second_len, third_len),
+        extract(&buf, first_len + second_len + third_len, fourth_len),
+        first_len as u8,
+        second_len as u8,
+        third_len as u8,
+        fourth_len as u8,
+        invalid,
+    )
+}
+
+#[inline]
+pub(crate) unsafe fn decode_eight_u8_unsafe(bytes: *const u8) -> ([u8; 8], u8) {
+    let (nums, len, _) = decode_eight_u8_checked_unsafe(bytes);
+    (nums, len)
+}
+
+#[inline]
+pub(crate) unsafe fn decode_eight_u8_checked_unsafe(bytes: *const u8) -> ([u8; 8], u8, u8) {
+    decode_eight_u8_impl::<false>(bytes)
+}
+
+#[inline]
+#[allow(dead_code)] // only used without SSSE3 or NEON
+pub(crate) unsafe fn decode_eight_u8_strict_unsafe(bytes: *const u8) -> ([u8; 8], u8, u8) {
+    decode_eight_u8_impl::<true>(bytes)
+}
+
+/// `STRICT` also marks the non-canonical varints in the overflow bitmask
+#[inline(always)]
+unsafe fn decode_eight_u8_impl<const STRICT: bool>(bytes: *const u8) -> ([u8; 8], u8, u8) {
+    let buf = load_window(bytes);
+
+    let mut nums = [0u8; 8];
+    let mut overflow = 0;
+    let mut pos = 0;
+    for (i, num) in nums.iter_mut().enumerate() {
+        // Each varint is assumed to be at most two bytes long
+        let first = buf[pos];
+        let second = buf[pos + 1];
+        let len = 1 + (first >> 7) as usize;
+
+        *num = (first & 0x7f) | ((second & (first >> 7)) << 7);
+        overflow |= ((len == 2 && (second > 1 || STRICT && second == 0)) as u8) << i;
+        pos += len;
+    }
+
+    (nums, pos as u8, overflow)
+}
+
+/// Equivalent of `_mm_movemask_epi8` on a `u64`, for a value with only the most significant bit of
+/// each byte set
+#[inline(always)]
+fn movemask_u64(msbs: u64) -> u32 {
+    // Each bit lands in a distinct position of the top byte, without any carries
+    ((msbs >> 7).wrapping_mul(0x0102040810204080) >> 56) as u32
+}
+
+/// Produces a bitmask of the continuation bytes in the 32-byte window at `bytes`, a bitmask of the
+/// bytes greater than `max`, which must be less than `0x80`, and a bitmask of the zero bytes
+#[inline(always)]
+#[allow(dead_code)] // only used without SSE2
+pub(crate) unsafe fn byte_masks(bytes: *const u8, max: u8) -> (u32, u32, u32) {
+    let mut cont = 0;
+    let mut big = 0;
+    let mut zero = 0;
+    for i in 0..4 {
+        let b = u64::from_le(bytes.cast::<u64>().add(i).read_unaligned());
+        let msbs = b & 0x8080808080808080;
+        // no carries between bytes, as both sides of the addition are at most 0x7f
+        let above = ((b & 0x7f7f7f7f7f7f7f7f) + 0x0101010101010101 * (0x7f - max) as u64)
+            & 0x8080808080808080;
+        // the MSB is set for every byte with any of its low bits set, again without carries
+        let nonzero = ((b & 0x7f7f7f7f7f7f7f7f) + 0x7f7f7f7f7f7f7f7f) | b;
+
+        cont |= movemask_u64(msbs) << (8 * i);
+        big |= movemask_u64(msbs | above) << (8 * i);
+        zero |= movemask_u64(!nonzero & 0x8080808080808080) << (8 * i);
+    }
+    (cont, big, zero)
+}
+
+/// Produces a bitmask of the continuation bytes in the 16-byte window at `bytes`
+#[inline(always)]
+#[allow(dead_code)] // only used without SSE2
+pub(crate) unsafe fn continuation_mask(bytes: *const u8) -> u32 {
+    let lo = u64::from_le(bytes.cast::<u64>().read_unaligned());
+    let hi = u64::from_le(bytes.cast::<u64>().add(1).read_unaligned());
+    movemask_u64(lo & 0x8080808080808080) | movemask_u64(hi & 0x8080808080808080) << 8
+}
@@ -0,0 +1,778 @@
+//! Scanning of buffers of adjacent varints using only their continuation bits, without decoding
+//! the values.
+//!
+//! The input is processed in 32-byte windows. For each window, a movemask produces one bitmask of
+//! the continuation bytes and one of the bytes that would be too large as the last byte of a
+//! varint of maximum length. A varint overflows exactly when its byte at index
+//! `MAX_VARINT_BYTES - 1` or later is in the second mask, i.e. when such a byte follows a run of
+//! `MAX_VARINT_BYTES - 1` continuation bytes, which may begin in the previous window. A third
+//! mask of the zero bytes finds the non-canonical varints, which end with a zero byte directly
+//! after a continuation byte.
+
+use core::iter::FusedIterator;
+
+use crate::num::VarIntTarget;
+use crate::{ValidationError, VarIntDecodeError};
+
+#[cfg(target_arch = "x86_64")]
+use crate::cpu;
+
+/// Skips over `n` adjacent varints at the start of the input slice, without decoding them.
+///
+/// Produces the number of bytes taken up by the `n` varints. Overflow is checked for the target
+/// type in the same way as [`decode`](fn@crate::decode), and `NotEnoughBytes` is returned if the
+/// input contains fewer than `n` varints.
+///
+/// # Examples
+/// ```
+/// use varint_simd::{skip, VarIntDecodeError};
+///
+/// assert_eq!(skip::<u32>(&[185, 10, 1, 172, 2], 2), Ok(3));
+/// assert_eq!(skip::<u32>(&[185, 10, 1, 172, 2], 3), Ok(5));
+/// assert_eq!(skip::<u32>(&[185, 10, 1, 172, 2], 4), Err(VarIntDecodeError::NotEnoughBytes));
+/// assert_eq!(skip::<u8>(&[185, 10, 1, 172, 2], 1), Err(VarIntDecodeError::Overflow));
+/// ```
+#[inline]
+pub fn skip<T: VarIntTarget>(bytes: &[u8], n: usize) -> Result<usize, VarIntDecodeError> {
+    #[cfg(target_arch = "x86_64")]
+    if cpu::has_avx2() {
+        return unsafe { skip_avx2::<T>(bytes, n) };
+    }
+
+    skip_impl::<T, false>(bytes, n)
+}
+
+#[inline]
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn skip_avx2<T: VarIntTarget>(bytes: &[u8], n: usize) -> Result<usize, VarIntDecodeError> {
+    skip_impl::<T, true>(bytes, n)
+}
+
+#[inline(always)]
+fn skip_impl<T: VarIntTarget, const AVX2: bool>(
+    bytes: &[u8],
+    n: usize,
+) -> Result<usize, VarIntDecodeError> {
+    if n == 0 {
+        return Ok(0);
+    }
+
+    let mut remaining = n;
+    let mut offset = 0;
+    let mut prev_cont = 0;
+
+    while offset < bytes.len() {
+        let (cont, big, _, valid) = window_masks::<T, AVX2>(bytes, offset);
+        let ends = !cont & valid;
+        let overflow = big & after_max_run::<T>(cont, prev_cont);
+
+        let count = ends.count_ones() as usize;
+        if count >= remaining {
+            let end = nth_bit(ends, remaining - 1);
+            // overflowing varints after the last one to be skipped do not matter
+            if overflow & (u32::MAX >> (31 - end)) != 0 {
+                return Err(VarIntDecodeError::Overflow);
+            }
+            return Ok(offset + end as usize + 1);
+        }
+
+        if overflow != 0 {
+            return Err(VarIntDecodeError::Overflow);
+        }
+
+        remaining -= count;
+        prev_cont = cont;
+        offset += 32;
+    }
+
+    Err(VarIntDecodeError::NotEnoughBytes)
+}
+
+/// Checks that the input slice consists entirely of complete varints that fit in the target type,
+/// without decoding them.
+///
+/// Produces the number of varints. If a varint overflows the target type or is cut off by the end
+/// of the input, an error is returned with its offset and index, exactly as [`decode_slice`]
+/// would. A buffer that passes can then be decoded with the unchecked multi-value decoders, as
+/// long as their 16-byte read requirement is met.
+///
+/// # Examples
+/// ```
+/// use varint_simd::{validate, VarIntDecodeError};
+///
+/// assert_eq!(validate::<u32>(&[185, 10, 1, 172, 2]), Ok(3));
+///
+/// let error = validate::<u8>(&[185, 10, 1, 172, 2]).unwrap_err();
+/// assert_eq!(error.error, VarIntDecodeError::Overflow);
+/// assert_eq!((error.offset, error.index), (0, 0));
+/// ```
+///
+/// [`decode_slice`]: crate::decode_slice
+#[inline]
+pub fn validate<T: VarIntTarget>(bytes: &[u8]) -> Result<usize, ValidationError> {
+    #[cfg(target_arch = "x86_64")]
+    if cpu::has_avx2() {
+        return unsafe { validate_avx2::<T, false>(bytes) };
+    }
+
+    validate_impl::<T, false, false>(bytes)
+}
+
+/// Checks that the input slice consists entirely of complete and canonical varints that fit in the
+/// target type, without decoding them. Errors are reported exactly as [`decode_slice_strict`]
+/// would. See also: [`validate`]
+///
+/// # Examples
+/// ```
+/// use varint_simd::{validate_strict, VarIntDecodeError};
+///
+/// assert_eq!(validate_strict::<u32>(&[185, 10, 1, 172, 2]), Ok(3));
+///
+/// let error = validate_strict::<u32>(&[185, 10, 0x81, 0x80, 0]).unwrap_err();
+/// assert_eq!(error.error, VarIntDecodeError::NonCanonical);
+/// assert_eq!((error.offset, error.index), (2, 1));
+/// ```
+///
+/// [`decode_slice_strict`]: crate::decode_slice_strict
+#[inline]
+pub fn validate_strict<T: VarIntTarget>(bytes: &[u8]) -> Result<usize, ValidationError> {
+    #[cfg(target_arch = "x86_64")]
+    if cpu::has_avx2() {
+        return unsafe { validate_avx2::<T, true>(bytes) };
+    }
+
+    validate_impl::<T, false, true>(bytes)
+}
+
+#[inline]
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn validate_avx2<T: VarIntTarget, const STRICT: bool>(
+    bytes: &[u8],
+) -> Result<usize, ValidationError> {
+    validate_impl::<T, true, STRICT>(bytes)
+}
+
+/// `STRICT` also rejects non-canonical varints
+#[inline(always)]
+fn validate_impl<T: VarIntTarget, const AVX2: bool, const STRICT: bool>(
+    bytes: &[u8],
+) -> Result<usize, ValidationError> {
+    let mut count = 0;
+    let mut offset = 0;
+    let mut prev_cont = 0;
+    // offset of the start of the varint following the last end seen
+    let mut next_start = 0;
+
+    while offset < bytes.len() {
+        let (cont, big, zero, valid) = window_masks::<T, AVX2>(bytes, offset);
+        let ends = !cont & valid;
+        let overflow = big & after_max_run::<T>(cont, prev_cont);
+        let non_canonical = if STRICT {
+            zero & (cont << 1 | prev_cont >> 31)
+        } else {
+            0
+        };
+
+        let invalid = overflow | non_canonical;
+        if invalid != 0 {
+            let first = invalid.trailing_zeros();
+            let ends_before = ends & ((1 << first) - 1);
+            if ends_before != 0 {
+                next_start = offset + (31 - ends_before.leading_zeros()) as usize + 1;
+            }
+            // a varint that is too long overflows before it can end
+            let error = if overflow & (1 << first) != 0 {
+                VarIntDecodeError::Overflow
+            } else {
+                VarIntDecodeError::NonCanonical
+            };
+            return Err(ValidationError {
+                error,
+                offset: next_start,
+                index: count + ends_before.count_ones() as usize,
+            });
+        }
+
+        if ends != 0 {
+            count += ends.count_ones() as usize;
+            next_start = offset + (31 - ends.leading_zeros()) as usize + 1;
+        }
+        prev_cont = cont;
+        offset += 32;
+    }
+
+    if next_start < bytes.len() {
+        return Err(ValidationError {
+            error: VarIntDecodeError::NotEnoughBytes,
+            offset: next_start,
+            index: count,
+        });
+    }
+
+    Ok(count)
+}
+
+/// Counts the complete varints in the input slice, without decoding them.
+///
+/// A varint is counted if its last byte is part of the input, whatever its length, so a truncated
+/// varint at the end of the input is not counted. Use [`validate`] to also check that the varints
+/// fit in a target type.
+///
+/// # Examples
+/// ```
+/// use varint_simd::count_varints;
+///
+/// assert_eq!(count_varints(&[185, 10, 1, 172, 2, 128]), 3);
+/// ```
+#[inline]
+pub fn count_varints(bytes: &[u8]) -> usize {
+    #[cfg(target_arch = "x86_64")]
+    if cpu::has_avx2() {
+        return unsafe { count_varints_avx2(bytes) };
+    }
+
+    count_varints_impl::<false>(bytes)
+}
+
+#[inline]
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn count_varints_avx2(bytes: &[u8]) -> usize {
+    count_varints_impl::<true>(bytes)
+}
+
+#[inline(always)]
+fn count_varints_impl<const AVX2: bool>(bytes: &[u8]) -> usize {
+    let mut count = 0;
+    let mut offset = 0;
+    while offset < bytes.len() {
+        let (cont, _, _, valid) = window_masks::<u8, AVX2>(bytes, offset);
+        count += (!cont & valid).count_ones() as usize;
+        offset += 32;
+    }
+    count
+}
+
+/// Produces an iterator over the offsets at which each complete varint in the input slice
+/// starts. See also: [`count_varints`], [`boundary_bitmap`]
+///
+/// # Examples
+/// ```
+/// use varint_simd::boundaries;
+///
+/// let starts: Vec<usize> = boundaries(&[185, 10, 1, 172, 2, 128]).collect();
+/// assert_eq!(starts, [0, 2, 3]);
+/// ```
+#[inline]
+pub fn boundaries(bytes: &[u8]) -> Boundaries<'_> {
+    Boundaries {
+        bytes,
+        offset: 0,
+        ends: 0,
+        start: 0,
+    }
+}
+
+/// An iterator over the offsets at which varints start, created by [`boundaries`]
+#[derive(Debug, Clone)]
+pub struct Boundaries<'a> {
+    bytes: &'a [u8],
+    /// Offset of the window after the one in `ends`
+    offset: usize,
+    /// Mask of the varint ends in the current window that have not been reached yet
+    ends: u32,
+    /// Offset of the start of the next varint
+    start: usize,
+}
+
+impl<'a> Iterator for Boundaries<'a> {
+    type Item = usize;
+
+    #[inline]
+    fn next(&mut self) -> Option<usize> {
+        while self.ends == 0 {
+            if self.offset >= self.bytes.len() {
+                return None;
+            }
+
+            let (cont, _, _, valid) = window_masks::<u8, false>(self.bytes, self.offset);
+            self.ends = !cont & valid;
+            self.offset += 32;
+        }
+
+        let start = self.start;
+        self.start = self.offset - 32 + self.ends.trailing_zeros() as usize + 1;
+        self.ends &= self.ends - 1;
+        Some(start)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let buffered = self.ends.count_ones() as usize;
+        let unread = self.bytes.len().max(self.offset) - self.offset;
+        (buffered, Some(buffered + unread))
+    }
+}
+
+impl<'a> FusedIterator for Boundaries<'a> {}
+
+/// Marks the offsets at which each complete varint in the input slice starts in a bitmap, where
+/// bit `i % 64` of `bitmap[i / 64]` corresponds to `bytes[i]`. Returns the number of varints.
+///
+/// The first `bytes.len() / 64` words of the bitmap, rounded up, are overwritten. See also:
+/// [`boundaries`]
+///
+/// # Panics
+/// Panics if the bitmap has fewer than `bytes.len() / 64` words, rounded up.
+///
+/// # Examples
+/// ```
+/// use varint_simd::boundary_bitmap;
+///
+/// let mut bitmap = [0u64; 1];
+/// assert_eq!(boundary_bitmap(&[185, 10, 1, 172, 2, 128], &mut bitmap), 3);
+/// assert_eq!(bitmap, [0b1101]);
+/// ```
+#[inline]
+pub fn boundary_bitmap(bytes: &[u8], bitmap: &mut [u64]) -> usize {
+    #[cfg(target_arch = "x86_64")]
+    if cpu::has_avx2() {
+        return unsafe { boundary_bitmap_avx2(bytes, bitmap) };
+    }
+
+    boundary_bitmap_impl::<false>(bytes, bitmap)
+}
+
+#[inline]
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn boundary_bitmap_avx2(bytes: &[u8], bitmap: &mut [u64]) -> usize {
+    boundary_bitmap_impl::<true>(bytes, bitmap)
+}
+
+#[inline(always)]
+fn boundary_bitmap_impl<const AVX2: bool>(bytes: &[u8], bitmap: &mut [u64]) -> usize {
+    let words = bytes.len().div_ceil(64);
+    assert!(
+        bitmap.len() >= words,
+        "bitmap of {} words is too short for {} bytes",
+        bitmap.len(),
+        bytes.len()
+    );
+    bitmap[..words].fill(0);
+
+    let mut count = 0;
+    let mut offset = 0;
+    // the first varint starts at the beginning of the input
+    let mut carry = 1;
+    let mut next_start = 0;
+    while offset < bytes.len() {
+        let (cont, _, _, valid) = window_masks::<u8, AVX2>(bytes, offset);
+        let ends = !cont & valid;
+
+        let starts = (ends << 1 | carry) & valid;
+        bitmap[offset / 64] |= (starts as u64) << (offset % 64);
+
+        if ends != 0 {
+            count += ends.count_ones() as usize;
+            next_start = offset + (31 - ends.leading_zeros()) as usize + 1;
+        }
+        carry = ends >> 31;
+        offset += 32;
+    }
+
+    // the varint after the last end is incomplete
+    if next_start < bytes.len() {
+        bitmap[next_start / 64] &= !(1 << (next_start % 64));
+    }
+
+    count
+}
+
+/// Produces the continuation byte mask, the mask of bytes greater than `T::MAX_LAST_VARINT_BYTE`,
+/// the mask of the zero bytes, and the mask of the bytes that are part of the input, for the
+/// 32-byte window at `offset`
+#[inline(always)]
+pub(super) fn window_masks<T: VarIntTarget, const AVX2: bool>(
+    bytes: &[u8],
+    offset: usize,
+) -> (u32, u32, u32, u32) {
+    let input = &bytes[offset..];
+
+    if input.len() >= 32 {
+        // SAFETY: there are at least 32 bytes after the pointer
+        let (cont, big, zero) =
+            unsafe { byte_masks::<AVX2>(input.as_ptr(), T::MAX_LAST_VARINT_BYTE) };
+        (cont, big, zero, u32::MAX)
+    } else {
+        let mut data = [0u8; 32];
+        data[..input.len()].copy_from_slice(input);
+        // SAFETY: the buffer is 32 bytes long
+        let (cont, big, zero) =
+            unsafe { byte_masks::<AVX2>(data.as_ptr(), T::MAX_LAST_VARINT_BYTE) };
+        // zero padding is neither a continuation byte nor too large, and does not count as zero
+        let valid = (1 << input.len()) - 1;
+        (cont, big, zero & valid, valid)
+    }
+}
+
+/// Produces a mask of the bytes of a window that are preceded by at least
+/// `T::MAX_VARINT_BYTES - 1` continuation bytes, given the continuation masks of the window and
+/// the previous one
+#[inline(always)]
+pub(super) fn after_max_run<T: VarIntTarget>(cont: u32, prev_cont: u32) -> u32 {
+    let combined = (cont as u64) << 32 | prev_cont as u64;
+
+    let mut run = combined;
+    for i in 1..T::MAX_VARINT_BYTES - 1 {
+        run &= combined << i;
+    }
+
+    ((run << 1) >> 32) as u32
+}
+
+/// The index of the `n`th (starting from zero) set bit of `mask`, which must have more than `n`
+/// bits set
+#[inline(always)]
+pub(super) fn nth_bit(mut mask: u32, n: usize) -> u32 {
+    for _ in 0..n {
+        mask &= mask - 1;
+    }
+    mask.trailing_zeros()
+}
+
+/// See [`super::scalar::byte_masks`]
+///
+/// # Safety
+/// There must be at least 32 bytes of allocated memory after the beginning of the pointer, and
+/// `AVX2` may only be set if AVX2 is available.
+#[inline(always)]
+unsafe fn byte_masks<const AVX2: bool>(bytes: *const u8, max: u8) -> (u32, u32, u32) {
+    #[cfg(target_arch = "x86_64")]
+    if AVX2 {
+        return super::x86::byte_masks_avx2(bytes, max);
+    }
+
+    #[cfg(all(
+        any(target_arch = "x86", target_arch = "x86_64"),
+        target_feature = "sse2",
+        not(miri)
+    ))]
+    {
+        super::x86::byte_masks_sse2(bytes, max)
+    }
+    #[cfg(not(all(
+        any(target_arch = "x86", target_arch = "x86_64"),
+        target_feature = "sse2",
+        not(miri)
+    )))]
+    {
+        super::scalar::byte_masks(bytes, max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{boundaries, boundary_bitmap, count_varints, skip, validate, validate_strict};
+    use crate::tests::{NUMS_U16, NUMS_U32, NUMS_U64, NUMS_U8};
+    use crate::{
+        decode_slice, decode_slice_strict, encode, encode_padded, ValidationError,
+        VarIntDecodeError, VarIntTarget,
+    };
+
+    /// Encodes every pair of values, and produces the encoding along with the offset after each
+    /// value
+    fn encode_pairs<T: VarIntTarget>(nums: &[T]) -> (Vec<u8>, Vec<usize>) {
+        let mut encoded = Vec::new();
+        let mut offsets = vec![0];
+        for i in nums {
+            for j in nums {
+                for value in [*i, *j] {
+                    let (bytes, len) = encode(value);
+                    encoded.extend_from_slice(&bytes[..len as usize]);
+                    offsets.push(encoded.len());
+                }
+            }
+        }
+        (encoded, offsets)
+    }
+
+    fn check_skip<T: VarIntTarget>(nums: &[T]) {
+        let (encoded, offsets) = encode_pairs(nums);
+        for (n, offset) in offsets.iter().enumerate() {
+            assert_eq!(skip::<T>(&encoded, n), Ok(*offset));
+        }
+        assert_eq!(
+            skip::<T>(&encoded, offsets.len()),
+            Err(VarIntDecodeError::NotEnoughBytes)
+        );
+    }
+
+    #[test]
+    fn test_skip() {
+        check_skip(&NUMS_U8[..]);
+        check_skip(&NUMS_U16[..]);
+        check_skip(&NUMS_U32[..]);
+        check_skip(&NUMS_U64[..]);
+    }
+
+    #[test]
+    fn test_skip_window_edges() {
+        // The last varint skipped ends just before, at and just after the end of a 16-byte or
+        // 32-byte window
+        for end in [15, 16, 17, 31, 32, 33] {
+            let input = [&vec![1u8; end - 2][..], &[0xAC, 0x02]].concat();
+            assert_eq!(skip::<u32>(&input, end - 2), Ok(end - 2), "{}", end);
+            assert_eq!(skip::<u32>(&input, end - 1), Ok(end), "{}", end);
+            assert_eq!(
+                skip::<u32>(&input[..end - 1], end - 1),
+                Err(VarIntDecodeError::NotEnoughBytes),
+                "{}",
+                end
+            );
+            assert_eq!(
+                skip::<u8>(&input, end - 1),
+                Err(VarIntDecodeError::Overflow),
+                "{}",
+                end
+            );
+        }
+    }
+
+    #[test]
+    fn test_skip_overflow_across_windows() {
+        // An overflowing u32 whose run of continuation bytes starts in the first window
+        let mut input = vec![1u8; 30];
+        input.extend_from_slice(&[0x80, 0x80, 0x80, 0x80, 0x10]);
+        input.resize(40, 1);
+
+        assert_eq!(skip::<u32>(&input, 30), Ok(30));
+        assert_eq!(skip::<u32>(&input, 31), Err(VarIntDecodeError::Overflow));
+        assert_eq!(skip::<u64>(&input, 31), Ok(35));
+    }
+
+    fn check_validate<T: VarIntTarget>(nums: &[T]) {
+        let (encoded, offsets) = encode_pairs(nums);
+        assert_eq!(validate::<T>(&encoded), Ok(offsets.len() - 1));
+
+        // Any error is the one that decode_slice stops at
+        let mut out = vec![T::cast_u32(0); offsets.len()];
+        for cut in (0..encoded.len()).step_by(7) {
+            let expected = decode_slice::<T>(&encoded[..cut], &mut out).map(|(count, _)| count);
+            assert_eq!(validate::<T>(&encoded[..cut]), expected);
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        check_validate(&NUMS_U8[..]);
+        check_validate(&NUMS_U16[..]);
+        check_validate(&NUMS_U32[..]);
+        check_validate(&NUMS_U64[..]);
+
+        // Wider values overflow narrower types
+        let (encoded, _) = encode_pairs(&NUMS_U64[..]);
+        let mut out = vec![0u8; encoded.len()];
+        let expected = decode_slice(&encoded, &mut out).map(|(count, _)| count);
+        assert_eq!(validate::<u8>(&encoded), expected);
+    }
+
+    #[test]
+    fn test_validate_window_edges() {
+        use VarIntDecodeError::*;
+
+        // An invalid last varint that ends just before, at and just after the end of a 16-byte or
+        // 32-byte window
+        for end in [15, 16, 17, 31, 32, 33] {
+            let mut input = vec![1u8; end - 2];
+            input.extend_from_slice(&[0xAC, 0x02]);
+            assert_eq!(validate::<u32>(&input), Ok(end - 1), "{}", end);
+
+            let error = |error| ValidationError {
+                error,
+                offset: end - 2,
+                index: end - 2,
+            };
+            assert_eq!(validate::<u8>(&input), Err(error(Overflow)), "{}", end);
+            input[end - 1] = 0x82;
+            assert_eq!(
+                validate::<u32>(&input),
+                Err(error(NotEnoughBytes)),
+                "{}",
+                end
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_overflow_across_windows() {
+        // An overflowing u32 whose run of continuation bytes starts in the first window
+        let mut input = vec![1u8; 30];
+        input.extend_from_slice(&[0x80, 0x80, 0x80, 0x80, 0x10]);
+        input.resize(40, 1);
+
+        let error = ValidationError {
+            error: VarIntDecodeError::Overflow,
+            offset: 30,
+            index: 30,
+        };
+        assert_eq!(validate::<u32>(&input), Err(error));
+        assert_eq!(validate::<u64>(&input), Ok(36));
+    }
+
+    fn check_boundaries<T: VarIntTarget>(nums: &[T]) {
+        let (encoded, offsets) = encode_pairs(nums);
+        let starts = &offsets[..offsets.len() - 1];
+
+        assert_eq!(count_varints(&encoded), starts.len());
+
+        let iter = boundaries(&encoded);
+        assert!(iter.size_hint().0 <= starts.len());
+        assert!(iter.size_hint().1.unwrap() >= starts.len());
+        assert_eq!(iter.collect::<Vec<_>>(), starts);
+
+        // The word after the ones covering the input is left alone
+        let mut bitmap = vec![u64::MAX; encoded.len().div_ceil(64) + 1];
+        assert_eq!(boundary_bitmap(&encoded, &mut bitmap), starts.len());
+        let marked: Vec<usize> = (0..encoded.len())
+            .filter(|i| bitmap[i / 64] & (1 << (i % 64)) != 0)
+            .collect();
+        assert_eq!(marked, starts);
+        assert_eq!(bitmap[bitmap.len() - 1], u64::MAX);
+
+        // A truncated varint at the end is not counted
+        let truncated = &encoded[..encoded.len() - 1];
+        assert_eq!(count_varints(truncated), starts.len() - 1);
+        assert_eq!(boundaries(truncated).count(), starts.len() - 1);
+    }
+
+    #[test]
+    fn test_boundaries() {
+        check_boundaries(&NUMS_U8[..]);
+        check_boundaries(&NUMS_U16[..]);
+        check_boundaries(&NUMS_U32[..]);
+        check_boundaries(&NUMS_U64[..]);
+    }
+
+    #[test]
+    fn test_boundaries_window_edges() {
+        // The last varint ends just before, at and just after the end of a vector or bitmap word
+        for len in [15, 16, 17, 31, 32, 33, 63, 64, 65] {
+            let mut input = vec![0x80u8; len];
+            input[len - 1] = 0x01;
+            assert_eq!(count_varints(&input), 1, "{}", len);
+            assert_eq!(boundaries(&input).collect::<Vec<_>>(), [0], "{}", len);
+
+            input[len - 2] = 0x01;
+            assert_eq!(count_varints(&input), 2, "{}", len);
+            assert_eq!(
+                boundaries(&input).collect::<Vec<_>>(),
+                [0, len - 1],
+                "{}",
+                len
+            );
+
+            let mut bitmap = [0u64; 3];
+            assert_eq!(boundary_bitmap(&input, &mut bitmap), 2, "{}", len);
+            let mut expected = [0u64; 3];
+            expected[0] = 1;
+            expected[(len - 1) / 64] |= 1 << ((len - 1) % 64);
+            assert_eq!(bitmap, expected, "{}", len);
+
+            // An unterminated varint at the end is neither counted nor marked
+            input[len - 1] = 0x80;
+            assert_eq!(count_varints(&input), 1, "{}", len);
+            assert_eq!(boundary_bitmap(&input, &mut bitmap), 1, "{}", len);
+            assert_eq!(bitmap, [1, 0, 0], "{}", len);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "too short")]
+    fn test_boundary_bitmap_too_short() {
+        boundary_bitmap(&[0; 65], &mut [0; 1]);
+    }
+
+    fn check_validate_strict<T: VarIntTarget>(nums: &[T]) {
+        let (encoded, offsets) = encode_pairs(nums);
+        assert_eq!(validate_strict::<T>(&encoded), Ok(offsets.len() - 1));
+
+        // Any error is the one that decode_slice_strict stops at
+        let mut out = vec![T::cast_u32(0); offsets.len()];
+        for cut in (0..encoded.len()).step_by(7) {
+            let expected =
+                decode_slice_strict::<T>(&encoded[..cut], &mut out).map(|(count, _)| count);
+            assert_eq!(validate_strict::<T>(&encoded[..cut]), expected);
+        }
+
+        // A non-canonical varint at each position, which validate accepts
+        for (index, offset) in offsets[..offsets.len() - 1].iter().enumerate().step_by(5) {
+            let mut input = encoded[..*offset].to_vec();
+            let padded = encode_padded(T::cast_u32(1), T::MAX_VARINT_BYTES);
+            input.extend_from_slice(&padded[..T::MAX_VARINT_BYTES as usize]);
+            input.extend_from_slice(&encoded[*offset..]);
+
+            assert_eq!(validate::<T>(&input), Ok(offsets.len()));
+            assert_eq!(
+                validate_strict::<T>(&input),
+                Err(ValidationError {
+                    error: VarIntDecodeError::NonCanonical,
+                    offset: *offset,
+                    index,
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_strict() {
+        check_validate_strict(&NUMS_U8[..]);
+        check_validate_strict(&NUMS_U16[..]);
+        check_validate_strict(&NUMS_U32[..]);
+        check_validate_strict(&NUMS_U64[..]);
+    }
+
+    #[test]
+    #[cfg(all(
+        any(target_arch = "x86", target_arch = "x86_64"),
+        target_feature = "sse2",
+        not(miri)
+    ))]
+    fn test_byte_masks() {
+        use crate::decode::{scalar, x86};
+
+        // Every byte value appears at every position of some window
+        for start in 0..=255u8 {
+            let mut window = [0u8; 32];
+            for (i, byte) in window.iter_mut().enumerate() {
+                *byte = start.wrapping_add((i as u8).wrapping_mul(67));
+            }
+
+            assert_eq!(
+                unsafe { x86::continuation_mask_sse2(window.as_ptr()) },
+                unsafe { scalar::continuation_mask(window.as_ptr()) }
+            );
+
+            for max in [0x01, 0x03, 0x0f, 0x7f] {
+                let expected = unsafe { scalar::byte_masks(window.as_ptr(), max) };
+                assert_eq!(
+                    unsafe { x86::byte_masks_sse2(window.as_ptr(), max) },
+                    expected
+                );
+
+                #[cfg(target_arch = "x86_64")]
+                if is_x86_feature_detected!("avx2") {
+                    assert_eq!(unsafe { byte_masks_avx2(window.as_ptr(), max) }, expected);
+                }
+            }
+        }
+
+        #[cfg(target_arch = "x86_64")]
+        #[target_feature(enable = "avx2")]
+        unsafe fn byte_masks_avx2(bytes: *const u8, max: u8) -> (u32, u32, u32) {
+            x86::byte_masks_avx2(bytes, max)
+        }
+    }
+}
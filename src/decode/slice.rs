@@ -0,0 +1,612 @@
+//! Decoding of whole sequences of varints, using the multi-value decoders where possible.
+
+use super::multi::is_valid;
+use super::zigzag::{decode_four_zigzag_bits, decode_two_zigzag_bits};
+use super::{
+    decode_dispatch, decode_eight_u8_checked_unsafe, decode_eight_u8_strict_unsafe,
+    decode_four_unsafe, decode_two_unsafe, decode_with_options,
+};
+use crate::num::{self, SignedVarIntTarget, VarIntTarget};
+use crate::SliceDecodeError;
+
+/// Decodes a sequence of adjacent varints from the input slice into the output slice, until either
+/// the output is full or the input is exhausted.
+///
+/// Produces a tuple containing the number of values written and the number of bytes read. While at
+/// least 16 bytes of input remain, several varints are decoded at once with the widest applicable
+/// kernel ([`decode_eight_u8_checked_unsafe`] for `u8`, [`decode_four_unsafe`] for `u16`, and
+/// [`decode_two_unsafe`] for `u32`). The last bytes, and all `u128` values, are decoded one at a
+/// time with [`decode`](fn@crate::decode).
+///
+/// If a varint overflows the target type or is cut off by the end of the input, an error is
+/// returned with its offset and index. The values before it have already been written to `out`.
+///
+/// # Examples
+/// ```
+/// use varint_simd::{decode_slice, VarIntDecodeError};
+///
+/// let mut out = [0u32; 4];
+/// assert_eq!(decode_slice(&[185, 10, 1, 172, 2], &mut out), Ok((3, 5)));
+/// assert_eq!(out, [1337, 1, 300, 0]);
+///
+/// let error = decode_slice::<u32>(&[1, 172], &mut out).unwrap_err();
+/// assert_eq!(error.error, VarIntDecodeError::NotEnoughBytes);
+/// assert_eq!((error.offset, error.index), (1, 1));
+/// ```
+#[inline]
+pub fn decode_slice<T: VarIntTarget>(
+    input: &[u8],
+    out: &mut [T],
+) -> Result<(usize, usize), SliceDecodeError> {
+    decode_slice_impl::<T, false, false, false>(input, out)
+}
+
+/// Decodes a sequence of adjacent varints from the input slice into the output slice, rejecting
+/// encodings that are not the shortest possible with
+/// [`VarIntDecodeError::NonCanonical`](crate::VarIntDecodeError::NonCanonical). The check is made
+/// for each window along with the overflow check, before falling back to
+/// [`decode_strict`](crate::decode_strict) to report the error. See also: [`decode_slice`]
+///
+/// # Examples
+/// ```
+/// use varint_simd::{decode_slice_strict, VarIntDecodeError};
+///
+/// let mut out = [0u32; 4];
+/// assert_eq!(decode_slice_strict(&[185, 10, 1, 172, 2], &mut out), Ok((3, 5)));
+///
+/// let error = decode_slice_strict::<u32>(&[1, 0x81, 0x80, 0], &mut out).unwrap_err();
+/// assert_eq!(error.error, VarIntDecodeError::NonCanonical);
+/// assert_eq!((error.offset, error.index), (1, 1));
+/// ```
+#[inline]
+pub fn decode_slice_strict<T: VarIntTarget>(
+    input: &[u8],
+    out: &mut [T],
+) -> Result<(usize, usize), SliceDecodeError> {
+    decode_slice_impl::<T, false, true, false>(input, out)
+}
+
+/// Decodes a sequence of adjacent varints in ZigZag format from the input slice into the output
+/// slice. The values are ZigZag decoded in the vector registers of the multi-value decoders, before
+/// they are extracted. See also: [`decode_slice`]
+///
+/// # Examples
+/// ```
+/// use varint_simd::decode_slice_zigzag;
+///
+/// let mut out = [0i32; 4];
+/// assert_eq!(decode_slice_zigzag(&[39, 40, 1, 0], &mut out), Ok((4, 4)));
+/// assert_eq!(out, [-20, 20, -1, 0]);
+/// ```
+#[inline]
+pub fn decode_slice_zigzag<T: SignedVarIntTarget>(
+    input: &[u8],
+    out: &mut [T],
+) -> Result<(usize, usize), SliceDecodeError> {
+    decode_slice_impl::<T::Unsigned, true, false, false>(input, num::unsigned_slice_mut(out))
+}
+
+/// Decodes a sequence of adjacent varints from the input slice into the output slice the way
+/// protobuf decodes packed `int32` and `int64` fields, as
+/// [`decode_sign_extended`](crate::decode_sign_extended) does. Non-negative values are decoded
+/// with the same multi-value decoders as [`decode_slice`], and negative values, which take up 10
+/// bytes, are decoded one at a time as a `u64` and truncated.
+///
+/// # Examples
+/// ```
+/// use varint_simd::decode_slice_sign_extended;
+///
+/// let mut out = [0i32; 4];
+/// let bytes = [1, 254, 255, 255, 255, 255, 255, 255, 255, 255, 1, 185, 10];
+/// assert_eq!(decode_slice_sign_extended(&bytes, &mut out), Ok((3, 13)));
+/// assert_eq!(out, [1, -2, 1337, 0]);
+/// ```
+#[inline]
+pub fn decode_slice_sign_extended<T: SignedVarIntTarget>(
+    input: &[u8],
+    out: &mut [T],
+) -> Result<(usize, usize), SliceDecodeError> {
+    decode_slice_impl::<T::Unsigned, false, false, true>(input, num::unsigned_slice_mut(out))
+}
+
+/// `ZIGZAG` decodes the values from ZigZag format, leaving the bits of the signed results in the
+/// unsigned type. `STRICT` and `SIGN_EXTEND` select the rules of [`decode_with_options`] for
+/// every value.
+#[inline(always)]
+fn decode_slice_impl<
+    T: VarIntTarget,
+    const ZIGZAG: bool,
+    const STRICT: bool,
+    const SIGN_EXTEND: bool,
+>(
+    input: &[u8],
+    out: &mut [T],
+) -> Result<(usize, usize), SliceDecodeError> {
+    let mut read = 0;
+    let mut written = 0;
+
+    // Varints that can be longer than 16 bytes are all decoded by the checked decoder below
+    while T::MAX_VARINT_BYTES <= 16 && input.len() - read >= 16 && written < out.len() {
+        let window = &input[read..read + 16];
+
+        let decoded = if T::MAX_VARINT_BYTES <= 2 && out.len() - written >= 8 {
+            decode_eight::<T, ZIGZAG, STRICT>(window, &mut out[written..written + 8])
+        } else if T::MAX_VARINT_BYTES <= 4 && out.len() - written >= 4 {
+            decode_four::<T, ZIGZAG, STRICT>(window, &mut out[written..written + 4])
+        } else if T::MAX_VARINT_BYTES <= 8 && out.len() - written >= 2 {
+            decode_two::<T, ZIGZAG, STRICT>(window, &mut out[written..written + 2])
+        } else {
+            decode_one::<T, ZIGZAG, STRICT>(window, &mut out[written])
+        };
+
+        let (count, len) = match decoded {
+            Some(decoded) => decoded,
+            // Something in the window looks wrong, so let the checked decoder either report the
+            // error or make progress
+            None => {
+                let (num, len) =
+                    decode_checked::<T, ZIGZAG, STRICT, SIGN_EXTEND>(input, read, written)?;
+                out[written] = num;
+                (1, len)
+            }
+        };
+        written += count;
+        read += len;
+    }
+
+    while read < input.len() && written < out.len() {
+        let (num, len) = decode_checked::<T, ZIGZAG, STRICT, SIGN_EXTEND>(input, read, written)?;
+
+        out[written] = num;
+        written += 1;
+        read += len;
+    }
+
+    Ok((written, read))
+}
+
+#[inline(always)]
+fn decode_checked<
+    T: VarIntTarget,
+    const ZIGZAG: bool,
+    const STRICT: bool,
+    const SIGN_EXTEND: bool,
+>(
+    input: &[u8],
+    read: usize,
+    written: usize,
+) -> Result<(T, usize), SliceDecodeError> {
+    match decode_with_options::<T, STRICT, SIGN_EXTEND>(&input[read..]) {
+        Ok((num, len)) if ZIGZAG => Ok((num::unzigzag_bits(num), len)),
+        Ok(decoded) => Ok(decoded),
+        Err(error) => Err(SliceDecodeError {
+            error,
+            offset: read,
+            index: written,
+        }),
+    }
+}
+
+#[inline(always)]
+fn decode_one<T: VarIntTarget, const ZIGZAG: bool, const STRICT: bool>(
+    window: &[u8],
+    out: &mut T,
+) -> Option<(usize, usize)> {
+    let (num, len) = decode_one_checked::<T, ZIGZAG, STRICT>(window)?;
+    *out = num;
+    Some((1, len))
+}
+
+#[inline(always)]
+fn decode_two<T: VarIntTarget, const ZIGZAG: bool, const STRICT: bool>(
+    window: &[u8],
+    out: &mut [T],
+) -> Option<(usize, usize)> {
+    let (nums, lens) = decode_two_checked::<T, ZIGZAG, STRICT>(window)?;
+    out.copy_from_slice(&nums);
+    Some((2, lens[0] as usize + lens[1] as usize))
+}
+
+#[inline(always)]
+fn decode_four<T: VarIntTarget, const ZIGZAG: bool, const STRICT: bool>(
+    window: &[u8],
+    out: &mut [T],
+) -> Option<(usize, usize)> {
+    let (nums, lens) = decode_four_checked::<T, ZIGZAG, STRICT>(window)?;
+    out.copy_from_slice(&nums);
+    Some((4, lens.iter().map(|len| *len as usize).sum()))
+}
+
+/// Decodes one varint from a 16-byte window, or returns `None` if it is invalid
+#[inline(always)]
+pub(super) fn decode_one_checked<T: VarIntTarget, const ZIGZAG: bool, const STRICT: bool>(
+    window: &[u8],
+) -> Option<(T, usize)> {
+    // SAFETY: the window is 16 bytes long
+    let (num, len) = unsafe { decode_dispatch::<T>(window[..16].as_ptr()) };
+
+    if is_valid::<T, STRICT>(window, 0, len) {
+        Some((if ZIGZAG { num::unzigzag_bits(num) } else { num }, len))
+    } else {
+        None
+    }
+}
+
+/// Decodes two varints from a 16-byte window, or returns `None` if either of them is invalid
+#[inline(always)]
+pub(super) fn decode_two_checked<T: VarIntTarget, const ZIGZAG: bool, const STRICT: bool>(
+    window: &[u8],
+) -> Option<([T; 2], [u8; 2])> {
+    // SAFETY: the window is 16 bytes long
+    let (first, second, first_len, second_len) = unsafe {
+        if ZIGZAG {
+            decode_two_zigzag_bits::<T, T>(window[..16].as_ptr())
+        } else {
+            decode_two_unsafe::<T, T>(window[..16].as_ptr())
+        }
+    };
+
+    if is_valid::<T, STRICT>(window, 0, first_len as usize)
+        && is_valid::<T, STRICT>(window, first_len as usize, second_len as usize)
+    {
+        Some(([first, second], [first_len, second_len]))
+    } else {
+        None
+    }
+}
+
+/// Decodes four varints from a 16-byte window, or returns `None` if any of them is invalid
+#[inline(always)]
+pub(super) fn decode_four_checked<T: VarIntTarget, const ZIGZAG: bool, const STRICT: bool>(
+    window: &[u8],
+) -> Option<([T; 4], [u8; 4])> {
+    // SAFETY: the window is 16 bytes long
+    let (first, second, third, fourth, first_len, second_len, third_len, fourth_len, invalid) = unsafe {
+        if ZIGZAG {
+            decode_four_zigzag_bits::<T, T, T, T>(window[..16].as_ptr())
+        } else {
+            decode_four_unsafe::<T, T, T, T>(window[..16].as_ptr())
+        }
+    };
+    let lens = [first_len, second_len, third_len, fourth_len];
+
+    if invalid {
+        return None;
+    }
+
+    let mut start = 0;
+    for len in lens {
+        if !is_valid::<T, STRICT>(window, start, len as usize) {
+            return None;
+        }
+        start += len as usize;
+    }
+
+    Some(([first, second, third, fourth], lens))
+}
+
+#[inline(always)]
+fn decode_eight<T: VarIntTarget, const ZIGZAG: bool, const STRICT: bool>(
+    window: &[u8],
+    out: &mut [T],
+) -> Option<(usize, usize)> {
+    // SAFETY: the window is 16 bytes long
+    let (nums, len, invalid) = unsafe {
+        if STRICT {
+            decode_eight_u8_strict_unsafe(window[..16].as_ptr())
+        } else {
+            decode_eight_u8_checked_unsafe(window[..16].as_ptr())
+        }
+    };
+    let len = len as usize;
+
+    if invalid != 0 {
+        return None;
+    }
+
+    for (out, num) in out.iter_mut().zip(nums) {
+        let num = T::cast_u32(num as u32);
+        *out = if ZIGZAG { num::unzigzag_bits(num) } else { num };
+    }
+    Some((8, len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        decode_slice, decode_slice_sign_extended, decode_slice_strict, decode_slice_zigzag,
+    };
+    use crate::tests::{NUMS_U128, NUMS_U16, NUMS_U32, NUMS_U64, NUMS_U8};
+    use crate::{
+        decode, decode_sign_extended, encode, encode_padded, encode_sign_extended, encode_wide,
+        SignedVarIntTarget, SliceDecodeError, VarIntDecodeError, VarIntTarget,
+    };
+
+    /// Decodes the input one varint at a time, for comparison with `decode_slice`
+    fn decode_slice_sequential<T: VarIntTarget>(
+        input: &[u8],
+        out: &mut [T],
+    ) -> Result<(usize, usize), SliceDecodeError> {
+        let mut read = 0;
+        let mut written = 0;
+        while read < input.len() && written < out.len() {
+            let (num, len) = decode::<T>(&input[read..]).map_err(|error| SliceDecodeError {
+                error,
+                offset: read,
+                index: written,
+            })?;
+            out[written] = num;
+            written += 1;
+            read += len;
+        }
+        Ok((written, read))
+    }
+
+    fn check_slice<T: VarIntTarget>(nums: &[T]) {
+        // Every pair of values, so that each kernel sees every combination of lengths
+        let mut values = Vec::new();
+        for i in nums {
+            for j in nums {
+                values.push(*i);
+                values.push(*j);
+            }
+        }
+
+        let mut encoded = Vec::new();
+        for value in &values {
+            let (bytes, len) = encode_wide(*value);
+            encoded.extend_from_slice(&bytes[..len as usize]);
+        }
+
+        let mut out = vec![T::cast_u32(0); values.len() + 3];
+        assert_eq!(
+            decode_slice(&encoded, &mut out),
+            Ok((values.len(), encoded.len()))
+        );
+        assert_eq!(&out[..values.len()], &values[..]);
+
+        // Stopping early because the output is full
+        for count in [0, 1, 7, values.len() / 2] {
+            let mut out = vec![T::cast_u32(0); count];
+            let mut expected = vec![T::cast_u32(0); count];
+            assert_eq!(
+                decode_slice(&encoded, &mut out),
+                decode_slice_sequential(&encoded, &mut expected)
+            );
+            assert_eq!(out, expected);
+        }
+
+        // Stopping early because the input is cut off, possibly in the middle of a varint
+        for cut in (0..encoded.len()).step_by(7) {
+            let mut out = vec![T::cast_u32(0); values.len()];
+            let mut expected = vec![T::cast_u32(0); values.len()];
+            assert_eq!(
+                decode_slice(&encoded[..cut], &mut out),
+                decode_slice_sequential(&encoded[..cut], &mut expected)
+            );
+            assert_eq!(out, expected);
+        }
+    }
+
+    #[test]
+    fn test_decode_slice() {
+        check_slice(&NUMS_U8[..]);
+        check_slice(&NUMS_U16[..]);
+        check_slice(&NUMS_U32[..]);
+        check_slice(&NUMS_U64[..]);
+        check_slice(&NUMS_U128[..]);
+    }
+
+    fn check_slice_window_edges<T: VarIntTarget>() {
+        // A two-byte varint ending just before, at and just after the end of a 16-byte window
+        for end in [15, 16, 17] {
+            let mut input = vec![0x01u8; end];
+            input[end - 2] = 0x80;
+
+            let mut out = vec![T::cast_u32(0); end];
+            assert_eq!(
+                decode_slice(&input, &mut out),
+                Ok((end - 1, end)),
+                "{}",
+                end
+            );
+            assert_eq!(out[end - 2], T::cast_u32(0x80));
+
+            let error = SliceDecodeError {
+                error: VarIntDecodeError::NotEnoughBytes,
+                offset: end - 2,
+                index: end - 2,
+            };
+            assert_eq!(decode_slice(&input[..end - 1], &mut out), Err(error));
+        }
+    }
+
+    #[test]
+    fn test_decode_slice_window_edges() {
+        check_slice_window_edges::<u8>();
+        check_slice_window_edges::<u16>();
+        check_slice_window_edges::<u32>();
+        check_slice_window_edges::<u64>();
+    }
+
+    fn check_slice_overflow_in_last_lane<T: VarIntTarget>(lanes: usize, overflow: &[u8]) {
+        let mut input = vec![0x01u8; lanes - 1];
+        input.extend_from_slice(overflow);
+        input.resize(32, 0x01);
+
+        let mut out = vec![T::cast_u32(0); 32];
+        let error = SliceDecodeError {
+            error: VarIntDecodeError::Overflow,
+            offset: lanes - 1,
+            index: lanes - 1,
+        };
+        assert_eq!(decode_slice(&input, &mut out), Err(error));
+        assert!(out[..lanes - 1].iter().all(|num| *num == T::cast_u32(1)));
+    }
+
+    #[test]
+    fn test_decode_slice_overflow_in_last_lane() {
+        check_slice_overflow_in_last_lane::<u8>(8, &[0x80, 0x02]);
+        check_slice_overflow_in_last_lane::<u16>(4, &[0x80, 0x80, 0x04]);
+        check_slice_overflow_in_last_lane::<u32>(2, &[0x80, 0x80, 0x80, 0x80, 0x10]);
+        check_slice_overflow_in_last_lane::<u64>(
+            1,
+            &[0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x02],
+        );
+    }
+
+    fn check_slice_zigzag<T: SignedVarIntTarget>(nums: &[T::Unsigned]) {
+        let mut encoded = Vec::new();
+        for i in nums {
+            for j in nums {
+                for value in [*i, *j] {
+                    let (bytes, len) = encode_wide(value);
+                    encoded.extend_from_slice(&bytes[..len as usize]);
+                }
+            }
+        }
+
+        // Stopping because the input ends, because the output is full, and at a cut-off varint
+        for (input, count) in [
+            (&encoded[..], nums.len() * nums.len() * 2 + 3),
+            (&encoded[..], 7),
+            (&encoded[..encoded.len() - 1], nums.len() * nums.len() * 2),
+        ] {
+            let mut out = vec![T::unzigzag(T::Unsigned::cast_u32(0)); count];
+            let mut expected = vec![T::Unsigned::cast_u32(0); count];
+            let result = decode_slice_zigzag(input, &mut out);
+            assert_eq!(result, decode_slice(input, &mut expected));
+
+            let written = match result {
+                Ok((written, _)) => written,
+                Err(error) => error.index,
+            };
+            for (out, expected) in out[..written].iter().zip(&expected) {
+                assert_eq!(*out, expected.unzigzag());
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_slice_zigzag() {
+        check_slice_zigzag::<i8>(&NUMS_U8[..]);
+        check_slice_zigzag::<i16>(&NUMS_U16[..]);
+        check_slice_zigzag::<i32>(&NUMS_U32[..]);
+        check_slice_zigzag::<i64>(&NUMS_U64[..]);
+        check_slice_zigzag::<i128>(&NUMS_U128[..]);
+    }
+
+    fn check_slice_strict<T: VarIntTarget>(nums: &[T]) {
+        let mut values = Vec::new();
+        for i in nums {
+            for j in nums {
+                values.push(*i);
+                values.push(*j);
+            }
+        }
+
+        let mut encoded = Vec::new();
+        let mut offsets = Vec::new();
+        for value in &values {
+            offsets.push(encoded.len());
+            let (bytes, len) = encode(*value);
+            encoded.extend_from_slice(&bytes[..len as usize]);
+        }
+
+        let mut out = vec![T::cast_u32(0); values.len()];
+        let mut expected = vec![T::cast_u32(0); values.len()];
+        assert_eq!(
+            decode_slice_strict(&encoded, &mut out),
+            decode_slice(&encoded, &mut expected)
+        );
+        assert_eq!(out, expected);
+
+        // A non-canonical varint at each position, after which nothing more is decoded
+        for (index, offset) in offsets.iter().enumerate().step_by(3) {
+            let mut input = encoded[..*offset].to_vec();
+            let padded = encode_padded(T::cast_u32(0), T::MAX_VARINT_BYTES);
+            input.extend_from_slice(&padded[..T::MAX_VARINT_BYTES as usize]);
+            input.extend_from_slice(&encoded[*offset..]);
+
+            let mut out = vec![T::cast_u32(0); values.len() + 1];
+            assert_eq!(
+                decode_slice_strict(&input, &mut out),
+                Err(SliceDecodeError {
+                    error: VarIntDecodeError::NonCanonical,
+                    offset: *offset,
+                    index,
+                })
+            );
+            assert_eq!(out[..index], values[..index]);
+        }
+    }
+
+    #[test]
+    fn test_decode_slice_strict() {
+        check_slice_strict(&NUMS_U8[..]);
+        check_slice_strict(&NUMS_U16[..]);
+        check_slice_strict(&NUMS_U32[..]);
+        check_slice_strict(&NUMS_U64[..]);
+    }
+
+    fn check_slice_sign_extended<T: SignedVarIntTarget>(nums: &[T::Unsigned]) {
+        let mut values = Vec::new();
+        for i in nums {
+            for j in nums {
+                values.push(i.unzigzag());
+                values.push(j.unzigzag());
+            }
+        }
+
+        let mut encoded = Vec::new();
+        for value in &values {
+            let (bytes, len) = encode_sign_extended(*value);
+            encoded.extend_from_slice(&bytes[..len as usize]);
+        }
+
+        let zero = T::unzigzag(T::Unsigned::cast_u32(0));
+        let mut out = vec![zero; values.len() + 3];
+        assert_eq!(
+            decode_slice_sign_extended(&encoded, &mut out),
+            Ok((values.len(), encoded.len()))
+        );
+        assert_eq!(&out[..values.len()], &values[..]);
+
+        // Stopping early because the input is cut off, possibly in the middle of a varint
+        for cut in (0..encoded.len()).step_by(7) {
+            let mut out = vec![zero; values.len()];
+            let result = decode_slice_sign_extended(&encoded[..cut], &mut out);
+
+            let mut read = 0;
+            let mut written = 0;
+            let expected = loop {
+                if read == cut {
+                    break Ok((written, read));
+                }
+                match decode_sign_extended::<T>(&encoded[read..cut]) {
+                    Ok((num, len)) => {
+                        assert_eq!(out[written], num);
+                        read += len;
+                        written += 1;
+                    }
+                    Err(error) => {
+                        break Err(SliceDecodeError {
+                            error,
+                            offset: read,
+                            index: written,
+                        })
+                    }
+                }
+            };
+            assert_eq!(result, expected);
+        }
+    }
+
+    #[test]
+    fn test_decode_slice_sign_extended() {
+        check_slice_sign_extended::<i8>(&NUMS_U8[..]);
+        check_slice_sign_extended::<i16>(&NUMS_U16[..]);
+        check_slice_sign_extended::<i32>(&NUMS_U32[..]);
+        check_slice_sign_extended::<i64>(&NUMS_U64[..]);
+    }
+}
@@ -0,0 +1,957 @@
+#[cfg(target_arch = "x86")]
+use core::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64::*;
+
+use super::lookup;
+#[cfg(target_arch = "x86_64")]
+use crate::cpu;
+use crate::num::{self, VarIntTarget};
+
+#[inline]
+pub(crate) unsafe fn decode_two_unsafe<T: VarIntTarget, U: VarIntTarget>(
+    bytes: *const u8,
+) -> (T, U, u8, u8) {
+    #[cfg(target_arch = "x86_64")]
+    if cpu::has_very_fast_pdep() {
+        return decode_two_bmi2(bytes);
+    }
+
+    decode_two_ssse3(bytes)
+}
+
+#[inline]
+#[target_feature(enable = "ssse3")]
+pub(crate) unsafe fn decode_two_ssse3<T: VarIntTarget, U: VarIntTarget>(
+    bytes: *const u8,
+) -> (T, U, u8, u8) {
+    decode_two::<T, U, false, false>(bytes)
+}
+
+#[inline]
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "ssse3,bmi2")]
+pub(crate) unsafe fn decode_two_bmi2<T: VarIntTarget, U: VarIntTarget>(
+    bytes: *const u8,
+) -> (T, U, u8, u8) {
+    decode_two::<T, U, true, false>(bytes)
+}
+
+#[inline]
+pub(crate) unsafe fn decode_two_zigzag_unsafe<T: VarIntTarget, U: VarIntTarget>(
+    bytes: *const u8,
+) -> (T, U, u8, u8) {
+    #[cfg(target_arch = "x86_64")]
+    if cpu::has_very_fast_pdep() {
+        return decode_two_zigzag_bmi2(bytes);
+    }
+
+    decode_two_zigzag_ssse3(bytes)
+}
+
+#[inline]
+#[target_feature(enable = "ssse3")]
+pub(crate) unsafe fn decode_two_zigzag_ssse3<T: VarIntTarget, U: VarIntTarget>(
+    bytes: *const u8,
+) -> (T, U, u8, u8) {
+    decode_two::<T, U, false, true>(bytes)
+}
+
+#[inline]
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "ssse3,bmi2")]
+pub(crate) unsafe fn decode_two_zigzag_bmi2<T: VarIntTarget, U: VarIntTarget>(
+    bytes: *const u8,
+) -> (T, U, u8, u8) {
+    decode_two::<T, U, true, true>(bytes)
+}
+
+/// `PDEP` selects PEXT over the SSE2 "turbo" arithmetic, for CPUs where it is very fast.
+/// `ZIGZAG` decodes the values from ZigZag format, leaving the bits of the signed results in the
+/// unsigned types.
+#[inline(always)]
+unsafe fn decode_two<T: VarIntTarget, U: VarIntTarget, const PDEP: bool, const ZIGZAG: bool>(
+    bytes: *const u8,
+) -> (T, U, u8, u8) {
+    if T::MAX_VARINT_BYTES <= 5 && U::MAX_VARINT_BYTES <= 5 {
+        // This will work with our lookup table, use that version
+        return decode_two_u32::<T, U, PDEP, ZIGZAG>(bytes);
+    }
+
+    let b = _mm_loadu_si128(bytes as *const __m128i);
+
+    // First find where the boundaries are
+    let bitmask = _mm_movemask_epi8(b) as u32;
+
+    // Find the number of bytes taken up by each varint
+    let bm_not = !bitmask;
+    let first_len = bm_not.trailing_zeros() + 1; // should compile to bsf or tzcnt
+    let bm_not_2 = bm_not >> first_len;
+    let second_len = bm_not_2.trailing_zeros() + 1;
+
+    let ascend = _mm_setr_epi8(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
+
+    let first_len_vec = _mm_set1_epi8(first_len as i8);
+    let first_mask = _mm_cmplt_epi8(ascend, first_len_vec);
+    let first = _mm_and_si128(b, first_mask);
+
+    let second_shuf = _mm_add_epi8(ascend, first_len_vec);
+    let second_shuffled = _mm_shuffle_epi8(b, second_shuf);
+    let second_mask = _mm_cmplt_epi8(ascend, _mm_set1_epi8(second_len as i8));
+    let second = _mm_and_si128(second_shuffled, second_mask);
+
+    let first_num;
+    let second_num;
+
+    // Only use "turbo" mode if the numbers fit in 64-bit lanes
+    let should_turbo = T::MAX_VARINT_BYTES <= 8 && U::MAX_VARINT_BYTES <= 8 && !PDEP;
+    if should_turbo {
+        // const, so optimized out
+        let comb = _mm_or_si128(first, _mm_bslli_si128(second, 8));
+
+        let x = if T::MAX_VARINT_BYTES <= 2 && U::MAX_VARINT_BYTES <= 2 {
+            dual_u8_stage2(comb)
+        } else if T::MAX_VARINT_BYTES <= 3 && U::MAX_VARINT_BYTES <= 3 {
+            dual_u16_stage2(comb)
+        } else {
+            dual_u32_stage2(comb)
+        };
+        let x = if ZIGZAG { unzigzag_dual::<T, U>(x) } else { x };
+
+        let x: [u32; 4] = core::mem::transmute(x);
+        // _mm_extract_epi32 requires SSE4.1
+        first_num = T::cast_u32(x[0]);
+        second_num = U::cast_u32(x[2]);
+    } else {
+        first_num = vector_to_num::<T, PDEP, ZIGZAG>(first);
+        second_num = vector_to_num::<U, PDEP, ZIGZAG>(second);
+    }
+
+    (first_num, second_num, first_len as u8, second_len as u8)
+}
+
+#[inline(always)]
+unsafe fn decode_two_u32<T: VarIntTarget, U: VarIntTarget, const PDEP: bool, const ZIGZAG: bool>(
+    bytes: *const u8,
+) -> (T, U, u8, u8) {
+    let b = _mm_loadu_si128(bytes as *const __m128i);
+
+    // Get the movemask and mask out irrelevant parts
+    let bitmask = _mm_movemask_epi8(b) as u32 & 0b1111111111;
+
+    // Use lookup table to get the shuffle mask
+    let (lookup, first_len, second_len) =
+        *lookup::LOOKUP_DOUBLE_STEP1.get_unchecked(bitmask as usize);
+    let shuf = *lookup::LOOKUP_DOUBLE_VEC.get_unchecked(lookup as usize);
+
+    let comb = _mm_shuffle_epi8(b, shuf);
+
+    let first_num;
+    let second_num;
+
+    // Only use "turbo" mode if PDEP/PEXT are not faster
+    let should_turbo = !PDEP;
+    if should_turbo {
+        // const, so optimized out
+
+        let x = if T::MAX_VARINT_BYTES <= 2 && U::MAX_VARINT_BYTES <= 2 {
+            dual_u8_stage2(comb)
+        } else if T::MAX_VARINT_BYTES <= 3 && U::MAX_VARINT_BYTES <= 3 {
+            dual_u16_stage2(comb)
+        } else {
+            dual_u32_stage2(comb)
+        };
+        let x = if ZIGZAG { unzigzag_dual::<T, U>(x) } else { x };
+
+        let x: [u32; 4] = core::mem::transmute(x);
+        // _mm_extract_epi32 requires SSE4.1
+        first_num = T::cast_u32(x[0]);
+        second_num = U::cast_u32(x[2]);
+    } else {
+        first_num = vector_to_num::<T, PDEP, ZIGZAG>(comb);
+        second_num = vector_to_num::<U, PDEP, ZIGZAG>(_mm_bsrli_si128(comb, 8));
+    }
+
+    (first_num, second_num, first_len, second_len)
+}
+
+/// Equivalent of [`num::vector_to_num`], ZigZag decoding the result if `ZIGZAG` is set
+#[inline(always)]
+unsafe fn vector_to_num<T: VarIntTarget, const PDEP: bool, const ZIGZAG: bool>(res: __m128i) -> T {
+    let num = num::vector_to_num::<T, PDEP>(core::mem::transmute::<__m128i, [u8; 16]>(res));
+    if ZIGZAG {
+        num::unzigzag_bits(num)
+    } else {
+        num
+    }
+}
+
+/// ZigZag decodes the value in each 64-bit lane, after truncating it to the width of its type
+#[inline(always)]
+unsafe fn unzigzag_dual<T: VarIntTarget, U: VarIntTarget>(x: __m128i) -> __m128i {
+    let x = _mm_and_si128(
+        x,
+        _mm_set_epi64x(num::width_mask::<U>() as i64, num::width_mask::<T>() as i64),
+    );
+    let sign = _mm_sub_epi64(_mm_setzero_si128(), _mm_and_si128(x, _mm_set1_epi64x(1)));
+    _mm_xor_si128(_mm_srli_epi64(x, 1), sign)
+}
+
+/// ZigZag decodes the value in each 32-bit lane, after truncating it to the width of its type
+#[inline(always)]
+unsafe fn unzigzag_quad<T: VarIntTarget, U: VarIntTarget, V: VarIntTarget, W: VarIntTarget>(
+    x: __m128i,
+) -> __m128i {
+    let x = _mm_and_si128(
+        x,
+        _mm_setr_epi32(
+            num::width_mask::<T>() as i32,
+            num::width_mask::<U>() as i32,
+            num::width_mask::<V>() as i32,
+            num::width_mask::<W>() as i32,
+        ),
+    );
+    let sign = _mm_sub_epi32(_mm_setzero_si128(), _mm_and_si128(x, _mm_set1_epi32(1)));
+    _mm_xor_si128(_mm_srli_epi32(x, 1), sign)
+}
+
+#[inline(always)]
+unsafe fn dual_u8_stage2(comb: __m128i) -> __m128i {
+    _mm_or_si128(
+        _mm_and_si128(comb, _mm_set_epi64x(0x000000000000007f, 0x000000000000007f)),
+        _mm_srli_epi64(
+            _mm_and_si128(comb, _mm_set_epi64x(0x0000000000000100, 0x0000000000000100)),
+            1,
+        ),
+    )
+}
+
+#[inline(always)]
+unsafe fn dual_u16_stage2(comb: __m128i) -> __m128i {
+    _mm_or_si128(
+        _mm_or_si128(
+            _mm_and_si128(comb, _mm_set_epi64x(0x000000000000007f, 0x000000000000007f)),
+            _mm_srli_epi64(
+                _mm_and_si128(comb, _mm_set_epi64x(0x0000000000030000, 0x0000000000030000)),
+                2,
+            ),
+        ),
+        _mm_srli_epi64(
+            _mm_and_si128(comb, _mm_set_epi64x(0x0000000000007f00, 0x0000000000007f00)),
+            1,
+        ),
+    )
+}
+
+#[inline(always)]
+unsafe fn dual_u32_stage2(comb: __m128i) -> __m128i {
+    _mm_or_si128(
+        _mm_or_si128(
+            _mm_and_si128(comb, _mm_set_epi64x(0x000000000000007f, 0x000000000000007f)),
+            _mm_srli_epi64(
+                _mm_and_si128(comb, _mm_set_epi64x(0x0000000f00000000, 0x0000000f00000000)),
+                4,
+            ),
+        ),
+        _mm_or_si128(
+            _mm_or_si128(
+                _mm_srli_epi64(
+                    _mm_and_si128(comb, _mm_set_epi64x(0x000000007f000000, 0x000000007f000000)),
+                    3,
+                ),
+                _mm_srli_epi64(
+                    _mm_and_si128(comb, _mm_set_epi64x(0x00000000007f0000, 0x00000000007f0000)),
+                    2,
+                ),
+            ),
+            _mm_srli_epi64(
+                _mm_and_si128(comb, _mm_set_epi64x(0x0000000000007f00, 0x0000000000007f00)),
+                1,
+            ),
+        ),
+    )
+}
+
+#[inline]
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+pub(crate) unsafe fn decode_two_wide_unsafe<T: VarIntTarget, U: VarIntTarget>(
+    bytes: *const u8,
+) -> (T, U, u8, u8) {
+    let b = _mm256_loadu_si256(bytes as *const __m256i);
+
+    // Get the most significant bits
+    let bitmask = _mm256_movemask_epi8(b) as u32;
+
+    // Find the number of bytes taken up by each varint
+    let bm_not = !bitmask;
+    let first_len = bm_not.trailing_zeros() + 1; // should compile to bsf or tzcnt
+    let bm_not_2 = bm_not >> first_len;
+    let second_len = bm_not_2.trailing_zeros() + 1;
+
+    // Create and parse vector consisting solely of the first varint
+    let ascend = _mm_setr_epi8(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
+    let first_mask = _mm_cmplt_epi8(ascend, _mm_set1_epi8(first_len as i8));
+    let first = _mm_and_si128(_mm256_extracti128_si256(b, 0), first_mask);
+
+    // The second is much more tricky.
+    let shuf_gen = _mm256_setr_epi8(
+        0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11,
+        12, 13, 14, 15,
+    );
+
+    // Rearrange each 128-bit lane such that ORing them together results in the window of data we want)
+    let shuf_add = _mm256_set_m128i(
+        _mm_set1_epi8(-(16i8 - first_len as i8)),
+        _mm_set1_epi8(first_len as i8),
+    );
+    let shuf_added = _mm256_add_epi8(shuf_gen, shuf_add);
+    let shuf = _mm256_or_si256(
+        shuf_added,
+        _mm256_cmpgt_epi8(shuf_added, _mm256_set1_epi8(15)), // TODO: Is this really necessary?
+    );
+    let shuffled = _mm256_shuffle_epi8(b, shuf);
+
+    // OR the halves together, and now we have a view of the second varint
+    let second_shifted = _mm_or_si128(
+        _mm256_extracti128_si256(shuffled, 0),
+        _mm256_extracti128_si256(shuffled, 1),
+    );
+    let second_mask = _mm_cmplt_epi8(ascend, _mm_set1_epi8(second_len as i8));
+    let second = _mm_and_si128(second_shifted, second_mask);
+
+    let first_num;
+    let second_num;
+
+    // PEXT on the two halves is still slower, at least on Coffee Lake and Broadwell
+    let should_turbo = true;
+    if should_turbo {
+        // Decode the two halves in parallel using SSE2
+        let comb_lo = _mm_unpacklo_epi64(first, second);
+        let x_lo = _mm_or_si128(
+            _mm_or_si128(
+                _mm_or_si128(
+                    _mm_and_si128(comb_lo, _mm_set1_epi64x(0x000000000000007f)),
+                    _mm_srli_epi64(
+                        _mm_and_si128(comb_lo, _mm_set1_epi64x(0x7f00000000000000)),
+                        7,
+                    ),
+                ),
+                _mm_or_si128(
+                    _mm_srli_epi64(
+                        _mm_and_si128(comb_lo, _mm_set1_epi64x(0x007f000000000000)),
+                        6,
+                    ),
+                    _mm_srli_epi64(
+                        _mm_and_si128(comb_lo, _mm_set1_epi64x(0x00007f0000000000)),
+                        5,
+                    ),
+                ),
+            ),
+            _mm_or_si128(
+                _mm_or_si128(
+                    _mm_srli_epi64(
+                        _mm_and_si128(comb_lo, _mm_set1_epi64x(0x0000007f00000000)),
+                        4,
+                    ),
+                    _mm_srli_epi64(
+                        _mm_and_si128(comb_lo, _mm_set1_epi64x(0x000000007f000000)),
+                        3,
+                    ),
+                ),
+                _mm_or_si128(
+                    _mm_srli_epi64(
+                        _mm_and_si128(comb_lo, _mm_set1_epi64x(0x00000000007f0000)),
+                        2,
+                    ),
+                    _mm_srli_epi64(
+                        _mm_and_si128(comb_lo, _mm_set1_epi64x(0x0000000000007f00)),
+                        1,
+                    ),
+                ),
+            ),
+        );
+
+        let comb_hi = _mm_unpackhi_epi64(first, second);
+        let x_hi = _mm_or_si128(
+            _mm_slli_epi64(
+                _mm_and_si128(comb_hi, _mm_set1_epi64x(0x0000000000000100)),
+                55,
+            ),
+            _mm_slli_epi64(
+                _mm_and_si128(comb_hi, _mm_set1_epi64x(0x000000000000007f)),
+                56,
+            ),
+        );
+
+        let x = _mm_or_si128(x_lo, x_hi);
+
+        first_num = T::cast_u64(_mm_extract_epi64(x, 0) as u64);
+        second_num = U::cast_u64(_mm_extract_epi64(x, 1) as u64);
+    } else {
+        first_num = T::vector_to_num(core::mem::transmute::<__m128i, [u8; 16]>(first));
+        second_num = U::vector_to_num(core::mem::transmute::<__m128i, [u8; 16]>(second));
+    }
+
+    (first_num, second_num, first_len as u8, second_len as u8)
+}
+
+#[inline]
+pub(crate) unsafe fn decode_four_unsafe<
+    T: VarIntTarget,
+    U: VarIntTarget,
+    V: VarIntTarget,
+    W: VarIntTarget,
+>(
+    bytes: *const u8,
+) -> (T, U, V, W, u8, u8, u8, u8, bool) {
+    #[cfg(target_arch = "x86_64")]
+    if cpu::has_very_fast_pdep() {
+        return decode_four_bmi2(bytes);
+    }
+
+    decode_four_ssse3(bytes)
+}
+
+#[inline]
+#[target_feature(enable = "ssse3")]
+pub(crate) unsafe fn decode_four_ssse3<
+    T: VarIntTarget,
+    U: VarIntTarget,
+    V: VarIntTarget,
+    W: VarIntTarget,
+>(
+    bytes: *const u8,
+) -> (T, U, V, W, u8, u8, u8, u8, bool) {
+    decode_four::<T, U, V, W, false, false>(bytes)
+}
+
+#[inline]
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "ssse3,bmi2")]
+pub(crate) unsafe fn decode_four_bmi2<
+    T: VarIntTarget,
+    U: VarIntTarget,
+    V: VarIntTarget,
+    W: VarIntTarget,
+>(
+    bytes: *const u8,
+) -> (T, U, V, W, u8, u8, u8, u8, bool) {
+    decode_four::<T, U, V, W, true, false>(bytes)
+}
+
+#[inline]
+pub(crate) unsafe fn decode_four_zigzag_unsafe<
+    T: VarIntTarget,
+    U: VarIntTarget,
+    V: VarIntTarget,
+    W: VarIntTarget,
+>(
+    bytes: *const u8,
+) -> (T, U, V, W, u8, u8, u8, u8, bool) {
+    #[cfg(target_arch = "x86_64")]
+    if cpu::has_very_fast_pdep() {
+        return decode_four_zigzag_bmi2(bytes);
+    }
+
+    decode_four_zigzag_ssse3(bytes)
+}
+
+#[inline]
+#[target_feature(enable = "ssse3")]
+pub(crate) unsafe fn decode_four_zigzag_ssse3<
+    T: VarIntTarget,
+    U: VarIntTarget,
+    V: VarIntTarget,
+    W: VarIntTarget,
+>(
+    bytes: *const u8,
+) -> (T, U, V, W, u8, u8, u8, u8, bool) {
+    decode_four::<T, U, V, W, false, true>(bytes)
+}
+
+#[inline]
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "ssse3,bmi2")]
+pub(crate) unsafe fn decode_four_zigzag_bmi2<
+    T: VarIntTarget,
+    U: VarIntTarget,
+    V: VarIntTarget,
+    W: VarIntTarget,
+>(
+    bytes: *const u8,
+) -> (T, U, V, W, u8, u8, u8, u8, bool) {
+    decode_four::<T, U, V, W, true, true>(bytes)
+}
+
+/// `PDEP` selects PEXT over the SSE2 "turbo" arithmetic, for CPUs where it is very fast.
+/// `ZIGZAG` decodes the values from ZigZag format, leaving the bits of the signed results in the
+/// unsigned types.
+#[inline(always)]
+unsafe fn decode_four<
+    T: VarIntTarget,
+    U: VarIntTarget,
+    V: VarIntTarget,
+    W: VarIntTarget,
+    const PDEP: bool,
+    const ZIGZAG: bool,
+>(
+    bytes: *const u8,
+) -> (T, U, V, W, u8, u8, u8, u8, bool) {
+    if T::MAX_VARINT_BYTES <= 3
+        && U::MAX_VARINT_BYTES <= 3
+        && V::MAX_VARINT_BYTES <= 3
+        && W::MAX_VARINT_BYTES <= 3
+    {
+        return decode_four_u16::<T, U, V, W, PDEP, ZIGZAG>(bytes);
+    }
+
+    let b = _mm_loadu_si128(bytes as *const __m128i);
+
+    // First find where the boundaries are
+    let bitmask = _mm_movemask_epi8(b) as u32;
+
+    // Find the number of bytes taken up by each varint
+    let bm_not = !bitmask;
+    let first_len = bm_not.trailing_zeros() + 1; // should compile to bsf or tzcnt
+    let bm_not_2 = bm_not >> first_len;
+    let second_len = bm_not_2.trailing_zeros() + 1;
+    let bm_not_3 = bm_not_2 >> second_len;
+    let third_len = bm_not_3.trailing_zeros() + 1;
+    let bm_not_4 = bm_not_3 >> third_len;
+    let fourth_len = bm_not_4.trailing_zeros() + 1;
+
+    let ascend = _mm_setr_epi8(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
+
+    let first_len_vec = _mm_set1_epi8(first_len as i8);
+    let first_mask = _mm_cmplt_epi8(ascend, first_len_vec);
+    let first = _mm_and_si128(b, first_mask);
+
+    let second_shuf = _mm_add_epi8(ascend, first_len_vec);
+    let second_shuffled = _mm_shuffle_epi8(b, second_shuf);
+    let second_len_vec = _mm_set1_epi8(second_len as i8);
+    let second_mask = _mm_cmplt_epi8(ascend, second_len_vec);
+    let second = _mm_and_si128(second_shuffled, second_mask);
+
+    let third_shuf = _mm_add_epi8(ascend, second_len_vec);
+    let third_shuffled = _mm_shuffle_epi8(second_shuffled, third_shuf);
+    let third_len_vec = _mm_set1_epi8(third_len as i8);
+    let third_mask = _mm_cmplt_epi8(ascend, third_len_vec);
+    let third = _mm_and_si128(third_shuffled, third_mask);
+
+    let fourth_shuf = _mm_add_epi8(ascend, third_len_vec);
+    let fourth_shuffled = _mm_shuffle_epi8(third_shuffled, fourth_shuf);
+    let fourth_len_vec = _mm_set1_epi8(fourth_len as i8);
+    let fourth_mask = _mm_cmplt_epi8(ascend, fourth_len_vec);
+    let fourth = _mm_and_si128(fourth_shuffled, fourth_mask);
+
+    let first_num;
+    let second_num;
+    let third_num;
+    let fourth_num;
+
+    // Only use "turbo" mode if the numbers fit in 64-bit lanes
+    let should_turbo = T::MAX_VARINT_BYTES <= 4
+        && U::MAX_VARINT_BYTES <= 4
+        && V::MAX_VARINT_BYTES <= 4
+        && W::MAX_VARINT_BYTES <= 4
+        // PDEP/PEXT are still a little faster here
+        && !PDEP;
+    if should_turbo {
+        // const, so optimized out
+        let comb = _mm_or_si128(
+            _mm_or_si128(first, _mm_bslli_si128(second, 4)),
+            _mm_or_si128(_mm_bslli_si128(third, 8), _mm_bslli_si128(fourth, 12)),
+        );
+
+        let x = if T::MAX_VARINT_BYTES <= 2
+            && U::MAX_VARINT_BYTES <= 2
+            && V::MAX_VARINT_BYTES <= 2
+            && W::MAX_VARINT_BYTES <= 2
+        {
+            _mm_or_si128(
+                _mm_and_si128(comb, _mm_set1_epi32(0x0000007f)),
+                _mm_srli_epi32(_mm_and_si128(comb, _mm_set1_epi32(0x00000100)), 1),
+            )
+        } else {
+            _mm_or_si128(
+                _mm_or_si128(
+                    _mm_and_si128(comb, _mm_set1_epi32(0x0000007f)),
+                    _mm_srli_epi32(_mm_and_si128(comb, _mm_set1_epi32(0x00030000)), 2),
+                ),
+                _mm_srli_epi32(_mm_and_si128(comb, _mm_set1_epi32(0x00007f00)), 1),
+            )
+        };
+        let x = if ZIGZAG {
+            unzigzag_quad::<T, U, V, W>(x)
+        } else {
+            x
+        };
+
+        let x: [u32; 4] = core::mem::transmute(x);
+        // _mm_extract_epi32 requires SSE4.1
+        first_num = T::cast_u32(x[0]);
+        second_num = U::cast_u32(x[1]);
+        third_num = V::cast_u32(x[2]);
+        fourth_num = W::cast_u32(x[3]);
+    } else {
+        first_num = vector_to_num::<T, PDEP, ZIGZAG>(first);
+        second_num = vector_to_num::<U, PDEP, ZIGZAG>(second);
+        third_num = vector_to_num::<V, PDEP, ZIGZAG>(third);
+        fourth_num = vector_to_num::<W, PDEP, ZIGZAG>(fourth);
+    }
+
+    (
+        first_num,
+        second_num,
+        third_num,
+        fourth_num,
+        first_len as u8,
+        second_len as u8,
+        third_len as u8,
+        fourth_len as u8,
+        false,
+    )
+}
+
+#[inline(always)]
+unsafe fn decode_four_u16<
+    T: VarIntTarget,
+    U: VarIntTarget,
+    V: VarIntTarget,
+    W: VarIntTarget,
+    const PDEP: bool,
+    const ZIGZAG: bool,
+>(
+    bytes: *const u8,
+) -> (T, U, V, W, u8, u8, u8, u8, bool) {
+    let b = _mm_loadu_si128(bytes as *const __m128i);
+
+    // First find where the boundaries are
+    let bitmask = _mm_movemask_epi8(b) as u32;
+
+    // Use the lookup table
+    let lookup = *lookup::LOOKUP_QUAD_STEP1.get_unchecked((bitmask & 0b111111111111) as usize);
+
+    // Fetch the shuffle mask
+    let shuf = *lookup::LOOKUP_QUAD_VEC.get_unchecked((lookup & 0b11111111) as usize);
+
+    // Extract the lengths while we're waiting
+    let first_len = (lookup >> 8) & 0b1111;
+    let second_len = (lookup >> 12) & 0b1111;
+    let third_len = (lookup >> 16) & 0b1111;
+    let fourth_len = (lookup >> 20) & 0b1111;
+
+    let comb = _mm_shuffle_epi8(b, shuf);
+
+    let invalid = lookup >> 31;
+
+    let first_num;
+    let second_num;
+    let third_num;
+    let fourth_num;
+
+    // PDEP/PEXT may be still a little faster here
+    let should_turbo = !PDEP;
+    if should_turbo {
+        // const, so optimized out
+
+        let x = if T::MAX_VARINT_BYTES <= 2
+            && U::MAX_VARINT_BYTES <= 2
+            && V::MAX_VARINT_BYTES <= 2
+            && W::MAX_VARINT_BYTES <= 2
+        {
+            _mm_or_si128(
+                _mm_and_si128(comb, _mm_set1_epi32(0x0000007f)),
+                _mm_srli_epi32(_mm_and_si128(comb, _mm_set1_epi32(0x00000100)), 1),
+            )
+        } else {
+            _mm_or_si128(
+                _mm_or_si128(
+                    _mm_and_si128(comb, _mm_set1_epi32(0x0000007f)),
+                    _mm_srli_epi32(_mm_and_si128(comb, _mm_set1_epi32(0x00030000)), 2),
+                ),
+                _mm_srli_epi32(_mm_and_si128(comb, _mm_set1_epi32(0x00007f00)), 1),
+            )
+        };
+        let x = if ZIGZAG {
+            unzigzag_quad::<T, U, V, W>(x)
+        } else {
+            x
+        };
+
+        let x: [u32; 4] = core::mem::transmute(x);
+        // _mm_extract_epi32 requires SSE4.1
+        first_num = T::cast_u32(x[0]);
+        second_num = U::cast_u32(x[1]);
+        third_num = V::cast_u32(x[2]);
+        fourth_num = W::cast_u32(x[3]);
+    } else {
+        first_num = vector_to_num::<T, PDEP, ZIGZAG>(comb);
+        second_num = vector_to_num::<U, PDEP, ZIGZAG>(_mm_bsrli_si128(comb, 4));
+        third_num = vector_to_num::<V, PDEP, ZIGZAG>(_mm_bsrli_si128(comb, 8));
+        fourth_num = vector_to_num::<W, PDEP, ZIGZAG>(_mm_bsrli_si128(comb, 12));
+    }
+
+    (
+        first_num,
+        second_num,
+        third_num,
+        fourth_num,
+        first_len as u8,
+        second_len as u8,
+        third_len as u8,
+        fourth_len as u8,
+        invalid != 0,
+    )
+}
+
+#[inline]
+#[target_feature(enable = "ssse3")]
+pub(crate) unsafe fn decode_eight_u8_unsafe(bytes: *const u8) -> ([u8; 8], u8) {
+    let (nums, len, _) = decode_eight_u8_impl::<false>(bytes);
+    (nums, len)
+}
+
+#[inline]
+#[target_feature(enable = "ssse3")]
+pub(crate) unsafe fn decode_eight_u8_checked_unsafe(bytes: *const u8) -> ([u8; 8], u8, u8) {
+    decode_eight_u8_impl::<false>(bytes)
+}
+
+#[inline]
+#[target_feature(enable = "ssse3")]
+pub(crate) unsafe fn decode_eight_u8_strict_unsafe(bytes: *const u8) -> ([u8; 8], u8, u8) {
+    decode_eight_u8_impl::<true>(bytes)
+}
+
+/// `STRICT` also marks the non-canonical varints in the overflow bitmask
+#[inline(always)]
+unsafe fn decode_eight_u8_impl<const STRICT: bool>(bytes: *const u8) -> ([u8; 8], u8, u8) {
+    let b = _mm_loadu_si128(bytes as *const __m128i);
+
+    let ones = _mm_set1_epi8(1);
+    let mut lens = _mm_setzero_si128();
+    let mut shift = _mm_and_si128(_mm_cmplt_epi8(b, _mm_setzero_si128()), ones);
+    let ascend = _mm_setr_epi8(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
+    let asc_one = _mm_setr_epi8(1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16);
+    let mut window_small = _mm_setr_epi8(1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0);
+
+    let broadcast_mask = _mm_setzero_si128();
+
+    // if the first byte is zero, shift down by 1, if the first byte is one, shift down by 2
+    // 0
+    let first_byte = _mm_shuffle_epi8(shift, broadcast_mask);
+    shift = _mm_shuffle_epi8(shift, _mm_add_epi8(asc_one, first_byte));
+    lens = _mm_or_si128(lens, _mm_and_si128(first_byte, window_small));
+    window_small = _mm_bslli_si128(window_small, 1);
+
+    // 1
+    let first_byte = _mm_shuffle_epi8(shift, broadcast_mask);
+    shift = _mm_shuffle_epi8(shift, _mm_add_epi8(asc_one, first_byte));
+    lens = _mm_or_si128(lens, _mm_and_si128(first_byte, window_small));
+    window_small = _mm_bslli_si128(window_small, 1);
+
+    // 2
+    let first_byte = _mm_shuffle_epi8(shift, broadcast_mask);
+    shift = _mm_shuffle_epi8(shift, _mm_add_epi8(asc_one, first_byte));
+    lens = _mm_or_si128(lens, _mm_and_si128(first_byte, window_small));
+    window_small = _mm_bslli_si128(window_small, 1);
+
+    // 3
+    let first_byte = _mm_shuffle_epi8(shift, broadcast_mask);
+    shift = _mm_shuffle_epi8(shift, _mm_add_epi8(asc_one, first_byte));
+    lens = _mm_or_si128(lens, _mm_and_si128(first_byte, window_small));
+    window_small = _mm_bslli_si128(window_small, 1);
+
+    // 4
+    let first_byte = _mm_shuffle_epi8(shift, broadcast_mask);
+    shift = _mm_shuffle_epi8(shift, _mm_add_epi8(asc_one, first_byte));
+    lens = _mm_or_si128(lens, _mm_and_si128(first_byte, window_small));
+    window_small = _mm_bslli_si128(window_small, 1);
+
+    // 5
+    let first_byte = _mm_shuffle_epi8(shift, broadcast_mask);
+    shift = _mm_shuffle_epi8(shift, _mm_add_epi8(asc_one, first_byte));
+    lens = _mm_or_si128(lens, _mm_and_si128(first_byte, window_small));
+    window_small = _mm_bslli_si128(window_small, 1);
+
+    // 6
+    let first_byte = _mm_shuffle_epi8(shift, broadcast_mask);
+    shift = _mm_shuffle_epi8(shift, _mm_add_epi8(asc_one, first_byte));
+    lens = _mm_or_si128(lens, _mm_and_si128(first_byte, window_small));
+    window_small = _mm_bslli_si128(window_small, 1);
+
+    // 7
+    let first_byte = _mm_shuffle_epi8(shift, broadcast_mask);
+    // shift = _mm_shuffle_epi8(shift, _mm_add_epi8(asc_one, first_byte));
+    lens = _mm_or_si128(lens, _mm_and_si128(first_byte, window_small));
+    // window_small = _mm_bslli_si128(window_small, 1);
+
+    // Construct the shuffle
+
+    let lens_invert = _mm_sub_epi8(ones, lens);
+    let mut cumul_lens = _mm_add_epi8(lens_invert, _mm_bslli_si128(lens_invert, 1));
+    cumul_lens = _mm_add_epi8(cumul_lens, _mm_bslli_si128(cumul_lens, 2));
+    cumul_lens = _mm_add_epi8(cumul_lens, _mm_bslli_si128(cumul_lens, 4));
+    cumul_lens = _mm_add_epi8(cumul_lens, _mm_bslli_si128(cumul_lens, 8));
+
+    let cumul_lens_2: [u8; 16] = core::mem::transmute(cumul_lens);
+    let last_len = 8 - cumul_lens_2[7] + 8;
+
+    // Set one-lengthed second bytes to negative
+    let second = _mm_shuffle_epi8(
+        _mm_add_epi8(lens, ones),
+        _mm_setr_epi8(-1, 0, -1, 1, -1, 2, -1, 3, -1, 4, -1, 5, -1, 6, -1, 7),
+    );
+
+    let shuf_pt1 = _mm_or_si128(ascend, _mm_cmpeq_epi8(second, ones));
+
+    // Subtract the cumulative sum of zero-lengths to adjust the indexes
+    let x_shuf = _mm_shuffle_epi8(
+        _mm_bslli_si128(cumul_lens, 1),
+        _mm_setr_epi8(0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7),
+    );
+
+    let shuf = _mm_sub_epi8(shuf_pt1, x_shuf);
+    let comb = _mm_shuffle_epi8(b, shuf);
+
+    let x = _mm_or_si128(
+        _mm_and_si128(comb, _mm_set1_epi16(0x0000007f)),
+        _mm_srli_epi16(_mm_and_si128(comb, _mm_set1_epi16(0x00000100)), 1),
+    );
+
+    let shuf = _mm_shuffle_epi8(
+        x,
+        _mm_setr_epi8(0, 2, 4, 6, 8, 10, 12, 14, -1, -1, -1, -1, -1, -1, -1, -1),
+    );
+    let lower: [u64; 2] = core::mem::transmute(shuf);
+    let nums = lower[0].to_ne_bytes();
+
+    // The second byte of each lane is zero for one-byte varints, and must be at most one otherwise
+    let overflow = _mm_cmpgt_epi16(_mm_srli_epi16(comb, 8), _mm_set1_epi16(1));
+    // A two-byte varint is non-canonical if its second byte is zero
+    let overflow = if STRICT {
+        let non_canonical = _mm_cmpeq_epi16(
+            _mm_and_si128(comb, _mm_set1_epi16(0xff80u16 as i16)),
+            _mm_set1_epi16(0x0080),
+        );
+        _mm_or_si128(overflow, non_canonical)
+    } else {
+        overflow
+    };
+    let overflow = _mm_movemask_epi8(_mm_packs_epi16(overflow, _mm_setzero_si128())) as u8;
+
+    (nums, last_len, overflow)
+}
+
+/// Produces a bitmask of the continuation bytes in the 32-byte window at `bytes`, a bitmask of the
+/// bytes greater than `max`, which must be less than `0x80`, and a bitmask of the zero bytes
+#[inline(always)]
+#[cfg(all(target_feature = "sse2", not(miri)))]
+pub(crate) unsafe fn byte_masks_sse2(bytes: *const u8, max: u8) -> (u32, u32, u32) {
+    let lo = _mm_loadu_si128(bytes as *const __m128i);
+    let hi = _mm_loadu_si128(bytes.add(16) as *const __m128i);
+    let max = _mm_set1_epi8(max as i8);
+
+    let cont = _mm_movemask_epi8(lo) as u32 | (_mm_movemask_epi8(hi) as u32) << 16;
+    // a signed comparison misses the continuation bytes, which are negative
+    let above = _mm_movemask_epi8(_mm_cmpgt_epi8(lo, max)) as u32
+        | (_mm_movemask_epi8(_mm_cmpgt_epi8(hi, max)) as u32) << 16;
+    let zero = _mm_movemask_epi8(_mm_cmpeq_epi8(lo, _mm_setzero_si128())) as u32
+        | (_mm_movemask_epi8(_mm_cmpeq_epi8(hi, _mm_setzero_si128())) as u32) << 16;
+
+    (cont, cont | above, zero)
+}
+
+/// Same as [`byte_masks_sse2`], with a single 32-byte load
+#[inline(always)]
+#[cfg(target_arch = "x86_64")]
+pub(crate) unsafe fn byte_masks_avx2(bytes: *const u8, max: u8) -> (u32, u32, u32) {
+    let b = _mm256_loadu_si256(bytes as *const __m256i);
+
+    let cont = _mm256_movemask_epi8(b) as u32;
+    // a signed comparison misses the continuation bytes, which are negative
+    let above = _mm256_movemask_epi8(_mm256_cmpgt_epi8(b, _mm256_set1_epi8(max as i8))) as u32;
+    let zero = _mm256_movemask_epi8(_mm256_cmpeq_epi8(b, _mm256_setzero_si256())) as u32;
+
+    (cont, cont | above, zero)
+}
+
+/// Produces a bitmask of the continuation bytes in the 16-byte window at `bytes`
+#[inline(always)]
+#[cfg(all(target_feature = "sse2", not(miri)))]
+pub(crate) unsafe fn continuation_mask_sse2(bytes: *const u8) -> u32 {
+    _mm_movemask_epi8(_mm_loadu_si128(bytes as *const __m128i)) as u32
+}
+
+#[inline]
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+pub(crate) unsafe fn decode_eight_u16_unsafe(bytes: *const u8) -> ([u16; 8], u8) {
+    let (comb, len) = quad_lane_shuffle(bytes, &lookup::LOOKUP_DUAL_U16, 0b111111);
+
+    // Each 32-bit lane holds up to three bytes of a varint
+    let x = _mm256_or_si256(
+        _mm256_or_si256(
+            _mm256_and_si256(comb, _mm256_set1_epi32(0x0000007f)),
+            _mm256_srli_epi32(_mm256_and_si256(comb, _mm256_set1_epi32(0x00007f00)), 1),
+        ),
+        _mm256_srli_epi32(_mm256_and_si256(comb, _mm256_set1_epi32(0x00030000)), 2),
+    );
+
+    let nums = _mm_packus_epi32(_mm256_castsi256_si128(x), _mm256_extracti128_si256(x, 1));
+    (core::mem::transmute::<__m128i, [u16; 8]>(nums), len)
+}
+
+#[inline]
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+pub(crate) unsafe fn decode_sixteen_u8_unsafe(bytes: *const u8) -> ([u8; 16], u8) {
+    let (comb, len) = quad_lane_shuffle(bytes, &lookup::LOOKUP_QUAD_U8, 0b11111111);
+
+    // Each 16-bit lane holds up to two bytes of a varint
+    let x = _mm256_or_si256(
+        _mm256_and_si256(comb, _mm256_set1_epi16(0x0000007f)),
+        _mm256_srli_epi16(_mm256_and_si256(comb, _mm256_set1_epi16(0x00000100)), 1),
+    );
+
+    let nums = _mm_packus_epi16(_mm256_castsi256_si128(x), _mm256_extracti128_si256(x, 1));
+    (core::mem::transmute::<__m128i, [u8; 16]>(nums), len)
+}
+
+/// Splits the 32-byte window at `bytes` into four groups of varints, each covered by one entry of
+/// `table`, and shuffles the first two groups into the low lane and the last two into the high
+/// lane. Produces the shuffled window and the total length of the groups.
+#[inline(always)]
+#[cfg(target_arch = "x86_64")]
+unsafe fn quad_lane_shuffle(
+    bytes: *const u8,
+    table: &[(u64, u8)],
+    index_mask: u32,
+) -> (__m256i, u8) {
+    let mask = _mm256_movemask_epi8(_mm256_loadu_si256(bytes as *const __m256i)) as u32;
+
+    let (first, first_len) = *table.get_unchecked((mask & index_mask) as usize);
+    let (second, second_len) = *table.get_unchecked((mask >> first_len & index_mask) as usize);
+    let hi_offset = first_len + second_len;
+    let (third, third_len) = *table.get_unchecked((mask >> hi_offset & index_mask) as usize);
+    let (fourth, fourth_len) =
+        *table.get_unchecked((mask >> (hi_offset + third_len) & index_mask) as usize);
+
+    // The second group of each lane starts after the first one, and the unused 0x80 bytes stay
+    // negative after the offset is added
+    let offset = |len: u8| len as u64 * 0x0101010101010101;
+    let shuf = _mm256_set_epi64x(
+        (fourth + offset(third_len)) as i64,
+        third as i64,
+        (second + offset(first_len)) as i64,
+        first as i64,
+    );
+
+    let lo = _mm_loadu_si128(bytes as *const __m128i);
+    let hi = _mm_loadu_si128(bytes.add(hi_offset as usize) as *const __m128i);
+    let comb = _mm256_shuffle_epi8(_mm256_set_m128i(hi, lo), shuf);
+
+    (comb, hi_offset + third_len + fourth_len)
+}
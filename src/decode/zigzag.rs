@@ -0,0 +1,305 @@
+//! Multi-value decoding of varints in ZigZag format, with the transform applied to the vector of
+//! decoded values before they are extracted.
+
+#[cfg(all(target_arch = "aarch64", target_feature = "neon", not(miri)))]
+use super::neon;
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+use super::x86;
+use super::{assert_fits_four, assert_fits_two, scalar};
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+use crate::cpu;
+use crate::num::{self, SignedVarIntTarget, VarIntTarget};
+
+/// Decodes two adjacent varints in ZigZag format simultaneously. Target types must fit within 16
+/// bytes when varint encoded. See also: [`decode_two_unsafe`](crate::decode_two_unsafe)
+///
+/// Returns a tuple containing the two decoded values and the two lengths of bytes read for each
+/// value.
+///
+/// # Examples
+/// ```
+/// use varint_simd::decode_two_zigzag_unsafe;
+///
+/// let bytes = [39, 40, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+/// let decoded = unsafe { decode_two_zigzag_unsafe::<i32, i8>(bytes.as_ptr()) };
+/// assert_eq!(decoded, (-20, 20, 1, 1));
+/// ```
+///
+/// # Safety
+/// There must be at least 16 bytes of allocated memory after the start of the pointer. Otherwise,
+/// there may be undefined behavior. Any data after the two varints are ignored. Truncated values
+/// will be returned if a varint exceeds the target type's limit.
+#[inline]
+pub unsafe fn decode_two_zigzag_unsafe<T: SignedVarIntTarget, U: SignedVarIntTarget>(
+    bytes: *const u8,
+) -> (T, U, u8, u8) {
+    let (first, second, first_len, second_len) =
+        decode_two_zigzag_bits::<T::Unsigned, U::Unsigned>(bytes);
+
+    (
+        num::to_signed(first),
+        num::to_signed(second),
+        first_len,
+        second_len,
+    )
+}
+
+/// Equivalent of [`decode_two_zigzag_unsafe`] that leaves the bits of the signed results in the
+/// unsigned types
+#[inline]
+pub(super) unsafe fn decode_two_zigzag_bits<T: VarIntTarget, U: VarIntTarget>(
+    bytes: *const u8,
+) -> (T, U, u8, u8) {
+    assert_fits_two::<T, U>();
+
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        if cpu::has_ssse3() {
+            x86::decode_two_zigzag_unsafe(bytes)
+        } else {
+            decode_two_zigzag_scalar(bytes)
+        }
+    }
+    #[cfg(all(target_arch = "aarch64", target_feature = "neon", not(miri)))]
+    {
+        neon::decode_two_zigzag_unsafe(bytes)
+    }
+    #[cfg(not(any(
+        target_arch = "x86",
+        target_arch = "x86_64",
+        all(target_arch = "aarch64", target_feature = "neon", not(miri))
+    )))]
+    {
+        decode_two_zigzag_scalar(bytes)
+    }
+}
+
+/// Decodes without SIMD instructions, for [`Backend::Scalar`](crate::Backend::Scalar)
+#[inline]
+pub(crate) unsafe fn decode_two_zigzag_scalar<T: VarIntTarget, U: VarIntTarget>(
+    bytes: *const u8,
+) -> (T, U, u8, u8) {
+    let (first, second, first_len, second_len) = scalar::decode_two_unsafe::<T, U>(bytes);
+
+    (
+        num::unzigzag_bits(first),
+        num::unzigzag_bits(second),
+        first_len,
+        second_len,
+    )
+}
+
+/// Decodes four adjacent varints in ZigZag format simultaneously. Target types must fit within 16
+/// bytes when varint encoded. See also: [`decode_four_unsafe`](crate::decode_four_unsafe)
+///
+/// Returns a tuple containing the four decoded values, followed by the number of bytes read for
+/// each encoded value, followed by a boolean indicator for whether the length values may be
+/// incorrect due to overflow.
+///
+/// # Examples
+/// ```
+/// use varint_simd::decode_four_zigzag_unsafe;
+///
+/// let bytes = [39, 40, 1, 172, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+/// let decoded = unsafe { decode_four_zigzag_unsafe::<i8, i8, i16, i16>(bytes.as_ptr()) };
+/// assert_eq!(decoded, (-20, 20, -1, 150, 1, 1, 1, 2, false));
+/// ```
+///
+/// # Safety
+/// There must be at least 16 bytes of allocated memory after the start of the pointer. Otherwise,
+/// there may be undefined behavior. Any data after the four varints are ignored. Truncated values
+/// will be returned if a varint exceeds the target type's limit.
+#[inline]
+pub unsafe fn decode_four_zigzag_unsafe<
+    T: SignedVarIntTarget,
+    U: SignedVarIntTarget,
+    V: SignedVarIntTarget,
+    W: SignedVarIntTarget,
+>(
+    bytes: *const u8,
+) -> (T, U, V, W, u8, u8, u8, u8, bool) {
+    let (first, second, third, fourth, first_len, second_len, third_len, fourth_len, invalid) =
+        decode_four_zigzag_bits::<T::Unsigned, U::Unsigned, V::Unsigned, W::Unsigned>(bytes);
+
+    (
+        num::to_signed(first),
+        num::to_signed(second),
+        num::to_signed(third),
+        num::to_signed(fourth),
+        first_len,
+        second_len,
+        third_len,
+        fourth_len,
+        invalid,
+    )
+}
+
+/// Equivalent of [`decode_four_zigzag_unsafe`] that leaves the bits of the signed results in the
+/// unsigned types
+#[inline]
+pub(super) unsafe fn decode_four_zigzag_bits<
+    T: VarIntTarget,
+    U: VarIntTarget,
+    V: VarIntTarget,
+    W: VarIntTarget,
+>(
+    bytes: *const u8,
+) -> (T, U, V, W, u8, u8, u8, u8, bool) {
+    assert_fits_four::<T, U, V, W>();
+
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        if cpu::has_ssse3() {
+            x86::decode_four_zigzag_unsafe(bytes)
+        } else {
+            decode_four_zigzag_scalar(bytes)
+        }
+    }
+    #[cfg(all(target_arch = "aarch64", target_feature = "neon", not(miri)))]
+    {
+        neon::decode_four_zigzag_unsafe(bytes)
+    }
+    #[cfg(not(any(
+        target_arch = "x86",
+        target_arch = "x86_64",
+        all(target_arch = "aarch64", target_feature = "neon", not(miri))
+    )))]
+    {
+        decode_four_zigzag_scalar(bytes)
+    }
+}
+
+/// Decodes without SIMD instructions, for [`Backend::Scalar`](crate::Backend::Scalar)
+#[inline]
+pub(crate) unsafe fn decode_four_zigzag_scalar<
+    T: VarIntTarget,
+    U: VarIntTarget,
+    V: VarIntTarget,
+    W: VarIntTarget,
+>(
+    bytes: *const u8,
+) -> (T, U, V, W, u8, u8, u8, u8, bool) {
+    let (first, second, third, fourth, first_len, second_len, third_len, fourth_len, invalid) =
+        scalar::decode_four_unsafe::<T, U, V, W>(bytes);
+
+    (
+        num::unzigzag_bits(first),
+        num::unzigzag_bits(second),
+        num::unzigzag_bits(third),
+        num::unzigzag_bits(fourth),
+        first_len,
+        second_len,
+        third_len,
+        fourth_len,
+        invalid,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_four_zigzag_unsafe, decode_two_zigzag_unsafe};
+    use crate::tests::{NUMS_U16, NUMS_U32, NUMS_U64, NUMS_U8};
+    use crate::{encode_zigzag, Backend, SignedVarIntTarget, VarIntTarget};
+
+    fn encode_vec<T: SignedVarIntTarget>(num: T, out: &mut Vec<u8>) -> u8 {
+        let (bytes, len) = encode_zigzag(num);
+        out.extend_from_slice(&bytes[..len as usize]);
+        len
+    }
+
+    fn signed<T: VarIntTarget>(nums: &[T]) -> Vec<T::Signed> {
+        nums.iter().map(|n| n.unzigzag()).collect()
+    }
+
+    fn check_decode_2x_zigzag<T: SignedVarIntTarget, U: SignedVarIntTarget>(a: &[T], b: &[U]) {
+        for i in a {
+            for j in b {
+                let mut enc = Vec::new();
+                let first_len = encode_vec(*i, &mut enc);
+                let second_len = encode_vec(*j, &mut enc);
+                enc.resize(16, 0);
+
+                let expected = (*i, *j, first_len, second_len);
+                let result = unsafe { decode_two_zigzag_unsafe::<T, U>(enc.as_ptr()) };
+                assert_eq!(result, expected);
+
+                for backend in Backend::supported() {
+                    let result = unsafe { backend.decode_two_zigzag_unsafe::<T, U>(enc.as_ptr()) };
+                    assert_eq!(result, expected, "{:?}", backend);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_2x_zigzag() {
+        let (a, b, c, d) = (
+            signed(&NUMS_U8[..]),
+            signed(&NUMS_U16[..]),
+            signed(&NUMS_U32[..]),
+            signed(&NUMS_U64[..]),
+        );
+
+        check_decode_2x_zigzag::<i8, i8>(&a, &a);
+        check_decode_2x_zigzag::<i8, i64>(&a, &d);
+        check_decode_2x_zigzag::<i16, i32>(&b, &c);
+        check_decode_2x_zigzag::<i32, i32>(&c, &c);
+        check_decode_2x_zigzag::<i64, i16>(&d, &b);
+        check_decode_2x_zigzag::<i64, i32>(&d, &c);
+    }
+
+    fn check_decode_4x_zigzag<
+        T: SignedVarIntTarget,
+        U: SignedVarIntTarget,
+        V: SignedVarIntTarget,
+        W: SignedVarIntTarget,
+    >(
+        a: &[T],
+        b: &[U],
+        c: &[V],
+        d: &[W],
+    ) {
+        for i in a {
+            for j in b {
+                for k in c {
+                    for l in d {
+                        let mut enc = Vec::new();
+                        let lens = [
+                            encode_vec(*i, &mut enc),
+                            encode_vec(*j, &mut enc),
+                            encode_vec(*k, &mut enc),
+                            encode_vec(*l, &mut enc),
+                        ];
+                        enc.resize(16, 0);
+
+                        let expected = (*i, *j, *k, *l, lens[0], lens[1], lens[2], lens[3], false);
+                        let result =
+                            unsafe { decode_four_zigzag_unsafe::<T, U, V, W>(enc.as_ptr()) };
+                        assert_eq!(result, expected);
+
+                        for backend in Backend::supported() {
+                            let result = unsafe {
+                                backend.decode_four_zigzag_unsafe::<T, U, V, W>(enc.as_ptr())
+                            };
+                            assert_eq!(result, expected, "{:?}", backend);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // too slow
+    fn test_decode_4x_zigzag() {
+        let (a, b, c) = (
+            signed(&NUMS_U8[..]),
+            signed(&NUMS_U16[..]),
+            signed(&NUMS_U32[..]),
+        );
+
+        check_decode_4x_zigzag::<i8, i8, i8, i8>(&a, &a, &a, &a);
+        check_decode_4x_zigzag::<i16, i16, i16, i16>(&b, &b, &b, &b);
+        check_decode_4x_zigzag::<i32, i8, i32, i16>(&c, &a, &c, &b);
+    }
+}
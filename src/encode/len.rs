@@ -0,0 +1,278 @@
+//! Total encoded size of a sequence of values, several values per vector.
+
+#[cfg(all(target_arch = "aarch64", target_feature = "neon", not(miri)))]
+use core::arch::aarch64::*;
+#[cfg(all(target_arch = "x86_64", not(miri)))]
+use core::arch::x86_64::*;
+#[cfg(any(
+    all(target_arch = "x86_64", not(miri)),
+    all(target_arch = "aarch64", target_feature = "neon", not(miri))
+))]
+use core::mem::size_of;
+
+use super::encoded_len;
+#[cfg(all(target_arch = "x86_64", not(miri)))]
+use crate::cpu;
+use crate::num::{self, SignedVarIntTarget, VarIntTarget};
+
+/// Returns the number of bytes needed to encode a sequence of values as adjacent varints, such as
+/// the length prefix of a packed protobuf field. Matches the sum of the lengths produced by
+/// [`encode`](crate::encode()).
+///
+/// With AVX2 or NEON, the length of each lane is the number of 7-bit groups below its leading
+/// zeros, found by comparing every lane against the smallest value of each varint length at once.
+///
+/// # Examples
+/// ```
+/// use varint_simd::encoded_len_slice;
+///
+/// assert_eq!(encoded_len_slice::<u32>(&[1337, 1, 300]), 5);
+/// assert_eq!(encoded_len_slice::<u64>(&[u64::MAX, 0]), 11);
+/// ```
+#[inline]
+pub fn encoded_len_slice<T: VarIntTarget>(values: &[T]) -> usize {
+    let (done, len) = len_blocks::<T, false>(values);
+
+    len + values[done..]
+        .iter()
+        .map(|value| encoded_len(*value))
+        .sum::<usize>()
+}
+
+/// Returns the number of bytes needed to encode a sequence of signed values in ZigZag format as
+/// adjacent varints. The values are ZigZag encoded in the vector registers before their lengths are
+/// counted. See also: [`encoded_len_slice`]
+///
+/// # Examples
+/// ```
+/// use varint_simd::encoded_len_slice_zigzag;
+///
+/// assert_eq!(encoded_len_slice_zigzag::<i64>(&[-20, 64, -65]), 5);
+/// ```
+#[inline]
+pub fn encoded_len_slice_zigzag<T: SignedVarIntTarget>(values: &[T]) -> usize {
+    let (done, len) = len_blocks::<T::Unsigned, true>(num::unsigned_slice(values));
+
+    len + values[done..]
+        .iter()
+        .map(|value| encoded_len(T::Unsigned::zigzag(*value)))
+        .sum::<usize>()
+}
+
+/// Counts the lengths of as many whole vectors of values as possible. Produces a tuple containing
+/// the number of values counted, and their total length. `ZIGZAG` treats the values as signed, and
+/// ZigZag encodes them first.
+#[inline(always)]
+fn len_blocks<T: VarIntTarget, const ZIGZAG: bool>(values: &[T]) -> (usize, usize) {
+    // The lengths of 128-bit values are counted one at a time
+    if T::MAX_VARINT_BYTES > 10 {
+        return (0, 0);
+    }
+
+    #[cfg(all(target_arch = "x86_64", not(miri)))]
+    {
+        if cpu::has_avx2() {
+            unsafe { len_blocks_avx2::<T, ZIGZAG>(values) }
+        } else {
+            (0, 0)
+        }
+    }
+    #[cfg(all(target_arch = "aarch64", target_feature = "neon", not(miri)))]
+    {
+        unsafe { len_blocks_neon::<T, ZIGZAG>(values) }
+    }
+    #[cfg(not(any(
+        all(target_arch = "x86_64", not(miri)),
+        all(target_arch = "aarch64", target_feature = "neon", not(miri))
+    )))]
+    {
+        let _ = values;
+        (0, 0)
+    }
+}
+
+/// The smallest value of each varint length after the first, up to the maximum for `T`
+#[inline(always)]
+#[cfg(any(
+    all(target_arch = "x86_64", not(miri)),
+    all(target_arch = "aarch64", target_feature = "neon", not(miri))
+))]
+fn thresholds<T: VarIntTarget>() -> impl Iterator<Item = u64> {
+    (1..T::MAX_VARINT_BYTES as u32).map(|bytes| 1 << (7 * bytes))
+}
+
+#[inline]
+#[cfg(all(target_arch = "x86_64", not(miri)))]
+#[target_feature(enable = "avx2")]
+unsafe fn len_blocks_avx2<T: VarIntTarget, const ZIGZAG: bool>(values: &[T]) -> (usize, usize) {
+    let per_vector = 32 / size_of::<T>();
+    let mut done = 0;
+    let mut sums = _mm256_setzero_si256();
+
+    while values.len() - done >= per_vector {
+        let x = _mm256_loadu_si256(values.as_ptr().add(done) as *const __m256i);
+        let x = if ZIGZAG {
+            crate::zigzag::zigzag_avx2::<T>(x)
+        } else {
+            x
+        };
+
+        // Every lane holds a length of at most 10, so summing the bytes of each 64-bit group adds
+        // up the lengths regardless of the lane width
+        let lens = lens_avx2::<T>(x);
+        sums = _mm256_add_epi64(sums, _mm256_sad_epu8(lens, _mm256_setzero_si256()));
+
+        done += per_vector;
+    }
+
+    let sums: [u64; 4] = core::mem::transmute(sums);
+    (done, sums.iter().sum::<u64>() as usize)
+}
+
+/// Produces the varint length of each lane
+#[inline(always)]
+#[cfg(all(target_arch = "x86_64", not(miri)))]
+unsafe fn lens_avx2<T: VarIntTarget>(x: __m256i) -> __m256i {
+    // AVX2 only has signed comparisons, so flip the sign bits of both sides: x >= t is the same as
+    // (x ^ MIN) > ((t - 1) ^ MIN)
+    match size_of::<T>() {
+        1 => _mm256_sub_epi8(
+            _mm256_set1_epi8(1),
+            _mm256_cmpgt_epi8(_mm256_setzero_si256(), x),
+        ),
+        2 => {
+            let x = _mm256_xor_si256(x, _mm256_set1_epi16(i16::MIN));
+            let mut lens = _mm256_set1_epi16(1);
+            for t in thresholds::<T>() {
+                let t = _mm256_set1_epi16(((t - 1) as i16) ^ i16::MIN);
+                lens = _mm256_sub_epi16(lens, _mm256_cmpgt_epi16(x, t));
+            }
+            lens
+        }
+        4 => {
+            let x = _mm256_xor_si256(x, _mm256_set1_epi32(i32::MIN));
+            let mut lens = _mm256_set1_epi32(1);
+            for t in thresholds::<T>() {
+                let t = _mm256_set1_epi32(((t - 1) as i32) ^ i32::MIN);
+                lens = _mm256_sub_epi32(lens, _mm256_cmpgt_epi32(x, t));
+            }
+            lens
+        }
+        _ => {
+            let x = _mm256_xor_si256(x, _mm256_set1_epi64x(i64::MIN));
+            let mut lens = _mm256_set1_epi64x(1);
+            for t in thresholds::<T>() {
+                let t = _mm256_set1_epi64x(((t - 1) as i64) ^ i64::MIN);
+                lens = _mm256_sub_epi64(lens, _mm256_cmpgt_epi64(x, t));
+            }
+            lens
+        }
+    }
+}
+
+#[inline(always)]
+#[cfg(all(target_arch = "aarch64", target_feature = "neon", not(miri)))]
+unsafe fn len_blocks_neon<T: VarIntTarget, const ZIGZAG: bool>(values: &[T]) -> (usize, usize) {
+    let per_vector = 16 / size_of::<T>();
+    let mut done = 0;
+    let mut sum = 0;
+
+    while values.len() - done >= per_vector {
+        let x = vld1q_u8(values.as_ptr().add(done) as *const u8);
+        let x = if ZIGZAG {
+            crate::zigzag::zigzag_neon::<T>(x)
+        } else {
+            x
+        };
+
+        sum += lens_neon::<T>(x);
+
+        done += per_vector;
+    }
+
+    (done, sum)
+}
+
+/// Produces the total varint length of the lanes
+#[inline(always)]
+#[cfg(all(target_arch = "aarch64", target_feature = "neon", not(miri)))]
+unsafe fn lens_neon<T: VarIntTarget>(x: uint8x16_t) -> usize {
+    // Subtracting an all-ones comparison result adds one for every threshold reached
+    match size_of::<T>() {
+        1 => {
+            let lens = vsubq_u8(vdupq_n_u8(1), vcgeq_u8(x, vdupq_n_u8(0x80)));
+            vaddlvq_u8(lens) as usize
+        }
+        2 => {
+            let x = vreinterpretq_u16_u8(x);
+            let mut lens = vdupq_n_u16(1);
+            for t in thresholds::<T>() {
+                lens = vsubq_u16(lens, vcgeq_u16(x, vdupq_n_u16(t as u16)));
+            }
+            vaddvq_u16(lens) as usize
+        }
+        4 => {
+            let x = vreinterpretq_u32_u8(x);
+            let mut lens = vdupq_n_u32(1);
+            for t in thresholds::<T>() {
+                lens = vsubq_u32(lens, vcgeq_u32(x, vdupq_n_u32(t as u32)));
+            }
+            vaddvq_u32(lens) as usize
+        }
+        _ => {
+            let x = vreinterpretq_u64_u8(x);
+            let mut lens = vdupq_n_u64(1);
+            for t in thresholds::<T>() {
+                lens = vsubq_u64(lens, vcgeq_u64(x, vdupq_n_u64(t)));
+            }
+            vaddvq_u64(lens) as usize
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{encoded_len_slice, encoded_len_slice_zigzag};
+    use crate::tests::{NUMS_U128, NUMS_U16, NUMS_U32, NUMS_U64, NUMS_U8};
+    use crate::{encode_wide, encode_wide_zigzag, SignedVarIntTarget, VarIntTarget};
+
+    fn check_encoded_len_slice<T: VarIntTarget>(nums: &[T]) {
+        // Every length up to a few 32-byte vectors, so that each one ends with a different tail
+        for len in 0..80 {
+            let values: Vec<T> = (0..len).map(|i| nums[(i * 7) % nums.len()]).collect();
+            let expected: usize = values.iter().map(|v| encode_wide(*v).1 as usize).sum();
+            assert_eq!(encoded_len_slice(&values), expected);
+        }
+    }
+
+    fn check_encoded_len_slice_zigzag<T: SignedVarIntTarget>(nums: &[T::Unsigned]) {
+        for len in 0..80 {
+            let values: Vec<T> = (0..len)
+                .map(|i| nums[(i * 7) % nums.len()].unzigzag())
+                .collect();
+            let expected: usize = values
+                .iter()
+                .map(|v| encode_wide_zigzag(*v).1 as usize)
+                .sum();
+            assert_eq!(encoded_len_slice_zigzag(&values), expected);
+        }
+    }
+
+    #[test]
+    fn test_encoded_len_slice() {
+        check_encoded_len_slice(&NUMS_U8[..]);
+        check_encoded_len_slice(&NUMS_U16[..]);
+        check_encoded_len_slice(&NUMS_U32[..]);
+        check_encoded_len_slice(&NUMS_U64[..]);
+        check_encoded_len_slice(&NUMS_U128[..]);
+    }
+
+    #[test]
+    fn test_encoded_len_slice_zigzag() {
+        check_encoded_len_slice_zigzag::<i8>(&NUMS_U8[..]);
+        check_encoded_len_slice_zigzag::<i16>(&NUMS_U16[..]);
+        check_encoded_len_slice_zigzag::<i32>(&NUMS_U32[..]);
+        check_encoded_len_slice_zigzag::<i64>(&NUMS_U64[..]);
+        check_encoded_len_slice_zigzag::<i128>(&NUMS_U128[..]);
+    }
+}
@@ -0,0 +1,398 @@
+//! Shuffle tables for the multi-value encoders, generated by `scripts/lookup4.js`. Each one is the
+//! inverse of a decoding table, packing varints from fixed-width lanes into adjacent bytes.
+
+#[cfg(target_arch = "aarch64")]
+use core::arch::aarch64::*;
+#[cfg(target_arch = "x86")]
+use core::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64::*;
+
+/// Native 128-bit vector type the shuffle tables are stored as
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+pub type Vec128 = __m128i;
+#[cfg(target_arch = "aarch64")]
+pub type Vec128 = uint8x16_t;
+
+#[repr(align(16), C)]
+struct Align128<T>(T);
+
+/// Indexed by `(first_len - 1) * 5 + second_len - 1`
+pub static LOOKUP_DOUBLE_ENCODE: [Vec128; 25] = unsafe {
+    core::mem::transmute(Align128::<[u8; 400]>([
+        0, 8, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 1, 1
+        0, 8, 9, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 1, 2
+        0, 8, 9, 10, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 1, 3
+        0, 8, 9, 10, 11, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 1, 4
+        0, 8, 9, 10, 11, 12, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 1, 5
+        0, 1, 8, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 2, 1
+        0, 1, 8, 9, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 2, 2
+        0, 1, 8, 9, 10, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 2, 3
+        0, 1, 8, 9, 10, 11, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 2, 4
+        0, 1, 8, 9, 10, 11, 12, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 2, 5
+        0, 1, 2, 8, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 3, 1
+        0, 1, 2, 8, 9, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 3, 2
+        0, 1, 2, 8, 9, 10, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 3, 3
+        0, 1, 2, 8, 9, 10, 11, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 3, 4
+        0, 1, 2, 8, 9, 10, 11, 12, 255, 255, 255, 255, 255, 255, 255, 255, // 3, 5
+        0, 1, 2, 3, 8, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 4, 1
+        0, 1, 2, 3, 8, 9, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 4, 2
+        0, 1, 2, 3, 8, 9, 10, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 4, 3
+        0, 1, 2, 3, 8, 9, 10, 11, 255, 255, 255, 255, 255, 255, 255, 255, // 4, 4
+        0, 1, 2, 3, 8, 9, 10, 11, 12, 255, 255, 255, 255, 255, 255, 255, // 4, 5
+        0, 1, 2, 3, 4, 8, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 5, 1
+        0, 1, 2, 3, 4, 8, 9, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 5, 2
+        0, 1, 2, 3, 4, 8, 9, 10, 255, 255, 255, 255, 255, 255, 255, 255, // 5, 3
+        0, 1, 2, 3, 4, 8, 9, 10, 11, 255, 255, 255, 255, 255, 255, 255, // 5, 4
+        0, 1, 2, 3, 4, 8, 9, 10, 11, 12, 255, 255, 255, 255, 255, 255, // 5, 5
+    ]))
+};
+
+/// Indexed by the lengths minus one, in base 3, like `LOOKUP_QUAD_VEC`
+pub static LOOKUP_QUAD_ENCODE: [Vec128; 81] = unsafe {
+    core::mem::transmute(Align128::<[u8; 1296]>([
+        0, 4, 8, 12, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 1, 1, 1, 1
+        0, 4, 8, 12, 13, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 1, 1, 1, 2
+        0, 4, 8, 12, 13, 14, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 1, 1, 1, 3
+        0, 4, 8, 9, 12, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 1, 1, 2, 1
+        0, 4, 8, 9, 12, 13, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 1, 1, 2, 2
+        0, 4, 8, 9, 12, 13, 14, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 1, 1, 2, 3
+        0, 4, 8, 9, 10, 12, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 1, 1, 3, 1
+        0, 4, 8, 9, 10, 12, 13, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 1, 1, 3, 2
+        0, 4, 8, 9, 10, 12, 13, 14, 255, 255, 255, 255, 255, 255, 255, 255, // 1, 1, 3, 3
+        0, 4, 5, 8, 12, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 1, 2, 1, 1
+        0, 4, 5, 8, 12, 13, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 1, 2, 1, 2
+        0, 4, 5, 8, 12, 13, 14, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 1, 2, 1, 3
+        0, 4, 5, 8, 9, 12, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 1, 2, 2, 1
+        0, 4, 5, 8, 9, 12, 13, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 1, 2, 2, 2
+        0, 4, 5, 8, 9, 12, 13, 14, 255, 255, 255, 255, 255, 255, 255, 255, // 1, 2, 2, 3
+        0, 4, 5, 8, 9, 10, 12, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 1, 2, 3, 1
+        0, 4, 5, 8, 9, 10, 12, 13, 255, 255, 255, 255, 255, 255, 255, 255, // 1, 2, 3, 2
+        0, 4, 5, 8, 9, 10, 12, 13, 14, 255, 255, 255, 255, 255, 255, 255, // 1, 2, 3, 3
+        0, 4, 5, 6, 8, 12, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 1, 3, 1, 1
+        0, 4, 5, 6, 8, 12, 13, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 1, 3, 1, 2
+        0, 4, 5, 6, 8, 12, 13, 14, 255, 255, 255, 255, 255, 255, 255, 255, // 1, 3, 1, 3
+        0, 4, 5, 6, 8, 9, 12, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 1, 3, 2, 1
+        0, 4, 5, 6, 8, 9, 12, 13, 255, 255, 255, 255, 255, 255, 255, 255, // 1, 3, 2, 2
+        0, 4, 5, 6, 8, 9, 12, 13, 14, 255, 255, 255, 255, 255, 255, 255, // 1, 3, 2, 3
+        0, 4, 5, 6, 8, 9, 10, 12, 255, 255, 255, 255, 255, 255, 255, 255, // 1, 3, 3, 1
+        0, 4, 5, 6, 8, 9, 10, 12, 13, 255, 255, 255, 255, 255, 255, 255, // 1, 3, 3, 2
+        0, 4, 5, 6, 8, 9, 10, 12, 13, 14, 255, 255, 255, 255, 255, 255, // 1, 3, 3, 3
+        0, 1, 4, 8, 12, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 2, 1, 1, 1
+        0, 1, 4, 8, 12, 13, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 2, 1, 1, 2
+        0, 1, 4, 8, 12, 13, 14, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 2, 1, 1, 3
+        0, 1, 4, 8, 9, 12, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 2, 1, 2, 1
+        0, 1, 4, 8, 9, 12, 13, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 2, 1, 2, 2
+        0, 1, 4, 8, 9, 12, 13, 14, 255, 255, 255, 255, 255, 255, 255, 255, // 2, 1, 2, 3
+        0, 1, 4, 8, 9, 10, 12, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 2, 1, 3, 1
+        0, 1, 4, 8, 9, 10, 12, 13, 255, 255, 255, 255, 255, 255, 255, 255, // 2, 1, 3, 2
+        0, 1, 4, 8, 9, 10, 12, 13, 14, 255, 255, 255, 255, 255, 255, 255, // 2, 1, 3, 3
+        0, 1, 4, 5, 8, 12, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 2, 2, 1, 1
+        0, 1, 4, 5, 8, 12, 13, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 2, 2, 1, 2
+        0, 1, 4, 5, 8, 12, 13, 14, 255, 255, 255, 255, 255, 255, 255, 255, // 2, 2, 1, 3
+        0, 1, 4, 5, 8, 9, 12, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 2, 2, 2, 1
+        0, 1, 4, 5, 8, 9, 12, 13, 255, 255, 255, 255, 255, 255, 255, 255, // 2, 2, 2, 2
+        0, 1, 4, 5, 8, 9, 12, 13, 14, 255, 255, 255, 255, 255, 255, 255, // 2, 2, 2, 3
+        0, 1, 4, 5, 8, 9, 10, 12, 255, 255, 255, 255, 255, 255, 255, 255, // 2, 2, 3, 1
+        0, 1, 4, 5, 8, 9, 10, 12, 13, 255, 255, 255, 255, 255, 255, 255, // 2, 2, 3, 2
+        0, 1, 4, 5, 8, 9, 10, 12, 13, 14, 255, 255, 255, 255, 255, 255, // 2, 2, 3, 3
+        0, 1, 4, 5, 6, 8, 12, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 2, 3, 1, 1
+        0, 1, 4, 5, 6, 8, 12, 13, 255, 255, 255, 255, 255, 255, 255, 255, // 2, 3, 1, 2
+        0, 1, 4, 5, 6, 8, 12, 13, 14, 255, 255, 255, 255, 255, 255, 255, // 2, 3, 1, 3
+        0, 1, 4, 5, 6, 8, 9, 12, 255, 255, 255, 255, 255, 255, 255, 255, // 2, 3, 2, 1
+        0, 1, 4, 5, 6, 8, 9, 12, 13, 255, 255, 255, 255, 255, 255, 255, // 2, 3, 2, 2
+        0, 1, 4, 5, 6, 8, 9, 12, 13, 14, 255, 255, 255, 255, 255, 255, // 2, 3, 2, 3
+        0, 1, 4, 5, 6, 8, 9, 10, 12, 255, 255, 255, 255, 255, 255, 255, // 2, 3, 3, 1
+        0, 1, 4, 5, 6, 8, 9, 10, 12, 13, 255, 255, 255, 255, 255, 255, // 2, 3, 3, 2
+        0, 1, 4, 5, 6, 8, 9, 10, 12, 13, 14, 255, 255, 255, 255, 255, // 2, 3, 3, 3
+        0, 1, 2, 4, 8, 12, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 3, 1, 1, 1
+        0, 1, 2, 4, 8, 12, 13, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 3, 1, 1, 2
+        0, 1, 2, 4, 8, 12, 13, 14, 255, 255, 255, 255, 255, 255, 255, 255, // 3, 1, 1, 3
+        0, 1, 2, 4, 8, 9, 12, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 3, 1, 2, 1
+        0, 1, 2, 4, 8, 9, 12, 13, 255, 255, 255, 255, 255, 255, 255, 255, // 3, 1, 2, 2
+        0, 1, 2, 4, 8, 9, 12, 13, 14, 255, 255, 255, 255, 255, 255, 255, // 3, 1, 2, 3
+        0, 1, 2, 4, 8, 9, 10, 12, 255, 255, 255, 255, 255, 255, 255, 255, // 3, 1, 3, 1
+        0, 1, 2, 4, 8, 9, 10, 12, 13, 255, 255, 255, 255, 255, 255, 255, // 3, 1, 3, 2
+        0, 1, 2, 4, 8, 9, 10, 12, 13, 14, 255, 255, 255, 255, 255, 255, // 3, 1, 3, 3
+        0, 1, 2, 4, 5, 8, 12, 255, 255, 255, 255, 255, 255, 255, 255, 255, // 3, 2, 1, 1
+        0, 1, 2, 4, 5, 8, 12, 13, 255, 255, 255, 255, 255, 255, 255, 255, // 3, 2, 1, 2
+        0, 1, 2, 4, 5, 8, 12, 13, 14, 255, 255, 255, 255, 255, 255, 255, // 3, 2, 1, 3
+        0, 1, 2, 4, 5, 8, 9, 12, 255, 255, 255, 255, 255, 255, 255, 255, // 3, 2, 2, 1
+        0, 1, 2, 4, 5, 8, 9, 12, 13, 255, 255, 255, 255, 255, 255, 255, // 3, 2, 2, 2
+        0, 1, 2, 4, 5, 8, 9, 12, 13, 14, 255, 255, 255, 255, 255, 255, // 3, 2, 2, 3
+        0, 1, 2, 4, 5, 8, 9, 10, 12, 255, 255, 255, 255, 255, 255, 255, // 3, 2, 3, 1
+        0, 1, 2, 4, 5, 8, 9, 10, 12, 13, 255, 255, 255, 255, 255, 255, // 3, 2, 3, 2
+        0, 1, 2, 4, 5, 8, 9, 10, 12, 13, 14, 255, 255, 255, 255, 255, // 3, 2, 3, 3
+        0, 1, 2, 4, 5, 6, 8, 12, 255, 255, 255, 255, 255, 255, 255, 255, // 3, 3, 1, 1
+        0, 1, 2, 4, 5, 6, 8, 12, 13, 255, 255, 255, 255, 255, 255, 255, // 3, 3, 1, 2
+        0, 1, 2, 4, 5, 6, 8, 12, 13, 14, 255, 255, 255, 255, 255, 255, // 3, 3, 1, 3
+        0, 1, 2, 4, 5, 6, 8, 9, 12, 255, 255, 255, 255, 255, 255, 255, // 3, 3, 2, 1
+        0, 1, 2, 4, 5, 6, 8, 9, 12, 13, 255, 255, 255, 255, 255, 255, // 3, 3, 2, 2
+        0, 1, 2, 4, 5, 6, 8, 9, 12, 13, 14, 255, 255, 255, 255, 255, // 3, 3, 2, 3
+        0, 1, 2, 4, 5, 6, 8, 9, 10, 12, 255, 255, 255, 255, 255, 255, // 3, 3, 3, 1
+        0, 1, 2, 4, 5, 6, 8, 9, 10, 12, 13, 255, 255, 255, 255, 255, // 3, 3, 3, 2
+        0, 1, 2, 4, 5, 6, 8, 9, 10, 12, 13, 14, 255, 255, 255, 255, // 3, 3, 3, 3
+    ]))
+};
+
+/// Indexed by a bitmask of the two-byte varints
+pub static LOOKUP_EIGHT_U8_ENCODE: [Vec128; 256] = unsafe {
+    core::mem::transmute(Align128::<[u8; 4096]>([
+        0, 2, 4, 6, 8, 10, 12, 14, 255, 255, 255, 255, 255, 255, 255, 255, // 0b00000000
+        0, 1, 2, 4, 6, 8, 10, 12, 14, 255, 255, 255, 255, 255, 255, 255, // 0b00000001
+        0, 2, 3, 4, 6, 8, 10, 12, 14, 255, 255, 255, 255, 255, 255, 255, // 0b00000010
+        0, 1, 2, 3, 4, 6, 8, 10, 12, 14, 255, 255, 255, 255, 255, 255, // 0b00000011
+        0, 2, 4, 5, 6, 8, 10, 12, 14, 255, 255, 255, 255, 255, 255, 255, // 0b00000100
+        0, 1, 2, 4, 5, 6, 8, 10, 12, 14, 255, 255, 255, 255, 255, 255, // 0b00000101
+        0, 2, 3, 4, 5, 6, 8, 10, 12, 14, 255, 255, 255, 255, 255, 255, // 0b00000110
+        0, 1, 2, 3, 4, 5, 6, 8, 10, 12, 14, 255, 255, 255, 255, 255, // 0b00000111
+        0, 2, 4, 6, 7, 8, 10, 12, 14, 255, 255, 255, 255, 255, 255, 255, // 0b00001000
+        0, 1, 2, 4, 6, 7, 8, 10, 12, 14, 255, 255, 255, 255, 255, 255, // 0b00001001
+        0, 2, 3, 4, 6, 7, 8, 10, 12, 14, 255, 255, 255, 255, 255, 255, // 0b00001010
+        0, 1, 2, 3, 4, 6, 7, 8, 10, 12, 14, 255, 255, 255, 255, 255, // 0b00001011
+        0, 2, 4, 5, 6, 7, 8, 10, 12, 14, 255, 255, 255, 255, 255, 255, // 0b00001100
+        0, 1, 2, 4, 5, 6, 7, 8, 10, 12, 14, 255, 255, 255, 255, 255, // 0b00001101
+        0, 2, 3, 4, 5, 6, 7, 8, 10, 12, 14, 255, 255, 255, 255, 255, // 0b00001110
+        0, 1, 2, 3, 4, 5, 6, 7, 8, 10, 12, 14, 255, 255, 255, 255, // 0b00001111
+        0, 2, 4, 6, 8, 9, 10, 12, 14, 255, 255, 255, 255, 255, 255, 255, // 0b00010000
+        0, 1, 2, 4, 6, 8, 9, 10, 12, 14, 255, 255, 255, 255, 255, 255, // 0b00010001
+        0, 2, 3, 4, 6, 8, 9, 10, 12, 14, 255, 255, 255, 255, 255, 255, // 0b00010010
+        0, 1, 2, 3, 4, 6, 8, 9, 10, 12, 14, 255, 255, 255, 255, 255, // 0b00010011
+        0, 2, 4, 5, 6, 8, 9, 10, 12, 14, 255, 255, 255, 255, 255, 255, // 0b00010100
+        0, 1, 2, 4, 5, 6, 8, 9, 10, 12, 14, 255, 255, 255, 255, 255, // 0b00010101
+        0, 2, 3, 4, 5, 6, 8, 9, 10, 12, 14, 255, 255, 255, 255, 255, // 0b00010110
+        0, 1, 2, 3, 4, 5, 6, 8, 9, 10, 12, 14, 255, 255, 255, 255, // 0b00010111
+        0, 2, 4, 6, 7, 8, 9, 10, 12, 14, 255, 255, 255, 255, 255, 255, // 0b00011000
+        0, 1, 2, 4, 6, 7, 8, 9, 10, 12, 14, 255, 255, 255, 255, 255, // 0b00011001
+        0, 2, 3, 4, 6, 7, 8, 9, 10, 12, 14, 255, 255, 255, 255, 255, // 0b00011010
+        0, 1, 2, 3, 4, 6, 7, 8, 9, 10, 12, 14, 255, 255, 255, 255, // 0b00011011
+        0, 2, 4, 5, 6, 7, 8, 9, 10, 12, 14, 255, 255, 255, 255, 255, // 0b00011100
+        0, 1, 2, 4, 5, 6, 7, 8, 9, 10, 12, 14, 255, 255, 255, 255, // 0b00011101
+        0, 2, 3, 4, 5, 6, 7, 8, 9, 10, 12, 14, 255, 255, 255, 255, // 0b00011110
+        0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 12, 14, 255, 255, 255, // 0b00011111
+        0, 2, 4, 6, 8, 10, 11, 12, 14, 255, 255, 255, 255, 255, 255, 255, // 0b00100000
+        0, 1, 2, 4, 6, 8, 10, 11, 12, 14, 255, 255, 255, 255, 255, 255, // 0b00100001
+        0, 2, 3, 4, 6, 8, 10, 11, 12, 14, 255, 255, 255, 255, 255, 255, // 0b00100010
+        0, 1, 2, 3, 4, 6, 8, 10, 11, 12, 14, 255, 255, 255, 255, 255, // 0b00100011
+        0, 2, 4, 5, 6, 8, 10, 11, 12, 14, 255, 255, 255, 255, 255, 255, // 0b00100100
+        0, 1, 2, 4, 5, 6, 8, 10, 11, 12, 14, 255, 255, 255, 255, 255, // 0b00100101
+        0, 2, 3, 4, 5, 6, 8, 10, 11, 12, 14, 255, 255, 255, 255, 255, // 0b00100110
+        0, 1, 2, 3, 4, 5, 6, 8, 10, 11, 12, 14, 255, 255, 255, 255, // 0b00100111
+        0, 2, 4, 6, 7, 8, 10, 11, 12, 14, 255, 255, 255, 255, 255, 255, // 0b00101000
+        0, 1, 2, 4, 6, 7, 8, 10, 11, 12, 14, 255, 255, 255, 255, 255, // 0b00101001
+        0, 2, 3, 4, 6, 7, 8, 10, 11, 12, 14, 255, 255, 255, 255, 255, // 0b00101010
+        0, 1, 2, 3, 4, 6, 7, 8, 10, 11, 12, 14, 255, 255, 255, 255, // 0b00101011
+        0, 2, 4, 5, 6, 7, 8, 10, 11, 12, 14, 255, 255, 255, 255, 255, // 0b00101100
+        0, 1, 2, 4, 5, 6, 7, 8, 10, 11, 12, 14, 255, 255, 255, 255, // 0b00101101
+        0, 2, 3, 4, 5, 6, 7, 8, 10, 11, 12, 14, 255, 255, 255, 255, // 0b00101110
+        0, 1, 2, 3, 4, 5, 6, 7, 8, 10, 11, 12, 14, 255, 255, 255, // 0b00101111
+        0, 2, 4, 6, 8, 9, 10, 11, 12, 14, 255, 255, 255, 255, 255, 255, // 0b00110000
+        0, 1, 2, 4, 6, 8, 9, 10, 11, 12, 14, 255, 255, 255, 255, 255, // 0b00110001
+        0, 2, 3, 4, 6, 8, 9, 10, 11, 12, 14, 255, 255, 255, 255, 255, // 0b00110010
+        0, 1, 2, 3, 4, 6, 8, 9, 10, 11, 12, 14, 255, 255, 255, 255, // 0b00110011
+        0, 2, 4, 5, 6, 8, 9, 10, 11, 12, 14, 255, 255, 255, 255, 255, // 0b00110100
+        0, 1, 2, 4, 5, 6, 8, 9, 10, 11, 12, 14, 255, 255, 255, 255, // 0b00110101
+        0, 2, 3, 4, 5, 6, 8, 9, 10, 11, 12, 14, 255, 255, 255, 255, // 0b00110110
+        0, 1, 2, 3, 4, 5, 6, 8, 9, 10, 11, 12, 14, 255, 255, 255, // 0b00110111
+        0, 2, 4, 6, 7, 8, 9, 10, 11, 12, 14, 255, 255, 255, 255, 255, // 0b00111000
+        0, 1, 2, 4, 6, 7, 8, 9, 10, 11, 12, 14, 255, 255, 255, 255, // 0b00111001
+        0, 2, 3, 4, 6, 7, 8, 9, 10, 11, 12, 14, 255, 255, 255, 255, // 0b00111010
+        0, 1, 2, 3, 4, 6, 7, 8, 9, 10, 11, 12, 14, 255, 255, 255, // 0b00111011
+        0, 2, 4, 5, 6, 7, 8, 9, 10, 11, 12, 14, 255, 255, 255, 255, // 0b00111100
+        0, 1, 2, 4, 5, 6, 7, 8, 9, 10, 11, 12, 14, 255, 255, 255, // 0b00111101
+        0, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 14, 255, 255, 255, // 0b00111110
+        0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 14, 255, 255, // 0b00111111
+        0, 2, 4, 6, 8, 10, 12, 13, 14, 255, 255, 255, 255, 255, 255, 255, // 0b01000000
+        0, 1, 2, 4, 6, 8, 10, 12, 13, 14, 255, 255, 255, 255, 255, 255, // 0b01000001
+        0, 2, 3, 4, 6, 8, 10, 12, 13, 14, 255, 255, 255, 255, 255, 255, // 0b01000010
+        0, 1, 2, 3, 4, 6, 8, 10, 12, 13, 14, 255, 255, 255, 255, 255, // 0b01000011
+        0, 2, 4, 5, 6, 8, 10, 12, 13, 14, 255, 255, 255, 255, 255, 255, // 0b01000100
+        0, 1, 2, 4, 5, 6, 8, 10, 12, 13, 14, 255, 255, 255, 255, 255, // 0b01000101
+        0, 2, 3, 4, 5, 6, 8, 10, 12, 13, 14, 255, 255, 255, 255, 255, // 0b01000110
+        0, 1, 2, 3, 4, 5, 6, 8, 10, 12, 13, 14, 255, 255, 255, 255, // 0b01000111
+        0, 2, 4, 6, 7, 8, 10, 12, 13, 14, 255, 255, 255, 255, 255, 255, // 0b01001000
+        0, 1, 2, 4, 6, 7, 8, 10, 12, 13, 14, 255, 255, 255, 255, 255, // 0b01001001
+        0, 2, 3, 4, 6, 7, 8, 10, 12, 13, 14, 255, 255, 255, 255, 255, // 0b01001010
+        0, 1, 2, 3, 4, 6, 7, 8, 10, 12, 13, 14, 255, 255, 255, 255, // 0b01001011
+        0, 2, 4, 5, 6, 7, 8, 10, 12, 13, 14, 255, 255, 255, 255, 255, // 0b01001100
+        0, 1, 2, 4, 5, 6, 7, 8, 10, 12, 13, 14, 255, 255, 255, 255, // 0b01001101
+        0, 2, 3, 4, 5, 6, 7, 8, 10, 12, 13, 14, 255, 255, 255, 255, // 0b01001110
+        0, 1, 2, 3, 4, 5, 6, 7, 8, 10, 12, 13, 14, 255, 255, 255, // 0b01001111
+        0, 2, 4, 6, 8, 9, 10, 12, 13, 14, 255, 255, 255, 255, 255, 255, // 0b01010000
+        0, 1, 2, 4, 6, 8, 9, 10, 12, 13, 14, 255, 255, 255, 255, 255, // 0b01010001
+        0, 2, 3, 4, 6, 8, 9, 10, 12, 13, 14, 255, 255, 255, 255, 255, // 0b01010010
+        0, 1, 2, 3, 4, 6, 8, 9, 10, 12, 13, 14, 255, 255, 255, 255, // 0b01010011
+        0, 2, 4, 5, 6, 8, 9, 10, 12, 13, 14, 255, 255, 255, 255, 255, // 0b01010100
+        0, 1, 2, 4, 5, 6, 8, 9, 10, 12, 13, 14, 255, 255, 255, 255, // 0b01010101
+        0, 2, 3, 4, 5, 6, 8, 9, 10, 12, 13, 14, 255, 255, 255, 255, // 0b01010110
+        0, 1, 2, 3, 4, 5, 6, 8, 9, 10, 12, 13, 14, 255, 255, 255, // 0b01010111
+        0, 2, 4, 6, 7, 8, 9, 10, 12, 13, 14, 255, 255, 255, 255, 255, // 0b01011000
+        0, 1, 2, 4, 6, 7, 8, 9, 10, 12, 13, 14, 255, 255, 255, 255, // 0b01011001
+        0, 2, 3, 4, 6, 7, 8, 9, 10, 12, 13, 14, 255, 255, 255, 255, // 0b01011010
+        0, 1, 2, 3, 4, 6, 7, 8, 9, 10, 12, 13, 14, 255, 255, 255, // 0b01011011
+        0, 2, 4, 5, 6, 7, 8, 9, 10, 12, 13, 14, 255, 255, 255, 255, // 0b01011100
+        0, 1, 2, 4, 5, 6, 7, 8, 9, 10, 12, 13, 14, 255, 255, 255, // 0b01011101
+        0, 2, 3, 4, 5, 6, 7, 8, 9, 10, 12, 13, 14, 255, 255, 255, // 0b01011110
+        0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 12, 13, 14, 255, 255, // 0b01011111
+        0, 2, 4, 6, 8, 10, 11, 12, 13, 14, 255, 255, 255, 255, 255, 255, // 0b01100000
+        0, 1, 2, 4, 6, 8, 10, 11, 12, 13, 14, 255, 255, 255, 255, 255, // 0b01100001
+        0, 2, 3, 4, 6, 8, 10, 11, 12, 13, 14, 255, 255, 255, 255, 255, // 0b01100010
+        0, 1, 2, 3, 4, 6, 8, 10, 11, 12, 13, 14, 255, 255, 255, 255, // 0b01100011
+        0, 2, 4, 5, 6, 8, 10, 11, 12, 13, 14, 255, 255, 255, 255, 255, // 0b01100100
+        0, 1, 2, 4, 5, 6, 8, 10, 11, 12, 13, 14, 255, 255, 255, 255, // 0b01100101
+        0, 2, 3, 4, 5, 6, 8, 10, 11, 12, 13, 14, 255, 255, 255, 255, // 0b01100110
+        0, 1, 2, 3, 4, 5, 6, 8, 10, 11, 12, 13, 14, 255, 255, 255, // 0b01100111
+        0, 2, 4, 6, 7, 8, 10, 11, 12, 13, 14, 255, 255, 255, 255, 255, // 0b01101000
+        0, 1, 2, 4, 6, 7, 8, 10, 11, 12, 13, 14, 255, 255, 255, 255, // 0b01101001
+        0, 2, 3, 4, 6, 7, 8, 10, 11, 12, 13, 14, 255, 255, 255, 255, // 0b01101010
+        0, 1, 2, 3, 4, 6, 7, 8, 10, 11, 12, 13, 14, 255, 255, 255, // 0b01101011
+        0, 2, 4, 5, 6, 7, 8, 10, 11, 12, 13, 14, 255, 255, 255, 255, // 0b01101100
+        0, 1, 2, 4, 5, 6, 7, 8, 10, 11, 12, 13, 14, 255, 255, 255, // 0b01101101
+        0, 2, 3, 4, 5, 6, 7, 8, 10, 11, 12, 13, 14, 255, 255, 255, // 0b01101110
+        0, 1, 2, 3, 4, 5, 6, 7, 8, 10, 11, 12, 13, 14, 255, 255, // 0b01101111
+        0, 2, 4, 6, 8, 9, 10, 11, 12, 13, 14, 255, 255, 255, 255, 255, // 0b01110000
+        0, 1, 2, 4, 6, 8, 9, 10, 11, 12, 13, 14, 255, 255, 255, 255, // 0b01110001
+        0, 2, 3, 4, 6, 8, 9, 10, 11, 12, 13, 14, 255, 255, 255, 255, // 0b01110010
+        0, 1, 2, 3, 4, 6, 8, 9, 10, 11, 12, 13, 14, 255, 255, 255, // 0b01110011
+        0, 2, 4, 5, 6, 8, 9, 10, 11, 12, 13, 14, 255, 255, 255, 255, // 0b01110100
+        0, 1, 2, 4, 5, 6, 8, 9, 10, 11, 12, 13, 14, 255, 255, 255, // 0b01110101
+        0, 2, 3, 4, 5, 6, 8, 9, 10, 11, 12, 13, 14, 255, 255, 255, // 0b01110110
+        0, 1, 2, 3, 4, 5, 6, 8, 9, 10, 11, 12, 13, 14, 255, 255, // 0b01110111
+        0, 2, 4, 6, 7, 8, 9, 10, 11, 12, 13, 14, 255, 255, 255, 255, // 0b01111000
+        0, 1, 2, 4, 6, 7, 8, 9, 10, 11, 12, 13, 14, 255, 255, 255, // 0b01111001
+        0, 2, 3, 4, 6, 7, 8, 9, 10, 11, 12, 13, 14, 255, 255, 255, // 0b01111010
+        0, 1, 2, 3, 4, 6, 7, 8, 9, 10, 11, 12, 13, 14, 255, 255, // 0b01111011
+        0, 2, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 255, 255, 255, // 0b01111100
+        0, 1, 2, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 255, 255, // 0b01111101
+        0, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 255, 255, // 0b01111110
+        0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 255, // 0b01111111
+        0, 2, 4, 6, 8, 10, 12, 14, 15, 255, 255, 255, 255, 255, 255, 255, // 0b10000000
+        0, 1, 2, 4, 6, 8, 10, 12, 14, 15, 255, 255, 255, 255, 255, 255, // 0b10000001
+        0, 2, 3, 4, 6, 8, 10, 12, 14, 15, 255, 255, 255, 255, 255, 255, // 0b10000010
+        0, 1, 2, 3, 4, 6, 8, 10, 12, 14, 15, 255, 255, 255, 255, 255, // 0b10000011
+        0, 2, 4, 5, 6, 8, 10, 12, 14, 15, 255, 255, 255, 255, 255, 255, // 0b10000100
+        0, 1, 2, 4, 5, 6, 8, 10, 12, 14, 15, 255, 255, 255, 255, 255, // 0b10000101
+        0, 2, 3, 4, 5, 6, 8, 10, 12, 14, 15, 255, 255, 255, 255, 255, // 0b10000110
+        0, 1, 2, 3, 4, 5, 6, 8, 10, 12, 14, 15, 255, 255, 255, 255, // 0b10000111
+        0, 2, 4, 6, 7, 8, 10, 12, 14, 15, 255, 255, 255, 255, 255, 255, // 0b10001000
+        0, 1, 2, 4, 6, 7, 8, 10, 12, 14, 15, 255, 255, 255, 255, 255, // 0b10001001
+        0, 2, 3, 4, 6, 7, 8, 10, 12, 14, 15, 255, 255, 255, 255, 255, // 0b10001010
+        0, 1, 2, 3, 4, 6, 7, 8, 10, 12, 14, 15, 255, 255, 255, 255, // 0b10001011
+        0, 2, 4, 5, 6, 7, 8, 10, 12, 14, 15, 255, 255, 255, 255, 255, // 0b10001100
+        0, 1, 2, 4, 5, 6, 7, 8, 10, 12, 14, 15, 255, 255, 255, 255, // 0b10001101
+        0, 2, 3, 4, 5, 6, 7, 8, 10, 12, 14, 15, 255, 255, 255, 255, // 0b10001110
+        0, 1, 2, 3, 4, 5, 6, 7, 8, 10, 12, 14, 15, 255, 255, 255, // 0b10001111
+        0, 2, 4, 6, 8, 9, 10, 12, 14, 15, 255, 255, 255, 255, 255, 255, // 0b10010000
+        0, 1, 2, 4, 6, 8, 9, 10, 12, 14, 15, 255, 255, 255, 255, 255, // 0b10010001
+        0, 2, 3, 4, 6, 8, 9, 10, 12, 14, 15, 255, 255, 255, 255, 255, // 0b10010010
+        0, 1, 2, 3, 4, 6, 8, 9, 10, 12, 14, 15, 255, 255, 255, 255, // 0b10010011
+        0, 2, 4, 5, 6, 8, 9, 10, 12, 14, 15, 255, 255, 255, 255, 255, // 0b10010100
+        0, 1, 2, 4, 5, 6, 8, 9, 10, 12, 14, 15, 255, 255, 255, 255, // 0b10010101
+        0, 2, 3, 4, 5, 6, 8, 9, 10, 12, 14, 15, 255, 255, 255, 255, // 0b10010110
+        0, 1, 2, 3, 4, 5, 6, 8, 9, 10, 12, 14, 15, 255, 255, 255, // 0b10010111
+        0, 2, 4, 6, 7, 8, 9, 10, 12, 14, 15, 255, 255, 255, 255, 255, // 0b10011000
+        0, 1, 2, 4, 6, 7, 8, 9, 10, 12, 14, 15, 255, 255, 255, 255, // 0b10011001
+        0, 2, 3, 4, 6, 7, 8, 9, 10, 12, 14, 15, 255, 255, 255, 255, // 0b10011010
+        0, 1, 2, 3, 4, 6, 7, 8, 9, 10, 12, 14, 15, 255, 255, 255, // 0b10011011
+        0, 2, 4, 5, 6, 7, 8, 9, 10, 12, 14, 15, 255, 255, 255, 255, // 0b10011100
+        0, 1, 2, 4, 5, 6, 7, 8, 9, 10, 12, 14, 15, 255, 255, 255, // 0b10011101
+        0, 2, 3, 4, 5, 6, 7, 8, 9, 10, 12, 14, 15, 255, 255, 255, // 0b10011110
+        0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 12, 14, 15, 255, 255, // 0b10011111
+        0, 2, 4, 6, 8, 10, 11, 12, 14, 15, 255, 255, 255, 255, 255, 255, // 0b10100000
+        0, 1, 2, 4, 6, 8, 10, 11, 12, 14, 15, 255, 255, 255, 255, 255, // 0b10100001
+        0, 2, 3, 4, 6, 8, 10, 11, 12, 14, 15, 255, 255, 255, 255, 255, // 0b10100010
+        0, 1, 2, 3, 4, 6, 8, 10, 11, 12, 14, 15, 255, 255, 255, 255, // 0b10100011
+        0, 2, 4, 5, 6, 8, 10, 11, 12, 14, 15, 255, 255, 255, 255, 255, // 0b10100100
+        0, 1, 2, 4, 5, 6, 8, 10, 11, 12, 14, 15, 255, 255, 255, 255, // 0b10100101
+        0, 2, 3, 4, 5, 6, 8, 10, 11, 12, 14, 15, 255, 255, 255, 255, // 0b10100110
+        0, 1, 2, 3, 4, 5, 6, 8, 10, 11, 12, 14, 15, 255, 255, 255, // 0b10100111
+        0, 2, 4, 6, 7, 8, 10, 11, 12, 14, 15, 255, 255, 255, 255, 255, // 0b10101000
+        0, 1, 2, 4, 6, 7, 8, 10, 11, 12, 14, 15, 255, 255, 255, 255, // 0b10101001
+        0, 2, 3, 4, 6, 7, 8, 10, 11, 12, 14, 15, 255, 255, 255, 255, // 0b10101010
+        0, 1, 2, 3, 4, 6, 7, 8, 10, 11, 12, 14, 15, 255, 255, 255, // 0b10101011
+        0, 2, 4, 5, 6, 7, 8, 10, 11, 12, 14, 15, 255, 255, 255, 255, // 0b10101100
+        0, 1, 2, 4, 5, 6, 7, 8, 10, 11, 12, 14, 15, 255, 255, 255, // 0b10101101
+        0, 2, 3, 4, 5, 6, 7, 8, 10, 11, 12, 14, 15, 255, 255, 255, // 0b10101110
+        0, 1, 2, 3, 4, 5, 6, 7, 8, 10, 11, 12, 14, 15, 255, 255, // 0b10101111
+        0, 2, 4, 6, 8, 9, 10, 11, 12, 14, 15, 255, 255, 255, 255, 255, // 0b10110000
+        0, 1, 2, 4, 6, 8, 9, 10, 11, 12, 14, 15, 255, 255, 255, 255, // 0b10110001
+        0, 2, 3, 4, 6, 8, 9, 10, 11, 12, 14, 15, 255, 255, 255, 255, // 0b10110010
+        0, 1, 2, 3, 4, 6, 8, 9, 10, 11, 12, 14, 15, 255, 255, 255, // 0b10110011
+        0, 2, 4, 5, 6, 8, 9, 10, 11, 12, 14, 15, 255, 255, 255, 255, // 0b10110100
+        0, 1, 2, 4, 5, 6, 8, 9, 10, 11, 12, 14, 15, 255, 255, 255, // 0b10110101
+        0, 2, 3, 4, 5, 6, 8, 9, 10, 11, 12, 14, 15, 255, 255, 255, // 0b10110110
+        0, 1, 2, 3, 4, 5, 6, 8, 9, 10, 11, 12, 14, 15, 255, 255, // 0b10110111
+        0, 2, 4, 6, 7, 8, 9, 10, 11, 12, 14, 15, 255, 255, 255, 255, // 0b10111000
+        0, 1, 2, 4, 6, 7, 8, 9, 10, 11, 12, 14, 15, 255, 255, 255, // 0b10111001
+        0, 2, 3, 4, 6, 7, 8, 9, 10, 11, 12, 14, 15, 255, 255, 255, // 0b10111010
+        0, 1, 2, 3, 4, 6, 7, 8, 9, 10, 11, 12, 14, 15, 255, 255, // 0b10111011
+        0, 2, 4, 5, 6, 7, 8, 9, 10, 11, 12, 14, 15, 255, 255, 255, // 0b10111100
+        0, 1, 2, 4, 5, 6, 7, 8, 9, 10, 11, 12, 14, 15, 255, 255, // 0b10111101
+        0, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 14, 15, 255, 255, // 0b10111110
+        0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 14, 15, 255, // 0b10111111
+        0, 2, 4, 6, 8, 10, 12, 13, 14, 15, 255, 255, 255, 255, 255, 255, // 0b11000000
+        0, 1, 2, 4, 6, 8, 10, 12, 13, 14, 15, 255, 255, 255, 255, 255, // 0b11000001
+        0, 2, 3, 4, 6, 8, 10, 12, 13, 14, 15, 255, 255, 255, 255, 255, // 0b11000010
+        0, 1, 2, 3, 4, 6, 8, 10, 12, 13, 14, 15, 255, 255, 255, 255, // 0b11000011
+        0, 2, 4, 5, 6, 8, 10, 12, 13, 14, 15, 255, 255, 255, 255, 255, // 0b11000100
+        0, 1, 2, 4, 5, 6, 8, 10, 12, 13, 14, 15, 255, 255, 255, 255, // 0b11000101
+        0, 2, 3, 4, 5, 6, 8, 10, 12, 13, 14, 15, 255, 255, 255, 255, // 0b11000110
+        0, 1, 2, 3, 4, 5, 6, 8, 10, 12, 13, 14, 15, 255, 255, 255, // 0b11000111
+        0, 2, 4, 6, 7, 8, 10, 12, 13, 14, 15, 255, 255, 255, 255, 255, // 0b11001000
+        0, 1, 2, 4, 6, 7, 8, 10, 12, 13, 14, 15, 255, 255, 255, 255, // 0b11001001
+        0, 2, 3, 4, 6, 7, 8, 10, 12, 13, 14, 15, 255, 255, 255, 255, // 0b11001010
+        0, 1, 2, 3, 4, 6, 7, 8, 10, 12, 13, 14, 15, 255, 255, 255, // 0b11001011
+        0, 2, 4, 5, 6, 7, 8, 10, 12, 13, 14, 15, 255, 255, 255, 255, // 0b11001100
+        0, 1, 2, 4, 5, 6, 7, 8, 10, 12, 13, 14, 15, 255, 255, 255, // 0b11001101
+        0, 2, 3, 4, 5, 6, 7, 8, 10, 12, 13, 14, 15, 255, 255, 255, // 0b11001110
+        0, 1, 2, 3, 4, 5, 6, 7, 8, 10, 12, 13, 14, 15, 255, 255, // 0b11001111
+        0, 2, 4, 6, 8, 9, 10, 12, 13, 14, 15, 255, 255, 255, 255, 255, // 0b11010000
+        0, 1, 2, 4, 6, 8, 9, 10, 12, 13, 14, 15, 255, 255, 255, 255, // 0b11010001
+        0, 2, 3, 4, 6, 8, 9, 10, 12, 13, 14, 15, 255, 255, 255, 255, // 0b11010010
+        0, 1, 2, 3, 4, 6, 8, 9, 10, 12, 13, 14, 15, 255, 255, 255, // 0b11010011
+        0, 2, 4, 5, 6, 8, 9, 10, 12, 13, 14, 15, 255, 255, 255, 255, // 0b11010100
+        0, 1, 2, 4, 5, 6, 8, 9, 10, 12, 13, 14, 15, 255, 255, 255, // 0b11010101
+        0, 2, 3, 4, 5, 6, 8, 9, 10, 12, 13, 14, 15, 255, 255, 255, // 0b11010110
+        0, 1, 2, 3, 4, 5, 6, 8, 9, 10, 12, 13, 14, 15, 255, 255, // 0b11010111
+        0, 2, 4, 6, 7, 8, 9, 10, 12, 13, 14, 15, 255, 255, 255, 255, // 0b11011000
+        0, 1, 2, 4, 6, 7, 8, 9, 10, 12, 13, 14, 15, 255, 255, 255, // 0b11011001
+        0, 2, 3, 4, 6, 7, 8, 9, 10, 12, 13, 14, 15, 255, 255, 255, // 0b11011010
+        0, 1, 2, 3, 4, 6, 7, 8, 9, 10, 12, 13, 14, 15, 255, 255, // 0b11011011
+        0, 2, 4, 5, 6, 7, 8, 9, 10, 12, 13, 14, 15, 255, 255, 255, // 0b11011100
+        0, 1, 2, 4, 5, 6, 7, 8, 9, 10, 12, 13, 14, 15, 255, 255, // 0b11011101
+        0, 2, 3, 4, 5, 6, 7, 8, 9, 10, 12, 13, 14, 15, 255, 255, // 0b11011110
+        0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 12, 13, 14, 15, 255, // 0b11011111
+        0, 2, 4, 6, 8, 10, 11, 12, 13, 14, 15, 255, 255, 255, 255, 255, // 0b11100000
+        0, 1, 2, 4, 6, 8, 10, 11, 12, 13, 14, 15, 255, 255, 255, 255, // 0b11100001
+        0, 2, 3, 4, 6, 8, 10, 11, 12, 13, 14, 15, 255, 255, 255, 255, // 0b11100010
+        0, 1, 2, 3, 4, 6, 8, 10, 11, 12, 13, 14, 15, 255, 255, 255, // 0b11100011
+        0, 2, 4, 5, 6, 8, 10, 11, 12, 13, 14, 15, 255, 255, 255, 255, // 0b11100100
+        0, 1, 2, 4, 5, 6, 8, 10, 11, 12, 13, 14, 15, 255, 255, 255, // 0b11100101
+        0, 2, 3, 4, 5, 6, 8, 10, 11, 12, 13, 14, 15, 255, 255, 255, // 0b11100110
+        0, 1, 2, 3, 4, 5, 6, 8, 10, 11, 12, 13, 14, 15, 255, 255, // 0b11100111
+        0, 2, 4, 6, 7, 8, 10, 11, 12, 13, 14, 15, 255, 255, 255, 255, // 0b11101000
+        0, 1, 2, 4, 6, 7, 8, 10, 11, 12, 13, 14, 15, 255, 255, 255, // 0b11101001
+        0, 2, 3, 4, 6, 7, 8, 10, 11, 12, 13, 14, 15, 255, 255, 255, // 0b11101010
+        0, 1, 2, 3, 4, 6, 7, 8, 10, 11, 12, 13, 14, 15, 255, 255, // 0b11101011
+        0, 2, 4, 5, 6, 7, 8, 10, 11, 12, 13, 14, 15, 255, 255, 255, // 0b11101100
+        0, 1, 2, 4, 5, 6, 7, 8, 10, 11, 12, 13, 14, 15, 255, 255, // 0b11101101
+        0, 2, 3, 4, 5, 6, 7, 8, 10, 11, 12, 13, 14, 15, 255, 255, // 0b11101110
+        0, 1, 2, 3, 4, 5, 6, 7, 8, 10, 11, 12, 13, 14, 15, 255, // 0b11101111
+        0, 2, 4, 6, 8, 9, 10, 11, 12, 13, 14, 15, 255, 255, 255, 255, // 0b11110000
+        0, 1, 2, 4, 6, 8, 9, 10, 11, 12, 13, 14, 15, 255, 255, 255, // 0b11110001
+        0, 2, 3, 4, 6, 8, 9, 10, 11, 12, 13, 14, 15, 255, 255, 255, // 0b11110010
+        0, 1, 2, 3, 4, 6, 8, 9, 10, 11, 12, 13, 14, 15, 255, 255, // 0b11110011
+        0, 2, 4, 5, 6, 8, 9, 10, 11, 12, 13, 14, 15, 255, 255, 255, // 0b11110100
+        0, 1, 2, 4, 5, 6, 8, 9, 10, 11, 12, 13, 14, 15, 255, 255, // 0b11110101
+        0, 2, 3, 4, 5, 6, 8, 9, 10, 11, 12, 13, 14, 15, 255, 255, // 0b11110110
+        0, 1, 2, 3, 4, 5, 6, 8, 9, 10, 11, 12, 13, 14, 15, 255, // 0b11110111
+        0, 2, 4, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 255, 255, 255, // 0b11111000
+        0, 1, 2, 4, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 255, 255, // 0b11111001
+        0, 2, 3, 4, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 255, 255, // 0b11111010
+        0, 1, 2, 3, 4, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 255, // 0b11111011
+        0, 2, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 255, 255, // 0b11111100
+        0, 1, 2, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 255, // 0b11111101
+        0, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 255, // 0b11111110
+        0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, // 0b11111111
+    ]))
+};
@@ -1,15 +1,48 @@
-#[cfg(target_arch = "x86")]
+#[cfg(all(target_arch = "aarch64", target_feature = "neon", not(miri)))]
+use core::arch::aarch64::*;
+#[cfg(all(target_arch = "x86", target_feature = "sse2", not(miri)))]
 use core::arch::x86::*;
-#[cfg(target_arch = "x86_64")]
+#[cfg(all(target_arch = "x86_64", target_feature = "sse2", not(miri)))]
 use core::arch::x86_64::*;
 
-use crate::num::{SignedVarIntTarget, VarIntTarget};
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+use crate::cpu;
+
+use core::mem::MaybeUninit;
+
+use crate::num::{self, NonZeroVarIntTarget, SignedVarIntTarget, VarIntTarget};
+
+mod len;
+#[cfg(any(
+    target_arch = "x86",
+    target_arch = "x86_64",
+    all(target_arch = "aarch64", target_feature = "neon")
+))]
+mod lookup;
+pub(crate) mod multi;
+mod padded;
+pub(crate) mod slice;
+#[cfg(feature = "std")]
+mod vec;
 
-/// Encodes a single number to a varint. Requires SSE2 support.
+pub use len::{encoded_len_slice, encoded_len_slice_zigzag};
+pub use multi::{encode_eight_u8_unsafe, encode_four_unsafe, encode_two_unsafe};
+pub(crate) use padded::assert_padded_width;
+pub use padded::{encode_padded, encode_padded_to_slice, encode_padded_unsafe, Backpatch};
+pub use slice::encode_slice;
+#[cfg(feature = "std")]
+pub use slice::encode_slice_to_vec;
+#[cfg(feature = "std")]
+pub use vec::VarIntVecExt;
+
+/// Encodes a single number to a varint.
 ///
 /// Produces a tuple, with the encoded data followed by the number of bytes used to encode the
 /// varint.
 ///
+/// `u128` is rejected at compile time, as its varints may not fit in 16 bytes. Use
+/// [`encode_wide`] or [`encode_to_slice`] instead.
+///
 /// # Examples
 /// ```
 /// use varint_simd::encode;
@@ -17,11 +50,14 @@ use crate::num::{SignedVarIntTarget, VarIntTarget};
 /// let encoded = encode::<u32>(1337);
 /// assert_eq!(encoded, ([185, 10, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0], 2));
 /// ```
+///
+/// ```compile_fail
+/// let encoded = varint_simd::encode::<u128>(1337);
+/// ```
 #[inline]
-#[cfg(any(target_feature = "sse2", doc))]
-#[cfg_attr(rustc_nightly, doc(cfg(target_feature = "sse2")))]
 pub fn encode<T: VarIntTarget>(num: T) -> ([u8; 16], u8) {
-    unsafe { encode_unsafe(num) }
+    num::assert_fits_vector::<T>();
+    unsafe { encode_dispatch(num) }
 }
 
 /// Convenience function for encoding a single signed integer in ZigZag format to a varint.
@@ -35,44 +71,325 @@ pub fn encode<T: VarIntTarget>(num: T) -> ([u8; 16], u8) {
 /// assert_eq!(encoded, ([39, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0], 1));
 /// ```
 #[inline]
-#[cfg(any(target_feature = "sse2", doc))]
-#[cfg_attr(rustc_nightly, doc(cfg(target_feature = "sse2")))]
 pub fn encode_zigzag<T: SignedVarIntTarget>(num: T) -> ([u8; 16], u8) {
-    unsafe { encode_unsafe(T::Unsigned::zigzag(num)) }
+    num::assert_fits_vector::<T::Unsigned>();
+    unsafe { encode_dispatch(T::Unsigned::zigzag(num)) }
+}
+
+/// Encodes a single signed integer to a varint the way protobuf encodes `int32` and `int64`
+/// fields: the value is sign extended to 64 bits and encoded as a `u64`, so every negative value
+/// takes up 10 bytes. See also: [`encode`], [`decode_sign_extended`](crate::decode_sign_extended)
+///
+/// `i128` is rejected at compile time, as its values do not fit in 64 bits.
+///
+/// # Examples
+/// ```
+/// use varint_simd::encode_sign_extended;
+///
+/// let (encoded, len) = encode_sign_extended::<i32>(-2);
+/// assert_eq!(encoded[..len as usize], [254, 255, 255, 255, 255, 255, 255, 255, 255, 1]);
+///
+/// assert_eq!(encode_sign_extended::<i32>(1337), ([185, 10, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0], 2));
+/// ```
+///
+/// ```compile_fail
+/// let encoded = varint_simd::encode_sign_extended::<i128>(1 << 100);
+/// ```
+#[inline]
+pub fn encode_sign_extended<T: SignedVarIntTarget>(num: T) -> ([u8; 16], u8) {
+    unsafe { encode_dispatch(sign_extend(num)) }
+}
+
+/// Convenience function for encoding a single nonzero integer to a varint, which decodes with
+/// [`decode_nonzero`](crate::decode_nonzero). See also: [`encode`]
+///
+/// `NonZeroU128` is rejected at compile time, as with [`encode`].
+///
+/// # Examples
+/// ```
+/// use core::num::NonZeroU32;
+/// use varint_simd::encode_nonzero;
+///
+/// let encoded = encode_nonzero(NonZeroU32::new(1337).unwrap());
+/// assert_eq!(encoded, ([185, 10, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0], 2));
+/// ```
+#[inline]
+pub fn encode_nonzero<T: NonZeroVarIntTarget>(num: T) -> ([u8; 16], u8) {
+    num::assert_fits_vector::<T::Inner>();
+    unsafe { encode_dispatch(num.get()) }
+}
+
+/// Encodes a single number to a varint, for any target type including `u128`, whose varints take
+/// up to 19 bytes. Types whose varints fit in 16 bytes are encoded exactly as by [`encode`].
+///
+/// Produces a tuple, with the encoded data followed by the number of bytes used to encode the
+/// varint.
+///
+/// # Examples
+/// ```
+/// use varint_simd::{decode, encode_wide};
+///
+/// let (encoded, len) = encode_wide::<u128>(1 << 127);
+/// assert_eq!(len, 19);
+/// assert_eq!(encoded[..19], [0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x02]);
+/// assert_eq!(decode::<u128>(&encoded), Ok((1 << 127, 19)));
+/// ```
+#[inline]
+pub fn encode_wide<T: VarIntTarget>(num: T) -> ([u8; 32], u8) {
+    if T::MAX_VARINT_BYTES > 16 {
+        encode_wide_impl(num)
+    } else {
+        let (data, size) = unsafe { encode_dispatch(num) };
+
+        let mut wide = [0u8; 32];
+        wide[..16].copy_from_slice(&data);
+        (wide, size)
+    }
+}
+
+/// Convenience function for encoding a single signed integer in ZigZag format to a varint, for any
+/// target type including `i128`. See also: [`encode_wide`]
+///
+/// # Examples
+/// ```
+/// use varint_simd::encode_wide_zigzag;
+///
+/// let (encoded, len) = encode_wide_zigzag::<i128>(-20);
+/// assert_eq!(encoded[..len as usize], [39]);
+/// ```
+#[inline]
+pub fn encode_wide_zigzag<T: SignedVarIntTarget>(num: T) -> ([u8; 32], u8) {
+    encode_wide(T::Unsigned::zigzag(num))
 }
 
 /// Encodes a single number to a varint, and writes the resulting data to the slice. Returns the
-/// number of bytes written (maximum 10 bytes).
+/// number of bytes written (maximum 19 bytes, for `u128`).
 ///
 /// See also: [`encode`]
 ///
 /// **Panics:** if the slice is too small to contain the varint.
 #[inline]
-#[cfg(any(target_feature = "sse2", doc))]
-#[cfg_attr(rustc_nightly, doc(cfg(target_feature = "sse2")))]
 pub fn encode_to_slice<T: VarIntTarget>(num: T, slice: &mut [u8]) -> u8 {
-    let (data, size) = encode(num);
+    if T::MAX_VARINT_BYTES > 16 {
+        let (data, size) = encode_wide(num);
+        slice[..size as usize].copy_from_slice(&data[..size as usize]);
+        return size;
+    }
+
+    let (data, size) = unsafe { encode_dispatch(num) };
     slice[..size as usize].copy_from_slice(&data[..size as usize]);
 
     size
 }
 
-/// Encodes a single number to a varint. Requires SSE2 support.
+/// Encodes a single number to a varint, and writes the resulting data to the pointer with a single
+/// unaligned 16-byte store. Returns the number of bytes used to encode the varint (maximum 10
+/// bytes). The bytes after the varint are overwritten with unspecified values.
+///
+/// See also: [`encode`]
+///
+/// # Examples
+/// ```
+/// use varint_simd::encode_to_ptr;
+///
+/// let mut out = [0u8; 16];
+/// let len = unsafe { encode_to_ptr::<u32>(1337, out.as_mut_ptr()) };
+/// assert_eq!(out[..len as usize], [185, 10]);
+/// ```
+///
+/// `u128` is rejected at compile time, as with [`encode`].
+///
+/// # Safety
+/// There must be at least 16 bytes of writable memory after the start of the pointer. The memory
+/// does not need to be initialized.
+#[inline]
+pub unsafe fn encode_to_ptr<T: VarIntTarget>(num: T, ptr: *mut u8) -> u8 {
+    num::assert_fits_vector::<T>();
+    encode_to_ptr_dispatch(num, ptr)
+}
+
+/// [`encode_to_ptr`] without the width check, for callers that only reach it for types whose
+/// varints fit in 16 bytes
+#[inline(always)]
+pub(crate) unsafe fn encode_to_ptr_dispatch<T: VarIntTarget>(num: T, ptr: *mut u8) -> u8 {
+    let (data, size) = encode_dispatch(num);
+    ptr.cast::<[u8; 16]>().write_unaligned(data);
+
+    size
+}
+
+/// Encodes a single number to a varint, and writes the resulting data to a possibly uninitialized
+/// slice. Returns the number of bytes written, which are initialized afterwards. When the slice is
+/// at least 16 bytes long, this is a single store, and the bytes after the varint are initialized
+/// to unspecified values.
+///
+/// See also: [`encode_to_ptr`]
+///
+/// **Panics:** if the slice is too small to contain the varint.
+///
+/// # Examples
+/// ```
+/// use core::mem::MaybeUninit;
+/// use varint_simd::encode_to_uninit;
+///
+/// let mut out = [MaybeUninit::<u8>::uninit(); 4];
+/// let len = encode_to_uninit::<u32>(1337, &mut out);
+/// assert_eq!(len, 2);
+/// assert_eq!(unsafe { [out[0].assume_init(), out[1].assume_init()] }, [185, 10]);
+/// ```
+#[inline]
+pub fn encode_to_uninit<T: VarIntTarget>(num: T, slice: &mut [MaybeUninit<u8>]) -> u8 {
+    if T::MAX_VARINT_BYTES <= 16 && slice.len() >= 16 {
+        return unsafe { encode_to_ptr_dispatch(num, slice.as_mut_ptr().cast()) };
+    }
+
+    let (data, size) = encode_wide(num);
+    let dest = &mut slice[..size as usize];
+    unsafe {
+        core::ptr::copy_nonoverlapping(data.as_ptr(), dest.as_mut_ptr().cast(), dest.len());
+    }
+
+    size
+}
+
+/// Encodes a single number to a varint at compile time. Produces the same output as [`encode`],
+/// but is much slower at runtime.
+///
+/// `u128` is rejected at compile time, as with [`encode`].
+///
+/// # Examples
+/// ```
+/// use varint_simd::encode_const;
+///
+/// const ENCODED: ([u8; 16], u8) = encode_const(1337u32);
+/// assert_eq!(ENCODED, ([185, 10, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0], 2));
+/// ```
+#[inline]
+pub const fn encode_const<T: VarIntTarget>(num: T) -> ([u8; 16], u8) {
+    num::assert_fits_vector::<T>();
+
+    let mut x = num::to_bits(num);
+    let mut data = [0u8; 16];
+    let mut len = 0;
+
+    while x >= 0x80 {
+        data[len] = x as u8 | 0x80;
+        x >>= 7;
+        len += 1;
+    }
+    data[len] = x as u8;
+
+    (data, len as u8 + 1)
+}
+
+/// Encodes a single signed integer in ZigZag format to a varint at compile time. Produces the same
+/// output as [`encode_zigzag`].
+///
+/// # Examples
+/// ```
+/// use varint_simd::encode_zigzag_const;
+///
+/// const ENCODED: ([u8; 16], u8) = encode_zigzag_const(-20i32);
+/// assert_eq!(ENCODED, ([39, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0], 1));
+/// ```
+#[inline]
+pub const fn encode_zigzag_const<T: SignedVarIntTarget>(num: T) -> ([u8; 16], u8) {
+    num::assert_fits_vector::<T::Unsigned>();
+
+    // Sign extending first gives the same result as zigzag encoding at the original width
+    let shift = 64 - 8 * core::mem::size_of::<T>() as u32;
+    let x = ((num::to_bits(num) as i64) << shift) >> shift;
+
+    encode_const(((x << 1) ^ (x >> 63)) as u64)
+}
+
+/// Returns the number of bytes needed to encode a number as a varint. Can be used in constants.
+///
+/// # Examples
+/// ```
+/// use varint_simd::encoded_len;
+///
+/// const TAG: [u8; encoded_len(300u32)] = [0xAC, 0x02];
+/// assert_eq!(encoded_len(0u64), 1);
+/// assert_eq!(encoded_len(u64::MAX), 10);
+/// ```
+#[inline]
+pub const fn encoded_len<T: VarIntTarget>(num: T) -> usize {
+    let bits = 128 - (num::to_bits(num) | 1).leading_zeros() as usize;
+    bits.div_ceil(7)
+}
+
+/// Sign extends a signed integer to 64 bits, reinterpreted as a `u64`. `i128` is rejected at
+/// compile time.
+#[inline(always)]
+pub(crate) fn sign_extend<T: SignedVarIntTarget>(num: T) -> u64 {
+    num::assert_sign_extends::<T>();
+
+    let shift = 128 - 8 * core::mem::size_of::<T>() as u32;
+    (((num::to_bits(num) << shift) as i128) >> shift) as u64
+}
+
+/// Encodes a single number to a varint. Uses SSE2 or NEON if available, and a scalar
+/// implementation otherwise.
 ///
 /// Produces a tuple, with the encoded data followed by the number of bytes used to encode the
 /// varint.
 ///
+/// `u128` is rejected at compile time, as with [`encode`].
+///
 /// # Safety
 /// This should not have any unsafe behavior with any input. However, it still calls a large number
 /// of unsafe functions.
 #[inline]
-#[cfg(any(target_feature = "sse2", doc))]
-#[cfg_attr(rustc_nightly, doc(cfg(target_feature = "sse2")))]
 pub unsafe fn encode_unsafe<T: VarIntTarget>(num: T) -> ([u8; 16], u8) {
+    num::assert_fits_vector::<T>();
+    encode_dispatch(num)
+}
+
+/// [`encode_unsafe`] without the width check, for callers that only reach it for types whose
+/// varints fit in 16 bytes
+#[inline(always)]
+pub(crate) unsafe fn encode_dispatch<T: VarIntTarget>(num: T) -> ([u8; 16], u8) {
+    #[cfg(target_arch = "x86_64")]
+    if cpu::has_fast_pdep() && cpu::has_lzcnt() {
+        return encode_pdep(num);
+    }
+
+    // The byte count is derived from a leading zero count, which is a single instruction with LZCNT
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    if !cfg!(target_feature = "lzcnt") && cpu::has_lzcnt() {
+        return encode_lzcnt(num);
+    }
+
+    encode_impl::<T, false>(num)
+}
+
+#[inline]
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "bmi2,lzcnt")]
+pub(crate) unsafe fn encode_pdep<T: VarIntTarget>(num: T) -> ([u8; 16], u8) {
+    encode_impl::<T, true>(num)
+}
+
+#[inline]
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[target_feature(enable = "lzcnt")]
+unsafe fn encode_lzcnt<T: VarIntTarget>(num: T) -> ([u8; 16], u8) {
+    encode_impl::<T, false>(num)
+}
+
+/// Encodes without SIMD or PDEP instructions, for [`Backend::Scalar`](crate::Backend::Scalar)
+#[inline]
+pub(crate) unsafe fn encode_scalar<T: VarIntTarget>(num: T) -> ([u8; 16], u8) {
+    encode_vector_scalar(num::unpack_portable(num))
+}
+
+#[inline(always)]
+pub(crate) unsafe fn encode_impl<T: VarIntTarget, const PDEP: bool>(num: T) -> ([u8; 16], u8) {
     if T::MAX_VARINT_BYTES <= 5 {
         // We could kick off a lzcnt here on the original number but that makes the math complicated and slow
 
-        let stage1 = num.num_to_scalar_stage1();
+        let stage1 = num::num_to_scalar_stage1::<T, PDEP>(num);
 
         // We could OR the data with 1 to avoid undefined behavior, but for some reason it's still faster to take the branch
         let leading = stage1.leading_zeros();
@@ -80,11 +397,7 @@ pub unsafe fn encode_unsafe<T: VarIntTarget>(num: T) -> ([u8; 16], u8) {
         let unused_bytes = (leading - 1) / 8;
         let bytes_needed = 8 - unused_bytes;
 
-        // set all but the last MSBs
-        let msbs = 0x8080808080808080;
-        let msbmask = 0xFFFFFFFFFFFFFFFF >> ((8 - bytes_needed + 1) * 8 - 1);
-
-        let merged = stage1 | (msbs & msbmask);
+        let merged = set_msbs_scalar(stage1, bytes_needed);
 
         (
             core::mem::transmute::<[u64; 2], [u8; 16]>([merged, 0]),
@@ -92,30 +405,312 @@ pub unsafe fn encode_unsafe<T: VarIntTarget>(num: T) -> ([u8; 16], u8) {
         )
     } else {
         // Break the number into 7-bit parts and spread them out into a vector
-        let stage1: __m128i = core::mem::transmute(num.num_to_vector_stage1());
+        #[cfg(all(target_feature = "sse2", not(miri)))]
+        {
+            encode_vector_sse2(num::num_to_vector_stage1::<T, PDEP>(num))
+        }
+        #[cfg(all(target_arch = "aarch64", target_feature = "neon", not(miri)))]
+        {
+            encode_vector_neon(num::num_to_vector_stage1::<T, PDEP>(num))
+        }
+        #[cfg(not(any(
+            all(target_feature = "sse2", not(miri)),
+            all(target_arch = "aarch64", target_feature = "neon", not(miri))
+        )))]
+        {
+            encode_vector_scalar(num::num_to_vector_stage1::<T, PDEP>(num))
+        }
+    }
+}
+
+/// Encodes a number whose varint may be longer than 16 bytes, split into 7-bit groups across two
+/// 16-byte vectors
+#[inline(always)]
+pub(crate) fn encode_wide_impl<T: VarIntTarget>(num: T) -> ([u8; 32], u8) {
+    encode_wide_stage1(num.num_to_wide_vector_stage1())
+}
 
-        // Create a mask for where there exist values
-        // This signed comparison works because all MSBs should be cleared at this point
-        // Also handle the special case when num == 0
-        let minimum = _mm_set_epi8(0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xffu8 as i8);
-        let exists = _mm_or_si128(_mm_cmpgt_epi8(stage1, _mm_setzero_si128()), minimum);
-        let bits = _mm_movemask_epi8(exists);
+/// [`encode_wide_impl`] without SIMD or PDEP instructions, for
+/// [`Backend::Scalar`](crate::Backend::Scalar)
+#[inline(always)]
+pub(crate) fn encode_wide_scalar<T: VarIntTarget>(num: T) -> ([u8; 32], u8) {
+    encode_wide_stage1(num::unpack_portable(num))
+}
+
+#[inline(always)]
+fn encode_wide_stage1(stage1: [u8; 32]) -> ([u8; 32], u8) {
+    let words: [u64; 4] = unsafe { core::mem::transmute(stage1) };
 
-        // Count the number of bytes used
-        let bytes = 32 - bits.leading_zeros() as u8; // lzcnt on supported CPUs
-                                                     // TODO: Compiler emits an unnecessary branch here when using bsr/bsl fallback
+    // Count the number of bytes used, special casing num == 0
+    let bytes = match words.iter().rposition(|word| *word != 0) {
+        Some(last) => 8 * last as u32 + (71 - words[last].leading_zeros()) / 8,
+        None => 1,
+    };
 
-        // Fill that many bytes into a vector
-        let ascend = _mm_setr_epi8(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
-        let mask = _mm_cmplt_epi8(ascend, _mm_set1_epi8(bytes as i8));
+    // Set the MSBs of all but the last byte
+    let mut merged = words;
+    for (i, word) in merged.iter_mut().enumerate() {
+        let msbs = (bytes - 1).saturating_sub(8 * i as u32).min(8);
+        if msbs > 0 {
+            *word |= 0x8080808080808080 >> (64 - 8 * msbs);
+        }
+    }
 
-        // Shift it down 1 byte so the last MSB is the only one set, and make sure only the MSB is set
-        let shift = _mm_bsrli_si128(mask, 1);
-        let msbmask = _mm_and_si128(shift, _mm_set1_epi8(128u8 as i8));
+    (
+        unsafe { core::mem::transmute::<[u64; 4], [u8; 32]>(merged) },
+        bytes as u8,
+    )
+}
 
-        // Merge the MSB bits into the vector
-        let merged = _mm_or_si128(stage1, msbmask);
+/// Encodes a single number to a varint padded to exactly `width` bytes, by setting the MSBs of
+/// every byte before the last one of the width rather than of the number.
+///
+/// # Safety
+/// `width` must be at least the encoded length of the number, and at most `T::MAX_VARINT_BYTES`.
+#[inline(always)]
+pub(crate) unsafe fn encode_padded_impl<T: VarIntTarget, const PDEP: bool>(
+    num: T,
+    width: u8,
+) -> [u8; 16] {
+    if T::MAX_VARINT_BYTES <= 5 {
+        let stage1 = num::num_to_scalar_stage1::<T, PDEP>(num);
+        let merged = set_msbs_scalar(stage1, width as u32);
+
+        core::mem::transmute::<[u64; 2], [u8; 16]>([merged, 0])
+    } else {
+        #[cfg(all(target_feature = "sse2", not(miri)))]
+        {
+            let stage1: __m128i = core::mem::transmute(num::num_to_vector_stage1::<T, PDEP>(num));
+            core::mem::transmute::<__m128i, [u8; 16]>(set_msbs_sse2(stage1, width))
+        }
+        #[cfg(all(target_arch = "aarch64", target_feature = "neon", not(miri)))]
+        {
+            let stage1 = vld1q_u8(num::num_to_vector_stage1::<T, PDEP>(num).as_ptr());
+            core::mem::transmute::<uint8x16_t, [u8; 16]>(set_msbs_neon(stage1, width))
+        }
+        #[cfg(not(any(
+            all(target_feature = "sse2", not(miri)),
+            all(target_arch = "aarch64", target_feature = "neon", not(miri))
+        )))]
+        {
+            set_msbs_vector_scalar(num::num_to_vector_stage1::<T, PDEP>(num), width as usize)
+        }
+    }
+}
+
+#[inline]
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "bmi2")]
+pub(crate) unsafe fn encode_padded_pdep<T: VarIntTarget>(num: T, width: u8) -> [u8; 16] {
+    encode_padded_impl::<T, true>(num, width)
+}
+
+/// Encodes without SIMD or PDEP instructions, for [`Backend::Scalar`](crate::Backend::Scalar)
+#[inline]
+pub(crate) unsafe fn encode_padded_scalar<T: VarIntTarget>(num: T, width: u8) -> [u8; 16] {
+    set_msbs_vector_scalar(num::unpack_portable(num), width as usize)
+}
+
+/// Sets the MSBs of the first `bytes - 1` bytes of a number split into 7-bit groups, for numbers
+/// of at most 8 bytes
+#[inline(always)]
+fn set_msbs_scalar(stage1: u64, bytes: u32) -> u64 {
+    // set all but the last MSBs
+    let msbs = 0x8080808080808080;
+    let msbmask = 0xFFFFFFFFFFFFFFFF >> ((8 - bytes + 1) * 8 - 1);
+
+    stage1 | (msbs & msbmask)
+}
+
+#[inline(always)]
+#[cfg(all(target_feature = "sse2", not(miri)))]
+unsafe fn encode_vector_sse2(stage1: [u8; 16]) -> ([u8; 16], u8) {
+    let stage1: __m128i = core::mem::transmute(stage1);
+
+    // Create a mask for where there exist values
+    // This signed comparison works because all MSBs should be cleared at this point
+    // Also handle the special case when num == 0
+    let minimum = _mm_set_epi8(0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xffu8 as i8);
+    let exists = _mm_or_si128(_mm_cmpgt_epi8(stage1, _mm_setzero_si128()), minimum);
+    let bits = _mm_movemask_epi8(exists);
+
+    // Count the number of bytes used
+    let bytes = 32 - bits.leading_zeros() as u8; // lzcnt on supported CPUs
+                                                 // TODO: Compiler emits an unnecessary branch here when using bsr/bsl fallback
+
+    let merged = set_msbs_sse2(stage1, bytes);
+
+    (core::mem::transmute::<__m128i, [u8; 16]>(merged), bytes)
+}
+
+/// Sets the MSBs of the first `bytes - 1` bytes of a number split into 7-bit groups
+#[inline(always)]
+#[cfg(all(target_feature = "sse2", not(miri)))]
+unsafe fn set_msbs_sse2(stage1: __m128i, bytes: u8) -> __m128i {
+    // Fill that many bytes into a vector
+    let ascend = _mm_setr_epi8(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
+    let mask = _mm_cmplt_epi8(ascend, _mm_set1_epi8(bytes as i8));
+
+    // Shift it down 1 byte so the last MSB is the only one set, and make sure only the MSB is set
+    let shift = _mm_bsrli_si128(mask, 1);
+    let msbmask = _mm_and_si128(shift, _mm_set1_epi8(128u8 as i8));
+
+    // Merge the MSB bits into the vector
+    _mm_or_si128(stage1, msbmask)
+}
+
+#[inline(always)]
+#[cfg(all(target_arch = "aarch64", target_feature = "neon", not(miri)))]
+unsafe fn encode_vector_neon(stage1: [u8; 16]) -> ([u8; 16], u8) {
+    let stage1 = vld1q_u8(stage1.as_ptr());
+
+    // Count the number of bytes used, by taking the highest (index + 1) of a nonzero byte
+    // Also handle the special case when num == 0
+    let asc_one = vld1q_u8([1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16].as_ptr());
+    let exists = vtstq_u8(stage1, stage1);
+    let bytes = vmaxvq_u8(vandq_u8(exists, asc_one)).max(1);
+
+    let merged = set_msbs_neon(stage1, bytes);
+
+    (core::mem::transmute::<uint8x16_t, [u8; 16]>(merged), bytes)
+}
+
+/// Sets the MSBs of the first `bytes - 1` bytes of a number split into 7-bit groups
+#[inline(always)]
+#[cfg(all(target_arch = "aarch64", target_feature = "neon", not(miri)))]
+unsafe fn set_msbs_neon(stage1: uint8x16_t, bytes: u8) -> uint8x16_t {
+    // Set the MSB of every byte before the last one
+    let asc_one = vld1q_u8([1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16].as_ptr());
+    let mask = vcltq_u8(asc_one, vdupq_n_u8(bytes));
+    let msbmask = vandq_u8(mask, vdupq_n_u8(0x80));
+
+    // Merge the MSB bits into the vector
+    vorrq_u8(stage1, msbmask)
+}
+
+#[inline(always)]
+pub(crate) fn encode_vector_scalar(stage1: [u8; 16]) -> ([u8; 16], u8) {
+    // Count the number of bytes used, special casing num == 0
+    let bytes = stage1
+        .iter()
+        .rposition(|b| *b != 0)
+        .map_or(1, |pos| pos + 1);
+
+    (set_msbs_vector_scalar(stage1, bytes), bytes as u8)
+}
+
+/// Sets the MSBs of the first `bytes - 1` bytes of a number split into 7-bit groups
+#[inline(always)]
+fn set_msbs_vector_scalar(stage1: [u8; 16], bytes: usize) -> [u8; 16] {
+    // Set the MSBs of all but the last byte
+    let mut merged = stage1;
+    for b in merged[..bytes - 1].iter_mut() {
+        *b |= 0x80;
+    }
+
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use core::mem::MaybeUninit;
+
+    use super::{encode, encode_sign_extended, encode_to_ptr, encode_to_uninit};
+    use crate::tests::{NUMS_U16, NUMS_U32, NUMS_U64, NUMS_U8};
+    use crate::{Backend, SignedVarIntTarget, VarIntTarget};
+
+    fn check_encode_to_ptr<T: VarIntTarget>(nums: &[T]) {
+        for num in nums {
+            let (expected, len) = encode(*num);
+
+            let mut out = [0xAAu8; 20];
+            assert_eq!(unsafe { encode_to_ptr(*num, out[1..].as_mut_ptr()) }, len);
+            assert_eq!(out[0], 0xAA);
+            assert_eq!(out[1..=len as usize], expected[..len as usize]);
+            assert_eq!(out[17..], [0xAA; 3]);
+
+            for backend in Backend::supported() {
+                let mut result = [0u8; 16];
+                assert_eq!(
+                    unsafe { backend.encode_to_ptr(*num, result.as_mut_ptr()) },
+                    len,
+                    "{:?}",
+                    backend
+                );
+                assert_eq!(result[..len as usize], expected[..len as usize]);
+            }
+        }
+    }
+
+    fn check_encode_to_uninit<T: VarIntTarget>(nums: &[T]) {
+        for num in nums {
+            let (expected, len) = encode(*num);
+
+            // Room for a single store, and exactly sized slices written through a copy
+            for size in [16, len as usize, len as usize + 1] {
+                let mut out = vec![MaybeUninit::<u8>::uninit(); size];
+                assert_eq!(encode_to_uninit(*num, &mut out), len);
+                let written: Vec<u8> = out[..len as usize]
+                    .iter()
+                    .map(|b| unsafe { b.assume_init() })
+                    .collect();
+                assert_eq!(written, expected[..len as usize]);
+
+                for backend in Backend::supported() {
+                    let mut out = vec![MaybeUninit::<u8>::uninit(); size];
+                    assert_eq!(backend.encode_to_uninit(*num, &mut out), len);
+                    let written: Vec<u8> = out[..len as usize]
+                        .iter()
+                        .map(|b| unsafe { b.assume_init() })
+                        .collect();
+                    assert_eq!(written, expected[..len as usize], "{:?}", backend);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_encode_to_ptr() {
+        check_encode_to_ptr(&NUMS_U8[..]);
+        check_encode_to_ptr(&NUMS_U16[..]);
+        check_encode_to_ptr(&NUMS_U32[..]);
+        check_encode_to_ptr(&NUMS_U64[..]);
+    }
+
+    #[test]
+    fn test_encode_to_uninit() {
+        check_encode_to_uninit(&NUMS_U8[..]);
+        check_encode_to_uninit(&NUMS_U16[..]);
+        check_encode_to_uninit(&NUMS_U32[..]);
+        check_encode_to_uninit(&NUMS_U64[..]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_encode_to_uninit_too_small() {
+        encode_to_uninit::<u32>(1337, &mut [MaybeUninit::uninit(); 1]);
+    }
+
+    fn check_encode_sign_extended<T: SignedVarIntTarget>(
+        nums: &[T::Unsigned],
+        widen: fn(T) -> i64,
+    ) {
+        for num in nums {
+            let num = num.unzigzag();
+            let expected = encode(widen(num) as u64);
+            assert_eq!(encode_sign_extended(num), expected);
+
+            for backend in Backend::supported() {
+                assert_eq!(backend.encode_sign_extended(num), expected, "{:?}", backend);
+            }
+        }
+    }
 
-        (core::mem::transmute::<__m128i, [u8; 16]>(merged), bytes)
+    #[test]
+    fn test_encode_sign_extended() {
+        check_encode_sign_extended::<i8>(&NUMS_U8[..], i64::from);
+        check_encode_sign_extended::<i16>(&NUMS_U16[..], i64::from);
+        check_encode_sign_extended::<i32>(&NUMS_U32[..], i64::from);
+        check_encode_sign_extended::<i64>(&NUMS_U64[..], i64::from);
     }
 }
@@ -0,0 +1,726 @@
+//! Encoding of a fixed number of values at once, the counterpart of the multi-value decoders.
+
+#[cfg(all(target_arch = "aarch64", target_feature = "neon", not(miri)))]
+use core::arch::aarch64::*;
+#[cfg(target_arch = "x86")]
+use core::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64::*;
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+use crate::cpu;
+
+#[cfg(any(
+    target_arch = "x86",
+    target_arch = "x86_64",
+    all(target_arch = "aarch64", target_feature = "neon", not(miri))
+))]
+use super::lookup;
+use super::{encode_impl, encode_scalar};
+use crate::decode::assert_fits_two;
+use crate::num::{self, VarIntTarget};
+
+/// Encodes two numbers to adjacent varints simultaneously. Target types must fit within 16 bytes
+/// when varint encoded. Uses SSSE3 or NEON if available, and a scalar implementation otherwise.
+///
+/// Produces a tuple containing the encoded data, the number of bytes used for each of the two
+/// varints, and the total number of bytes used. The bytes after the second varint are zero.
+///
+/// For best performance, ensure each target type is `u32` or smaller.
+///
+/// # Safety
+/// This should not have any unsafe behavior with any input. However, it still calls a large number
+/// of unsafe functions.
+///
+/// # Examples
+/// ```
+/// use varint_simd::encode_two_unsafe;
+///
+/// let (data, first_len, second_len, len) = unsafe { encode_two_unsafe::<u8, u32>(8, 1337) };
+/// assert_eq!(data[..len as usize], [8, 185, 10]);
+/// assert_eq!((first_len, second_len), (1, 2));
+/// ```
+#[inline]
+pub unsafe fn encode_two_unsafe<T: VarIntTarget, U: VarIntTarget>(
+    first: T,
+    second: U,
+) -> ([u8; 16], u8, u8, u8) {
+    assert_fits_two::<T, U>();
+
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        #[cfg(target_arch = "x86_64")]
+        if cpu::has_fast_pdep() && cpu::has_ssse3() && cpu::has_lzcnt() {
+            return encode_two_pdep(first, second);
+        }
+
+        if cpu::has_ssse3() {
+            encode_two_ssse3(first, second)
+        } else {
+            encode_two_scalar(first, second)
+        }
+    }
+    #[cfg(all(target_arch = "aarch64", target_feature = "neon", not(miri)))]
+    {
+        encode_two_neon(first, second)
+    }
+    #[cfg(not(any(
+        target_arch = "x86",
+        target_arch = "x86_64",
+        all(target_arch = "aarch64", target_feature = "neon", not(miri))
+    )))]
+    {
+        encode_two_scalar(first, second)
+    }
+}
+
+/// Encodes four numbers to adjacent varints simultaneously. Target types must be `u32` or
+/// smaller. Uses SSSE3 or NEON if available, and a scalar implementation otherwise.
+///
+/// Produces a tuple containing the encoded data, the number of bytes used for each of the four
+/// varints, and the total number of bytes used. The bytes after the fourth varint are zero.
+///
+/// For best performance, ensure each target type is `u16` or smaller, in which case the four
+/// varints are packed with a single shuffle.
+///
+/// # Safety
+/// This should not have any unsafe behavior with any input. However, it still calls a large number
+/// of unsafe functions.
+///
+/// # Examples
+/// ```
+/// use varint_simd::encode_four_unsafe;
+///
+/// let (data, _, _, _, fourth_len, len) =
+///     unsafe { encode_four_unsafe::<u16, u8, u8, u32>(1337, 1, 2, 300) };
+/// assert_eq!(data[..len as usize], [185, 10, 1, 2, 172, 2]);
+/// assert_eq!(fourth_len, 2);
+/// ```
+#[inline]
+pub unsafe fn encode_four_unsafe<
+    T: VarIntTarget,
+    U: VarIntTarget,
+    V: VarIntTarget,
+    W: VarIntTarget,
+>(
+    first: T,
+    second: U,
+    third: V,
+    fourth: W,
+) -> ([u8; 32], u8, u8, u8, u8, u8) {
+    assert_fits_encode_four::<T, U, V, W>();
+
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        #[cfg(target_arch = "x86_64")]
+        if cpu::has_fast_pdep() && cpu::has_ssse3() && cpu::has_lzcnt() {
+            return encode_four_pdep(first, second, third, fourth);
+        }
+
+        if cpu::has_ssse3() {
+            encode_four_ssse3(first, second, third, fourth)
+        } else {
+            encode_four_scalar(first, second, third, fourth)
+        }
+    }
+    #[cfg(all(target_arch = "aarch64", target_feature = "neon", not(miri)))]
+    {
+        encode_four_neon(first, second, third, fourth)
+    }
+    #[cfg(not(any(
+        target_arch = "x86",
+        target_arch = "x86_64",
+        all(target_arch = "aarch64", target_feature = "neon", not(miri))
+    )))]
+    {
+        encode_four_scalar(first, second, third, fourth)
+    }
+}
+
+/// Encodes eight u8's to adjacent varints simultaneously. Uses SSSE3 or NEON if available, and a
+/// scalar implementation otherwise.
+///
+/// Produces a tuple containing the encoded data, the number of bytes used for each varint, and
+/// the total number of bytes used. The bytes after the last varint are zero.
+///
+/// # Safety
+/// This should not have any unsafe behavior with any input. However, it still calls a large number
+/// of unsafe functions.
+///
+/// # Examples
+/// ```
+/// use varint_simd::encode_eight_u8_unsafe;
+///
+/// let (data, lens, len) = unsafe { encode_eight_u8_unsafe([1, 2, 3, 4, 5, 6, 7, 128]) };
+/// assert_eq!(data[..len as usize], [1, 2, 3, 4, 5, 6, 7, 128, 1]);
+/// assert_eq!(lens, [1, 1, 1, 1, 1, 1, 1, 2]);
+/// ```
+#[inline]
+pub unsafe fn encode_eight_u8_unsafe(nums: [u8; 8]) -> ([u8; 16], [u8; 8], u8) {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        if cpu::has_ssse3() {
+            encode_eight_u8_ssse3(nums)
+        } else {
+            encode_eight_u8_scalar(nums)
+        }
+    }
+    #[cfg(all(target_arch = "aarch64", target_feature = "neon", not(miri)))]
+    {
+        encode_eight_u8_neon(nums)
+    }
+    #[cfg(not(any(
+        target_arch = "x86",
+        target_arch = "x86_64",
+        all(target_arch = "aarch64", target_feature = "neon", not(miri))
+    )))]
+    {
+        encode_eight_u8_scalar(nums)
+    }
+}
+
+#[inline]
+pub(crate) unsafe fn encode_two_scalar<T: VarIntTarget, U: VarIntTarget>(
+    first: T,
+    second: U,
+) -> ([u8; 16], u8, u8, u8) {
+    encode_two_impl::<T, U, false, false>(first, second)
+}
+
+#[inline]
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[target_feature(enable = "ssse3")]
+pub(crate) unsafe fn encode_two_ssse3<T: VarIntTarget, U: VarIntTarget>(
+    first: T,
+    second: U,
+) -> ([u8; 16], u8, u8, u8) {
+    encode_two_impl::<T, U, true, false>(first, second)
+}
+
+#[inline]
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "ssse3,bmi2,lzcnt")]
+pub(crate) unsafe fn encode_two_pdep<T: VarIntTarget, U: VarIntTarget>(
+    first: T,
+    second: U,
+) -> ([u8; 16], u8, u8, u8) {
+    encode_two_impl::<T, U, true, true>(first, second)
+}
+
+#[inline]
+#[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+pub(crate) unsafe fn encode_two_neon<T: VarIntTarget, U: VarIntTarget>(
+    first: T,
+    second: U,
+) -> ([u8; 16], u8, u8, u8) {
+    encode_two_impl::<T, U, true, false>(first, second)
+}
+
+/// Encodes two values with the second varint directly following the first. With `SHUFFLE`, values
+/// of at most five bytes are spread into the two halves of a vector, and packed with one shuffle.
+#[inline(always)]
+pub(crate) unsafe fn encode_two_impl<
+    T: VarIntTarget,
+    U: VarIntTarget,
+    const SHUFFLE: bool,
+    const PDEP: bool,
+>(
+    first: T,
+    second: U,
+) -> ([u8; 16], u8, u8, u8) {
+    if !SHUFFLE || T::MAX_VARINT_BYTES > 5 || U::MAX_VARINT_BYTES > 5 {
+        // Without a shuffle, or if at least one of the values takes up a whole vector, combine
+        // them one after the other
+        let (first, first_len) = encode_one::<T, SHUFFLE, PDEP>(first);
+        let (second, second_len) = encode_one::<U, SHUFFLE, PDEP>(second);
+
+        // The bytes after each varint are zero, and the total length is at most 16
+        let mut data = [0u8; 32];
+        data[..16].copy_from_slice(&first);
+        data[first_len as usize..first_len as usize + 16].copy_from_slice(&second);
+
+        let mut packed = [0u8; 16];
+        packed.copy_from_slice(&data[..16]);
+        return (packed, first_len, second_len, first_len + second_len);
+    }
+
+    let (first, first_len) = scalar_with_msbs::<T, PDEP>(first);
+    let (second, second_len) = scalar_with_msbs::<U, PDEP>(second);
+    let len = first_len + second_len;
+
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    if SHUFFLE {
+        let shuf = *lookup::LOOKUP_DOUBLE_ENCODE
+            .get_unchecked(((first_len - 1) * 5 + second_len - 1) as usize);
+        let packed = _mm_shuffle_epi8(_mm_set_epi64x(second as i64, first as i64), shuf);
+
+        return (
+            core::mem::transmute::<__m128i, [u8; 16]>(packed),
+            first_len,
+            second_len,
+            len,
+        );
+    }
+
+    #[cfg(all(target_arch = "aarch64", target_feature = "neon", not(miri)))]
+    if SHUFFLE {
+        let shuf = *lookup::LOOKUP_DOUBLE_ENCODE
+            .get_unchecked(((first_len - 1) * 5 + second_len - 1) as usize);
+        let data = vcombine_u8(vcreate_u8(first), vcreate_u8(second));
+        let packed = vqtbl1q_u8(data, shuf);
+
+        return (
+            core::mem::transmute::<uint8x16_t, [u8; 16]>(packed),
+            first_len,
+            second_len,
+            len,
+        );
+    }
+
+    let packed = first as u128 | (second as u128) << (first_len * 8);
+    (packed.to_le_bytes(), first_len, second_len, len)
+}
+
+#[inline]
+pub(crate) unsafe fn encode_four_scalar<
+    T: VarIntTarget,
+    U: VarIntTarget,
+    V: VarIntTarget,
+    W: VarIntTarget,
+>(
+    first: T,
+    second: U,
+    third: V,
+    fourth: W,
+) -> ([u8; 32], u8, u8, u8, u8, u8) {
+    encode_four_impl::<T, U, V, W, false, false>(first, second, third, fourth)
+}
+
+#[inline]
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[target_feature(enable = "ssse3")]
+pub(crate) unsafe fn encode_four_ssse3<
+    T: VarIntTarget,
+    U: VarIntTarget,
+    V: VarIntTarget,
+    W: VarIntTarget,
+>(
+    first: T,
+    second: U,
+    third: V,
+    fourth: W,
+) -> ([u8; 32], u8, u8, u8, u8, u8) {
+    encode_four_impl::<T, U, V, W, true, false>(first, second, third, fourth)
+}
+
+#[inline]
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "ssse3,bmi2,lzcnt")]
+pub(crate) unsafe fn encode_four_pdep<
+    T: VarIntTarget,
+    U: VarIntTarget,
+    V: VarIntTarget,
+    W: VarIntTarget,
+>(
+    first: T,
+    second: U,
+    third: V,
+    fourth: W,
+) -> ([u8; 32], u8, u8, u8, u8, u8) {
+    encode_four_impl::<T, U, V, W, true, true>(first, second, third, fourth)
+}
+
+#[inline]
+#[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+pub(crate) unsafe fn encode_four_neon<
+    T: VarIntTarget,
+    U: VarIntTarget,
+    V: VarIntTarget,
+    W: VarIntTarget,
+>(
+    first: T,
+    second: U,
+    third: V,
+    fourth: W,
+) -> ([u8; 32], u8, u8, u8, u8, u8) {
+    encode_four_impl::<T, U, V, W, true, false>(first, second, third, fourth)
+}
+
+/// Encodes four values to adjacent varints. With `SHUFFLE`, values of at most three bytes are
+/// spread into the four 32-bit lanes of a vector, and packed with one shuffle. Otherwise, the
+/// values are encoded as two pairs.
+#[inline(always)]
+unsafe fn encode_four_impl<
+    T: VarIntTarget,
+    U: VarIntTarget,
+    V: VarIntTarget,
+    W: VarIntTarget,
+    const SHUFFLE: bool,
+    const PDEP: bool,
+>(
+    first: T,
+    second: U,
+    third: V,
+    fourth: W,
+) -> ([u8; 32], u8, u8, u8, u8, u8) {
+    #[cfg(any(
+        target_arch = "x86",
+        target_arch = "x86_64",
+        all(target_arch = "aarch64", target_feature = "neon", not(miri))
+    ))]
+    if SHUFFLE
+        && T::MAX_VARINT_BYTES <= 3
+        && U::MAX_VARINT_BYTES <= 3
+        && V::MAX_VARINT_BYTES <= 3
+        && W::MAX_VARINT_BYTES <= 3
+    {
+        let (first, first_len) = scalar_with_msbs::<T, PDEP>(first);
+        let (second, second_len) = scalar_with_msbs::<U, PDEP>(second);
+        let (third, third_len) = scalar_with_msbs::<V, PDEP>(third);
+        let (fourth, fourth_len) = scalar_with_msbs::<W, PDEP>(fourth);
+
+        let index =
+            (first_len - 1) * 27 + (second_len - 1) * 9 + (third_len - 1) * 3 + fourth_len - 1;
+        let shuf = *lookup::LOOKUP_QUAD_ENCODE.get_unchecked(index as usize);
+
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        let packed: [u8; 16] = {
+            let data = _mm_setr_epi32(first as i32, second as i32, third as i32, fourth as i32);
+            core::mem::transmute(_mm_shuffle_epi8(data, shuf))
+        };
+        #[cfg(all(target_arch = "aarch64", target_feature = "neon", not(miri)))]
+        let packed: [u8; 16] = {
+            let data = vcombine_u8(
+                vcreate_u8(first | second << 32),
+                vcreate_u8(third | fourth << 32),
+            );
+            core::mem::transmute(vqtbl1q_u8(data, shuf))
+        };
+
+        let mut data = [0u8; 32];
+        data[..16].copy_from_slice(&packed);
+        return (
+            data,
+            first_len,
+            second_len,
+            third_len,
+            fourth_len,
+            first_len + second_len + third_len + fourth_len,
+        );
+    }
+
+    let (low, first_len, second_len, low_len) =
+        encode_two_impl::<T, U, SHUFFLE, PDEP>(first, second);
+    let (high, third_len, fourth_len, high_len) =
+        encode_two_impl::<V, W, SHUFFLE, PDEP>(third, fourth);
+
+    // The first pair takes up at most 10 bytes, and the bytes after it are zero
+    let mut data = [0u8; 32];
+    data[..16].copy_from_slice(&low);
+    data[low_len as usize..low_len as usize + 16].copy_from_slice(&high);
+
+    (
+        data,
+        first_len,
+        second_len,
+        third_len,
+        fourth_len,
+        low_len + high_len,
+    )
+}
+
+#[inline]
+pub(crate) unsafe fn encode_eight_u8_scalar(nums: [u8; 8]) -> ([u8; 16], [u8; 8], u8) {
+    let mut data = [0u8; 16];
+    let mut lens = [0u8; 8];
+    let mut len = 0;
+
+    for (num, num_len) in nums.iter().zip(lens.iter_mut()) {
+        data[len] = *num;
+        if *num >= 0x80 {
+            data[len + 1] = 1;
+        }
+
+        *num_len = 1 + (*num >> 7);
+        len += *num_len as usize;
+    }
+
+    (data, lens, len as u8)
+}
+
+#[inline]
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[target_feature(enable = "ssse3")]
+pub(crate) unsafe fn encode_eight_u8_ssse3(nums: [u8; 8]) -> ([u8; 16], [u8; 8], u8) {
+    let nums = _mm_loadl_epi64(nums.as_ptr() as *const __m128i);
+
+    // A number with its MSB set is already the first byte of its varint, and the second byte is 1
+    let large = _mm_movemask_epi8(nums) as u32;
+    let second = _mm_and_si128(_mm_srli_epi16(nums, 7), _mm_set1_epi8(1));
+    let lanes = _mm_unpacklo_epi8(nums, second);
+
+    let shuf = *lookup::LOOKUP_EIGHT_U8_ENCODE.get_unchecked(large as usize);
+    let packed = _mm_shuffle_epi8(lanes, shuf);
+
+    let lens: [u64; 2] = core::mem::transmute(_mm_add_epi8(second, _mm_set1_epi8(1)));
+
+    (
+        core::mem::transmute::<__m128i, [u8; 16]>(packed),
+        lens[0].to_ne_bytes(),
+        8 + large.count_ones() as u8,
+    )
+}
+
+#[inline]
+#[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+pub(crate) unsafe fn encode_eight_u8_neon(nums: [u8; 8]) -> ([u8; 16], [u8; 8], u8) {
+    #[cfg(not(miri))]
+    {
+        let nums = vld1_u8(nums.as_ptr());
+
+        // A number with its MSB set is already the first byte of its varint, and the second byte
+        // is 1
+        let second = vshr_n_u8::<7>(nums);
+        let lanes = vcombine_u8(vzip1_u8(nums, second), vzip2_u8(nums, second));
+
+        let bits = vld1_u8([1, 2, 4, 8, 16, 32, 64, 128].as_ptr());
+        let large = vaddv_u8(vmul_u8(second, bits));
+
+        let shuf = *lookup::LOOKUP_EIGHT_U8_ENCODE.get_unchecked(large as usize);
+        let packed = vqtbl1q_u8(lanes, shuf);
+
+        let lens = vadd_u8(second, vdup_n_u8(1));
+
+        (
+            core::mem::transmute::<uint8x16_t, [u8; 16]>(packed),
+            core::mem::transmute::<uint8x8_t, [u8; 8]>(lens),
+            8 + large.count_ones() as u8,
+        )
+    }
+    #[cfg(miri)]
+    {
+        encode_eight_u8_scalar(nums)
+    }
+}
+
+/// Encodes a single value, with the SIMD implementation if `SHUFFLE` is set
+#[inline(always)]
+pub(super) unsafe fn encode_one<T: VarIntTarget, const SHUFFLE: bool, const PDEP: bool>(
+    num: T,
+) -> ([u8; 16], u8) {
+    if SHUFFLE {
+        encode_impl::<T, PDEP>(num)
+    } else {
+        encode_scalar(num)
+    }
+}
+
+/// Same as the scalar path of [`encode_impl`], producing the encoded data as an integer
+#[inline(always)]
+pub(super) unsafe fn scalar_with_msbs<T: VarIntTarget, const PDEP: bool>(num: T) -> (u64, u8) {
+    let stage1 = num::num_to_scalar_stage1::<T, PDEP>(num);
+
+    let leading = stage1.leading_zeros();
+    let bytes_needed = 8 - (leading - 1) / 8;
+
+    // set all but the last MSBs
+    let msbmask = 0xFFFFFFFFFFFFFFFF >> ((8 - bytes_needed + 1) * 8 - 1);
+    (stage1 | (0x8080808080808080 & msbmask), bytes_needed as u8)
+}
+
+#[inline(always)]
+pub(crate) fn assert_fits_encode_four<
+    T: VarIntTarget,
+    U: VarIntTarget,
+    V: VarIntTarget,
+    W: VarIntTarget,
+>() {
+    if T::MAX_VARINT_BYTES > 5
+        || U::MAX_VARINT_BYTES > 5
+        || V::MAX_VARINT_BYTES > 5
+        || W::MAX_VARINT_BYTES > 5
+    {
+        // check will be eliminated at compile time
+        panic!(
+            "exceeded length limit: cannot encode {}, {}, {} and {}, each type must be u32 or smaller",
+            core::any::type_name::<T>(),
+            core::any::type_name::<U>(),
+            core::any::type_name::<V>(),
+            core::any::type_name::<W>()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{encode_eight_u8_unsafe, encode_four_unsafe, encode_two_unsafe};
+    use crate::tests::{NUMS_U16, NUMS_U32, NUMS_U64, NUMS_U8};
+    use crate::{encode, Backend, VarIntTarget};
+
+    fn encode_vec<T: VarIntTarget>(num: T, out: &mut Vec<u8>) -> u8 {
+        let (bytes, len) = encode(num);
+        out.extend_from_slice(&bytes[..len as usize]);
+        len
+    }
+
+    fn check_encode_2x<T: VarIntTarget, U: VarIntTarget>(a: &[T], b: &[U]) {
+        for i in a {
+            for j in b {
+                let mut expected = Vec::new();
+                let first_len = encode_vec(*i, &mut expected);
+                let second_len = encode_vec(*j, &mut expected);
+                let len = expected.len();
+
+                // The bytes after the second varint are zero
+                expected.resize(16, 0);
+
+                let result = unsafe { encode_two_unsafe(*i, *j) };
+                assert_eq!(
+                    result,
+                    (
+                        expected[..].try_into().unwrap(),
+                        first_len,
+                        second_len,
+                        len as u8
+                    )
+                );
+
+                for backend in Backend::supported() {
+                    assert_eq!(
+                        unsafe { backend.encode_two_unsafe(*i, *j) },
+                        result,
+                        "{:?}",
+                        backend
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_encode_2x() {
+        check_encode_2x::<u8, u8>(&NUMS_U8[..], &NUMS_U8[..]);
+        check_encode_2x::<u8, u64>(&NUMS_U8[..], &NUMS_U64[..]);
+        check_encode_2x::<u16, u32>(&NUMS_U16[..], &NUMS_U32[..]);
+        check_encode_2x::<u32, u32>(&NUMS_U32[..], &NUMS_U32[..]);
+        check_encode_2x::<u64, u16>(&NUMS_U64[..], &NUMS_U16[..]);
+        check_encode_2x::<u64, u32>(&NUMS_U64[..], &NUMS_U32[..]);
+    }
+
+    fn check_encode_4x<T: VarIntTarget, U: VarIntTarget, V: VarIntTarget, W: VarIntTarget>(
+        a: &[T],
+        b: &[U],
+        c: &[V],
+        d: &[W],
+    ) {
+        for i in a {
+            for j in b {
+                for k in c {
+                    for l in d {
+                        let mut expected = Vec::new();
+                        let lens = [
+                            encode_vec(*i, &mut expected),
+                            encode_vec(*j, &mut expected),
+                            encode_vec(*k, &mut expected),
+                            encode_vec(*l, &mut expected),
+                        ];
+                        let len = expected.len() as u8;
+                        expected.resize(32, 0);
+
+                        let result = unsafe { encode_four_unsafe(*i, *j, *k, *l) };
+                        assert_eq!(
+                            result,
+                            (
+                                expected[..].try_into().unwrap(),
+                                lens[0],
+                                lens[1],
+                                lens[2],
+                                lens[3],
+                                len
+                            )
+                        );
+
+                        for backend in Backend::supported() {
+                            assert_eq!(
+                                unsafe { backend.encode_four_unsafe(*i, *j, *k, *l) },
+                                result,
+                                "{:?}",
+                                backend
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // too slow
+    fn test_encode_4x() {
+        check_encode_4x::<u8, u8, u8, u8>(&NUMS_U8[..], &NUMS_U8[..], &NUMS_U8[..], &NUMS_U8[..]);
+        check_encode_4x::<u16, u16, u16, u16>(
+            &NUMS_U16[..],
+            &NUMS_U16[..],
+            &NUMS_U16[..],
+            &NUMS_U16[..],
+        );
+        check_encode_4x::<u32, u8, u32, u16>(
+            &NUMS_U32[..],
+            &NUMS_U8[..],
+            &NUMS_U32[..],
+            &NUMS_U16[..],
+        );
+        check_encode_4x::<u32, u32, u32, u32>(
+            &NUMS_U32[..],
+            &NUMS_U32[..],
+            &NUMS_U32[..],
+            &NUMS_U32[..],
+        );
+    }
+
+    #[test]
+    fn test_encode_4x_window_edges() {
+        // Varints taking up 15, 16 and 17 bytes in total, so that the last one ends just before,
+        // at and just after the end of a 16-byte vector
+        for last in [1u32, 300, 1 << 14] {
+            check_encode_4x::<u32, u32, u32, u32>(&[u32::MAX], &[u32::MAX], &[1 << 21], &[last]);
+        }
+    }
+
+    #[test]
+    fn test_encode_8x_u8() {
+        let nums = &NUMS_U8[..];
+        for i in 0..nums.len() {
+            for j in 1..nums.len() {
+                let values: [u8; 8] = core::array::from_fn(|k| nums[(i + j * k) % nums.len()]);
+
+                let mut expected = Vec::new();
+                let lens: Vec<u8> = values
+                    .iter()
+                    .map(|v| encode_vec(*v, &mut expected))
+                    .collect();
+                let len = expected.len() as u8;
+                expected.resize(16, 0);
+
+                let result = unsafe { encode_eight_u8_unsafe(values) };
+                assert_eq!(
+                    result,
+                    (
+                        expected[..].try_into().unwrap(),
+                        lens[..].try_into().unwrap(),
+                        len
+                    )
+                );
+
+                for backend in Backend::supported() {
+                    assert_eq!(
+                        unsafe { backend.encode_eight_u8_unsafe(values) },
+                        result,
+                        "{:?}",
+                        backend
+                    );
+                }
+            }
+        }
+    }
+}
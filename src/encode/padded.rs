@@ -0,0 +1,293 @@
+//! Varints padded to a fixed width with redundant continuation bytes, so that space can be reserved
+//! for a value, such as the length prefix of a nested message, before the value is known.
+
+#[cfg(target_arch = "x86_64")]
+use super::encode_padded_pdep;
+use super::{encode_padded_impl, encoded_len};
+#[cfg(target_arch = "x86_64")]
+use crate::cpu;
+use crate::num::{self, VarIntTarget};
+use core::marker::PhantomData;
+
+/// Encodes a single number to a varint padded to exactly `width` bytes. Every byte before the last
+/// one has its continuation bit set, even where the remaining bits are zero, so the result is a
+/// non-canonical encoding that is still accepted by [`decode`](crate::decode()).
+///
+/// Produces the encoded data. Only the first `width` bytes are significant, and the rest are zero.
+///
+/// **Panics:** if `width` is smaller than the encoded length of the number, or larger than the
+/// maximum encoded length of the target type. `u128` is rejected at compile time, as its varints
+/// may not fit in 16 bytes.
+///
+/// # Examples
+/// ```
+/// use varint_simd::{decode, encode_padded};
+///
+/// let encoded = encode_padded::<u32>(5, 5);
+/// assert_eq!(encoded[..5], [0x85, 0x80, 0x80, 0x80, 0x00]);
+/// assert_eq!(decode::<u32>(&encoded).unwrap(), (5, 5));
+/// ```
+#[inline]
+pub fn encode_padded<T: VarIntTarget>(num: T, width: u8) -> [u8; 16] {
+    assert_padded_width::<T>(num, width);
+    unsafe { encode_padded_unsafe(num, width) }
+}
+
+/// Encodes a single number to a varint padded to exactly `width` bytes, and writes the resulting
+/// data to the slice. See also: [`encode_padded`]
+///
+/// **Panics:** if `width` is out of range for the number, or if the slice is shorter than `width`.
+#[inline]
+pub fn encode_padded_to_slice<T: VarIntTarget>(num: T, width: u8, slice: &mut [u8]) {
+    let data = encode_padded(num, width);
+    slice[..width as usize].copy_from_slice(&data[..width as usize]);
+}
+
+/// Encodes a single number to a varint padded to exactly `width` bytes, without checking the
+/// width. See also: [`encode_padded`]
+///
+/// # Safety
+/// `width` must be at least the encoded length of the number, and at most the maximum encoded
+/// length of the target type.
+#[inline]
+pub unsafe fn encode_padded_unsafe<T: VarIntTarget>(num: T, width: u8) -> [u8; 16] {
+    num::assert_fits_vector::<T>();
+
+    #[cfg(target_arch = "x86_64")]
+    if cpu::has_fast_pdep() {
+        return encode_padded_pdep(num, width);
+    }
+
+    encode_padded_impl::<T, false>(num, width)
+}
+
+#[inline(always)]
+pub(crate) fn assert_padded_width<T: VarIntTarget>(num: T, width: u8) {
+    num::assert_fits_vector::<T>();
+    assert!(
+        width <= T::MAX_VARINT_BYTES,
+        "padded width exceeds the maximum varint length of the target type"
+    );
+    assert!(
+        encoded_len(num) <= width as usize,
+        "number does not fit in the padded width"
+    );
+}
+
+/// A placeholder for a varint of a fixed width, reserved in an output buffer so that its value can
+/// be filled in later. This is typically used for the length prefix of a nested message, which is
+/// only known once the body of the message has been written after it.
+///
+/// The final value is written with [`encode_padded`], so it takes up the full reserved width
+/// regardless of its size.
+///
+/// # Examples
+/// ```
+/// use varint_simd::{decode, Backpatch, VarIntVecExt};
+///
+/// let mut out = Vec::new();
+/// let prefix = Backpatch::<u32>::reserve(&mut out);
+/// out.push_varint::<u32>(1337);
+/// out.push_varint::<u32>(1);
+/// prefix.patch_len(&mut out);
+///
+/// assert_eq!(out, [0x83, 0x80, 0x80, 0x80, 0x00, 185, 10, 1]);
+/// assert_eq!(decode::<u32>(&out).unwrap(), (3, 5));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Backpatch<T: VarIntTarget> {
+    offset: usize,
+    width: u8,
+    phantom: PhantomData<T>,
+}
+
+impl<T: VarIntTarget> Backpatch<T> {
+    /// Creates a handle for a varint of `width` bytes that starts at `offset` in the output buffer.
+    /// The bytes are not reserved or written.
+    ///
+    /// **Panics:** if `width` is zero, or larger than the maximum encoded length of the target
+    /// type. `u128` is rejected at compile time.
+    #[inline]
+    pub fn new(offset: usize, width: u8) -> Self {
+        num::assert_fits_vector::<T>();
+        assert!(
+            (1..=T::MAX_VARINT_BYTES).contains(&width),
+            "padded width out of range for the target type"
+        );
+
+        Self {
+            offset,
+            width,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Appends a padded zero of the maximum encoded length of the target type to the vector, and
+    /// returns a handle for patching it. Any value of the type can be written to it later.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn reserve(out: &mut Vec<u8>) -> Self {
+        Self::reserve_width(out, T::MAX_VARINT_BYTES)
+    }
+
+    /// Appends a padded zero of `width` bytes to the vector, and returns a handle for patching it.
+    /// Only values with an encoded length of at most `width` can be written to it later.
+    ///
+    /// **Panics:** if `width` is zero, or larger than the maximum encoded length of the target
+    /// type.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn reserve_width(out: &mut Vec<u8>, width: u8) -> Self {
+        let patch = Self::new(out.len(), width);
+
+        let data = unsafe { encode_padded_unsafe(T::cast_u32(0), width) };
+        out.extend_from_slice(&data[..width as usize]);
+
+        patch
+    }
+
+    /// The position of the first reserved byte in the output buffer.
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The number of reserved bytes.
+    #[inline]
+    pub fn width(&self) -> u8 {
+        self.width
+    }
+
+    /// Writes the padded encoding of `num` over the reserved bytes.
+    ///
+    /// **Panics:** if the number does not fit in the reserved width, or if the reserved bytes are
+    /// out of bounds of the buffer.
+    #[inline]
+    pub fn patch(self, out: &mut [u8], num: T) {
+        encode_padded_to_slice(num, self.width, &mut out[self.offset..]);
+    }
+
+    /// Writes the number of bytes in the buffer after the reserved bytes over the reserved bytes,
+    /// for filling in the length prefix of a nested message once its body is complete.
+    ///
+    /// **Panics:** if the length does not fit in the target type or the reserved width, or if the
+    /// reserved bytes are out of bounds of the buffer.
+    #[inline]
+    pub fn patch_len(self, out: &mut [u8]) {
+        let len = out.len() - (self.offset + self.width as usize);
+        assert!(
+            len as u64 <= num::width_mask::<T>(),
+            "length does not fit in the target type"
+        );
+
+        self.patch(out, T::cast_u64(len as u64));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{encode_padded, encode_padded_to_slice, Backpatch};
+    use crate::tests::{NUMS_U16, NUMS_U32, NUMS_U64, NUMS_U8};
+    use crate::{decode, encoded_len, Backend, VarIntTarget, VarIntVecExt};
+
+    fn check_encode_padded<T: VarIntTarget>(nums: &[T]) {
+        for num in nums {
+            for width in encoded_len(*num) as u8..=T::MAX_VARINT_BYTES {
+                let encoded = encode_padded(*num, width);
+                let width = width as usize;
+
+                // Continuation bits on every byte but the last, and zeros after the width
+                assert!(encoded[..width - 1].iter().all(|b| b & 0x80 != 0));
+                assert_eq!(encoded[width - 1] & 0x80, 0);
+                assert!(encoded[width..].iter().all(|b| *b == 0));
+                assert_eq!(decode::<T>(&encoded[..width]), Ok((*num, width)));
+
+                let mut out = [0xAA; 17];
+                encode_padded_to_slice(*num, width as u8, &mut out[1..]);
+                assert_eq!(out[0], 0xAA);
+                assert_eq!(out[1..=width], encoded[..width]);
+                assert!(out[width + 1..].iter().all(|b| *b == 0xAA));
+
+                for backend in Backend::supported() {
+                    assert_eq!(
+                        backend.encode_padded(*num, width as u8),
+                        encoded,
+                        "{:?}",
+                        backend
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_encode_padded() {
+        check_encode_padded(&NUMS_U8[..]);
+        check_encode_padded(&NUMS_U16[..]);
+        check_encode_padded(&NUMS_U32[..]);
+        check_encode_padded(&NUMS_U64[..]);
+    }
+
+    #[test]
+    #[should_panic(expected = "does not fit")]
+    fn test_encode_padded_too_narrow() {
+        encode_padded::<u32>(1337, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "exceeds the maximum")]
+    fn test_encode_padded_too_wide() {
+        encode_padded::<u16>(1, 4);
+    }
+
+    #[test]
+    fn test_backpatch() {
+        let mut out = vec![8];
+        let prefix = Backpatch::<u64>::reserve(&mut out);
+        assert_eq!((prefix.offset(), prefix.width()), (1, 10));
+        assert_eq!(decode::<u64>(&out[1..]), Ok((0, 10)));
+
+        let value = Backpatch::<u16>::reserve_width(&mut out, 2);
+        assert_eq!((value.offset(), value.width()), (11, 2));
+        let mut body = Vec::new();
+        for i in 0..200u32 {
+            body.push_varint(i);
+        }
+        out.extend_from_slice(&body);
+
+        prefix.patch_len(&mut out);
+        value.patch(&mut out, 300);
+        assert_eq!(out[0], 8);
+        assert_eq!(decode::<u64>(&out[1..]), Ok((body.len() as u64 + 2, 10)));
+        assert_eq!(decode::<u16>(&out[11..]), Ok((300, 2)));
+        assert_eq!(out[13..], body);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn test_backpatch_zero_width() {
+        Backpatch::<u32>::new(0, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "does not fit in the padded width")]
+    fn test_backpatch_value_too_large() {
+        let mut out = Vec::new();
+        Backpatch::<u32>::reserve_width(&mut out, 1).patch(&mut out, 128);
+    }
+
+    #[test]
+    #[should_panic(expected = "does not fit in the target type")]
+    fn test_backpatch_len_too_large() {
+        let mut out = Vec::new();
+        let prefix = Backpatch::<u8>::reserve(&mut out);
+        out.resize(out.len() + 256, 0);
+        prefix.patch_len(&mut out);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_backpatch_out_of_bounds() {
+        Backpatch::<u32>::new(4, 5).patch(&mut [0; 8], 1);
+    }
+}
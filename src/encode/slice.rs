@@ -0,0 +1,290 @@
+//! Encoding of whole sequences of values, several values per step where possible.
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+use crate::cpu;
+
+use super::encode_to_slice;
+use super::multi::{encode_one, encode_two_impl};
+#[cfg(feature = "std")]
+use super::VarIntVecExt;
+use crate::num::VarIntTarget;
+
+/// Encodes a sequence of values as adjacent varints into the output slice. Returns the number of
+/// bytes written.
+///
+/// While at least 16 bytes of output remain, `u8`, `u16` and `u32` values are encoded two at a
+/// time with [`encode_two_unsafe`]: both are split into 7-bit groups in a single vector, which is
+/// then compressed so that the second varint directly follows the first. `u64` values are encoded
+/// one at a time. The last values, and all `u128` values, are written with [`encode_to_slice`].
+///
+/// **Panics:** if the slice is too small to contain the varints. The values that fit have already
+/// been written.
+///
+/// # Examples
+/// ```
+/// use varint_simd::encode_slice;
+///
+/// let mut out = [0u8; 8];
+/// assert_eq!(encode_slice::<u32>(&[1337, 1, 300], &mut out), 5);
+/// assert_eq!(out[..5], [185, 10, 1, 172, 2]);
+/// ```
+///
+/// [`encode_two_unsafe`]: crate::encode_two_unsafe
+#[inline]
+pub fn encode_slice<T: VarIntTarget>(values: &[T], out: &mut [u8]) -> usize {
+    unsafe { encode_slice_with(values, out, encode_batch) }
+}
+
+/// Runs `batch` over as much of the output as it can fill, and encodes the remaining values one at
+/// a time
+///
+/// # Safety
+/// `batch` must have the same contract as [`encode_batch`].
+#[inline(always)]
+pub(crate) unsafe fn encode_slice_with<T: VarIntTarget>(
+    values: &[T],
+    out: &mut [u8],
+    batch: unsafe fn(&[T], *mut u8, usize) -> (usize, usize),
+) -> usize {
+    let (count, mut written) = batch(values, out.as_mut_ptr(), out.len());
+
+    for value in &values[count..] {
+        written += encode_to_slice(*value, &mut out[written..]) as usize;
+    }
+
+    written
+}
+
+/// Encodes a sequence of values as adjacent varints, and appends them to the vector. Returns the
+/// number of bytes appended. See also: [`encode_slice`]
+///
+/// # Examples
+/// ```
+/// use varint_simd::encode_slice_to_vec;
+///
+/// let mut out = vec![8];
+/// assert_eq!(encode_slice_to_vec::<u64>(&[1337, 1, 300], &mut out), 5);
+/// assert_eq!(out, [8, 185, 10, 1, 172, 2]);
+/// ```
+#[inline]
+#[cfg(feature = "std")]
+pub fn encode_slice_to_vec<T: VarIntTarget>(values: &[T], out: &mut Vec<u8>) -> usize {
+    let start = out.len();
+
+    if T::MAX_VARINT_BYTES > 16 {
+        for value in values {
+            out.push_varint(*value);
+        }
+        return out.len() - start;
+    }
+
+    // Reserving for the worst case of a few values at a time keeps the excess capacity small
+    for chunk in values.chunks(64) {
+        out.reserve(chunk.len() * T::MAX_VARINT_BYTES as usize + 16);
+
+        let len = out.len();
+        unsafe {
+            let (count, written) =
+                encode_batch(chunk, out.as_mut_ptr().add(len), out.capacity() - len);
+            debug_assert_eq!(count, chunk.len());
+            out.set_len(len + written);
+        }
+    }
+
+    out.len() - start
+}
+
+/// Encodes values to `out` while at least 16 bytes of the `len` bytes after it remain, with one
+/// unaligned 16-byte store per step. Produces a tuple containing the number of values encoded, and
+/// the number of bytes written.
+///
+/// # Safety
+/// There must be `len` bytes of writable memory after the start of the pointer.
+#[inline]
+unsafe fn encode_batch<T: VarIntTarget>(values: &[T], out: *mut u8, len: usize) -> (usize, usize) {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        #[cfg(target_arch = "x86_64")]
+        if cpu::has_fast_pdep() && cpu::has_ssse3() && cpu::has_lzcnt() {
+            return encode_batch_pdep(values, out, len);
+        }
+
+        if cpu::has_ssse3() {
+            encode_batch_ssse3(values, out, len)
+        } else {
+            encode_batch_scalar(values, out, len)
+        }
+    }
+    #[cfg(all(target_arch = "aarch64", target_feature = "neon", not(miri)))]
+    {
+        encode_batch_neon(values, out, len)
+    }
+    #[cfg(not(any(
+        target_arch = "x86",
+        target_arch = "x86_64",
+        all(target_arch = "aarch64", target_feature = "neon", not(miri))
+    )))]
+    {
+        encode_batch_scalar(values, out, len)
+    }
+}
+
+/// Encodes without SIMD instructions, for [`Backend::Scalar`](crate::Backend::Scalar)
+#[inline]
+pub(crate) unsafe fn encode_batch_scalar<T: VarIntTarget>(
+    values: &[T],
+    out: *mut u8,
+    len: usize,
+) -> (usize, usize) {
+    encode_batch_impl::<T, false, false>(values, out, len)
+}
+
+#[inline]
+#[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+pub(crate) unsafe fn encode_batch_neon<T: VarIntTarget>(
+    values: &[T],
+    out: *mut u8,
+    len: usize,
+) -> (usize, usize) {
+    encode_batch_impl::<T, true, false>(values, out, len)
+}
+
+#[inline]
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "ssse3,bmi2,lzcnt")]
+pub(crate) unsafe fn encode_batch_pdep<T: VarIntTarget>(
+    values: &[T],
+    out: *mut u8,
+    len: usize,
+) -> (usize, usize) {
+    encode_batch_impl::<T, true, true>(values, out, len)
+}
+
+#[inline]
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[target_feature(enable = "ssse3")]
+pub(crate) unsafe fn encode_batch_ssse3<T: VarIntTarget>(
+    values: &[T],
+    out: *mut u8,
+    len: usize,
+) -> (usize, usize) {
+    encode_batch_impl::<T, true, false>(values, out, len)
+}
+
+#[inline(always)]
+unsafe fn encode_batch_impl<T: VarIntTarget, const SHUFFLE: bool, const PDEP: bool>(
+    values: &[T],
+    out: *mut u8,
+    len: usize,
+) -> (usize, usize) {
+    let mut count = 0;
+    let mut written = 0;
+
+    // Varints that can be longer than 16 bytes do not fit in a single store
+    if T::MAX_VARINT_BYTES > 16 {
+        return (count, written);
+    }
+
+    // Two varints of at most five bytes each fit in a single store
+    if T::MAX_VARINT_BYTES <= 5 {
+        while values.len() - count >= 2 && len - written >= 16 {
+            let (data, _, _, bytes) =
+                encode_two_impl::<T, T, SHUFFLE, PDEP>(values[count], values[count + 1]);
+            out.add(written).cast::<[u8; 16]>().write_unaligned(data);
+
+            count += 2;
+            written += bytes as usize;
+        }
+    }
+
+    while count < values.len() && len - written >= 16 {
+        let (data, bytes) = encode_one::<T, SHUFFLE, PDEP>(values[count]);
+        out.add(written).cast::<[u8; 16]>().write_unaligned(data);
+
+        count += 1;
+        written += bytes as usize;
+    }
+
+    (count, written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{encode_slice, encode_slice_to_vec};
+    use crate::tests::{NUMS_U128, NUMS_U16, NUMS_U32, NUMS_U64, NUMS_U8};
+    use crate::{encode_wide, Backend, VarIntTarget};
+
+    /// Encodes the values one at a time, for comparison with `encode_slice`
+    fn encode_sequential<T: VarIntTarget>(values: &[T]) -> Vec<u8> {
+        let mut expected = Vec::new();
+        for value in values {
+            let (bytes, len) = encode_wide(*value);
+            expected.extend_from_slice(&bytes[..len as usize]);
+        }
+        expected
+    }
+
+    fn check_encode_slice<T: VarIntTarget>(values: &[T]) {
+        let expected = encode_sequential(values);
+
+        // An exactly sized output, and one with room to spare
+        for extra in [0, 16] {
+            let mut out = vec![0; expected.len() + extra];
+            assert_eq!(encode_slice(values, &mut out), expected.len());
+            assert_eq!(out[..expected.len()], expected);
+
+            for backend in Backend::supported() {
+                let mut result = vec![0; expected.len() + extra];
+                assert_eq!(backend.encode_slice(values, &mut result), expected.len());
+                assert_eq!(result[..expected.len()], expected, "{:?}", backend);
+            }
+        }
+
+        let mut out = vec![8];
+        assert_eq!(encode_slice_to_vec(values, &mut out), expected.len());
+        assert_eq!(out[0], 8);
+        assert_eq!(out[1..], expected);
+    }
+
+    fn check_encode_slice_pairs<T: VarIntTarget>(nums: &[T]) {
+        // Every pair of values, so that the packed stores see every combination of lengths
+        let mut values = Vec::new();
+        for i in nums {
+            for j in nums {
+                values.push(*i);
+                values.push(*j);
+            }
+        }
+
+        check_encode_slice(&values);
+        check_encode_slice(&values[1..]);
+        check_encode_slice(&values[..0]);
+    }
+
+    #[test]
+    fn test_encode_slice() {
+        check_encode_slice_pairs(&NUMS_U8[..]);
+        check_encode_slice_pairs(&NUMS_U16[..]);
+        check_encode_slice_pairs(&NUMS_U32[..]);
+        check_encode_slice_pairs(&NUMS_U64[..]);
+        check_encode_slice_pairs(&NUMS_U128[..]);
+    }
+
+    #[test]
+    fn test_encode_slice_store_edges() {
+        // Varints taking up 15, 16 and 17 bytes in total, into exactly sized outputs, so that the
+        // last values are encoded one at a time
+        for last in [1u32, 300, 1 << 14] {
+            check_encode_slice(&[u32::MAX, u32::MAX, 1 << 21, last]);
+        }
+        for last in [1u64 << 28, 1 << 35, 1 << 42] {
+            check_encode_slice(&[u64::MAX, last]);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_encode_slice_too_small() {
+        encode_slice::<u32>(&[1, 300, 1337], &mut [0; 4]);
+    }
+}
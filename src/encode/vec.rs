@@ -0,0 +1,107 @@
+//! Appending varints to a `Vec<u8>` without zero-initializing or copying through a temporary.
+
+use super::{encode_to_ptr_dispatch, encode_wide};
+use crate::num::{SignedVarIntTarget, VarIntTarget};
+
+/// Extension methods for appending varints to a `Vec<u8>`.
+///
+/// Each varint is written directly into the spare capacity of the vector with a single 16-byte
+/// store, after which the length is increased by the size of the varint. The capacity is grown so
+/// that at least 16 bytes are available, so the vector may reserve slightly more than it uses.
+///
+/// # Examples
+/// ```
+/// use varint_simd::VarIntVecExt;
+///
+/// let mut out = Vec::new();
+/// assert_eq!(out.push_varint::<u32>(1337), 2);
+/// assert_eq!(out.push_varint_zigzag::<i32>(-20), 1);
+/// assert_eq!(out, [185, 10, 39]);
+/// ```
+pub trait VarIntVecExt {
+    /// Encodes a single number to a varint, and appends it. Returns the number of bytes appended.
+    fn push_varint<T: VarIntTarget>(&mut self, num: T) -> u8;
+
+    /// Encodes a single signed integer in ZigZag format to a varint, and appends it. Returns the
+    /// number of bytes appended.
+    fn push_varint_zigzag<T: SignedVarIntTarget>(&mut self, num: T) -> u8;
+}
+
+impl VarIntVecExt for Vec<u8> {
+    #[inline]
+    fn push_varint<T: VarIntTarget>(&mut self, num: T) -> u8 {
+        if T::MAX_VARINT_BYTES > 16 {
+            let (data, size) = encode_wide(num);
+            self.extend_from_slice(&data[..size as usize]);
+            return size;
+        }
+
+        self.reserve(16);
+
+        let len = self.len();
+        unsafe {
+            let size = encode_to_ptr_dispatch(num, self.as_mut_ptr().add(len));
+            self.set_len(len + size as usize);
+            size
+        }
+    }
+
+    #[inline]
+    fn push_varint_zigzag<T: SignedVarIntTarget>(&mut self, num: T) -> u8 {
+        self.push_varint(T::Unsigned::zigzag(num))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VarIntVecExt;
+    use crate::tests::{NUMS_U128, NUMS_U16, NUMS_U32, NUMS_U64, NUMS_U8};
+    use crate::{encode_wide, encode_wide_zigzag, SignedVarIntTarget, VarIntTarget};
+
+    fn check_push_varint<T: VarIntTarget>(nums: &[T]) {
+        let mut out = vec![8];
+        let mut expected = vec![8];
+        for num in nums {
+            let (bytes, len) = encode_wide(*num);
+            expected.extend_from_slice(&bytes[..len as usize]);
+
+            assert_eq!(out.push_varint(*num), len);
+            assert_eq!(out, expected);
+        }
+    }
+
+    fn check_push_varint_zigzag<T: SignedVarIntTarget>(nums: &[T]) {
+        let mut out = Vec::new();
+        let mut expected = Vec::new();
+        for num in nums {
+            let (bytes, len) = encode_wide_zigzag(*num);
+            expected.extend_from_slice(&bytes[..len as usize]);
+
+            assert_eq!(out.push_varint_zigzag(*num), len);
+            assert_eq!(out, expected);
+        }
+    }
+
+    #[test]
+    fn test_push_varint() {
+        check_push_varint(&NUMS_U8[..]);
+        check_push_varint(&NUMS_U16[..]);
+        check_push_varint(&NUMS_U32[..]);
+        check_push_varint(&NUMS_U64[..]);
+        check_push_varint(&NUMS_U128[..]);
+    }
+
+    #[test]
+    fn test_push_varint_zigzag() {
+        let nums: Vec<i8> = NUMS_U8.iter().map(|n| n.unzigzag()).collect();
+        check_push_varint_zigzag(&nums);
+        let nums: Vec<i16> = NUMS_U16.iter().map(|n| n.unzigzag()).collect();
+        check_push_varint_zigzag(&nums);
+        let nums: Vec<i32> = NUMS_U32.iter().map(|n| n.unzigzag()).collect();
+        check_push_varint_zigzag(&nums);
+        let nums: Vec<i64> = NUMS_U64.iter().map(|n| n.unzigzag()).collect();
+        check_push_varint_zigzag(&nums);
+        let nums: Vec<i128> = NUMS_U128.iter().map(|n| n.unzigzag()).collect();
+        check_push_varint_zigzag(&nums);
+    }
+}
@@ -16,31 +16,44 @@ use core::arch::x86_64::*;
 
 use core::fmt::Debug;
 
+pub mod backend;
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+mod cpu;
 pub mod decode;
 pub mod encode;
 pub mod num;
+pub mod zigzag;
 
+pub use backend::Backend;
 #[doc(inline)]
 pub use decode::*;
 #[doc(inline)]
 pub use encode::*;
 pub use num::*;
+pub use zigzag::*;
 
 // Functions to help with debugging
 #[allow(dead_code)]
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
 fn slice_m128i(n: __m128i) -> [u8; 16] {
     unsafe { core::mem::transmute(n) }
 }
 
 #[allow(dead_code)]
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
 fn slice_m256i(n: __m256i) -> [i8; 32] {
     unsafe { core::mem::transmute(n) }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum VarIntDecodeError {
     Overflow,
     NotEnoughBytes,
+    NonCanonical,
+    /// The varint is well-formed but decoded to zero, which a `NonZero` target type cannot hold.
+    /// Only returned by [`decode_nonzero`] and [`Backend::decode_nonzero`].
+    Zero,
 }
 
 impl core::fmt::Display for VarIntDecodeError {
@@ -52,13 +65,41 @@ impl core::fmt::Display for VarIntDecodeError {
 #[cfg(feature = "std")]
 impl std::error::Error for VarIntDecodeError {}
 
+/// An error that occurred while decoding a sequence of varints, along with its location
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SliceDecodeError {
+    /// The reason the varint could not be decoded
+    pub error: VarIntDecodeError,
+    /// The offset in bytes of the start of the varint
+    pub offset: usize,
+    /// The index of the varint in the sequence, i.e. the number of values decoded before it
+    pub index: usize,
+}
+
+impl core::fmt::Display for SliceDecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{} at offset {} (index {})",
+            self.error, self.offset, self.index
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SliceDecodeError {}
+
+/// An error that occurred while validating a sequence of varints with [`validate`], along with
+/// its location
+pub type ValidationError = SliceDecodeError;
+
 #[cfg(test)]
 mod tests {
-    #[cfg(target_feature = "avx2")]
-    use crate::decode_two_wide_unsafe;
     use crate::{
-        decode, decode_eight_u8_unsafe, decode_four_unsafe, decode_len, decode_two_unsafe, encode,
-        encode_to_slice, VarIntDecodeError, VarIntTarget,
+        decode, decode_eight_u8_unsafe, decode_four_unsafe, decode_len, decode_len_unsafe,
+        decode_two_unsafe, decode_two_wide_unsafe, decode_unsafe, decode_wide_unsafe, encode,
+        encode_const, encode_to_slice, encode_wide, encode_wide_zigzag, encode_zigzag,
+        encode_zigzag_const, encoded_len, Backend, VarIntDecodeError, VarIntTarget,
     };
 
     use lazy_static::lazy_static;
@@ -76,9 +117,22 @@ mod tests {
         assert_eq!(a.0, expected);
         assert_eq!(a.1 as usize, encoded.len());
 
+        assert_eq!(encode_const(value), a);
+        assert_eq!(encoded_len(value), encoded.len());
+
+        for backend in Backend::supported() {
+            assert_eq!(backend.encode(value), a, "{:?}", backend);
+            assert_eq!(
+                backend.decode::<T>(&expected),
+                Ok((value, encoded.len())),
+                "{:?}",
+                backend
+            );
+        }
+
         let roundtrip: (T, usize) = decode(&expected).unwrap();
         assert_eq!(roundtrip.0, value);
-        assert_eq!(roundtrip.1 as usize, encoded.len());
+        assert_eq!(roundtrip.1, encoded.len());
 
         let len = decode_len::<T>(&expected).unwrap();
         assert_eq!(len, encoded.len());
@@ -184,6 +238,174 @@ mod tests {
         );
     }
 
+    fn check_wide<T: VarIntTarget>(value: T, encoded: &[u8]) {
+        let mut expected = [0u8; 32];
+        expected[..encoded.len()].copy_from_slice(encoded);
+
+        let a = encode_wide(value);
+        assert_eq!(a.0, expected);
+        assert_eq!(a.1 as usize, encoded.len());
+        assert_eq!(encoded_len(value), encoded.len());
+
+        let mut slice = [0u8; 19];
+        assert_eq!(encode_to_slice(value, &mut slice) as usize, encoded.len());
+        assert_eq!(slice[..encoded.len()], *encoded);
+
+        let decoded = unsafe { decode_wide_unsafe::<T>(expected.as_ptr()) };
+        assert_eq!(decoded, (value, encoded.len()));
+
+        for backend in Backend::supported() {
+            assert_eq!(backend.encode_wide(value), a, "{:?}", backend);
+            assert_eq!(
+                backend.decode::<T>(&expected),
+                Ok((value, encoded.len())),
+                "{:?}",
+                backend
+            );
+            assert_eq!(
+                unsafe { backend.decode_wide_unsafe::<T>(expected.as_ptr()) },
+                decoded,
+                "{:?}",
+                backend
+            );
+        }
+
+        let roundtrip: (T, usize) = decode(&expected).unwrap();
+        assert_eq!(roundtrip.0, value);
+        assert_eq!(roundtrip.1, encoded.len());
+
+        let len = decode_len::<T>(&expected).unwrap();
+        assert_eq!(len, encoded.len());
+    }
+
+    #[test]
+    fn roundtrip_u128() {
+        check_wide(2u128.pow(0) - 1, &[0x00]);
+        check_wide(2u128.pow(0), &[0x01]);
+
+        check_wide(2u128.pow(7) - 1, &[0x7F]);
+        check_wide(2u128.pow(7), &[0x80, 0x01]);
+        check_wide(300u128, &[0xAC, 0x02]);
+
+        check_wide(
+            2u128.pow(63),
+            &[0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x01],
+        );
+        check_wide(
+            u64::MAX as u128 + 1,
+            &[0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x02],
+        );
+
+        check_wide(
+            2u128.pow(112) - 1,
+            &[
+                0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+                0xFF, 0x7F,
+            ],
+        );
+        check_wide(
+            2u128.pow(112),
+            &[
+                0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80,
+                0x80, 0x80, 0x01,
+            ],
+        );
+
+        check_wide(
+            2u128.pow(126) - 1,
+            &[
+                0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+                0xFF, 0xFF, 0xFF, 0x7F,
+            ],
+        );
+        check_wide(
+            2u128.pow(126),
+            &[
+                0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80,
+                0x80, 0x80, 0x80, 0x80, 0x01,
+            ],
+        );
+
+        check_wide(
+            u128::MAX,
+            &[
+                0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+                0xFF, 0xFF, 0xFF, 0xFF, 0x03,
+            ],
+        );
+    }
+
+    #[test]
+    fn roundtrip_i128() {
+        for value in [i128::MIN, i128::MIN + 1, -65, -64, -1, 0, 63, 64, i128::MAX] {
+            let (encoded, len) = encode_wide_zigzag(value);
+            assert_eq!(encode_wide(u128::zigzag(value)), (encoded, len));
+            assert_eq!(
+                decode::<u128>(&encoded).map(|(num, len)| (num.unzigzag(), len)),
+                Ok((value, len as usize))
+            );
+        }
+    }
+
+    #[test]
+    fn roundtrip_usize() {
+        for value in NUMS_U64.iter() {
+            if let Ok(num) = usize::try_from(*value) {
+                let (encoded, len) = encode(*value);
+                check(num, &encoded[..len as usize]);
+            }
+        }
+
+        for value in [
+            isize::MIN,
+            isize::MIN + 1,
+            -65,
+            -64,
+            -1,
+            0,
+            63,
+            64,
+            isize::MAX,
+        ] {
+            assert_eq!(encode_zigzag(value), encode_zigzag(value as i64));
+            assert_eq!(encode_zigzag_const(value), encode_zigzag(value));
+        }
+    }
+
+    #[test]
+    fn zigzag_const() {
+        const MIN: ([u8; 16], u8) = encode_zigzag_const(i64::MIN);
+        static HEADER: ([u8; 16], u8) = encode_zigzag_const(-20i32);
+        assert_eq!(MIN, encode_zigzag(i64::MIN));
+        assert_eq!(HEADER, encode_zigzag(-20i32));
+
+        for i in i8::MIN..=i8::MAX {
+            assert_eq!(encode_zigzag_const(i), encode_zigzag(i));
+        }
+        for i in [
+            i16::MIN,
+            -8193,
+            -8192,
+            -65,
+            -64,
+            -1,
+            0,
+            63,
+            64,
+            8191,
+            8192,
+            i16::MAX,
+        ] {
+            assert_eq!(encode_zigzag_const(i), encode_zigzag(i));
+        }
+        for i in [i32::MIN, i32::MIN + 1, -65, -64, -1, 0, 63, 64, i32::MAX] {
+            assert_eq!(encode_zigzag_const(i), encode_zigzag(i));
+        }
+        for i in [i64::MIN, i64::MIN + 1, -65, -64, -1, 0, 63, 64, i64::MAX] {
+            assert_eq!(encode_zigzag_const(i), encode_zigzag(i));
+        }
+    }
+
     #[test]
     fn overflow_u8() {
         let encoded = encode(u8::MAX as u16 + 1);
@@ -208,10 +430,20 @@ mod tests {
             .expect_err("should overflow");
     }
 
+    #[test]
+    fn overflow_u128() {
+        let mut encoded = [0xFF; 19];
+        encoded[18] = 0x04;
+        decode::<u128>(&encoded).expect_err("should overflow");
+
+        let encoded = encode_wide(u128::MAX);
+        decode::<u64>(&encoded.0).expect_err("should overflow");
+    }
+
     #[test]
     fn truncated() {
         for i in 1..10 {
-            let encoded = encode(1u64 << 7 * i);
+            let encoded = encode(1u64 << (7 * i));
             for j in 0..=i {
                 assert_eq!(
                     decode::<u64>(&encoded.0[..j]),
@@ -234,11 +466,15 @@ mod tests {
                 assert_eq!(decoded.1, *j);
                 assert_eq!(decoded.2, first_len);
                 assert_eq!(decoded.3, second_len);
+
+                for backend in Backend::supported() {
+                    let result = unsafe { backend.decode_two_unsafe::<T, U>(enc.as_ptr()) };
+                    assert_eq!(result, decoded, "{:?}", backend);
+                }
             }
         }
     }
 
-    #[cfg(target_feature = "avx2")]
     fn check_decode_wide_2x<T: VarIntTarget, U: VarIntTarget>(a: &[T], b: &[U]) {
         for i in a {
             for j in b {
@@ -252,6 +488,11 @@ mod tests {
                 assert_eq!(decoded.1, *j);
                 assert_eq!(decoded.2, first_len);
                 assert_eq!(decoded.3, second_len);
+
+                for backend in Backend::supported() {
+                    let result = unsafe { backend.decode_two_wide_unsafe::<T, U>(enc.as_ptr()) };
+                    assert_eq!(result, decoded, "{:?}", backend);
+                }
             }
         }
     }
@@ -288,6 +529,12 @@ mod tests {
                         assert_eq!(decoded.6, third_len);
                         assert_eq!(decoded.7, fourth_len);
                         assert!(!decoded.8);
+
+                        for backend in Backend::supported() {
+                            let result =
+                                unsafe { backend.decode_four_unsafe::<T, U, V, W>(enc.as_ptr()) };
+                            assert_eq!(result, decoded, "{:?}", backend);
+                        }
                     }
                 }
             }
@@ -295,14 +542,14 @@ mod tests {
     }
 
     lazy_static! {
-        static ref NUMS_U8: [u8; 5] = [
+        pub(crate) static ref NUMS_U8: [u8; 5] = [
             2u8.pow(0) - 1,
             2u8.pow(0),
             2u8.pow(7) - 1,
             2u8.pow(7),
             u8::MAX
         ];
-        static ref NUMS_U16: [u16; 8] = [
+        pub(crate) static ref NUMS_U16: [u16; 8] = [
             2u16.pow(0) - 1,
             2u16.pow(0),
             2u16.pow(7) - 1,
@@ -312,7 +559,7 @@ mod tests {
             2u16.pow(14),
             u16::MAX
         ];
-        static ref NUMS_U32: [u32; 12] = [
+        pub(crate) static ref NUMS_U32: [u32; 12] = [
             2u32.pow(0) - 1,
             2u32.pow(0),
             2u32.pow(7) - 1,
@@ -326,7 +573,7 @@ mod tests {
             2u32.pow(28),
             u32::MAX
         ];
-        static ref NUMS_U64: [u64; 22] = [
+        pub(crate) static ref NUMS_U64: [u64; 22] = [
             2u64.pow(0) - 1,
             2u64.pow(0),
             2u64.pow(7) - 1,
@@ -350,6 +597,24 @@ mod tests {
             2u64.pow(63),
             u64::MAX
         ];
+        pub(crate) static ref NUMS_U128: [u128; 16] = [
+            2u128.pow(0) - 1,
+            2u128.pow(0),
+            2u128.pow(7) - 1,
+            2u128.pow(7),
+            300,
+            2u128.pow(14),
+            2u128.pow(35),
+            2u128.pow(63) - 1,
+            2u128.pow(63),
+            u64::MAX as u128,
+            u64::MAX as u128 + 1,
+            2u128.pow(112) - 1,
+            2u128.pow(112),
+            2u128.pow(126) - 1,
+            2u128.pow(126),
+            u128::MAX
+        ];
     }
 
     #[test]
@@ -361,7 +626,6 @@ mod tests {
     }
 
     #[test]
-    #[cfg(target_feature = "avx2")]
     fn test_decode_2x_wide_u8_x() {
         check_decode_wide_2x::<u8, u8>(&NUMS_U8[..], &NUMS_U8[..]);
         check_decode_wide_2x::<u8, u16>(&NUMS_U8[..], &NUMS_U16[..]);
@@ -378,7 +642,6 @@ mod tests {
     }
 
     #[test]
-    #[cfg(target_feature = "avx2")]
     fn test_decode_2x_wide_u16_x() {
         check_decode_wide_2x::<u16, u8>(&NUMS_U16[..], &NUMS_U8[..]);
         check_decode_wide_2x::<u16, u16>(&NUMS_U16[..], &NUMS_U16[..]);
@@ -395,7 +658,6 @@ mod tests {
     }
 
     #[test]
-    #[cfg(target_feature = "avx2")]
     fn test_decode_2x_wide_u32_x() {
         check_decode_wide_2x::<u32, u8>(&NUMS_U32[..], &NUMS_U8[..]);
         check_decode_wide_2x::<u32, u16>(&NUMS_U32[..], &NUMS_U16[..]);
@@ -412,7 +674,6 @@ mod tests {
     }
 
     #[test]
-    #[cfg(target_feature = "avx2")]
     fn test_decode_2x_wide_u64_x() {
         check_decode_wide_2x::<u64, u8>(&NUMS_U64[..], &NUMS_U8[..]);
         check_decode_wide_2x::<u64, u16>(&NUMS_U64[..], &NUMS_U16[..]);
@@ -421,6 +682,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg_attr(miri, ignore)] // too slow
     fn test_decode_4x_u8_u8_x_x() {
         check_decode_4x::<u8, u8, u8, u8>(&NUMS_U8[..], &NUMS_U8[..], &NUMS_U8[..], &NUMS_U8[..]);
         check_decode_4x::<u8, u8, u8, u16>(&NUMS_U8[..], &NUMS_U8[..], &NUMS_U8[..], &NUMS_U16[..]);
@@ -443,6 +705,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg_attr(miri, ignore)] // too slow
     fn test_decode_4x_u8_u16_x_x() {
         check_decode_4x::<u8, u16, u8, u8>(&NUMS_U8[..], &NUMS_U16[..], &NUMS_U8[..], &NUMS_U8[..]);
         check_decode_4x::<u8, u16, u8, u16>(
@@ -479,6 +742,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg_attr(miri, ignore)] // too slow
     fn test_decode_4x_u8_u32_x_x() {
         check_decode_4x::<u8, u32, u8, u8>(&NUMS_U8[..], &NUMS_U32[..], &NUMS_U8[..], &NUMS_U8[..]);
         check_decode_4x::<u8, u32, u8, u16>(
@@ -515,11 +779,13 @@ mod tests {
     }
 
     #[test]
+    #[cfg_attr(miri, ignore)] // too slow
     fn test_decode_4x_u8_u64_x_x() {
         check_decode_4x::<u8, u64, u8, u8>(&NUMS_U8[..], &NUMS_U64[..], &NUMS_U8[..], &NUMS_U8[..]);
     }
 
     #[test]
+    #[cfg_attr(miri, ignore)] // too slow
     fn test_decode_4x_u16_u8_x_x() {
         check_decode_4x::<u16, u8, u8, u8>(&NUMS_U16[..], &NUMS_U8[..], &NUMS_U8[..], &NUMS_U8[..]);
         check_decode_4x::<u16, u8, u8, u16>(
@@ -556,6 +822,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg_attr(miri, ignore)] // too slow
     fn test_decode_4x_u16_u16_x_x() {
         check_decode_4x::<u16, u16, u8, u8>(
             &NUMS_U16[..],
@@ -597,6 +864,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg_attr(miri, ignore)] // too slow
     fn test_decode_4x_u16_u32_x_x() {
         check_decode_4x::<u16, u32, u8, u8>(
             &NUMS_U16[..],
@@ -638,6 +906,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg_attr(miri, ignore)] // too slow
     fn test_decode_4x_u32_u8_x_x() {
         check_decode_4x::<u32, u8, u8, u8>(&NUMS_U32[..], &NUMS_U8[..], &NUMS_U8[..], &NUMS_U8[..]);
         check_decode_4x::<u32, u8, u8, u16>(
@@ -674,6 +943,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg_attr(miri, ignore)] // too slow
     fn test_decode_4x_u32_u16_x_x() {
         check_decode_4x::<u32, u16, u8, u8>(
             &NUMS_U32[..],
@@ -715,6 +985,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg_attr(miri, ignore)] // too slow
     fn test_decode_4x_u32_u32_x_x() {
         check_decode_4x::<u32, u32, u8, u8>(
             &NUMS_U32[..],
@@ -744,6 +1015,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg_attr(miri, ignore)] // too slow
     fn test_decode_4x_u64_u8_x_x() {
         check_decode_4x::<u64, u8, u8, u8>(&NUMS_U64[..], &NUMS_U8[..], &NUMS_U8[..], &NUMS_U8[..]);
     }
@@ -825,6 +1097,13 @@ mod tests {
                                                 + seventh_len
                                                 + eighth_len
                                         );
+
+                                        for backend in Backend::supported() {
+                                            let result = unsafe {
+                                                backend.decode_eight_u8_unsafe(enc.as_ptr())
+                                            };
+                                            assert_eq!(result, decoded, "{:?}", backend);
+                                        }
                                     }
                                 }
                             }
@@ -836,50 +1115,129 @@ mod tests {
     }
 
     #[test]
+    #[cfg_attr(miri, ignore)] // too slow
     fn test_decode_8x_u8() {
         check_decode_8x_u8(&NUMS_U8[..]);
     }
 
-    // #[test]
-    // fn test_two() {
-    //     // let result = unsafe { decode_two_unsafe::<u32, u32>([0x80, 0x80, 0x80, 0x80, 0x01, 0x80, 0x80, 0x80, 0x80, 0x01, 0, 0, 0, 0, 0, 0].as_ptr()) };
-    //     let result = unsafe {
-    //         decode_two_wide_unsafe::<u8, u8>(
-    //             [
-    //                 0x80, 0x01, 0x70, 0x01, 0x01, 0x80, 0x80, 0x80, 0x80, 0x01, 0, 0, 0, 0, 0, 0,
-    //             ]
-    //             .as_ptr(),
-    //         )
-    //     };
-    //     println!("{:?}", result);
-    // }
-    //
-    // #[test]
-    // fn test_four() {
-    //     let result = unsafe {
-    //         decode_four_unsafe::<u16, u16, u16, u16>(
-    //             [
-    //                 0x01, 0x82, 0x01, 0x83, 0x80, 0x01, 0x84, 0x80, 0x01, 0, 0, 0, 0, 0, 0,
-    //             ]
-    //             .as_ptr(),
-    //         )
-    //     };
-    //
-    //     println!("{:?}", result);
-    // }
-    //
-    // #[test]
-    // fn test_eight() {
-    //     let result = unsafe {
-    //         decode_eight_u8_unsafe(
-    //             [
-    //                 0x80, 0x01, 0x80, 0x01, 0x01, 0x90, 0x01, 0x01, 0x01, 0x02, 0x90, 0x01, 0, 0,
-    //                 0, 0, 0, 0, 0, 0,
-    //             ]
-    //             .as_ptr(),
-    //         )
-    //     };
-    //
-    //     println!("{:?}", result);
-    // }
+    // The following tests place the input at the very end of an allocation of exactly the size
+    // that the safety contract requires, so that Miri reports any read past it
+
+    fn check_window<T: VarIntTarget>(nums: &[T]) {
+        for num in nums {
+            let (encoded, len) = encode(*num);
+
+            let window: Box<[u8]> = encoded.into();
+            let decoded = unsafe { decode_unsafe::<T>(window.as_ptr()) };
+            assert_eq!(decoded, (*num, len as usize));
+            assert_eq!(
+                unsafe { decode_len_unsafe::<T>(window.as_ptr()) },
+                len as usize
+            );
+
+            for backend in Backend::supported() {
+                let result = unsafe { backend.decode_unsafe::<T>(window.as_ptr()) };
+                assert_eq!(result, decoded, "{:?}", backend);
+            }
+
+            // The safe functions must not assume any padding
+            let exact: Box<[u8]> = encoded[..len as usize].into();
+            assert_eq!(decode::<T>(&exact), Ok(decoded));
+            assert_eq!(decode_len::<T>(&exact), Ok(len as usize));
+        }
+    }
+
+    #[test]
+    fn decode_window() {
+        check_window(&NUMS_U8[..]);
+        check_window(&NUMS_U16[..]);
+        check_window(&NUMS_U32[..]);
+        check_window(&NUMS_U64[..]);
+    }
+
+    fn check_wide_window<T: VarIntTarget>(nums: &[T]) {
+        for num in nums {
+            let (encoded, len) = encode_wide(*num);
+
+            let window: Box<[u8]> = encoded.into();
+            let decoded = unsafe { decode_wide_unsafe::<T>(window.as_ptr()) };
+            assert_eq!(decoded, (*num, len as usize));
+
+            for backend in Backend::supported() {
+                let result = unsafe { backend.decode_wide_unsafe::<T>(window.as_ptr()) };
+                assert_eq!(result, decoded, "{:?}", backend);
+            }
+
+            let exact: Box<[u8]> = encoded[..len as usize].into();
+            assert_eq!(decode::<T>(&exact), Ok(decoded));
+            assert_eq!(decode_len::<T>(&exact), Ok(len as usize));
+        }
+    }
+
+    #[test]
+    fn decode_wide_window() {
+        check_wide_window(&NUMS_U8[..]);
+        check_wide_window(&NUMS_U16[..]);
+        check_wide_window(&NUMS_U32[..]);
+        check_wide_window(&NUMS_U64[..]);
+        check_wide_window(&NUMS_U128[..]);
+    }
+
+    #[test]
+    fn decode_multiple_window() {
+        let mut window = Box::new([0u8; 16]);
+        let first_len = encode_to_slice(u32::MAX, &mut window[..]);
+        encode_to_slice(u32::MAX, &mut window[first_len as usize..]);
+        let decoded = unsafe { decode_two_unsafe::<u32, u32>(window.as_ptr()) };
+        assert_eq!(decoded, (u32::MAX, u32::MAX, 5, 5));
+
+        let mut window = Box::new([0u8; 16]);
+        for i in 0..4 {
+            encode_to_slice(u16::MAX, &mut window[i * 3..]);
+        }
+        let decoded = unsafe { decode_four_unsafe::<u16, u16, u16, u16>(window.as_ptr()) };
+        assert_eq!(
+            decoded,
+            (u16::MAX, u16::MAX, u16::MAX, u16::MAX, 3, 3, 3, 3, false)
+        );
+
+        let mut window = Box::new([0u8; 16]);
+        for i in 0..8 {
+            encode_to_slice(u8::MAX, &mut window[i * 2..]);
+        }
+        let decoded = unsafe { decode_eight_u8_unsafe(window.as_ptr()) };
+        assert_eq!(decoded, ([u8::MAX; 8], 16));
+
+        let mut window = Box::new([0u8; 32]);
+        let first_len = encode_to_slice(u64::MAX, &mut window[..]);
+        encode_to_slice(u64::MAX, &mut window[first_len as usize..]);
+        let decoded = unsafe { decode_two_wide_unsafe::<u64, u64>(window.as_ptr()) };
+        assert_eq!(decoded, (u64::MAX, u64::MAX, 10, 10));
+
+        for backend in Backend::supported() {
+            let result = unsafe { backend.decode_two_wide_unsafe::<u64, u64>(window.as_ptr()) };
+            assert_eq!(result, decoded, "{:?}", backend);
+        }
+    }
+
+    #[test]
+    #[cfg(target_arch = "x86_64")]
+    fn pdep_perf_classification() {
+        use crate::cpu::pdep::{pdep_perf, PdepPerf};
+
+        // Haswell
+        assert!(pdep_perf(b"GenuineIntel", 0x000306c3) == PdepPerf::VeryFast);
+        // Excavator
+        assert!(pdep_perf(b"AuthenticAMD", 0x00660f01) == PdepPerf::Slow);
+        // Zen 2
+        assert!(pdep_perf(b"AuthenticAMD", 0x00870f10) == PdepPerf::Slow);
+        // Zen 3
+        assert!(pdep_perf(b"AuthenticAMD", 0x00a20f10) == PdepPerf::Fast);
+        // Zen 4
+        assert!(pdep_perf(b"AuthenticAMD", 0x00a60f12) == PdepPerf::Fast);
+        // Zen 5
+        assert!(pdep_perf(b"AuthenticAMD", 0x00b40f40) == PdepPerf::Fast);
+        // Hygon Dhyana
+        assert!(pdep_perf(b"HygonGenuine", 0x00900f02) == PdepPerf::Slow);
+    }
 }
@@ -1,12 +1,43 @@
-#[cfg(target_arch = "x86")]
+#[cfg(all(target_arch = "aarch64", target_feature = "neon", not(miri)))]
+use core::arch::aarch64::*;
+#[cfg(all(target_arch = "x86", target_feature = "avx2", not(miri)))]
 use core::arch::x86::*;
 #[cfg(target_arch = "x86_64")]
 use core::arch::x86_64::*;
 
 use core::fmt::Debug;
+use core::marker::PhantomData;
+use core::num::{NonZeroU128, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize};
+
+mod sealed {
+    /// Implemented only for the primitive integers and their `NonZero` counterparts, so code
+    /// generic over the varint traits can rely on their representation.
+    pub trait Sealed {}
+
+    macro_rules! impl_sealed {
+        ($($t:ty),*) => { $(impl Sealed for $t {})* };
+    }
+
+    impl_sealed!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+    impl_sealed!(
+        core::num::NonZeroU8,
+        core::num::NonZeroU16,
+        core::num::NonZeroU32,
+        core::num::NonZeroU64,
+        core::num::NonZeroU128,
+        core::num::NonZeroUsize
+    );
+}
+
+use sealed::Sealed;
+
+// Under Miri, the conversions below use the portable bit manipulation rather than intrinsics, so
+// that everything surrounding them can be checked.
 
 /// Represents an unsigned scalar value that can be encoded to and decoded from a varint.
-pub trait VarIntTarget: Debug + Eq + PartialEq + PartialOrd + Sized + Copy {
+///
+/// This trait is sealed, and is only implemented for the primitive unsigned integers.
+pub trait VarIntTarget: Sealed + Debug + Eq + PartialEq + PartialOrd + Sized + Copy {
     /// The signed version of this type
     type Signed: SignedVarIntTarget;
 
@@ -32,12 +63,66 @@ pub trait VarIntTarget: Debug + Eq + PartialEq + PartialOrd + Sized + Copy {
     /// Cast from u64 to self
     fn cast_u64(num: u64) -> Self;
 
+    /// Cast from u128 to self
+    fn cast_u128(num: u128) -> Self;
+
     /// Splits this number into 7-bit segments for encoding
     fn num_to_scalar_stage1(self) -> u64;
 
     /// Splits this number into 7-bit segments for encoding
     fn num_to_vector_stage1(self) -> [u8; 16];
 
+    /// Equivalent of [`vector_to_num`](Self::vector_to_num) using PEXT
+    ///
+    /// # Safety
+    /// The CPU must support BMI2.
+    #[doc(hidden)]
+    #[cfg(target_arch = "x86_64")]
+    unsafe fn vector_to_num_pdep(res: [u8; 16]) -> Self;
+
+    /// Equivalent of [`scalar_to_num`](Self::scalar_to_num) using PEXT
+    ///
+    /// # Safety
+    /// The CPU must support BMI2.
+    #[doc(hidden)]
+    #[cfg(target_arch = "x86_64")]
+    unsafe fn scalar_to_num_pdep(x: u64) -> Self;
+
+    /// Equivalent of [`num_to_scalar_stage1`](Self::num_to_scalar_stage1) using PDEP
+    ///
+    /// # Safety
+    /// The CPU must support BMI2.
+    #[doc(hidden)]
+    #[cfg(target_arch = "x86_64")]
+    unsafe fn num_to_scalar_stage1_pdep(self) -> u64;
+
+    /// Equivalent of [`num_to_vector_stage1`](Self::num_to_vector_stage1) using PDEP
+    ///
+    /// # Safety
+    /// The CPU must support BMI2.
+    #[doc(hidden)]
+    #[cfg(target_arch = "x86_64")]
+    unsafe fn num_to_vector_stage1_pdep(self) -> [u8; 16];
+
+    /// Converts a 256-bit vector to this number, for varints longer than 16 bytes. Types whose
+    /// varints fit in 16 bytes only use the first half of the vector.
+    #[doc(hidden)]
+    #[inline(always)]
+    fn wide_vector_to_num(res: [u8; 32]) -> Self {
+        let mut half = [0u8; 16];
+        half.copy_from_slice(&res[..16]);
+        Self::vector_to_num(half)
+    }
+
+    /// Splits this number into 7-bit segments for encoding to a varint longer than 16 bytes
+    #[doc(hidden)]
+    #[inline(always)]
+    fn num_to_wide_vector_stage1(self) -> [u8; 32] {
+        let mut res = [0u8; 32];
+        res[..16].copy_from_slice(&self.num_to_vector_stage1());
+        res
+    }
+
     /// ZigZag encodes this value
     fn zigzag(from: Self::Signed) -> Self;
 
@@ -59,13 +144,13 @@ impl VarIntTarget for u8 {
     }
 
     #[inline(always)]
-    #[cfg(all(target_arch = "x86_64", target_feature = "bmi2", fast_pdep))]
+    #[cfg(all(target_arch = "x86_64", target_feature = "bmi2", fast_pdep, not(miri)))]
     fn scalar_to_num(x: u64) -> Self {
-        unsafe { _pext_u64(x, 0x000000000000017f) as u8 }
+        unsafe { Self::scalar_to_num_pdep(x) }
     }
 
     #[inline(always)]
-    #[cfg(not(all(target_arch = "x86_64", target_feature = "bmi2", fast_pdep)))]
+    #[cfg(not(all(target_arch = "x86_64", target_feature = "bmi2", fast_pdep, not(miri))))]
     fn scalar_to_num(x: u64) -> Self {
         ((x & 0x000000000000007f) | ((x & 0x0000000000000100) >> 1)) as u8
     }
@@ -81,38 +166,57 @@ impl VarIntTarget for u8 {
     }
 
     #[inline(always)]
-    #[cfg(all(target_arch = "x86_64", target_feature = "bmi2", fast_pdep))]
+    fn cast_u128(num: u128) -> Self {
+        num as u8
+    }
+
+    #[inline(always)]
+    #[cfg(all(target_arch = "x86_64", target_feature = "bmi2", fast_pdep, not(miri)))]
     fn num_to_scalar_stage1(self) -> u64 {
-        let x = self as u64;
-        unsafe { _pdep_u64(x, 0x000000000000017f) }
+        unsafe { self.num_to_scalar_stage1_pdep() }
     }
 
     #[inline(always)]
-    #[cfg(not(all(target_arch = "x86_64", target_feature = "bmi2", fast_pdep)))]
+    #[cfg(not(all(target_arch = "x86_64", target_feature = "bmi2", fast_pdep, not(miri))))]
     fn num_to_scalar_stage1(self) -> u64 {
         let x = self as u64;
         (x & 0x000000000000007f) | ((x & 0x0000000000000080) << 1)
     }
 
     #[inline(always)]
-    #[cfg(all(target_arch = "x86_64", target_feature = "bmi2", fast_pdep))]
     fn num_to_vector_stage1(self) -> [u8; 16] {
         let mut res = [0u64; 2];
-        let x = self as u64;
-
         res[0] = self.num_to_scalar_stage1();
 
         unsafe { core::mem::transmute(res) }
     }
 
     #[inline(always)]
-    #[cfg(not(all(target_arch = "x86_64", target_feature = "bmi2", fast_pdep)))]
-    fn num_to_vector_stage1(self) -> [u8; 16] {
-        let mut res = [0u64; 2];
+    #[cfg(target_arch = "x86_64")]
+    unsafe fn vector_to_num_pdep(res: [u8; 16]) -> Self {
+        let arr: [u64; 2] = core::mem::transmute(res);
+        Self::scalar_to_num_pdep(arr[0])
+    }
 
-        res[0] = self.num_to_scalar_stage1();
+    #[inline(always)]
+    #[cfg(target_arch = "x86_64")]
+    unsafe fn scalar_to_num_pdep(x: u64) -> Self {
+        _pext_u64(x, 0x000000000000017f) as u8
+    }
 
-        unsafe { core::mem::transmute(res) }
+    #[inline(always)]
+    #[cfg(target_arch = "x86_64")]
+    unsafe fn num_to_scalar_stage1_pdep(self) -> u64 {
+        _pdep_u64(self as u64, 0x000000000000017f)
+    }
+
+    #[inline(always)]
+    #[cfg(target_arch = "x86_64")]
+    unsafe fn num_to_vector_stage1_pdep(self) -> [u8; 16] {
+        let mut res = [0u64; 2];
+        res[0] = self.num_to_scalar_stage1_pdep();
+
+        core::mem::transmute(res)
     }
 
     #[inline(always)]
@@ -140,13 +244,13 @@ impl VarIntTarget for u16 {
     }
 
     #[inline(always)]
-    #[cfg(all(target_arch = "x86_64", target_feature = "bmi2", fast_pdep))]
+    #[cfg(all(target_arch = "x86_64", target_feature = "bmi2", fast_pdep, not(miri)))]
     fn scalar_to_num(x: u64) -> Self {
-        unsafe { _pext_u64(x, 0x0000000000037f7f) as u16 }
+        unsafe { Self::scalar_to_num_pdep(x) }
     }
 
     #[inline(always)]
-    #[cfg(not(all(target_arch = "x86_64", target_feature = "bmi2", fast_pdep)))]
+    #[cfg(not(all(target_arch = "x86_64", target_feature = "bmi2", fast_pdep, not(miri))))]
     fn scalar_to_num(x: u64) -> Self {
         ((x & 0x000000000000007f)
             | ((x & 0x0000000000030000) >> 2)
@@ -164,37 +268,57 @@ impl VarIntTarget for u16 {
     }
 
     #[inline(always)]
-    #[cfg(all(target_arch = "x86_64", target_feature = "bmi2", fast_pdep))]
+    fn cast_u128(num: u128) -> Self {
+        num as u16
+    }
+
+    #[inline(always)]
+    #[cfg(all(target_arch = "x86_64", target_feature = "bmi2", fast_pdep, not(miri)))]
     fn num_to_scalar_stage1(self) -> u64 {
-        let x = self as u64;
-        unsafe { _pdep_u64(x, 0x0000000000037f7f) }
+        unsafe { self.num_to_scalar_stage1_pdep() }
     }
 
     #[inline(always)]
-    #[cfg(not(all(target_arch = "x86_64", target_feature = "bmi2", fast_pdep)))]
+    #[cfg(not(all(target_arch = "x86_64", target_feature = "bmi2", fast_pdep, not(miri))))]
     fn num_to_scalar_stage1(self) -> u64 {
         let x = self as u64;
         (x & 0x000000000000007f) | ((x & 0x0000000000003f80) << 1) | ((x & 0x000000000000c000) << 2)
     }
 
     #[inline(always)]
-    #[cfg(all(target_arch = "x86_64", target_feature = "bmi2", fast_pdep))]
     fn num_to_vector_stage1(self) -> [u8; 16] {
         let mut res = [0u64; 2];
-        let x = self as u64;
-
         res[0] = self.num_to_scalar_stage1();
 
         unsafe { core::mem::transmute(res) }
     }
 
     #[inline(always)]
-    #[cfg(not(all(target_arch = "x86_64", target_feature = "bmi2", fast_pdep)))]
-    fn num_to_vector_stage1(self) -> [u8; 16] {
+    #[cfg(target_arch = "x86_64")]
+    unsafe fn vector_to_num_pdep(res: [u8; 16]) -> Self {
+        let arr: [u64; 2] = core::mem::transmute(res);
+        Self::scalar_to_num_pdep(arr[0])
+    }
+
+    #[inline(always)]
+    #[cfg(target_arch = "x86_64")]
+    unsafe fn scalar_to_num_pdep(x: u64) -> Self {
+        _pext_u64(x, 0x0000000000037f7f) as u16
+    }
+
+    #[inline(always)]
+    #[cfg(target_arch = "x86_64")]
+    unsafe fn num_to_scalar_stage1_pdep(self) -> u64 {
+        _pdep_u64(self as u64, 0x0000000000037f7f)
+    }
+
+    #[inline(always)]
+    #[cfg(target_arch = "x86_64")]
+    unsafe fn num_to_vector_stage1_pdep(self) -> [u8; 16] {
         let mut res = [0u64; 2];
-        res[0] = self.num_to_scalar_stage1();
+        res[0] = self.num_to_scalar_stage1_pdep();
 
-        unsafe { core::mem::transmute(res) }
+        core::mem::transmute(res)
     }
 
     #[inline(always)]
@@ -222,13 +346,13 @@ impl VarIntTarget for u32 {
     }
 
     #[inline(always)]
-    #[cfg(all(target_arch = "x86_64", target_feature = "bmi2", fast_pdep))]
+    #[cfg(all(target_arch = "x86_64", target_feature = "bmi2", fast_pdep, not(miri)))]
     fn scalar_to_num(x: u64) -> Self {
-        unsafe { _pext_u64(x, 0x0000000f7f7f7f7f) as u32 }
+        unsafe { Self::scalar_to_num_pdep(x) }
     }
 
     #[inline(always)]
-    #[cfg(not(all(target_arch = "x86_64", target_feature = "bmi2", fast_pdep)))]
+    #[cfg(not(all(target_arch = "x86_64", target_feature = "bmi2", fast_pdep, not(miri))))]
     fn scalar_to_num(x: u64) -> Self {
         ((x & 0x000000000000007f)
             | ((x & 0x0000000f00000000) >> 4)
@@ -248,14 +372,18 @@ impl VarIntTarget for u32 {
     }
 
     #[inline(always)]
-    #[cfg(all(target_arch = "x86_64", target_feature = "bmi2", fast_pdep))]
+    fn cast_u128(num: u128) -> Self {
+        num as u32
+    }
+
+    #[inline(always)]
+    #[cfg(all(target_arch = "x86_64", target_feature = "bmi2", fast_pdep, not(miri)))]
     fn num_to_scalar_stage1(self) -> u64 {
-        let x = self as u64;
-        unsafe { _pdep_u64(x, 0x0000000f7f7f7f7f) }
+        unsafe { self.num_to_scalar_stage1_pdep() }
     }
 
     #[inline(always)]
-    #[cfg(not(all(target_arch = "x86_64", target_feature = "bmi2", fast_pdep)))]
+    #[cfg(not(all(target_arch = "x86_64", target_feature = "bmi2", fast_pdep, not(miri))))]
     fn num_to_scalar_stage1(self) -> u64 {
         let x = self as u64;
         (x & 0x000000000000007f)
@@ -266,23 +394,39 @@ impl VarIntTarget for u32 {
     }
 
     #[inline(always)]
-    #[cfg(all(target_arch = "x86_64", target_feature = "bmi2", fast_pdep))]
     fn num_to_vector_stage1(self) -> [u8; 16] {
         let mut res = [0u64; 2];
-        let x = self as u64;
-
         res[0] = self.num_to_scalar_stage1();
 
         unsafe { core::mem::transmute(res) }
     }
 
     #[inline(always)]
-    #[cfg(not(all(target_arch = "x86_64", target_feature = "bmi2", fast_pdep)))]
-    fn num_to_vector_stage1(self) -> [u8; 16] {
+    #[cfg(target_arch = "x86_64")]
+    unsafe fn vector_to_num_pdep(res: [u8; 16]) -> Self {
+        let arr: [u64; 2] = core::mem::transmute(res);
+        Self::scalar_to_num_pdep(arr[0])
+    }
+
+    #[inline(always)]
+    #[cfg(target_arch = "x86_64")]
+    unsafe fn scalar_to_num_pdep(x: u64) -> Self {
+        _pext_u64(x, 0x0000000f7f7f7f7f) as u32
+    }
+
+    #[inline(always)]
+    #[cfg(target_arch = "x86_64")]
+    unsafe fn num_to_scalar_stage1_pdep(self) -> u64 {
+        _pdep_u64(self as u64, 0x0000000f7f7f7f7f)
+    }
+
+    #[inline(always)]
+    #[cfg(target_arch = "x86_64")]
+    unsafe fn num_to_vector_stage1_pdep(self) -> [u8; 16] {
         let mut res = [0u64; 2];
-        res[0] = self.num_to_scalar_stage1();
+        res[0] = self.num_to_scalar_stage1_pdep();
 
-        unsafe { core::mem::transmute(res) }
+        core::mem::transmute(res)
     }
 
     #[inline(always)]
@@ -306,33 +450,56 @@ impl VarIntTarget for u64 {
     }
 
     #[inline(always)]
-    #[cfg(all(target_feature = "bmi2", fast_pdep))]
+    #[cfg(all(target_arch = "x86_64", target_feature = "bmi2", fast_pdep, not(miri)))]
     fn vector_to_num(res: [u8; 16]) -> Self {
-        let arr: [u64; 2] = unsafe { core::mem::transmute(res) };
+        unsafe { Self::vector_to_num_pdep(res) }
+    }
+
+    #[inline(always)]
+    #[cfg(all(target_arch = "x86_64", target_feature = "bmi2", fast_pdep, not(miri)))]
+    fn num_to_vector_stage1(self) -> [u8; 16] {
+        unsafe { self.num_to_vector_stage1_pdep() }
+    }
+
+    #[inline(always)]
+    #[cfg(target_arch = "x86_64")]
+    unsafe fn vector_to_num_pdep(res: [u8; 16]) -> Self {
+        let arr: [u64; 2] = core::mem::transmute(res);
 
         let x = arr[0];
         let y = arr[1];
 
-        let res = unsafe { _pext_u64(x, 0x7f7f7f7f7f7f7f7f) }
-            | (unsafe { _pext_u64(y, 0x000000000000017f) } << 56);
+        _pext_u64(x, 0x7f7f7f7f7f7f7f7f) | (_pext_u64(y, 0x000000000000017f) << 56)
+    }
 
-        res
+    #[cfg(target_arch = "x86_64")]
+    unsafe fn scalar_to_num_pdep(_x: u64) -> Self {
+        unimplemented!("destination too wide")
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    unsafe fn num_to_scalar_stage1_pdep(self) -> u64 {
+        panic!("source too wide")
     }
 
     #[inline(always)]
-    #[cfg(all(target_feature = "bmi2", fast_pdep))]
-    fn num_to_vector_stage1(self) -> [u8; 16] {
+    #[cfg(target_arch = "x86_64")]
+    unsafe fn num_to_vector_stage1_pdep(self) -> [u8; 16] {
         let mut res = [0u64; 2];
         let x = self;
 
-        res[0] = unsafe { _pdep_u64(x, 0x7f7f7f7f7f7f7f7f) };
-        res[1] = unsafe { _pdep_u64(x >> 56, 0x000000000000017f) };
+        res[0] = _pdep_u64(x, 0x7f7f7f7f7f7f7f7f);
+        res[1] = _pdep_u64(x >> 56, 0x000000000000017f);
 
-        unsafe { core::mem::transmute(res) }
+        core::mem::transmute(res)
     }
 
     #[inline(always)]
-    #[cfg(all(target_feature = "avx2", not(all(target_feature = "bmi2", fast_pdep))))]
+    #[cfg(all(
+        target_feature = "avx2",
+        not(miri),
+        not(all(target_arch = "x86_64", target_feature = "bmi2", fast_pdep))
+    ))]
     fn vector_to_num(res: [u8; 16]) -> Self {
         let pt1 = unsafe {
             let b = core::mem::transmute::<[u8; 16], __m128i>(res);
@@ -381,7 +548,11 @@ impl VarIntTarget for u64 {
     }
 
     #[inline(always)]
-    #[cfg(all(target_feature = "avx2", not(all(target_feature = "bmi2", fast_pdep))))]
+    #[cfg(all(
+        target_feature = "avx2",
+        not(miri),
+        not(all(target_arch = "x86_64", target_feature = "bmi2", fast_pdep))
+    ))]
     fn num_to_vector_stage1(self) -> [u8; 16] {
         let mut res = [0u64; 2];
         let x = self;
@@ -420,7 +591,97 @@ impl VarIntTarget for u64 {
     }
 
     #[inline(always)]
-    #[cfg(not(any(target_feature = "avx2", all(target_feature = "bmi2", fast_pdep))))]
+    #[cfg(all(target_arch = "aarch64", target_feature = "neon", not(miri)))]
+    fn vector_to_num(res: [u8; 16]) -> Self {
+        let arr: [u64; 2] = unsafe { core::mem::transmute(res) };
+
+        let pt1 = unsafe {
+            let c = vdupq_n_u64(arr[0]);
+            let mask = |lo: u64, hi: u64| vld1q_u64([lo, hi].as_ptr());
+            // Negative shifts are right shifts
+            let shift = |lo: i64, hi: i64| vld1q_s64([lo, hi].as_ptr());
+
+            let d = vorrq_u64(
+                vorrq_u64(
+                    vshlq_u64(
+                        vandq_u64(c, mask(0x7f00000000000000, 0x000000000000007f)),
+                        shift(-7, 0),
+                    ),
+                    vshlq_u64(
+                        vandq_u64(c, mask(0x00007f0000000000, 0x007f000000000000)),
+                        shift(-5, -6),
+                    ),
+                ),
+                vorrq_u64(
+                    vshlq_u64(
+                        vandq_u64(c, mask(0x000000007f000000, 0x0000007f00000000)),
+                        shift(-3, -4),
+                    ),
+                    vshlq_u64(
+                        vandq_u64(c, mask(0x0000000000007f00, 0x00000000007f0000)),
+                        shift(-1, -2),
+                    ),
+                ),
+            );
+
+            vgetq_lane_u64::<0>(d) | vgetq_lane_u64::<1>(d)
+        };
+
+        let y = arr[1];
+
+        pt1
+            // don't forget about bytes spilling to the other word
+            | ((y & 0x0000000000000100) << 55)
+            | ((y & 0x000000000000007f) << 56)
+    }
+
+    #[inline(always)]
+    #[cfg(all(target_arch = "aarch64", target_feature = "neon", not(miri)))]
+    fn num_to_vector_stage1(self) -> [u8; 16] {
+        let mut res = [0u64; 2];
+        let x = self;
+
+        res[0] = unsafe {
+            let b = vdupq_n_u64(self);
+            let mask = |lo: u64, hi: u64| vld1q_u64([lo, hi].as_ptr());
+            let shift = |lo: i64, hi: i64| vld1q_s64([lo, hi].as_ptr());
+
+            let c = vorrq_u64(
+                vorrq_u64(
+                    vshlq_u64(
+                        vandq_u64(b, mask(0x000003f800000000, 0x00000007f0000000)),
+                        shift(5, 4),
+                    ),
+                    vshlq_u64(
+                        vandq_u64(b, mask(0x00fe000000000000, 0x0001fc0000000000)),
+                        shift(7, 6),
+                    ),
+                ),
+                vorrq_u64(
+                    vshlq_u64(
+                        vandq_u64(b, mask(0x0000000000003f80, 0x000000000000007f)),
+                        shift(1, 0),
+                    ),
+                    vshlq_u64(
+                        vandq_u64(b, mask(0x000000000fe00000, 0x00000000001fc000)),
+                        shift(3, 2),
+                    ),
+                ),
+            );
+
+            vgetq_lane_u64::<0>(c) | vgetq_lane_u64::<1>(c)
+        };
+        res[1] = ((x & 0x7f00000000000000) >> 56) | ((x & 0x8000000000000000) >> 55);
+
+        unsafe { core::mem::transmute(res) }
+    }
+
+    #[inline(always)]
+    #[cfg(not(any(
+        all(target_feature = "avx2", not(miri)),
+        all(target_arch = "x86_64", target_feature = "bmi2", fast_pdep, not(miri)),
+        all(target_arch = "aarch64", target_feature = "neon", not(miri))
+    )))]
     fn vector_to_num(res: [u8; 16]) -> Self {
         let arr: [u64; 2] = unsafe { core::mem::transmute(res) };
 
@@ -442,7 +703,11 @@ impl VarIntTarget for u64 {
     }
 
     #[inline(always)]
-    #[cfg(not(any(target_feature = "avx2", all(target_feature = "bmi2", fast_pdep))))]
+    #[cfg(not(any(
+        all(target_feature = "avx2", not(miri)),
+        all(target_arch = "x86_64", target_feature = "bmi2", fast_pdep, not(miri)),
+        all(target_arch = "aarch64", target_feature = "neon", not(miri))
+    )))]
     fn num_to_vector_stage1(self) -> [u8; 16] {
         let mut res = [0u64; 2];
         let x = self;
@@ -470,6 +735,11 @@ impl VarIntTarget for u64 {
         num
     }
 
+    #[inline(always)]
+    fn cast_u128(num: u128) -> Self {
+        num as u64
+    }
+
     #[inline(always)]
     fn zigzag(from: Self::Signed) -> Self {
         ((from << 1) ^ (from >> 63)) as Self
@@ -481,9 +751,317 @@ impl VarIntTarget for u64 {
     }
 }
 
+impl VarIntTarget for u128 {
+    type Signed = i128;
+    const MAX_VARINT_BYTES: u8 = 19;
+    const MAX_LAST_VARINT_BYTE: u8 = 0b00000011;
+
+    fn vector_to_num(_res: [u8; 16]) -> Self {
+        unimplemented!("destination too wide")
+    }
+
+    fn scalar_to_num(_x: u64) -> Self {
+        unimplemented!("destination too wide")
+    }
+
+    #[inline(always)]
+    fn cast_u32(num: u32) -> Self {
+        num as u128
+    }
+
+    #[inline(always)]
+    fn cast_u64(num: u64) -> Self {
+        num as u128
+    }
+
+    #[inline(always)]
+    fn cast_u128(num: u128) -> Self {
+        num
+    }
+
+    fn num_to_scalar_stage1(self) -> u64 {
+        panic!("source too wide")
+    }
+
+    fn num_to_vector_stage1(self) -> [u8; 16] {
+        panic!("source too wide")
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    unsafe fn vector_to_num_pdep(_res: [u8; 16]) -> Self {
+        unimplemented!("destination too wide")
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    unsafe fn scalar_to_num_pdep(_x: u64) -> Self {
+        unimplemented!("destination too wide")
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    unsafe fn num_to_scalar_stage1_pdep(self) -> u64 {
+        panic!("source too wide")
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    unsafe fn num_to_vector_stage1_pdep(self) -> [u8; 16] {
+        panic!("source too wide")
+    }
+
+    #[inline(always)]
+    fn wide_vector_to_num(res: [u8; 32]) -> Self {
+        let arr: [u64; 4] = unsafe { core::mem::transmute(res) };
+
+        // Each 8-byte word holds 56 bits of the number, and the last one holds the remaining 16
+        let groups = pack_groups(arr);
+        groups[0] as u128 | (groups[1] as u128) << 56 | (groups[2] as u128) << 112
+    }
+
+    #[inline(always)]
+    fn num_to_wide_vector_stage1(self) -> [u8; 32] {
+        let mask = (1 << 56) - 1;
+        let groups = [
+            self as u64 & mask,
+            (self >> 56) as u64 & mask,
+            (self >> 112) as u64,
+            0,
+        ];
+
+        unsafe { core::mem::transmute(unpack_groups(groups)) }
+    }
+
+    #[inline(always)]
+    fn zigzag(from: Self::Signed) -> Self {
+        ((from << 1) ^ (from >> 127)) as Self
+    }
+
+    #[inline(always)]
+    fn unzigzag(self) -> Self::Signed {
+        ((self >> 1) ^ (-((self & 1) as i128)) as u128) as i128
+    }
+}
+
+// Pointer-sized integers are converted by the fixed-width type of the same size
+
+#[cfg(target_pointer_width = "16")]
+type PointerWidth = u16;
+#[cfg(target_pointer_width = "32")]
+type PointerWidth = u32;
+#[cfg(target_pointer_width = "64")]
+type PointerWidth = u64;
+
+#[cfg(target_pointer_width = "16")]
+type SignedPointerWidth = i16;
+#[cfg(target_pointer_width = "32")]
+type SignedPointerWidth = i32;
+#[cfg(target_pointer_width = "64")]
+type SignedPointerWidth = i64;
+
+impl VarIntTarget for usize {
+    type Signed = isize;
+    const MAX_VARINT_BYTES: u8 = PointerWidth::MAX_VARINT_BYTES;
+    const MAX_LAST_VARINT_BYTE: u8 = PointerWidth::MAX_LAST_VARINT_BYTE;
+
+    #[inline(always)]
+    fn vector_to_num(res: [u8; 16]) -> Self {
+        PointerWidth::vector_to_num(res) as usize
+    }
+
+    #[inline(always)]
+    fn scalar_to_num(x: u64) -> Self {
+        PointerWidth::scalar_to_num(x) as usize
+    }
+
+    #[inline(always)]
+    fn cast_u32(num: u32) -> Self {
+        num as usize
+    }
+
+    #[inline(always)]
+    fn cast_u64(num: u64) -> Self {
+        num as usize
+    }
+
+    #[inline(always)]
+    fn cast_u128(num: u128) -> Self {
+        num as usize
+    }
+
+    #[inline(always)]
+    fn num_to_scalar_stage1(self) -> u64 {
+        (self as PointerWidth).num_to_scalar_stage1()
+    }
+
+    #[inline(always)]
+    fn num_to_vector_stage1(self) -> [u8; 16] {
+        (self as PointerWidth).num_to_vector_stage1()
+    }
+
+    #[inline(always)]
+    #[cfg(target_arch = "x86_64")]
+    unsafe fn vector_to_num_pdep(res: [u8; 16]) -> Self {
+        PointerWidth::vector_to_num_pdep(res) as usize
+    }
+
+    #[inline(always)]
+    #[cfg(target_arch = "x86_64")]
+    unsafe fn scalar_to_num_pdep(x: u64) -> Self {
+        PointerWidth::scalar_to_num_pdep(x) as usize
+    }
+
+    #[inline(always)]
+    #[cfg(target_arch = "x86_64")]
+    unsafe fn num_to_scalar_stage1_pdep(self) -> u64 {
+        (self as PointerWidth).num_to_scalar_stage1_pdep()
+    }
+
+    #[inline(always)]
+    #[cfg(target_arch = "x86_64")]
+    unsafe fn num_to_vector_stage1_pdep(self) -> [u8; 16] {
+        (self as PointerWidth).num_to_vector_stage1_pdep()
+    }
+
+    #[inline(always)]
+    fn zigzag(from: Self::Signed) -> Self {
+        PointerWidth::zigzag(from as SignedPointerWidth) as usize
+    }
+
+    #[inline(always)]
+    fn unzigzag(self) -> Self::Signed {
+        (self as PointerWidth).unzigzag() as isize
+    }
+}
+
+/// Packs the 7-bit groups in the bytes of each 64-bit lane into its low 56 bits, for varints
+/// longer than 16 bytes
+#[inline(always)]
+fn pack_groups(lanes: [u64; 4]) -> [u64; 4] {
+    #[cfg(all(target_arch = "x86_64", target_feature = "bmi2", fast_pdep, not(miri)))]
+    {
+        lanes.map(|x| unsafe { _pext_u64(x, 0x7f7f7f7f7f7f7f7f) })
+    }
+    #[cfg(all(
+        target_feature = "avx2",
+        not(miri),
+        not(all(target_arch = "x86_64", target_feature = "bmi2", fast_pdep))
+    ))]
+    unsafe {
+        let step = |x: __m256i, lo: u64, hi: u64, shift: i32| {
+            _mm256_or_si256(
+                _mm256_and_si256(x, _mm256_set1_epi64x(lo as i64)),
+                _mm256_srlv_epi64(
+                    _mm256_and_si256(x, _mm256_set1_epi64x(hi as i64)),
+                    _mm256_set1_epi64x(shift as i64),
+                ),
+            )
+        };
+
+        // Merge pairs of adjacent groups, then pairs of those, then the two halves of each lane
+        let x = core::mem::transmute::<[u64; 4], __m256i>(lanes);
+        let x = step(x, 0x007f007f007f007f, 0x7f007f007f007f00, 1);
+        let x = step(x, 0x00003fff00003fff, 0x3fff00003fff0000, 2);
+        let x = step(x, 0x000000000fffffff, 0x0fffffff00000000, 4);
+        core::mem::transmute::<__m256i, [u64; 4]>(x)
+    }
+    #[cfg(all(target_arch = "aarch64", target_feature = "neon", not(miri)))]
+    unsafe {
+        // Negative shifts are right shifts
+        let step = |x: uint64x2_t, lo: u64, hi: u64, shift: i64| {
+            vorrq_u64(
+                vandq_u64(x, vdupq_n_u64(lo)),
+                vshlq_u64(vandq_u64(x, vdupq_n_u64(hi)), vdupq_n_s64(-shift)),
+            )
+        };
+        let pack = |x: uint64x2_t| {
+            let x = step(x, 0x007f007f007f007f, 0x7f007f007f007f00, 1);
+            let x = step(x, 0x00003fff00003fff, 0x3fff00003fff0000, 2);
+            step(x, 0x000000000fffffff, 0x0fffffff00000000, 4)
+        };
+
+        let lo = pack(vld1q_u64(lanes.as_ptr()));
+        let hi = pack(vld1q_u64(lanes.as_ptr().add(2)));
+        core::mem::transmute::<[uint64x2_t; 2], [u64; 4]>([lo, hi])
+    }
+    #[cfg(not(any(
+        all(target_feature = "avx2", not(miri)),
+        all(target_arch = "x86_64", target_feature = "bmi2", fast_pdep, not(miri)),
+        all(target_arch = "aarch64", target_feature = "neon", not(miri))
+    )))]
+    {
+        lanes.map(|x| {
+            let x = (x & 0x007f007f007f007f) | ((x & 0x7f007f007f007f00) >> 1);
+            let x = (x & 0x00003fff00003fff) | ((x & 0x3fff00003fff0000) >> 2);
+            (x & 0x000000000fffffff) | ((x & 0x0fffffff00000000) >> 4)
+        })
+    }
+}
+
+/// Spreads the low 56 bits of each 64-bit lane into 7-bit groups, one per byte. Inverse of
+/// [`pack_groups`].
+#[inline(always)]
+fn unpack_groups(lanes: [u64; 4]) -> [u64; 4] {
+    #[cfg(all(target_arch = "x86_64", target_feature = "bmi2", fast_pdep, not(miri)))]
+    {
+        lanes.map(|x| unsafe { _pdep_u64(x, 0x7f7f7f7f7f7f7f7f) })
+    }
+    #[cfg(all(
+        target_feature = "avx2",
+        not(miri),
+        not(all(target_arch = "x86_64", target_feature = "bmi2", fast_pdep))
+    ))]
+    unsafe {
+        let step = |x: __m256i, lo: u64, hi: u64, shift: i32| {
+            _mm256_or_si256(
+                _mm256_and_si256(x, _mm256_set1_epi64x(lo as i64)),
+                _mm256_sllv_epi64(
+                    _mm256_and_si256(x, _mm256_set1_epi64x(hi as i64)),
+                    _mm256_set1_epi64x(shift as i64),
+                ),
+            )
+        };
+
+        let x = core::mem::transmute::<[u64; 4], __m256i>(lanes);
+        let x = step(x, 0x000000000fffffff, 0x00fffffff0000000, 4);
+        let x = step(x, 0x00003fff00003fff, 0x0fffc0000fffc000, 2);
+        let x = step(x, 0x007f007f007f007f, 0x3f803f803f803f80, 1);
+        core::mem::transmute::<__m256i, [u64; 4]>(x)
+    }
+    #[cfg(all(target_arch = "aarch64", target_feature = "neon", not(miri)))]
+    unsafe {
+        let step = |x: uint64x2_t, lo: u64, hi: u64, shift: i64| {
+            vorrq_u64(
+                vandq_u64(x, vdupq_n_u64(lo)),
+                vshlq_u64(vandq_u64(x, vdupq_n_u64(hi)), vdupq_n_s64(shift)),
+            )
+        };
+        let unpack = |x: uint64x2_t| {
+            let x = step(x, 0x000000000fffffff, 0x00fffffff0000000, 4);
+            let x = step(x, 0x00003fff00003fff, 0x0fffc0000fffc000, 2);
+            step(x, 0x007f007f007f007f, 0x3f803f803f803f80, 1)
+        };
+
+        let lo = unpack(vld1q_u64(lanes.as_ptr()));
+        let hi = unpack(vld1q_u64(lanes.as_ptr().add(2)));
+        core::mem::transmute::<[uint64x2_t; 2], [u64; 4]>([lo, hi])
+    }
+    #[cfg(not(any(
+        all(target_feature = "avx2", not(miri)),
+        all(target_arch = "x86_64", target_feature = "bmi2", fast_pdep, not(miri)),
+        all(target_arch = "aarch64", target_feature = "neon", not(miri))
+    )))]
+    {
+        lanes.map(|x| {
+            let x = (x & 0x000000000fffffff) | ((x & 0x00fffffff0000000) << 4);
+            let x = (x & 0x00003fff00003fff) | ((x & 0x0fffc0000fffc000) << 2);
+            (x & 0x007f007f007f007f) | ((x & 0x3f803f803f803f80) << 1)
+        })
+    }
+}
+
 /// Represents a signed scalar value that can be encoded to and decoded from a varint in ZigZag
 /// format.
-pub trait SignedVarIntTarget: Debug + Eq + PartialEq + Sized + Copy {
+///
+/// This trait is sealed, and is only implemented for the primitive signed integers.
+pub trait SignedVarIntTarget: Sealed + Debug + Eq + PartialEq + Sized + Copy {
     type Unsigned: VarIntTarget<Signed = Self>;
 
     /// ZigZag encodes this value
@@ -514,3 +1092,308 @@ impl SignedVarIntTarget for i32 {
 impl SignedVarIntTarget for i64 {
     type Unsigned = u64;
 }
+
+impl SignedVarIntTarget for i128 {
+    type Unsigned = u128;
+}
+
+impl SignedVarIntTarget for isize {
+    type Unsigned = usize;
+}
+
+/// Represents a nonzero unsigned value that can be encoded to and decoded from a varint. Decoding
+/// a varint of zero to one of these types produces
+/// [`VarIntDecodeError::Zero`](crate::VarIntDecodeError::Zero).
+///
+/// This trait is sealed, and is only implemented for the `NonZero` unsigned integers.
+pub trait NonZeroVarIntTarget: Sealed + Debug + Eq + PartialEq + Sized + Copy {
+    /// The unsigned type holding the value
+    type Inner: VarIntTarget;
+
+    /// Converts the unsigned value, returning `None` if it is zero
+    fn new(num: Self::Inner) -> Option<Self>;
+
+    /// Returns the value as its unsigned type
+    fn get(self) -> Self::Inner;
+}
+
+impl NonZeroVarIntTarget for NonZeroU8 {
+    type Inner = u8;
+
+    #[inline(always)]
+    fn new(num: u8) -> Option<Self> {
+        NonZeroU8::new(num)
+    }
+
+    #[inline(always)]
+    fn get(self) -> u8 {
+        NonZeroU8::get(self)
+    }
+}
+
+impl NonZeroVarIntTarget for NonZeroU16 {
+    type Inner = u16;
+
+    #[inline(always)]
+    fn new(num: u16) -> Option<Self> {
+        NonZeroU16::new(num)
+    }
+
+    #[inline(always)]
+    fn get(self) -> u16 {
+        NonZeroU16::get(self)
+    }
+}
+
+impl NonZeroVarIntTarget for NonZeroU32 {
+    type Inner = u32;
+
+    #[inline(always)]
+    fn new(num: u32) -> Option<Self> {
+        NonZeroU32::new(num)
+    }
+
+    #[inline(always)]
+    fn get(self) -> u32 {
+        NonZeroU32::get(self)
+    }
+}
+
+impl NonZeroVarIntTarget for NonZeroU64 {
+    type Inner = u64;
+
+    #[inline(always)]
+    fn new(num: u64) -> Option<Self> {
+        NonZeroU64::new(num)
+    }
+
+    #[inline(always)]
+    fn get(self) -> u64 {
+        NonZeroU64::get(self)
+    }
+}
+
+impl NonZeroVarIntTarget for NonZeroU128 {
+    type Inner = u128;
+
+    #[inline(always)]
+    fn new(num: u128) -> Option<Self> {
+        NonZeroU128::new(num)
+    }
+
+    #[inline(always)]
+    fn get(self) -> u128 {
+        NonZeroU128::get(self)
+    }
+}
+
+impl NonZeroVarIntTarget for NonZeroUsize {
+    type Inner = usize;
+
+    #[inline(always)]
+    fn new(num: usize) -> Option<Self> {
+        NonZeroUsize::new(num)
+    }
+
+    #[inline(always)]
+    fn get(self) -> usize {
+        NonZeroUsize::get(self)
+    }
+}
+
+/// Reads the value of an integer as a `u128`. Trait methods cannot be called in a `const fn`, so
+/// this reads the bytes directly; [`Sealed`] limits `T` to primitive integers of at most 128 bits.
+#[inline(always)]
+pub(crate) const fn to_bits<T: Sealed + Copy>(num: T) -> u128 {
+    let size = core::mem::size_of::<T>();
+    assert!(size <= 16);
+
+    let ptr = &num as *const T as *const u8;
+    let mut bytes = [0u8; 16];
+    let mut i = 0;
+    while i < size {
+        // The least significant bytes of the result come from `num`, the rest are zero
+        let dest = if cfg!(target_endian = "little") {
+            i
+        } else {
+            16 - size + i
+        };
+        // SAFETY: `ptr` points to `size` initialized bytes
+        bytes[dest] = unsafe { ptr.add(i).read() };
+        i += 1;
+    }
+
+    u128::from_ne_bytes(bytes)
+}
+
+// Conversions for `Backend::Scalar`. The trait methods use PDEP/PEXT or SIMD instructions when
+// the corresponding target features are enabled at compile time, so these go one 7-bit group at a
+// time instead.
+
+/// Packs the 7-bit groups of a varint, least significant first, into a number. Bits that don't
+/// fit in the target are discarded.
+#[inline(always)]
+pub(crate) fn pack_portable<T: VarIntTarget>(groups: &[u8]) -> T {
+    T::cast_u128(
+        groups
+            .iter()
+            .rev()
+            .fold(0, |num, b| num << 7 | (b & 0x7f) as u128),
+    )
+}
+
+/// Splits a number into 7-bit groups, one per byte, least significant first. Inverse of
+/// [`pack_portable`].
+#[inline(always)]
+pub(crate) fn unpack_portable<T: VarIntTarget, const N: usize>(num: T) -> [u8; N] {
+    let bits = to_bits(num);
+    core::array::from_fn(|i| (bits.checked_shr(7 * i as u32).unwrap_or(0) & 0x7f) as u8)
+}
+
+// Conversions for kernels that choose between PDEP/PEXT and bit manipulation at runtime. `PDEP`
+// may only be set when the CPU supports BMI2, and the calling kernel should be compiled with it
+// so that the instructions are inlined.
+
+#[inline(always)]
+pub(crate) unsafe fn vector_to_num<T: VarIntTarget, const PDEP: bool>(res: [u8; 16]) -> T {
+    #[cfg(target_arch = "x86_64")]
+    if PDEP {
+        return T::vector_to_num_pdep(res);
+    }
+
+    T::vector_to_num(res)
+}
+
+#[inline(always)]
+pub(crate) unsafe fn scalar_to_num<T: VarIntTarget, const PDEP: bool>(x: u64) -> T {
+    #[cfg(target_arch = "x86_64")]
+    if PDEP {
+        return T::scalar_to_num_pdep(x);
+    }
+
+    T::scalar_to_num(x)
+}
+
+#[inline(always)]
+pub(crate) unsafe fn num_to_scalar_stage1<T: VarIntTarget, const PDEP: bool>(num: T) -> u64 {
+    #[cfg(target_arch = "x86_64")]
+    if PDEP {
+        return num.num_to_scalar_stage1_pdep();
+    }
+
+    num.num_to_scalar_stage1()
+}
+
+#[inline(always)]
+pub(crate) unsafe fn num_to_vector_stage1<T: VarIntTarget, const PDEP: bool>(num: T) -> [u8; 16] {
+    #[cfg(target_arch = "x86_64")]
+    if PDEP {
+        return num.num_to_vector_stage1_pdep();
+    }
+
+    num.num_to_vector_stage1()
+}
+
+/// Holds the compile-time check that varints of a target type fit in a 16-byte vector
+pub(crate) struct FitsVector<T>(PhantomData<T>);
+
+impl<T: VarIntTarget> FitsVector<T> {
+    pub(crate) const ASSERT: () = assert!(
+        T::MAX_VARINT_BYTES <= 16,
+        "exceeded length limit: varints of the target type may not fit in 16 bytes, use the wide \
+         functions instead"
+    );
+}
+
+/// Makes using a function with a type whose varints may not fit in 16 bytes, i.e. `u128`, a
+/// compile error. Code that is generic over every target type must not reach a function that calls
+/// this, even in a branch that is never taken for `u128`.
+#[inline(always)]
+pub(crate) const fn assert_fits_vector<T: VarIntTarget>() {
+    FitsVector::<T>::ASSERT
+}
+
+/// Holds the compile-time check that a signed target type can be sign extended to 64 bits
+pub(crate) struct FitsSignExtend<T>(PhantomData<T>);
+
+impl<T: SignedVarIntTarget> FitsSignExtend<T> {
+    pub(crate) const ASSERT: () = assert!(
+        core::mem::size_of::<T>() <= 8,
+        "exceeded width limit: only signed types of at most 64 bits can be sign extended"
+    );
+}
+
+/// Makes sign extending a type wider than 64 bits, i.e. `i128`, a compile error, rather than
+/// silently dropping its upper bits
+#[inline(always)]
+pub(crate) const fn assert_sign_extends<T: SignedVarIntTarget>() {
+    FitsSignExtend::<T>::ASSERT
+}
+
+/// Mask covering the bits of a target type, for truncating vector lanes that are wider than it
+#[inline(always)]
+pub(crate) const fn width_mask<T>() -> u64 {
+    let size = core::mem::size_of::<T>();
+    if size >= 8 {
+        u64::MAX
+    } else {
+        u64::MAX >> (64 - 8 * size)
+    }
+}
+
+/// Holds the compile-time check that two target types have the same size and alignment. Both are
+/// primitive integers, for which every bit pattern is valid, so with the same layout a value or a
+/// slice of one can be reinterpreted as the other. This is what allows the signed functions to
+/// share the kernels of their unsigned counterparts.
+pub(crate) struct SameLayout<A, B>(PhantomData<(A, B)>);
+
+impl<A, B> SameLayout<A, B> {
+    pub(crate) const ASSERT: () = assert!(
+        core::mem::size_of::<A>() == core::mem::size_of::<B>()
+            && core::mem::align_of::<A>() == core::mem::align_of::<B>(),
+        "signed and unsigned target types must have the same size and alignment"
+    );
+}
+
+/// Makes reinterpreting between two types of different layouts a compile error
+#[inline(always)]
+pub(crate) const fn assert_same_layout<A, B>() {
+    SameLayout::<A, B>::ASSERT
+}
+
+/// ZigZag decodes a value, keeping the bits of the signed result in the unsigned type. Used by the
+/// multi-value kernels where the transform cannot be applied to a whole vector at once.
+#[inline(always)]
+pub(crate) fn unzigzag_bits<T: VarIntTarget>(num: T) -> T {
+    assert_same_layout::<T, T::Signed>();
+
+    // SAFETY: checked by `SameLayout`
+    unsafe { core::mem::transmute_copy(&num.unzigzag()) }
+}
+
+/// Reinterprets the bits of an unsigned value as its signed counterpart
+#[inline(always)]
+pub(crate) fn to_signed<T: SignedVarIntTarget>(num: T::Unsigned) -> T {
+    assert_same_layout::<T, T::Unsigned>();
+
+    // SAFETY: checked by `SameLayout`
+    unsafe { core::mem::transmute_copy(&num) }
+}
+
+/// Reinterprets a slice of signed values as their unsigned counterparts
+#[inline(always)]
+pub(crate) fn unsigned_slice<T: SignedVarIntTarget>(values: &[T]) -> &[T::Unsigned] {
+    assert_same_layout::<T, T::Unsigned>();
+
+    // SAFETY: checked by `SameLayout`
+    unsafe { core::slice::from_raw_parts(values.as_ptr().cast(), values.len()) }
+}
+
+/// Reinterprets a mutable slice of signed values as their unsigned counterparts
+#[inline(always)]
+pub(crate) fn unsigned_slice_mut<T: SignedVarIntTarget>(values: &mut [T]) -> &mut [T::Unsigned] {
+    assert_same_layout::<T, T::Unsigned>();
+
+    // SAFETY: checked by `SameLayout`
+    unsafe { core::slice::from_raw_parts_mut(values.as_mut_ptr().cast(), values.len()) }
+}
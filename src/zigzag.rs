@@ -0,0 +1,394 @@
+//! ZigZag encoding and decoding of whole slices, several values per vector.
+
+#[cfg(all(target_arch = "aarch64", target_feature = "neon", not(miri)))]
+use core::arch::aarch64::*;
+#[cfg(all(target_arch = "x86", target_feature = "sse2", not(miri)))]
+use core::arch::x86::*;
+#[cfg(all(target_arch = "x86_64", target_feature = "sse2", not(miri)))]
+use core::arch::x86_64::*;
+
+#[cfg(all(target_arch = "x86_64", target_feature = "sse2", not(miri)))]
+use crate::cpu;
+use crate::num::{self, SignedVarIntTarget, VarIntTarget};
+
+/// ZigZag encodes a slice of signed integers into the output slice. Uses AVX2, SSE2 or NEON if
+/// available, and a scalar implementation otherwise. See also: [`VarIntTarget::zigzag`]
+///
+/// **Panics:** if the slices have different lengths.
+///
+/// # Examples
+/// ```
+/// use varint_simd::zigzag_slice;
+///
+/// let mut out = [0u32; 4];
+/// zigzag_slice::<i32>(&[0, -1, 1, -20], &mut out);
+/// assert_eq!(out, [0, 1, 2, 39]);
+/// ```
+#[inline]
+pub fn zigzag_slice<T: SignedVarIntTarget>(values: &[T], out: &mut [T::Unsigned]) {
+    assert_eq!(
+        values.len(),
+        out.len(),
+        "input and output slices have different lengths"
+    );
+
+    // SAFETY: both slices hold `values.len()` values of the same type
+    let done = unsafe {
+        transform_blocks::<T::Unsigned, false>(
+            num::unsigned_slice(values).as_ptr().cast(),
+            out.as_mut_ptr().cast(),
+            values.len(),
+        )
+    };
+
+    for (out, value) in out[done..].iter_mut().zip(&values[done..]) {
+        *out = T::Unsigned::zigzag(*value);
+    }
+}
+
+/// ZigZag decodes a slice of unsigned integers into the output slice. Uses AVX2, SSE2 or NEON if
+/// available, and a scalar implementation otherwise. See also: [`VarIntTarget::unzigzag`]
+///
+/// **Panics:** if the slices have different lengths.
+///
+/// # Examples
+/// ```
+/// use varint_simd::unzigzag_slice;
+///
+/// let mut out = [0i32; 4];
+/// unzigzag_slice::<i32>(&[0, 1, 2, 39], &mut out);
+/// assert_eq!(out, [0, -1, 1, -20]);
+/// ```
+#[inline]
+pub fn unzigzag_slice<T: SignedVarIntTarget>(values: &[T::Unsigned], out: &mut [T]) {
+    assert_eq!(
+        values.len(),
+        out.len(),
+        "input and output slices have different lengths"
+    );
+
+    // SAFETY: both slices hold `values.len()` values of the same type
+    let done = unsafe {
+        transform_blocks::<T::Unsigned, true>(
+            values.as_ptr().cast(),
+            num::unsigned_slice_mut(out).as_mut_ptr().cast(),
+            values.len(),
+        )
+    };
+
+    for (out, value) in out[done..].iter_mut().zip(&values[done..]) {
+        *out = value.unzigzag();
+    }
+}
+
+/// Transforms as many whole vectors of `len` values of type `T` as possible. Returns the number of
+/// values transformed, which leaves fewer than one vector's worth for the caller.
+///
+/// # Safety
+/// There must be `len` values of readable memory after `src`, and of writable memory after `dst`.
+#[inline(always)]
+unsafe fn transform_blocks<T, const UNZIGZAG: bool>(
+    src: *const u8,
+    dst: *mut u8,
+    len: usize,
+) -> usize {
+    // 128-bit values are transformed one at a time
+    if core::mem::size_of::<T>() > 8 {
+        return 0;
+    }
+
+    #[cfg(all(
+        any(target_arch = "x86", target_arch = "x86_64"),
+        target_feature = "sse2",
+        not(miri)
+    ))]
+    {
+        #[cfg(target_arch = "x86_64")]
+        if cpu::has_avx2() {
+            return transform_avx2::<T, UNZIGZAG>(src, dst, len);
+        }
+
+        transform_sse2::<T, UNZIGZAG>(src, dst, len, 0)
+    }
+    #[cfg(all(target_arch = "aarch64", target_feature = "neon", not(miri)))]
+    {
+        transform_neon::<T, UNZIGZAG>(src, dst, len)
+    }
+    #[cfg(not(any(
+        all(
+            any(target_arch = "x86", target_arch = "x86_64"),
+            target_feature = "sse2",
+            not(miri)
+        ),
+        all(target_arch = "aarch64", target_feature = "neon", not(miri))
+    )))]
+    {
+        let _ = (src, dst, len);
+        0
+    }
+}
+
+/// Continues from the value at index `start`, 16 bytes at a time
+#[inline(always)]
+#[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "sse2",
+    not(miri)
+))]
+unsafe fn transform_sse2<T, const UNZIGZAG: bool>(
+    src: *const u8,
+    dst: *mut u8,
+    len: usize,
+    start: usize,
+) -> usize {
+    let per_vector = 16 / core::mem::size_of::<T>();
+    let mut done = start;
+
+    while len - done >= per_vector {
+        let offset = done * core::mem::size_of::<T>();
+        let x = _mm_loadu_si128(src.add(offset) as *const __m128i);
+        let x = if UNZIGZAG {
+            unzigzag_sse2::<T>(x)
+        } else {
+            zigzag_sse2::<T>(x)
+        };
+        _mm_storeu_si128(dst.add(offset) as *mut __m128i, x);
+
+        done += per_vector;
+    }
+
+    done
+}
+
+#[inline(always)]
+#[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "sse2",
+    not(miri)
+))]
+unsafe fn zigzag_sse2<T>(x: __m128i) -> __m128i {
+    // (x << 1) ^ (x >> (bits - 1)), with an arithmetic shift for the sign
+    match core::mem::size_of::<T>() {
+        // There are no 8-bit shifts, so double the value and compare against zero instead
+        1 => _mm_xor_si128(_mm_add_epi8(x, x), _mm_cmpgt_epi8(_mm_setzero_si128(), x)),
+        2 => _mm_xor_si128(_mm_slli_epi16(x, 1), _mm_srai_epi16(x, 15)),
+        4 => _mm_xor_si128(_mm_slli_epi32(x, 1), _mm_srai_epi32(x, 31)),
+        // There is no 64-bit arithmetic shift, so copy the sign of the upper half to both halves
+        _ => _mm_xor_si128(
+            _mm_slli_epi64(x, 1),
+            _mm_shuffle_epi32(_mm_srai_epi32(x, 31), 0b11_11_01_01),
+        ),
+    }
+}
+
+#[inline(always)]
+#[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "sse2",
+    not(miri)
+))]
+unsafe fn unzigzag_sse2<T>(x: __m128i) -> __m128i {
+    // (x >> 1) ^ -(x & 1), with a logical shift
+    let zero = _mm_setzero_si128();
+    match core::mem::size_of::<T>() {
+        // There are no 8-bit shifts, so shift 16-bit lanes and clear the bit shifted in
+        1 => _mm_xor_si128(
+            _mm_and_si128(_mm_srli_epi16(x, 1), _mm_set1_epi8(0x7f)),
+            _mm_sub_epi8(zero, _mm_and_si128(x, _mm_set1_epi8(1))),
+        ),
+        2 => _mm_xor_si128(
+            _mm_srli_epi16(x, 1),
+            _mm_sub_epi16(zero, _mm_and_si128(x, _mm_set1_epi16(1))),
+        ),
+        4 => _mm_xor_si128(
+            _mm_srli_epi32(x, 1),
+            _mm_sub_epi32(zero, _mm_and_si128(x, _mm_set1_epi32(1))),
+        ),
+        _ => _mm_xor_si128(
+            _mm_srli_epi64(x, 1),
+            _mm_sub_epi64(zero, _mm_and_si128(x, _mm_set1_epi64x(1))),
+        ),
+    }
+}
+
+/// Transforms 32 bytes at a time, followed by a final 16 bytes with SSE2
+#[inline]
+#[cfg(all(target_arch = "x86_64", not(miri)))]
+#[target_feature(enable = "avx2")]
+unsafe fn transform_avx2<T, const UNZIGZAG: bool>(
+    src: *const u8,
+    dst: *mut u8,
+    len: usize,
+) -> usize {
+    let per_vector = 32 / core::mem::size_of::<T>();
+    let mut done = 0;
+
+    while len - done >= per_vector {
+        let offset = done * core::mem::size_of::<T>();
+        let x = _mm256_loadu_si256(src.add(offset) as *const __m256i);
+        let x = if UNZIGZAG {
+            unzigzag_avx2::<T>(x)
+        } else {
+            zigzag_avx2::<T>(x)
+        };
+        _mm256_storeu_si256(dst.add(offset) as *mut __m256i, x);
+
+        done += per_vector;
+    }
+
+    transform_sse2::<T, UNZIGZAG>(src, dst, len, done)
+}
+
+#[inline(always)]
+#[cfg(all(target_arch = "x86_64", not(miri)))]
+pub(crate) unsafe fn zigzag_avx2<T>(x: __m256i) -> __m256i {
+    match core::mem::size_of::<T>() {
+        1 => _mm256_xor_si256(
+            _mm256_add_epi8(x, x),
+            _mm256_cmpgt_epi8(_mm256_setzero_si256(), x),
+        ),
+        2 => _mm256_xor_si256(_mm256_slli_epi16(x, 1), _mm256_srai_epi16(x, 15)),
+        4 => _mm256_xor_si256(_mm256_slli_epi32(x, 1), _mm256_srai_epi32(x, 31)),
+        _ => _mm256_xor_si256(
+            _mm256_slli_epi64(x, 1),
+            _mm256_shuffle_epi32(_mm256_srai_epi32(x, 31), 0b11_11_01_01),
+        ),
+    }
+}
+
+#[inline(always)]
+#[cfg(all(target_arch = "x86_64", not(miri)))]
+unsafe fn unzigzag_avx2<T>(x: __m256i) -> __m256i {
+    let zero = _mm256_setzero_si256();
+    match core::mem::size_of::<T>() {
+        1 => _mm256_xor_si256(
+            _mm256_and_si256(_mm256_srli_epi16(x, 1), _mm256_set1_epi8(0x7f)),
+            _mm256_sub_epi8(zero, _mm256_and_si256(x, _mm256_set1_epi8(1))),
+        ),
+        2 => _mm256_xor_si256(
+            _mm256_srli_epi16(x, 1),
+            _mm256_sub_epi16(zero, _mm256_and_si256(x, _mm256_set1_epi16(1))),
+        ),
+        4 => _mm256_xor_si256(
+            _mm256_srli_epi32(x, 1),
+            _mm256_sub_epi32(zero, _mm256_and_si256(x, _mm256_set1_epi32(1))),
+        ),
+        _ => _mm256_xor_si256(
+            _mm256_srli_epi64(x, 1),
+            _mm256_sub_epi64(zero, _mm256_and_si256(x, _mm256_set1_epi64x(1))),
+        ),
+    }
+}
+
+#[inline(always)]
+#[cfg(all(target_arch = "aarch64", target_feature = "neon", not(miri)))]
+unsafe fn transform_neon<T, const UNZIGZAG: bool>(
+    src: *const u8,
+    dst: *mut u8,
+    len: usize,
+) -> usize {
+    let per_vector = 16 / core::mem::size_of::<T>();
+    let mut done = 0;
+
+    while len - done >= per_vector {
+        let offset = done * core::mem::size_of::<T>();
+        let x = vld1q_u8(src.add(offset));
+        let x = if UNZIGZAG {
+            unzigzag_neon::<T>(x)
+        } else {
+            zigzag_neon::<T>(x)
+        };
+        vst1q_u8(dst.add(offset), x);
+
+        done += per_vector;
+    }
+
+    done
+}
+
+#[inline(always)]
+#[cfg(all(target_arch = "aarch64", target_feature = "neon", not(miri)))]
+pub(crate) unsafe fn zigzag_neon<T>(x: uint8x16_t) -> uint8x16_t {
+    match core::mem::size_of::<T>() {
+        1 => {
+            let x = vreinterpretq_s8_u8(x);
+            vreinterpretq_u8_s8(veorq_s8(vshlq_n_s8::<1>(x), vshrq_n_s8::<7>(x)))
+        }
+        2 => {
+            let x = vreinterpretq_s16_u8(x);
+            vreinterpretq_u8_s16(veorq_s16(vshlq_n_s16::<1>(x), vshrq_n_s16::<15>(x)))
+        }
+        4 => {
+            let x = vreinterpretq_s32_u8(x);
+            vreinterpretq_u8_s32(veorq_s32(vshlq_n_s32::<1>(x), vshrq_n_s32::<31>(x)))
+        }
+        _ => {
+            let x = vreinterpretq_s64_u8(x);
+            vreinterpretq_u8_s64(veorq_s64(vshlq_n_s64::<1>(x), vshrq_n_s64::<63>(x)))
+        }
+    }
+}
+
+#[inline(always)]
+#[cfg(all(target_arch = "aarch64", target_feature = "neon", not(miri)))]
+unsafe fn unzigzag_neon<T>(x: uint8x16_t) -> uint8x16_t {
+    match core::mem::size_of::<T>() {
+        1 => {
+            let sign = vnegq_s8(vreinterpretq_s8_u8(vandq_u8(x, vdupq_n_u8(1))));
+            veorq_u8(vshrq_n_u8::<1>(x), vreinterpretq_u8_s8(sign))
+        }
+        2 => {
+            let x = vreinterpretq_u16_u8(x);
+            let sign = vnegq_s16(vreinterpretq_s16_u16(vandq_u16(x, vdupq_n_u16(1))));
+            vreinterpretq_u8_u16(veorq_u16(vshrq_n_u16::<1>(x), vreinterpretq_u16_s16(sign)))
+        }
+        4 => {
+            let x = vreinterpretq_u32_u8(x);
+            let sign = vnegq_s32(vreinterpretq_s32_u32(vandq_u32(x, vdupq_n_u32(1))));
+            vreinterpretq_u8_u32(veorq_u32(vshrq_n_u32::<1>(x), vreinterpretq_u32_s32(sign)))
+        }
+        _ => {
+            let x = vreinterpretq_u64_u8(x);
+            let sign = vnegq_s64(vreinterpretq_s64_u64(vandq_u64(x, vdupq_n_u64(1))));
+            vreinterpretq_u8_u64(veorq_u64(vshrq_n_u64::<1>(x), vreinterpretq_u64_s64(sign)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{unzigzag_slice, zigzag_slice};
+    use crate::tests::{NUMS_U128, NUMS_U16, NUMS_U32, NUMS_U64, NUMS_U8};
+    use crate::{SignedVarIntTarget, VarIntTarget};
+
+    fn check_zigzag_slice<T: SignedVarIntTarget>(nums: &[T::Unsigned]) {
+        // Every length up to a few 32-byte vectors, so that each one ends with a different tail
+        for len in 0..80 {
+            let unsigned: Vec<T::Unsigned> = (0..len).map(|i| nums[i % nums.len()]).collect();
+            let signed: Vec<T> = unsigned.iter().map(|n| n.unzigzag()).collect();
+
+            let mut out = vec![T::Unsigned::cast_u32(0); len];
+            zigzag_slice(&signed, &mut out);
+            assert_eq!(out, unsigned);
+
+            let mut out = vec![T::unzigzag(T::Unsigned::cast_u32(0)); len];
+            unzigzag_slice(&unsigned, &mut out);
+            assert_eq!(out, signed);
+        }
+    }
+
+    #[test]
+    fn test_zigzag_slice() {
+        check_zigzag_slice::<i8>(&NUMS_U8[..]);
+        check_zigzag_slice::<i16>(&NUMS_U16[..]);
+        check_zigzag_slice::<i32>(&NUMS_U32[..]);
+        check_zigzag_slice::<i64>(&NUMS_U64[..]);
+        check_zigzag_slice::<i128>(&NUMS_U128[..]);
+    }
+
+    #[test]
+    #[should_panic(expected = "different lengths")]
+    fn test_zigzag_slice_different_lengths() {
+        zigzag_slice::<i32>(&[1, 2, 3], &mut [0; 2]);
+    }
+}